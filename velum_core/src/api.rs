@@ -1,8 +1,10 @@
-use crate::piece_tree::{PieceTree, TextAttributes, Piece};
+use crate::piece_tree::{count_words, PieceTree, TextAttributes, Piece};
 use crate::find::SearchOptions;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -34,6 +36,120 @@ impl Default for DocumentMetadata {
     }
 }
 
+/// Counts describing a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DocumentStats {
+    /// Unicode scalar values
+    pub char_count: usize,
+    /// Words, as counted by [`count_words`]
+    pub word_count: usize,
+    /// Lines separated by `\n`; 0 for an empty document
+    pub line_count: usize,
+    /// Lines containing anything other than whitespace
+    pub paragraph_count: usize,
+}
+
+impl DocumentStats {
+    /// Computes the stats of `text`
+    pub fn from_text(text: &str) -> Self {
+        let mut stats = DocumentStats::default();
+        if text.is_empty() {
+            return stats;
+        }
+
+        stats.word_count = count_words(text);
+        stats.line_count = 1;
+        let mut line_has_content = false;
+        for ch in text.chars() {
+            stats.char_count += 1;
+            if ch == '\n' {
+                stats.line_count += 1;
+                if line_has_content {
+                    stats.paragraph_count += 1;
+                }
+                line_has_content = false;
+            } else if !ch.is_whitespace() {
+                line_has_content = true;
+            }
+        }
+        if line_has_content {
+            stats.paragraph_count += 1;
+        }
+
+        stats
+    }
+}
+
+/// Line ending convention used in a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// `\r`
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns the characters of this line ending
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+
+    /// Detects the dominant line ending in text (LF if there are none or on ties)
+    pub fn detect(text: &str) -> Self {
+        let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\r' if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    crlf += 1;
+                }
+                '\r' => cr += 1,
+                '\n' => lf += 1,
+                _ => {}
+            }
+        }
+
+        if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Rewrites every `\r\n`, `\r` or `\n` in text to this line ending
+    pub fn normalize(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\r' => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    result.push_str(self.as_str());
+                }
+                '\n' => result.push_str(self.as_str()),
+                _ => result.push(ch),
+            }
+        }
+
+        result
+    }
+}
+
 /// Document structure containing both content and metadata
 pub struct Document {
     pub content: PieceTree,
@@ -50,7 +166,7 @@ impl Document {
 
     pub fn new(content: String) -> Self {
         let char_count = content.chars().count();
-        let word_count = content.split_whitespace().count();
+        let word_count = count_words(&content);
         let mut metadata = DocumentMetadata::default();
         metadata.char_count = char_count;
         metadata.word_count = word_count;
@@ -61,10 +177,26 @@ impl Document {
         }
     }
 
+    /// Returns the dominant line ending of the document content
+    pub fn line_ending(&self) -> LineEnding {
+        LineEnding::detect(&self.content.get_text())
+    }
+
+    /// Inserts pasted text, normalizing its line endings to the document's.
+    /// The paste is a single insertion, so one undo reverts all of it.
+    pub fn paste(&mut self, offset: usize, text: &str) -> bool {
+        let normalized = self.line_ending().normalize(text);
+        let inserted = self.content.insert(offset, normalized);
+        if inserted {
+            self.update_metadata();
+        }
+        inserted
+    }
+
     pub fn update_metadata(&mut self) {
         let text = self.content.get_text();
         self.metadata.char_count = text.chars().count();
-        self.metadata.word_count = text.split_whitespace().count();
+        self.metadata.word_count = count_words(&text);
         self.metadata.modified_at = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -72,18 +204,94 @@ impl Document {
     }
 }
 
-static DOCUMENT: Lazy<RwLock<Document>> = Lazy::new(|| RwLock::new(Document::empty()));
+/// Identifies an open document in the registry. Handles are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DocumentHandle(pub u64);
+
+static DOCUMENTS: Lazy<RwLock<HashMap<DocumentHandle, Document>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static NEXT_HANDLE: AtomicU64 = AtomicU64::new(1);
+
+/// Opens a new empty document and returns its handle
+pub fn create_document() -> DocumentHandle {
+    let handle = DocumentHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+    DOCUMENTS.write().unwrap_or_else(|e| e.into_inner()).insert(handle, Document::empty());
+    handle
+}
+
+/// Closes a document; closing a handle that is not open is an error
+pub fn close_document(handle: DocumentHandle) -> Result<(), ApiError> {
+    DOCUMENTS.write().unwrap_or_else(|e| e.into_inner()).remove(&handle).map(drop).ok_or(ApiError::UnknownHandle(handle))
+}
+
+/// Errors returned by editing API calls instead of panicking across the FFI boundary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// No open document has this handle
+    UnknownHandle(DocumentHandle),
+    /// The offset lies past the end of the document
+    OffsetOutOfRange { offset: usize, document_length: usize },
+    /// The range `offset..offset + length` extends past the end of the document
+    RangeOutOfBounds { offset: usize, length: usize, document_length: usize },
+    /// A JSON argument could not be parsed
+    InvalidJson(String),
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::UnknownHandle(handle) => write!(f, "Unknown document handle: {}", handle.0),
+            ApiError::OffsetOutOfRange { offset, document_length } => write!(
+                f,
+                "Offset {} is past the end of the document (length {})",
+                offset, document_length
+            ),
+            ApiError::RangeOutOfBounds { offset, length, document_length } => write!(
+                f,
+                "Range {}..{} is past the end of the document (length {})",
+                offset,
+                offset.saturating_add(*length),
+                document_length
+            ),
+            ApiError::InvalidJson(message) => write!(f, "Invalid JSON: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// A single edit in an [`apply_edits`] batch. All offsets and lengths count characters.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum EditOp {
+    /// Inserts `text` at `offset`
+    Insert { offset: usize, text: String },
+    /// Deletes `length` characters at `offset`
+    Delete { offset: usize, length: usize },
+    /// Replaces `length` characters at `offset` with `text`
+    Replace { offset: usize, length: usize, text: String },
+}
+
+/// Runs `f` on the document, failing if the handle is not open
+fn with_document<R>(handle: DocumentHandle, f: impl FnOnce(&Document) -> R) -> Result<R, ApiError> {
+    DOCUMENTS.read().unwrap_or_else(|e| e.into_inner()).get(&handle).map(f).ok_or(ApiError::UnknownHandle(handle))
+}
+
+/// Runs `f` on the document mutably, failing if the handle is not open
+fn with_document_mut<R>(handle: DocumentHandle, f: impl FnOnce(&mut Document) -> R) -> Result<R, ApiError> {
+    DOCUMENTS.write().unwrap_or_else(|e| e.into_inner()).get_mut(&handle).map(f).ok_or(ApiError::UnknownHandle(handle))
+}
 
 pub fn hello_velum() -> String {
     "Hello from Velum Core (Rust)!".to_string()
 }
 
-pub fn get_sample_document() -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    *doc = Document::new("Welcome to Velum.".to_string());
-    doc.content.insert(16, " This is Microsoft Word 1:1 replica project.".to_string());
-    doc.update_metadata();
-    doc.content.get_text()
+pub fn get_sample_document(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        *doc = Document::new("Welcome to Velum.".to_string());
+        doc.content.insert(16, " This is Microsoft Word 1:1 replica project.".to_string());
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
 pub fn multiply(a: i32, b: i32) -> i32 {
@@ -91,198 +299,305 @@ pub fn multiply(a: i32, b: i32) -> i32 {
 }
 
 // 创建空文档
-pub fn create_empty_document() -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    *doc = Document::empty();
-    doc.content.get_text()
+pub fn create_empty_document(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        *doc = Document::empty();
+        doc.content.get_text()
+    })
+}
+
+// 在指定位置插入文本（偏移越界时返回错误，不修改文档）
+pub fn insert_text(handle: DocumentHandle, offset: usize, new_text: String) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        let document_length = doc.content.total_char_count;
+        if offset > document_length {
+            return Err(ApiError::OffsetOutOfRange { offset, document_length });
+        }
+        doc.content.insert(offset, new_text);
+        doc.update_metadata();
+        Ok(doc.content.get_text())
+    })?
+}
+
+// 批量编辑：一次加锁依次应用，记录为一个撤销步骤；任一编辑越界时整批回滚
+pub fn apply_edits(handle: DocumentHandle, edits: Vec<EditOp>) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.content.group_edits(|content| {
+            for edit in edits {
+                let document_length = content.total_char_count;
+                match edit {
+                    EditOp::Insert { offset, text } => {
+                        if offset > document_length {
+                            return Err(ApiError::OffsetOutOfRange { offset, document_length });
+                        }
+                        content.insert(offset, text);
+                    }
+                    EditOp::Delete { offset, length } => {
+                        let (start, end) = char_range_to_bytes(content, offset, length)?;
+                        content.delete(start, end - start);
+                    }
+                    EditOp::Replace { offset, length, text } => {
+                        let (start, end) = char_range_to_bytes(content, offset, length)?;
+                        content.replace(start, end - start, text);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+        doc.update_metadata();
+        Ok(doc.content.get_text())
+    })?
 }
 
-// 在指定位置插入文本
-pub fn insert_text(offset: usize, new_text: String) -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.insert(offset, new_text);
-    doc.update_metadata();
-    doc.content.get_text()
+// 批量编辑（JSON 形式，供 FFI 调用）：edits_json 为 EditOp 数组，如 [{"op":"insert","offset":0,"text":"a"}]
+pub fn apply_edits_json(handle: DocumentHandle, edits_json: String) -> Result<String, ApiError> {
+    let edits: Vec<EditOp> = serde_json::from_str(&edits_json)
+        .map_err(|e| ApiError::InvalidJson(e.to_string()))?;
+    apply_edits(handle, edits)
 }
 
-// 删除指定范围文本
-pub fn delete_text(offset: usize, length: usize) -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.delete(offset, length);
-    doc.update_metadata();
-    doc.content.get_text()
+/// Converts the character range `offset..offset + length` to a byte range
+fn char_range_to_bytes(content: &PieceTree, offset: usize, length: usize) -> Result<(usize, usize), ApiError> {
+    let document_length = content.total_char_count;
+    let out_of_bounds = ApiError::RangeOutOfBounds { offset, length, document_length };
+    let end = offset.checked_add(length).ok_or(out_of_bounds.clone())?;
+    match (content.char_to_byte_offset(offset), content.char_to_byte_offset(end)) {
+        (Some(start), Some(end)) => Ok((start, end)),
+        _ => Err(out_of_bounds),
+    }
+}
+
+// 粘贴文本（按文档换行符规范化；偏移越界时返回错误）
+pub fn paste_text(handle: DocumentHandle, offset: usize, text: String) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        let document_length = doc.content.total_char_count;
+        if offset > document_length || !doc.paste(offset, &text) {
+            return Err(ApiError::OffsetOutOfRange { offset, document_length });
+        }
+        Ok(doc.content.get_text())
+    })?
+}
+
+// 删除指定范围文本（范围越界时返回错误，不修改文档）
+pub fn delete_text(handle: DocumentHandle, offset: usize, length: usize) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        let (start, end) = char_range_to_bytes(&doc.content, offset, length)?;
+        doc.content.delete(start, end - start);
+        doc.update_metadata();
+        Ok(doc.content.get_text())
+    })?
 }
 
 // 获取文本范围
-pub fn get_text_range(offset: usize, length: usize) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_text_range(offset, length)
+pub fn get_text_range(handle: DocumentHandle, offset: usize, length: usize) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_text_range(offset, length)
+    })
 }
 
 // 获取行数统计
-pub fn get_line_count() -> usize {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_line_count()
+pub fn get_line_count(handle: DocumentHandle) -> Result<usize, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_line_count()
+    })
 }
 
 // 获取指定行内容
-pub fn get_line_content(line_number: usize) -> Option<String> {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_line(line_number)
+pub fn get_line_content(handle: DocumentHandle, line_number: usize) -> Result<Option<String>, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_line(line_number)
+    })
 }
 
 // 获取指定行的字符偏移量
-pub fn get_offset_at_line(line_number: usize) -> usize {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_offset_at_line(line_number)
+pub fn get_offset_at_line(handle: DocumentHandle, line_number: usize) -> Result<usize, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_offset_at_line(line_number)
+    })
 }
 
 // 获取完整文本
-pub fn get_full_text() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_text()
+pub fn get_full_text(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_text()
+    })
 }
 
 // 撤销
-pub fn undo() -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.undo();
-    doc.update_metadata();
-    doc.content.get_text()
+pub fn undo(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.content.undo();
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
 // 重做
-pub fn redo() -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.redo();
-    doc.update_metadata();
-    doc.content.get_text()
+pub fn redo(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.content.redo();
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
 // 检查是否可以撤销
-pub fn can_undo() -> bool {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.can_undo()
+pub fn can_undo(handle: DocumentHandle) -> Result<bool, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.can_undo()
+    })
 }
 
 // 检查是否可以重做
-pub fn can_redo() -> bool {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.can_redo()
+pub fn can_redo(handle: DocumentHandle) -> Result<bool, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.can_redo()
+    })
+}
+
+/// Returns the number of undoable and redoable steps, for history UI
+pub fn history_depth(handle: DocumentHandle) -> Result<(usize, usize), ApiError> {
+    with_document(handle, |doc| {
+        (doc.content.undo_count(), doc.content.redo_count())
+    })
 }
 
 // ==================== Document Metadata APIs ====================
 
 // 获取文档标题
-pub fn get_document_title() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.title.clone()
+pub fn get_document_title(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.title.clone()
+    })
 }
 
 // 设置文档标题
-pub fn set_document_title(title: String) {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.metadata.title = title;
-    doc.metadata.modified_at = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
+pub fn set_document_title(handle: DocumentHandle, title: String) -> Result<(), ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.metadata.title = title;
+        doc.metadata.modified_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+    })
 }
 
 // 获取文档作者
-pub fn get_document_author() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.author.clone()
+pub fn get_document_author(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.author.clone()
+    })
 }
 
 // 设置文档作者
-pub fn set_document_author(author: String) {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.metadata.author = author;
+pub fn set_document_author(handle: DocumentHandle, author: String) -> Result<(), ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.metadata.author = author;
+    })
 }
 
 // 获取创建时间
-pub fn get_document_created_at() -> u64 {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.created_at
+pub fn get_document_created_at(handle: DocumentHandle) -> Result<u64, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.created_at
+    })
 }
 
 // 获取修改时间
-pub fn get_document_modified_at() -> u64 {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.modified_at
+pub fn get_document_modified_at(handle: DocumentHandle) -> Result<u64, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.modified_at
+    })
 }
 
 // 获取字数统计
-pub fn get_word_count() -> usize {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.word_count
+pub fn get_word_count(handle: DocumentHandle) -> Result<usize, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.word_count
+    })
 }
 
 // 获取字符统计
-pub fn get_char_count() -> usize {
-    let doc = DOCUMENT.read().unwrap();
-    doc.metadata.char_count
+pub fn get_char_count(handle: DocumentHandle) -> Result<usize, ApiError> {
+    with_document(handle, |doc| {
+        doc.metadata.char_count
+    })
+}
+
+// 一次性获取字符、字数、行数和段落统计
+pub fn get_document_stats(handle: DocumentHandle) -> Result<DocumentStats, ApiError> {
+    with_document(handle, |doc| {
+        DocumentStats::from_text(&doc.content.get_text())
+    })
 }
 
 // 获取当前光标位置的行列号 (1-indexed)
-pub fn get_cursor_position(char_offset: usize) -> (usize, usize) {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.move_to(char_offset)
+pub fn get_cursor_position(handle: DocumentHandle, char_offset: usize) -> Result<(usize, usize), ApiError> {
+    with_document(handle, |doc| {
+        doc.content.move_to(char_offset)
+    })
 }
 
 // ==================== Selection APIs ====================
 
 /// Gets the selection anchor position (i32 for FFI)
-pub fn get_selection_anchor() -> i32 {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_selection_anchor() as i32
+pub fn get_selection_anchor(handle: DocumentHandle) -> Result<i32, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_selection_anchor() as i32
+    })
 }
 
 /// Gets the selection active position (i32 for FFI)
-pub fn get_selection_active() -> i32 {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_selection_active() as i32
+pub fn get_selection_active(handle: DocumentHandle) -> Result<i32, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_selection_active() as i32
+    })
 }
 
 /// Sets the selection with anchor and active positions
-pub fn set_selection(anchor: i32, active: i32) {
-    let mut doc = DOCUMENT.write().unwrap();
-    let anchor = anchor.max(0) as usize;
-    let active = active.max(0) as usize;
-    doc.content.set_selection(anchor, active);
+pub fn set_selection(handle: DocumentHandle, anchor: i32, active: i32) -> Result<(), ApiError> {
+    with_document_mut(handle, |doc| {
+        let anchor = anchor.max(0) as usize;
+        let active = active.max(0) as usize;
+        doc.content.set_selection(anchor, active);
+    })
 }
 
 /// Gets the selected text content
-pub fn get_selection_text() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_selection_text()
+pub fn get_selection_text(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_selection_text()
+    })
 }
 
 /// Moves the selection to the specified position (collapses to cursor)
-pub fn move_selection_to(offset: i32) {
-    let mut doc = DOCUMENT.write().unwrap();
-    let offset = offset.max(0) as usize;
-    doc.content.move_selection_to(offset);
+pub fn move_selection_to(handle: DocumentHandle, offset: i32) -> Result<(), ApiError> {
+    with_document_mut(handle, |doc| {
+        let offset = offset.max(0) as usize;
+        doc.content.move_selection_to(offset);
+    })
 }
 
 /// Clears the selection by collapsing to the end of the document
-pub fn clear_selection() {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.clear_selection();
+pub fn clear_selection(handle: DocumentHandle) -> Result<(), ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.content.clear_selection();
+    })
 }
 
 /// Returns true if there is a non-empty selection
-pub fn has_selection() -> bool {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.has_selection()
+pub fn has_selection(handle: DocumentHandle) -> Result<bool, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.has_selection()
+    })
 }
 
 /// Gets the selection range as (start, end)
-pub fn get_selection_range() -> (i32, i32) {
-    let doc = DOCUMENT.read().unwrap();
-    let (start, end) = doc.content.get_selection_range();
-    (start as i32, end as i32)
+pub fn get_selection_range(handle: DocumentHandle) -> Result<(i32, i32), ApiError> {
+    with_document(handle, |doc| {
+        let (start, end) = doc.content.get_selection_range();
+        (start as i32, end as i32)
+    })
 }
 
 // ==================== Find and Replace APIs ====================
@@ -293,9 +608,10 @@ pub fn get_selection_range() -> (i32, i32) {
 /// * `options_json` - JSON serialized SearchOptions
 /// # Returns
 /// JSON serialized SearchResultSet
-pub fn find_text(query: &str, options_json: &str) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.find_text_json(query, options_json)
+pub fn find_text(handle: DocumentHandle, query: &str, options_json: &str) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.find_text_json(query, options_json)
+    })
 }
 
 /// Replaces text and returns the number of replacements made
@@ -305,9 +621,10 @@ pub fn find_text(query: &str, options_json: &str) -> String {
 /// * `all` - If true, replace all; otherwise replace only the first
 /// # Returns
 /// Number of replacements made
-pub fn replace_text(find: &str, replace: &str, all: bool) -> i32 {
-    let mut doc = DOCUMENT.write().unwrap();
-    doc.content.replace_text_json(find, replace, all)
+pub fn replace_text(handle: DocumentHandle, find: &str, replace: &str, all: bool) -> Result<i32, ApiError> {
+    with_document_mut(handle, |doc| {
+        doc.content.replace_text_json(find, replace, all)
+    })
 }
 
 /// Gets the count of matches for a query
@@ -315,100 +632,104 @@ pub fn replace_text(find: &str, replace: &str, all: bool) -> i32 {
 /// * `query` - Text to find
 /// # Returns
 /// Number of matches found
-pub fn get_match_count(query: &str) -> i32 {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_match_count(query)
+pub fn get_match_count(handle: DocumentHandle, query: &str) -> Result<i32, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_match_count(query)
+    })
 }
 
 /// Advanced find with full options (JSON input/output)
-pub fn find_with_options(options_json: &str) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let options: Result<SearchOptions, _> = serde_json::from_str(options_json);
-    match options {
-        Ok(options) => {
-            let results = doc.content.find_all(&options);
-            serde_json::to_string(&results).unwrap_or_else(|_| "{}".to_string())
+pub fn find_with_options(handle: DocumentHandle, options_json: &str) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let options: Result<SearchOptions, _> = serde_json::from_str(options_json);
+        match options {
+            Ok(options) => {
+                let results = doc.content.find_all(&options);
+                serde_json::to_string(&results).unwrap_or_else(|_| "{}".to_string())
+            }
+            Err(e) => format!(r#"{{"error": "{}"}}"#, e),
         }
-        Err(e) => format!(r#"{{"error": "{}"}}"#, e),
-    }
+    })
 }
 
 /// Find next match from current cursor position
-pub fn find_next(query: &str) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let options = SearchOptions {
-        query: query.to_string(),
-        ..Default::default()
-    };
-    let result = doc.content.find_next(&options, doc.content.get_selection_active());
-    match result {
-        Some(r) => serde_json::to_string(&r).unwrap_or_else(|_| "{}".to_string()),
-        None => "{}".to_string(),
-    }
+pub fn find_next(handle: DocumentHandle, query: &str) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let options = SearchOptions {
+            query: query.to_string(),
+            ..Default::default()
+        };
+        let result = doc.content.find_next(&options, doc.content.get_selection_active());
+        match result {
+            Some(r) => serde_json::to_string(&r).unwrap_or_else(|_| "{}".to_string()),
+            None => "{}".to_string(),
+        }
+    })
 }
 
 /// Find previous match from current cursor position
-pub fn find_previous(query: &str) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let options = SearchOptions {
-        query: query.to_string(),
-        ..Default::default()
-    };
-    let result = doc.content.find_previous(&options, doc.content.get_selection_active());
-    match result {
-        Some(r) => serde_json::to_string(&r).unwrap_or_else(|_| "{}".to_string()),
-        None => "{}".to_string(),
-    }
+pub fn find_previous(handle: DocumentHandle, query: &str) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let options = SearchOptions {
+            query: query.to_string(),
+            ..Default::default()
+        };
+        let result = doc.content.find_previous(&options, doc.content.get_selection_active());
+        match result {
+            Some(r) => serde_json::to_string(&r).unwrap_or_else(|_| "{}".to_string()),
+            None => "{}".to_string(),
+        }
+    })
 }
 
 // 查找文本，返回所有匹配的位置（字节偏移量）- 旧版兼容函数
 #[deprecated(since = "0.2.0", note = "Use find_with_options instead")]
-pub fn find_text_legacy(query: String) -> Vec<usize> {
-    let doc = DOCUMENT.read().unwrap();
-    let text = doc.content.get_text();
-    let mut positions = Vec::new();
+pub fn find_text_legacy(handle: DocumentHandle, query: String) -> Result<Vec<usize>, ApiError> {
+    with_document(handle, |doc| {
+        let text = doc.content.get_text();
+        let mut positions = Vec::new();
     
-    if query.is_empty() {
-        return positions;
-    }
+        if query.is_empty() {
+            return positions;
+        }
     
-    let mut start = 0;
-    while let Some(pos) = text[start..].find(&query) {
-        let absolute_pos = start + pos;
-        positions.push(absolute_pos);
-        start = absolute_pos + query.len();
-        if start >= text.len() {
-            break;
+        let mut start = 0;
+        while let Some(pos) = text[start..].find(&query) {
+            let absolute_pos = start + pos;
+            positions.push(absolute_pos);
+            start = absolute_pos + query.len();
+            if start >= text.len() {
+                break;
+            }
         }
-    }
     
-    positions
+        positions
+    })
 }
 
 // 查找并替换第一个匹配项 - 旧版兼容函数
 #[deprecated(since = "0.2.0", note = "Use replace_text instead")]
-pub fn replace_first(query: String, replacement: String) -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    let text = doc.content.get_text();
+pub fn replace_first(handle: DocumentHandle, query: String, replacement: String) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        let text = doc.content.get_text();
     
-    if let Some(pos) = text.find(&query) {
-        doc.content.delete(pos, query.len());
-        doc.content.insert(pos, replacement);
-        doc.update_metadata();
-    }
+        if let Some(pos) = text.find(&query) {
+            doc.content.delete(pos, query.len());
+            doc.content.insert(pos, replacement);
+            doc.update_metadata();
+        }
     
-    doc.content.get_text()
+        doc.content.get_text()
+    })
 }
 
 // 查找并替换所有匹配项 - 旧版兼容函数
 #[deprecated(since = "0.2.0", note = "Use replace_text with all=true instead")]
-pub fn replace_all_legacy(query: String, replacement: String) -> String {
-    let doc_read = DOCUMENT.read().unwrap();
-    let text = doc_read.content.get_text();
-    drop(doc_read);
+pub fn replace_all_legacy(handle: DocumentHandle, query: String, replacement: String) -> Result<String, ApiError> {
+    let text = get_full_text(handle)?;
     
     if query.is_empty() || !text.contains(&query) {
-        return text;
+        return Ok(text);
     }
     
     // 由于每次替换都会改变文本，我们重新构建文本
@@ -424,10 +745,7 @@ pub fn replace_all_legacy(query: String, replacement: String) -> String {
     result.push_str(&text[last_end..]);
     
     // 替换整个文档内容
-    let mut doc_write = DOCUMENT.write().unwrap();
-    *doc_write = Document::new(result);
-    doc_write.update_metadata();
-    doc_write.content.get_text()
+    load_document_from_text(handle, result)
 }
 
 // ==================== Document Save/Load APIs ====================
@@ -443,27 +761,27 @@ struct SerializableDocument {
 }
 
 // 将文档保存为 JSON 字符串
-pub fn save_document_to_json() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let serializable = SerializableDocument {
-        title: doc.metadata.title.clone(),
-        author: doc.metadata.author.clone(),
-        created_at: doc.metadata.created_at,
-        modified_at: doc.metadata.modified_at,
-        content: doc.content.get_text(),
-    };
+pub fn save_document_to_json(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let serializable = SerializableDocument {
+            title: doc.metadata.title.clone(),
+            author: doc.metadata.author.clone(),
+            created_at: doc.metadata.created_at,
+            modified_at: doc.metadata.modified_at,
+            content: doc.content.get_text(),
+        };
     
-    match serde_json::to_string(&serializable) {
-        Ok(json) => json,
-        Err(e) => format!("Error: {}", e),
-    }
+        match serde_json::to_string(&serializable) {
+            Ok(json) => json,
+            Err(e) => format!("Error: {}", e),
+        }
+    })
 }
 
 // 从 JSON 字符串加载文档
-pub fn load_document_from_json(json: String) -> String {
+pub fn load_document_from_json(handle: DocumentHandle, json: String) -> Result<String, ApiError> {
     match serde_json::from_str::<SerializableDocument>(&json) {
-        Ok(serializable) => {
-            let mut doc = DOCUMENT.write().unwrap();
+        Ok(serializable) => with_document_mut(handle, |doc| {
             *doc = Document {
                 content: PieceTree::new(serializable.content),
                 metadata: DocumentMetadata {
@@ -477,278 +795,284 @@ pub fn load_document_from_json(json: String) -> String {
             };
             doc.update_metadata();
             doc.content.get_text()
-        }
-        Err(e) => format!("Error: {}", e),
+        }),
+        Err(e) => Ok(format!("Error: {}", e)),
     }
 }
 
 // 获取文档的纯文本内容（用于保存为 .txt）
-pub fn get_document_as_text() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    doc.content.get_text()
+pub fn get_document_as_text(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        doc.content.get_text()
+    })
 }
 
 // 从纯文本加载文档
-pub fn load_document_from_text(text: String) -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    *doc = Document::new(text);
-    doc.update_metadata();
-    doc.content.get_text()
+pub fn load_document_from_text(handle: DocumentHandle, text: String) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        *doc = Document::new(text);
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
-pub fn save_to_file(path: String) -> String {
-    let json = save_document_to_json();
+pub fn save_to_file(handle: DocumentHandle, path: String) -> Result<String, ApiError> {
+    let json = save_document_to_json(handle)?;
     if json.starts_with("Error:") {
-        return json;
+        return Ok(json);
     }
     
-    match fs::write(&path, json) {
+    Ok(match fs::write(&path, json) {
         Ok(_) => format!("Successfully saved to {}", path),
         Err(e) => format!("Error saving file: {}", e),
-    }
+    })
 }
 
 // 从指定路径加载文档 (JSON 格式)
-pub fn load_from_file(path: String) -> String {
+pub fn load_from_file(handle: DocumentHandle, path: String) -> Result<String, ApiError> {
     match fs::read_to_string(&path) {
-        Ok(json) => load_document_from_json(json),
-        Err(e) => format!("Error reading file: {}", e),
+        Ok(json) => load_document_from_json(handle, json),
+        Err(e) => Ok(format!("Error reading file: {}", e)),
     }
 }
 
 // 导出为纯文本文件
-pub fn export_to_txt(path: String) -> String {
-    let text = get_document_as_text();
-    match fs::write(&path, text) {
+pub fn export_to_txt(handle: DocumentHandle, path: String) -> Result<String, ApiError> {
+    let text = get_document_as_text(handle)?;
+    Ok(match fs::write(&path, text) {
         Ok(_) => format!("Successfully exported to {}", path),
         Err(e) => format!("Error exporting file: {}", e),
-    }
+    })
 }
 
 // ==================== Text Attributes APIs ====================
 
 /// Gets text attributes at the specified offset
-pub fn get_text_attributes_at(offset: usize) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let offset = offset.min(doc.content.total_char_count);
+pub fn get_text_attributes_at(handle: DocumentHandle, offset: usize) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let offset = offset.min(doc.content.total_char_count);
     
-    // Find the piece at the given offset
-    let mut accumulated_chars = 0usize;
-    for piece in &doc.content.pieces {
-        let piece_start = accumulated_chars;
-        let piece_end = accumulated_chars + piece.piece_char_length;
+        // Find the piece at the given offset
+        let mut accumulated_chars = 0usize;
+        for piece in &doc.content.pieces {
+            let piece_start = accumulated_chars;
+            let piece_end = accumulated_chars + piece.piece_char_length;
         
-        if offset >= piece_start && offset < piece_end {
-            if let Some(attrs) = &piece.attributes {
-                return format!(
-                    "{},{},{},{},{},{},{}",
-                    attrs.bold.map_or("None", |b| if b { "true" } else { "false" }),
-                    attrs.italic.map_or("None", |b| if b { "true" } else { "false" }),
-                    attrs.underline.map_or("None", |b| if b { "true" } else { "false" }),
-                    attrs.font_size.map(|s| s.to_string()).unwrap_or_else(|| "None".to_string()),
-                    attrs.font_family.clone().unwrap_or_else(|| "None".to_string()),
-                    attrs.foreground.clone().unwrap_or_else(|| "None".to_string()),
-                    attrs.background.clone().unwrap_or_else(|| "None".to_string())
-                );
+            if offset >= piece_start && offset < piece_end {
+                if let Some(attrs) = &piece.attributes {
+                    return format!(
+                        "{},{},{},{},{},{},{}",
+                        attrs.bold.map_or("None", |b| if b { "true" } else { "false" }),
+                        attrs.italic.map_or("None", |b| if b { "true" } else { "false" }),
+                        attrs.underline.map_or("None", |b| if b { "true" } else { "false" }),
+                        attrs.font_size.map(|s| s.to_string()).unwrap_or_else(|| "None".to_string()),
+                        attrs.font_family.clone().unwrap_or_else(|| "None".to_string()),
+                        attrs.foreground.clone().unwrap_or_else(|| "None".to_string()),
+                        attrs.background.clone().unwrap_or_else(|| "None".to_string())
+                    );
+                }
+                return "None,None,None,None,None,None,None".to_string();
             }
-            return "None,None,None,None,None,None,None".to_string();
-        }
         
-        accumulated_chars = piece_end;
-    }
+            accumulated_chars = piece_end;
+        }
     
-    "None,None,None,None,None,None,None".to_string()
+        "None,None,None,None,None,None,None".to_string()
+    })
 }
 
 /// Applies text attributes to the specified range
-pub fn apply_text_attributes(start: usize, end: usize, attributes_json: String) -> String {
+pub fn apply_text_attributes(handle: DocumentHandle, start: usize, end: usize, attributes_json: String) -> Result<String, ApiError> {
     // Parse the attributes JSON
     let attributes: Result<TextAttributes, _> = serde_json::from_str(&attributes_json);
     if attributes.is_err() {
-        return format!("Error: Invalid attributes JSON");
+        return Ok("Error: Invalid attributes JSON".to_string());
     }
     let attrs = attributes.unwrap();
     
-    let mut doc = DOCUMENT.write().unwrap();
-    let start = start.min(doc.content.total_char_count);
-    let end = end.min(doc.content.total_char_count);
+    with_document_mut(handle, |doc| {
+        let start = start.min(doc.content.total_char_count);
+        let end = end.min(doc.content.total_char_count);
     
-    if start >= end {
-        return String::new();
-    }
+        if start >= end {
+            return String::new();
+        }
     
-    // Clone the content to iterate
-    let pieces: Vec<_> = doc.content.pieces.clone();
-    let mut new_pieces = Vec::new();
-    let mut accumulated_chars = 0usize;
+        // Clone the content to iterate
+        let pieces: Vec<_> = doc.content.pieces.clone();
+        let mut new_pieces = Vec::new();
+        let mut accumulated_chars = 0usize;
     
-    for piece in pieces {
-        let piece_start = accumulated_chars;
-        let piece_end = accumulated_chars + piece.piece_char_length;
+        for piece in pieces {
+            let piece_start = accumulated_chars;
+            let piece_end = accumulated_chars + piece.piece_char_length;
         
-        if piece_end <= start {
-            // Piece is entirely before the range
-            new_pieces.push(piece);
-        } else if piece_start >= end {
-            // Piece is entirely after the range
-            new_pieces.push(piece);
-        } else {
-            // Piece overlaps with the range - may need to split
-            let range_start = start.max(piece_start);
-            let range_end = end.min(piece_end);
-            
-            // Left part (before range)
-            if range_start > piece_start {
-                let left_piece = Piece::new_with_attrs(
-                    piece.start,
-                    range_start - piece_start,
-                    piece.buffer_id,
-                    range_start - piece_start,
-                    piece.attributes.clone(),
-                );
-                new_pieces.push(left_piece);
-            }
+            if piece_end <= start {
+                // Piece is entirely before the range
+                new_pieces.push(piece);
+            } else if piece_start >= end {
+                // Piece is entirely after the range
+                new_pieces.push(piece);
+            } else {
+                // Piece overlaps with the range - may need to split
+                let range_start = start.max(piece_start);
+                let range_end = end.min(piece_end);
             
-            // Middle part (with new attributes)
-            let middle_piece = Piece::new_with_attrs(
-                piece.start + (range_start - piece_start),
-                range_end - range_start,
-                piece.buffer_id,
-                range_end - range_start,
-                Some(attrs.clone()),
-            );
-            new_pieces.push(middle_piece);
+                // Left part (before range)
+                if range_start > piece_start {
+                    let left_piece = Piece::new_with_attrs(
+                        piece.start,
+                        range_start - piece_start,
+                        piece.buffer_id,
+                        range_start - piece_start,
+                        piece.attributes.clone(),
+                    );
+                    new_pieces.push(left_piece);
+                }
             
-            // Right part (after range)
-            if range_end < piece_end {
-                let right_start = piece.start + (range_end - piece_start);
-                let right_length = piece_end - range_end;
-                let right_piece = Piece::new_with_attrs(
-                    right_start,
-                    right_length,
+                // Middle part (with new attributes)
+                let middle_piece = Piece::new_with_attrs(
+                    piece.start + (range_start - piece_start),
+                    range_end - range_start,
                     piece.buffer_id,
-                    right_length,
-                    piece.attributes.clone(),
+                    range_end - range_start,
+                    Some(attrs.clone()),
                 );
-                new_pieces.push(right_piece);
+                new_pieces.push(middle_piece);
+            
+                // Right part (after range)
+                if range_end < piece_end {
+                    let right_start = piece.start + (range_end - piece_start);
+                    let right_length = piece_end - range_end;
+                    let right_piece = Piece::new_with_attrs(
+                        right_start,
+                        right_length,
+                        piece.buffer_id,
+                        right_length,
+                        piece.attributes.clone(),
+                    );
+                    new_pieces.push(right_piece);
+                }
             }
-        }
         
-        accumulated_chars = piece_end;
-    }
+            accumulated_chars = piece_end;
+        }
     
-    doc.content.pieces = new_pieces;
-    doc.update_metadata();
-    doc.content.get_text()
+        doc.content.pieces = new_pieces;
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
 /// Removes text attributes from the specified range
-pub fn remove_text_attributes(start: usize, end: usize) -> String {
-    let mut doc = DOCUMENT.write().unwrap();
-    let start = start.min(doc.content.total_char_count);
-    let end = end.min(doc.content.total_char_count);
+pub fn remove_text_attributes(handle: DocumentHandle, start: usize, end: usize) -> Result<String, ApiError> {
+    with_document_mut(handle, |doc| {
+        let start = start.min(doc.content.total_char_count);
+        let end = end.min(doc.content.total_char_count);
     
-    if start >= end {
-        return String::new();
-    }
+        if start >= end {
+            return String::new();
+        }
     
-    // Clone the content to iterate
-    let pieces: Vec<_> = doc.content.pieces.clone();
-    let mut new_pieces = Vec::new();
-    let mut accumulated_chars = 0usize;
+        // Clone the content to iterate
+        let pieces: Vec<_> = doc.content.pieces.clone();
+        let mut new_pieces = Vec::new();
+        let mut accumulated_chars = 0usize;
     
-    for piece in pieces {
-        let piece_start = accumulated_chars;
-        let piece_end = accumulated_chars + piece.piece_char_length;
+        for piece in pieces {
+            let piece_start = accumulated_chars;
+            let piece_end = accumulated_chars + piece.piece_char_length;
         
-        if piece_end <= start {
-            // Piece is entirely before the range
-            new_pieces.push(piece);
-        } else if piece_start >= end {
-            // Piece is entirely after the range
-            new_pieces.push(piece);
-        } else {
-            // Piece overlaps with the range - may need to split
-            let range_start = start.max(piece_start);
-            let range_end = end.min(piece_end);
-            
-            // Left part (before range)
-            if range_start > piece_start {
-                let left_piece = Piece::new_with_attrs(
-                    piece.start,
-                    range_start - piece_start,
-                    piece.buffer_id,
-                    range_start - piece_start,
-                    piece.attributes.clone(),
-                );
-                new_pieces.push(left_piece);
-            }
+            if piece_end <= start {
+                // Piece is entirely before the range
+                new_pieces.push(piece);
+            } else if piece_start >= end {
+                // Piece is entirely after the range
+                new_pieces.push(piece);
+            } else {
+                // Piece overlaps with the range - may need to split
+                let range_start = start.max(piece_start);
+                let range_end = end.min(piece_end);
             
-            // Middle part (without attributes)
-            let middle_piece = Piece::new_with_attrs(
-                piece.start + (range_start - piece_start),
-                range_end - range_start,
-                piece.buffer_id,
-                range_end - range_start,
-                None,
-            );
-            new_pieces.push(middle_piece);
+                // Left part (before range)
+                if range_start > piece_start {
+                    let left_piece = Piece::new_with_attrs(
+                        piece.start,
+                        range_start - piece_start,
+                        piece.buffer_id,
+                        range_start - piece_start,
+                        piece.attributes.clone(),
+                    );
+                    new_pieces.push(left_piece);
+                }
             
-            // Right part (after range)
-            if range_end < piece_end {
-                let right_start = piece.start + (range_end - piece_start);
-                let right_length = piece_end - range_end;
-                let right_piece = Piece::new_with_attrs(
-                    right_start,
-                    right_length,
+                // Middle part (without attributes)
+                let middle_piece = Piece::new_with_attrs(
+                    piece.start + (range_start - piece_start),
+                    range_end - range_start,
                     piece.buffer_id,
-                    right_length,
-                    piece.attributes.clone(),
+                    range_end - range_start,
+                    None,
                 );
-                new_pieces.push(right_piece);
+                new_pieces.push(middle_piece);
+            
+                // Right part (after range)
+                if range_end < piece_end {
+                    let right_start = piece.start + (range_end - piece_start);
+                    let right_length = piece_end - range_end;
+                    let right_piece = Piece::new_with_attrs(
+                        right_start,
+                        right_length,
+                        piece.buffer_id,
+                        right_length,
+                        piece.attributes.clone(),
+                    );
+                    new_pieces.push(right_piece);
+                }
             }
-        }
         
-        accumulated_chars = piece_end;
-    }
+            accumulated_chars = piece_end;
+        }
     
-    doc.content.pieces = new_pieces;
-    doc.update_metadata();
-    doc.content.get_text()
+        doc.content.pieces = new_pieces;
+        doc.update_metadata();
+        doc.content.get_text()
+    })
 }
 
 /// Gets all text with their attributes as JSON
-pub fn get_text_with_attributes() -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let mut result = Vec::new();
-
-    for piece in &doc.content.pieces {
-        let buffer_idx = PieceTree::buffer_idx(&piece.buffer_id);
-        if let Some(buffer) = doc.content.buffers.get(buffer_idx) {
-            let piece_text = if piece.start + piece.length <= buffer.len() {
-                buffer[piece.start..piece.start + piece.length].to_string()
-            } else {
-                String::new()
-            };
+pub fn get_text_with_attributes(handle: DocumentHandle) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let mut result = Vec::new();
+
+        for piece in &doc.content.pieces {
+            let buffer_idx = PieceTree::buffer_idx(&piece.buffer_id);
+            if let Some(buffer) = doc.content.buffer(buffer_idx) {
+                let piece_text = if piece.start + piece.length <= buffer.len() {
+                    buffer[piece.start..piece.start + piece.length].to_string()
+                } else {
+                    String::new()
+                };
             
-            let attrs_json = if let Some(attrs) = &piece.attributes {
-                serde_json::to_string(attrs).unwrap_or_else(|_| "null".to_string())
-            } else {
-                "null".to_string()
-            };
+                let attrs_json = if let Some(attrs) = &piece.attributes {
+                    serde_json::to_string(attrs).unwrap_or_else(|_| "null".to_string())
+                } else {
+                    "null".to_string()
+                };
             
-            result.push(format!("{{\"text\": \"{}\", \"attrs\": {}}}",
-                piece_text.replace('"', "\\\"").replace('\n', "\\n"),
-                attrs_json));
+                result.push(format!("{{\"text\": \"{}\", \"attrs\": {}}}",
+                    piece_text.replace('"', "\\\"").replace('\n', "\\n"),
+                    attrs_json));
+            }
         }
-    }
 
-    format!("[{}]", result.join(", "))
+        format!("[{}]", result.join(", "))
+    })
 }
 
 // ==================== Line Breaking APIs ====================
 
-use crate::line_layout::LineLayout;
+use crate::line_layout::{LineLayout, LineLayoutConfig};
 
 /// Layouts text and returns JSON layout information
 pub fn layout_text(text: &str, width: f32) -> String {
@@ -775,16 +1099,18 @@ pub fn get_text_height(text: &str, width: f32, line_height: f32, font_size: f32)
 }
 
 /// Layouts the current document state and returns JSON layout information
-pub fn layout_current_document(width: f32) -> String {
-    let doc = DOCUMENT.read().unwrap();
-    let text = doc.content.get_text();
-    let mut layout = LineLayout::new();
-    layout.layout_to_json(&text, width)
+pub fn layout_current_document(handle: DocumentHandle, width: f32) -> Result<String, ApiError> {
+    with_document(handle, |doc| {
+        let text = doc.content.get_text();
+        let mut layout = LineLayout::new();
+        layout.layout_to_json(&text, width)
+    })
 }
 
 // ==================== OOXML Document APIs ====================
 
 use crate::ooxml::{parse_ooxml, ParsedDocument};
+use crate::page_layout::{PageConfig, PageLayout, RenderedPage};
 
 /// Load and parse an OOXML (.docx) document from file path
 /// Returns JSON string containing extracted text, styles, and metadata
@@ -937,6 +1263,25 @@ fn create_minimal_docx(text: &str) -> Vec<u8> {
     buffer.into_inner()
 }
 
+/// Lay out a .docx file on the page setup of its first section, honoring its default tab stop
+/// Returns JSON array of rendered pages
+pub fn layout_ooxml_pages(file_data: &[u8]) -> String {
+    match parse_ooxml(file_data) {
+        Ok(document) => {
+            let section = document.sections.first().cloned().unwrap_or_default();
+            let page_config = PageConfig::from(&section);
+            let config = document.settings.as_ref().map(LineLayoutConfig::from).unwrap_or_default();
+            let mut line_layout = LineLayout::with_config(config);
+            let layout = line_layout.layout_document(&document.text, page_config.column_width());
+
+            let mut page_layout = PageLayout::with_page_config(page_config);
+            let pages: Vec<RenderedPage> = page_layout.pages_iter(&layout.paragraphs).collect();
+            serde_json::to_string(&pages).unwrap_or_else(|e| format!("JSON error: {}", e))
+        }
+        Err(e) => format!("OOXML error: {}", e),
+    }
+}
+
 /// Get just the text content from a .docx file
 pub fn extract_ooxml_text(file_path: &str) -> String {
     match std::fs::read(file_path) {
@@ -973,3 +1318,340 @@ pub fn get_ooxml_stats(file_path: &str) -> String {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_ending_detect() {
+        assert_eq!(LineEnding::detect("no breaks"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("a\nb\nc"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\rb\rc"), LineEnding::Cr);
+    }
+
+    #[test]
+    fn test_line_ending_normalize_mixed() {
+        let mixed = "one\r\ntwo\rthree\nfour";
+        assert_eq!(LineEnding::Lf.normalize(mixed), "one\ntwo\nthree\nfour");
+        assert_eq!(LineEnding::CrLf.normalize(mixed), "one\r\ntwo\r\nthree\r\nfour");
+    }
+
+    #[test]
+    fn test_paste_crlf_into_lf_document() {
+        let mut doc = Document::new("first\nlast".to_string());
+        assert_eq!(doc.line_ending(), LineEnding::Lf);
+
+        assert!(doc.paste(6, "pasted\r\nlines\r\n"));
+        assert_eq!(doc.content.get_text(), "first\npasted\nlines\nlast");
+        assert!(!doc.content.get_text().contains('\r'));
+
+        // The whole paste is reverted by a single undo
+        assert!(doc.content.undo());
+        assert_eq!(doc.content.get_text(), "first\nlast");
+    }
+
+    #[test]
+    fn test_documents_are_isolated_by_handle() {
+        let first = create_document();
+        let second = create_document();
+        assert_ne!(first, second);
+
+        insert_text(first, 0, "first document".to_string()).unwrap();
+        insert_text(second, 0, "second".to_string()).unwrap();
+        delete_text(first, 0, 6).unwrap();
+
+        assert_eq!(get_full_text(first).unwrap(), "document");
+        assert_eq!(get_full_text(second).unwrap(), "second");
+
+        set_document_title(second, "Second".to_string()).unwrap();
+        assert_eq!(get_document_title(first).unwrap(), "Untitled Document");
+        assert_eq!(get_document_title(second).unwrap(), "Second");
+
+        undo(first).unwrap();
+        assert_eq!(get_full_text(first).unwrap(), "first document");
+        assert_eq!(get_full_text(second).unwrap(), "second");
+
+        close_document(first).unwrap();
+        close_document(second).unwrap();
+    }
+
+    #[test]
+    fn test_closed_handle_is_rejected() {
+        let handle = create_document();
+        insert_text(handle, 0, "text".to_string()).unwrap();
+        close_document(handle).unwrap();
+
+        assert_eq!(close_document(handle), Err(ApiError::UnknownHandle(handle)));
+        assert_eq!(get_full_text(handle), Err(ApiError::UnknownHandle(handle)));
+        assert_eq!(
+            insert_text(handle, 0, "ignored".to_string()),
+            Err(ApiError::UnknownHandle(handle))
+        );
+        assert_eq!(get_line_content(handle, 0), Err(ApiError::UnknownHandle(handle)));
+    }
+
+    #[test]
+    fn test_insert_past_end_is_an_error() {
+        let handle = create_document();
+        insert_text(handle, 0, "abc".to_string()).unwrap();
+
+        assert_eq!(
+            insert_text(handle, 10, "x".to_string()),
+            Err(ApiError::OffsetOutOfRange { offset: 10, document_length: 3 })
+        );
+        assert_eq!(get_full_text(handle).unwrap(), "abc");
+
+        // Appending exactly at the end is still allowed
+        assert_eq!(insert_text(handle, 3, "d".to_string()).unwrap(), "abcd");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_paste_past_end_is_an_error() {
+        let handle = create_document();
+        insert_text(handle, 0, "abc".to_string()).unwrap();
+
+        assert_eq!(
+            paste_text(handle, 10, "x".to_string()),
+            Err(ApiError::OffsetOutOfRange { offset: 10, document_length: 3 })
+        );
+        assert_eq!(get_full_text(handle).unwrap(), "abc");
+        assert_eq!(paste_text(handle, 3, "d".to_string()).unwrap(), "abcd");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_registry_survives_a_panicking_edit() {
+        let handle = create_document();
+        let result = std::thread::spawn(move || {
+            with_document_mut(handle, |_| panic!("edit failed")).ok();
+        })
+        .join();
+        assert!(result.is_err());
+
+        // The poisoned lock is recovered instead of panicking every later call
+        assert_eq!(insert_text(handle, 0, "ok".to_string()).unwrap(), "ok");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_delete_past_end_is_an_error() {
+        let handle = create_document();
+        insert_text(handle, 0, "abc".to_string()).unwrap();
+
+        assert_eq!(
+            delete_text(handle, 2, 5),
+            Err(ApiError::RangeOutOfBounds { offset: 2, length: 5, document_length: 3 })
+        );
+        assert!(matches!(
+            delete_text(handle, usize::MAX, 2),
+            Err(ApiError::RangeOutOfBounds { .. })
+        ));
+        assert_eq!(get_full_text(handle).unwrap(), "abc");
+        assert_eq!(delete_text(handle, 1, 2).unwrap(), "a");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_delete_counts_characters() {
+        let handle = create_document();
+        insert_text(handle, 0, "aéb".to_string()).unwrap();
+
+        // "é" is one character even though it takes two bytes
+        assert_eq!(
+            delete_text(handle, 2, 2),
+            Err(ApiError::RangeOutOfBounds { offset: 2, length: 2, document_length: 3 })
+        );
+        assert_eq!(get_full_text(handle).unwrap(), "aéb");
+        assert_eq!(delete_text(handle, 1, 1).unwrap(), "ab");
+
+        // Matches the same delete made through apply_edits
+        let other = create_document();
+        insert_text(other, 0, "aéb".to_string()).unwrap();
+        assert_eq!(apply_edits(other, vec![EditOp::Delete { offset: 1, length: 1 }]).unwrap(), "ab");
+        close_document(handle).unwrap();
+        close_document(other).unwrap();
+    }
+
+    #[test]
+    fn test_apply_edits_is_one_undo_step() {
+        let handle = create_document();
+        insert_text(handle, 0, "hello world".to_string()).unwrap();
+
+        let text = apply_edits(
+            handle,
+            vec![
+                EditOp::Insert { offset: 0, text: "> ".to_string() },
+                EditOp::Delete { offset: 7, length: 6 },
+                EditOp::Insert { offset: 7, text: " there".to_string() },
+                EditOp::Replace { offset: 2, length: 5, text: "Hello".to_string() },
+                EditOp::Insert { offset: 13, text: "!".to_string() },
+            ],
+        )
+        .unwrap();
+        assert_eq!(text, "> Hello there!");
+
+        assert_eq!(undo(handle).unwrap(), "hello world");
+        assert_eq!(redo(handle).unwrap(), "> Hello there!");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_apply_edits_counts_characters() {
+        let handle = create_document();
+        insert_text(handle, 0, "héllo wörld".to_string()).unwrap();
+
+        let text = apply_edits(
+            handle,
+            vec![
+                EditOp::Delete { offset: 5, length: 6 },
+                EditOp::Replace { offset: 1, length: 1, text: "e".to_string() },
+                EditOp::Insert { offset: 5, text: "!".to_string() },
+            ],
+        )
+        .unwrap();
+        assert_eq!(text, "hello!");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_apply_edits_json() {
+        let handle = create_document();
+        insert_text(handle, 0, "abc".to_string()).unwrap();
+
+        let edits = r#"[{"op":"replace","offset":0,"length":1,"text":"A"},{"op":"insert","offset":3,"text":"d"}]"#;
+        assert_eq!(apply_edits_json(handle, edits.to_string()).unwrap(), "Abcd");
+        assert!(matches!(
+            apply_edits_json(handle, r#"[{"op":"move"}]"#.to_string()),
+            Err(ApiError::InvalidJson(_))
+        ));
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_apply_edits_rolls_back_on_error() {
+        let handle = create_document();
+        insert_text(handle, 0, "abc".to_string()).unwrap();
+
+        let result = apply_edits(
+            handle,
+            vec![
+                EditOp::Insert { offset: 3, text: "def".to_string() },
+                EditOp::Delete { offset: 4, length: 10 },
+            ],
+        );
+        assert_eq!(
+            result,
+            Err(ApiError::RangeOutOfBounds { offset: 4, length: 10, document_length: 6 })
+        );
+        assert_eq!(get_full_text(handle).unwrap(), "abc");
+
+        // The failed batch left no undo step behind
+        assert_eq!(undo(handle).unwrap(), "");
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_document_stats_ascii() {
+        let handle = create_document();
+        insert_text(handle, 0, "Hello, world!\nIt's 2024.\n\n  \nLast line".to_string()).unwrap();
+
+        let stats = get_document_stats(handle).unwrap();
+        assert_eq!(stats.char_count, 38);
+        assert_eq!(stats.word_count, 6);
+        assert_eq!(stats.line_count, 5);
+        assert_eq!(stats.paragraph_count, 3);
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_document_stats_cjk() {
+        // Whitespace splitting would see one word per line here
+        let stats = DocumentStats::from_text("你好，世界\n日本語");
+        assert_eq!(stats.char_count, 9);
+        assert_eq!(stats.word_count, 7);
+        assert_eq!(stats.line_count, 2);
+        assert_eq!(stats.paragraph_count, 2);
+    }
+
+    #[test]
+    fn test_word_count_matches_document_stats() {
+        let handle = create_document();
+        insert_text(handle, 0, "你好，世界 - Hello, world!".to_string()).unwrap();
+
+        assert_eq!(get_word_count(handle).unwrap(), 6);
+        assert_eq!(get_word_count(handle).unwrap(), get_document_stats(handle).unwrap().word_count);
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_document_stats_empty() {
+        assert_eq!(DocumentStats::from_text(""), DocumentStats::default());
+        let handle = create_document();
+        assert_eq!(get_document_stats(handle).unwrap(), DocumentStats::default());
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_paste_lf_into_crlf_document() {
+        let mut doc = Document::new("a\r\nb".to_string());
+        assert!(doc.paste(0, "x\ny\n"));
+        assert_eq!(doc.content.get_text(), "x\r\ny\r\na\r\nb");
+    }
+
+    #[test]
+    fn test_history_flags_follow_edits() {
+        let handle = create_document();
+        assert!(!can_undo(handle).unwrap());
+        assert!(!can_redo(handle).unwrap());
+        assert_eq!(history_depth(handle).unwrap(), (0, 0));
+
+        insert_text(handle, 0, "one".to_string()).unwrap();
+        insert_text(handle, 3, " two".to_string()).unwrap();
+        assert!(can_undo(handle).unwrap());
+        assert!(!can_redo(handle).unwrap());
+        assert_eq!(history_depth(handle).unwrap(), (2, 0));
+
+        undo(handle).unwrap();
+        assert!(can_undo(handle).unwrap());
+        assert!(can_redo(handle).unwrap());
+        assert_eq!(history_depth(handle).unwrap(), (1, 1));
+
+        // Exhausting the stack leaves only redo available, and extra undos are no-ops
+        undo(handle).unwrap();
+        assert_eq!(undo(handle).unwrap(), "");
+        assert!(!can_undo(handle).unwrap());
+        assert!(can_redo(handle).unwrap());
+        assert_eq!(history_depth(handle).unwrap(), (0, 2));
+
+        // A new edit discards the redo history
+        insert_text(handle, 0, "three".to_string()).unwrap();
+        assert!(!can_redo(handle).unwrap());
+        assert_eq!(history_depth(handle).unwrap(), (1, 0));
+
+        close_document(handle).unwrap();
+    }
+
+    #[test]
+    fn test_history_of_unknown_handle_is_an_error() {
+        let handle = create_document();
+        close_document(handle).unwrap();
+
+        assert_eq!(can_undo(handle), Err(ApiError::UnknownHandle(handle)));
+        assert_eq!(can_redo(handle), Err(ApiError::UnknownHandle(handle)));
+        assert_eq!(history_depth(handle), Err(ApiError::UnknownHandle(handle)));
+    }
+
+    #[test]
+    fn test_layout_ooxml_pages_uses_section_page_size() {
+        let json = layout_ooxml_pages(&create_minimal_docx("Hello\nWorld"));
+        let pages: Vec<RenderedPage> = serde_json::from_str(&json).unwrap();
+
+        // Without a <w:sectPr> the document is US Letter, not the paginator's A4 default
+        assert_eq!(pages.len(), 1);
+        assert_eq!((pages[0].page_width, pages[0].page_height), (612.0, 792.0));
+        assert_eq!(pages[0].content_bounds.width, 468.0);
+        assert_eq!(pages[0].lines.len(), 2);
+    }
+}
@@ -87,6 +87,7 @@ pub struct FfiSearchOptions {
     pub case_sensitive: bool,
     pub whole_word: bool,
     pub regex: bool,
+    pub wildcard: bool,
     pub wrap_around: bool,
     pub search_backward: bool,
 }
@@ -99,8 +100,10 @@ impl From<FfiSearchOptions> for SearchOptions {
             case_sensitive: opts.case_sensitive,
             whole_word: opts.whole_word,
             regex: opts.regex,
+            wildcard: opts.wildcard,
             wrap_around: opts.wrap_around,
             search_backward: opts.search_backward,
+            ignore_diacritics: false,
         }
     }
 }
@@ -7,8 +7,8 @@ pub extern "C" fn wire_hello_velum(port_: i64) {
 }
 
 #[no_mangle]
-pub extern "C" fn wire_get_sample_document(port_: i64) {
-    wire_get_sample_document_impl(port_)
+pub extern "C" fn wire_get_sample_document(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_get_sample_document_impl(port_, handle)
 }
 
 #[no_mangle]
@@ -17,52 +17,110 @@ pub extern "C" fn wire_multiply(port_: i64, a: i32, b: i32) {
 }
 
 #[no_mangle]
-pub extern "C" fn wire_create_empty_document(port_: i64) {
-    wire_create_empty_document_impl(port_)
+pub extern "C" fn wire_create_document(port_: i64) {
+    wire_create_document_impl(port_)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_insert_text(port_: i64, offset: usize, new_text: *mut wire_uint_8_list) {
-    wire_insert_text_impl(port_, offset, new_text)
+pub extern "C" fn wire_close_document(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_close_document_impl(port_, handle)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_delete_text(port_: i64, offset: usize, length: usize) {
-    wire_delete_text_impl(port_, offset, length)
+pub extern "C" fn wire_create_empty_document(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_create_empty_document_impl(port_, handle)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_get_text_range(port_: i64, offset: usize, length: usize) {
-    wire_get_text_range_impl(port_, offset, length)
+pub extern "C" fn wire_insert_text(
+    port_: i64,
+    handle: *mut wire_DocumentHandle,
+    offset: usize,
+    new_text: *mut wire_uint_8_list,
+) {
+    wire_insert_text_impl(port_, handle, offset, new_text)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_get_line_count(port_: i64) {
-    wire_get_line_count_impl(port_)
+pub extern "C" fn wire_apply_edits_json(
+    port_: i64,
+    handle: *mut wire_DocumentHandle,
+    edits_json: *mut wire_uint_8_list,
+) {
+    wire_apply_edits_json_impl(port_, handle, edits_json)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_get_line_content(port_: i64, line_number: usize) {
-    wire_get_line_content_impl(port_, line_number)
+pub extern "C" fn wire_delete_text(
+    port_: i64,
+    handle: *mut wire_DocumentHandle,
+    offset: usize,
+    length: usize,
+) {
+    wire_delete_text_impl(port_, handle, offset, length)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_get_full_text(port_: i64) {
-    wire_get_full_text_impl(port_)
+pub extern "C" fn wire_get_text_range(
+    port_: i64,
+    handle: *mut wire_DocumentHandle,
+    offset: usize,
+    length: usize,
+) {
+    wire_get_text_range_impl(port_, handle, offset, length)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_undo(port_: i64) {
-    wire_undo_impl(port_)
+pub extern "C" fn wire_get_line_count(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_get_line_count_impl(port_, handle)
 }
 
 #[no_mangle]
-pub extern "C" fn wire_redo(port_: i64) {
-    wire_redo_impl(port_)
+pub extern "C" fn wire_get_line_content(
+    port_: i64,
+    handle: *mut wire_DocumentHandle,
+    line_number: usize,
+) {
+    wire_get_line_content_impl(port_, handle, line_number)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_get_full_text(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_get_full_text_impl(port_, handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_undo(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_undo_impl(port_, handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_redo(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_redo_impl(port_, handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_can_undo(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_can_undo_impl(port_, handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_can_redo(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_can_redo_impl(port_, handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wire_history_depth(port_: i64, handle: *mut wire_DocumentHandle) {
+    wire_history_depth_impl(port_, handle)
 }
 
 // Section: allocate functions
 
+#[no_mangle]
+pub extern "C" fn new_box_autoadd_document_handle_0() -> *mut wire_DocumentHandle {
+    support::new_leak_box_ptr(wire_DocumentHandle::new_with_null_ptr())
+}
+
 #[no_mangle]
 pub extern "C" fn new_uint_8_list_0(len: i32) -> *mut wire_uint_8_list {
     let ans = wire_uint_8_list {
@@ -82,6 +140,17 @@ impl Wire2Api<String> for *mut wire_uint_8_list {
         String::from_utf8_lossy(&vec).into_owned()
     }
 }
+impl Wire2Api<DocumentHandle> for *mut wire_DocumentHandle {
+    fn wire2api(self) -> DocumentHandle {
+        let wrap = unsafe { support::box_from_leak_ptr(self) };
+        Wire2Api::<DocumentHandle>::wire2api(*wrap).into()
+    }
+}
+impl Wire2Api<DocumentHandle> for wire_DocumentHandle {
+    fn wire2api(self) -> DocumentHandle {
+        DocumentHandle(self.field0.wire2api())
+    }
+}
 
 impl Wire2Api<Vec<u8>> for *mut wire_uint_8_list {
     fn wire2api(self) -> Vec<u8> {
@@ -94,6 +163,12 @@ impl Wire2Api<Vec<u8>> for *mut wire_uint_8_list {
 
 // Section: wire structs
 
+#[repr(C)]
+#[derive(Clone)]
+pub struct wire_DocumentHandle {
+    field0: u64,
+}
+
 #[repr(C)]
 #[derive(Clone)]
 pub struct wire_uint_8_list {
@@ -113,6 +188,20 @@ impl<T> NewWithNullPtr for *mut T {
     }
 }
 
+impl NewWithNullPtr for wire_DocumentHandle {
+    fn new_with_null_ptr() -> Self {
+        Self {
+            field0: Default::default(),
+        }
+    }
+}
+
+impl Default for wire_DocumentHandle {
+    fn default() -> Self {
+        Self::new_with_null_ptr()
+    }
+}
+
 // Section: sync execution mode utility
 
 #[no_mangle]
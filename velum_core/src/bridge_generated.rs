@@ -32,14 +32,20 @@ fn wire_hello_velum_impl(port_: MessagePort) {
         move || move |task_callback| Result::<_, ()>::Ok(hello_velum()),
     )
 }
-fn wire_get_sample_document_impl(port_: MessagePort) {
+fn wire_get_sample_document_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
         WrapInfo {
             debug_name: "get_sample_document",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(get_sample_document()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| get_sample_document(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
 fn wire_multiply_impl(
@@ -60,18 +66,51 @@ fn wire_multiply_impl(
         },
     )
 }
-fn wire_create_empty_document_impl(port_: MessagePort) {
+fn wire_create_document_impl(port_: MessagePort) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, DocumentHandle, _>(
+        WrapInfo {
+            debug_name: "create_document",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || move |task_callback| Result::<_, ()>::Ok(create_document()),
+    )
+}
+fn wire_close_document_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, (), _>(
+        WrapInfo {
+            debug_name: "close_document",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| close_document(api_handle).map_err(|e| e.to_string())
+        },
+    )
+}
+fn wire_create_empty_document_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
         WrapInfo {
             debug_name: "create_empty_document",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(create_empty_document()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| create_empty_document(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
 fn wire_insert_text_impl(
     port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
     offset: impl Wire2Api<usize> + UnwindSafe,
     new_text: impl Wire2Api<String> + UnwindSafe,
 ) {
@@ -82,14 +121,38 @@ fn wire_insert_text_impl(
             mode: FfiCallMode::Normal,
         },
         move || {
+            let api_handle = handle.wire2api();
             let api_offset = offset.wire2api();
             let api_new_text = new_text.wire2api();
-            move |task_callback| Result::<_, ()>::Ok(insert_text(api_offset, api_new_text))
+            move |task_callback| {
+                insert_text(api_handle, api_offset, api_new_text).map_err(|e| e.to_string())
+            }
+        },
+    )
+}
+fn wire_apply_edits_json_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+    edits_json: impl Wire2Api<String> + UnwindSafe,
+) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
+        WrapInfo {
+            debug_name: "apply_edits_json",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || {
+            let api_handle = handle.wire2api();
+            let api_edits_json = edits_json.wire2api();
+            move |task_callback| {
+                apply_edits_json(api_handle, api_edits_json).map_err(|e| e.to_string())
+            }
         },
     )
 }
 fn wire_delete_text_impl(
     port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
     offset: impl Wire2Api<usize> + UnwindSafe,
     length: impl Wire2Api<usize> + UnwindSafe,
 ) {
@@ -100,14 +163,18 @@ fn wire_delete_text_impl(
             mode: FfiCallMode::Normal,
         },
         move || {
+            let api_handle = handle.wire2api();
             let api_offset = offset.wire2api();
             let api_length = length.wire2api();
-            move |task_callback| Result::<_, ()>::Ok(delete_text(api_offset, api_length))
+            move |task_callback| {
+                delete_text(api_handle, api_offset, api_length).map_err(|e| e.to_string())
+            }
         },
     )
 }
 fn wire_get_text_range_impl(
     port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
     offset: impl Wire2Api<usize> + UnwindSafe,
     length: impl Wire2Api<usize> + UnwindSafe,
 ) {
@@ -118,23 +185,36 @@ fn wire_get_text_range_impl(
             mode: FfiCallMode::Normal,
         },
         move || {
+            let api_handle = handle.wire2api();
             let api_offset = offset.wire2api();
             let api_length = length.wire2api();
-            move |task_callback| Result::<_, ()>::Ok(get_text_range(api_offset, api_length))
+            move |task_callback| {
+                get_text_range(api_handle, api_offset, api_length).map_err(|e| e.to_string())
+            }
         },
     )
 }
-fn wire_get_line_count_impl(port_: MessagePort) {
+fn wire_get_line_count_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, usize, _>(
         WrapInfo {
             debug_name: "get_line_count",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(get_line_count()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| get_line_count(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
-fn wire_get_line_content_impl(port_: MessagePort, line_number: impl Wire2Api<usize> + UnwindSafe) {
+fn wire_get_line_content_impl(
+    port_: MessagePort,
+    handle: impl Wire2Api<DocumentHandle> + UnwindSafe,
+    line_number: impl Wire2Api<usize> + UnwindSafe,
+) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, Option<String>, _>(
         WrapInfo {
             debug_name: "get_line_content",
@@ -142,41 +222,93 @@ fn wire_get_line_content_impl(port_: MessagePort, line_number: impl Wire2Api<usi
             mode: FfiCallMode::Normal,
         },
         move || {
+            let api_handle = handle.wire2api();
             let api_line_number = line_number.wire2api();
-            move |task_callback| Result::<_, ()>::Ok(get_line_content(api_line_number))
+            move |task_callback| {
+                get_line_content(api_handle, api_line_number).map_err(|e| e.to_string())
+            }
         },
     )
 }
-fn wire_get_full_text_impl(port_: MessagePort) {
+fn wire_get_full_text_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
         WrapInfo {
             debug_name: "get_full_text",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(get_full_text()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| get_full_text(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
-fn wire_undo_impl(port_: MessagePort) {
+fn wire_undo_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
         WrapInfo {
             debug_name: "undo",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(undo()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| undo(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
-fn wire_redo_impl(port_: MessagePort) {
+fn wire_redo_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
     FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, String, _>(
         WrapInfo {
             debug_name: "redo",
             port: Some(port_),
             mode: FfiCallMode::Normal,
         },
-        move || move |task_callback| Result::<_, ()>::Ok(redo()),
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| redo(api_handle).map_err(|e| e.to_string())
+        },
+    )
+}
+fn wire_can_undo_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, bool, _>(
+        WrapInfo {
+            debug_name: "can_undo",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| can_undo(api_handle).map_err(|e| e.to_string())
+        },
+    )
+}
+fn wire_can_redo_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, bool, _>(
+        WrapInfo {
+            debug_name: "can_redo",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| can_redo(api_handle).map_err(|e| e.to_string())
+        },
+    )
+}
+fn wire_history_depth_impl(port_: MessagePort, handle: impl Wire2Api<DocumentHandle> + UnwindSafe) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap::<_, _, _, (usize, usize), _>(
+        WrapInfo {
+            debug_name: "history_depth",
+            port: Some(port_),
+            mode: FfiCallMode::Normal,
+        },
+        move || {
+            let api_handle = handle.wire2api();
+            move |task_callback| history_depth(api_handle).map_err(|e| e.to_string())
+        },
     )
 }
+
 // Section: wrapper structs
 
 // Section: static checks
@@ -205,6 +337,11 @@ impl Wire2Api<i32> for i32 {
         self
     }
 }
+impl Wire2Api<u64> for u64 {
+    fn wire2api(self) -> u64 {
+        self
+    }
+}
 impl Wire2Api<u8> for u8 {
     fn wire2api(self) -> u8 {
         self
@@ -218,6 +355,18 @@ impl Wire2Api<usize> for usize {
 }
 // Section: impl IntoDart
 
+impl support::IntoDart for DocumentHandle {
+    fn into_dart(self) -> support::DartAbi {
+        vec![self.0.into_into_dart().into_dart()].into_dart()
+    }
+}
+impl support::IntoDartExceptPrimitive for DocumentHandle {}
+impl rust2dart::IntoIntoDart<DocumentHandle> for DocumentHandle {
+    fn into_into_dart(self) -> Self {
+        self
+    }
+}
+
 // Section: executor
 
 support::lazy_static! {
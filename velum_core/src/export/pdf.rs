@@ -734,6 +734,7 @@ mod tests {
                     width: 200.0,
                     start: 0,
                     end: 13,
+                    text: None,
                 },
                 RenderedLine {
                     line_index: 1,
@@ -745,12 +746,16 @@ mod tests {
                     width: 400.0,
                     start: 13,
                     end: 54,
+                    text: None,
                 },
             ],
             header_region: None,
             footer_region: None,
             page_width: 595.28,
             page_height: 841.89,
+            keep_violations: Vec::new(),
+            header_lines: Vec::new(),
+            footer_lines: Vec::new(),
         }];
 
         RenderedDocument {
@@ -1,5 +1,12 @@
 use serde::{Deserialize, Serialize};
 use regex::Regex;
+use unicode_segmentation::GraphemeCursor;
+use caseless::Caseless;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use std::borrow::Cow;
+use std::iter;
+use crate::piece_tree::PieceTree;
 
 /// Search options for find and replace operations
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,18 +18,28 @@ pub struct SearchOptions {
     /// Match case (default: false)
     #[serde(default)]
     pub case_sensitive: bool,
-    /// Match whole words only (default: false)
+    /// Match whole words only, so "cat" does not match inside "category" (default: false)
     #[serde(default)]
     pub whole_word: bool,
-    /// Treat query as regular expression (default: false)
+    /// Treat query as regular expression; replacements may reference its
+    /// capture groups as `$1` or `${1}` (default: false)
     #[serde(default)]
     pub regex: bool,
+    /// Treat `?` in the query as any character and `*` as any run of characters
+    /// within a line, as in Word's wildcard find; ignored when `regex` is set.
+    /// Replacements are inserted literally (default: false)
+    #[serde(default)]
+    pub wildcard: bool,
     /// Continue from beginning when reaching end (default: true)
     #[serde(default = "default_wrap")]
     pub wrap_around: bool,
     /// Search backward (upward) (default: false)
     #[serde(default)]
     pub search_backward: bool,
+    /// Ignore accents and other combining marks, so "resume" finds "résumé";
+    /// plain queries only (default: false)
+    #[serde(default)]
+    pub ignore_diacritics: bool,
 }
 
 fn default_wrap() -> bool {
@@ -37,12 +54,64 @@ impl Default for SearchOptions {
             case_sensitive: false,
             whole_word: false,
             regex: false,
+            wildcard: false,
             wrap_around: true,
             search_backward: false,
+            ignore_diacritics: false,
+        }
+    }
+}
+
+/// How a query is matched against the text, as selected by [`SearchOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Literal text, compared after case and diacritic folding
+    Plain,
+    /// Regular expression with capture groups
+    Regex,
+    /// `?` and `*` wildcards, everything else literal
+    Wildcard,
+}
+
+impl SearchOptions {
+    /// The matching mode the `regex` and `wildcard` flags select
+    pub fn mode(&self) -> SearchMode {
+        if self.regex {
+            SearchMode::Regex
+        } else if self.wildcard {
+            SearchMode::Wildcard
+        } else {
+            SearchMode::Plain
+        }
+    }
+
+    /// The regex pattern a regex or wildcard query compiles to; `None` for plain queries
+    fn pattern(&self) -> Option<Cow<'_, str>> {
+        match self.mode() {
+            SearchMode::Plain => None,
+            SearchMode::Regex => Some(Cow::Borrowed(&self.query)),
+            SearchMode::Wildcard => Some(Cow::Owned(wildcard_pattern(&self.query))),
         }
     }
 }
 
+/// Errors raised while preparing a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindError {
+    /// The query is not a valid regular expression
+    InvalidPattern(String),
+}
+
+impl std::fmt::Display for FindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindError::InvalidPattern(msg) => write!(f, "Invalid pattern: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FindError {}
+
 /// Result of a single search match
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -52,6 +121,10 @@ pub struct SearchResult {
     pub end: usize,
     /// The actual matched text
     pub matched_text: String,
+    /// Capture groups of a regex match, group 0 being the whole match;
+    /// empty for plain searches
+    #[serde(default)]
+    pub captures: Vec<Option<String>>,
 }
 
 impl SearchResult {
@@ -61,6 +134,21 @@ impl SearchResult {
             start,
             end,
             matched_text,
+            captures: Vec::new(),
+        }
+    }
+
+    /// Creates a search result from a regex match, offset by `base` bytes
+    fn from_captures(captures: &regex::Captures, base: usize) -> Self {
+        let whole = captures.get(0).expect("group 0 always participates");
+        SearchResult {
+            start: base + whole.start(),
+            end: base + whole.end(),
+            matched_text: whole.as_str().to_string(),
+            captures: captures
+                .iter()
+                .map(|group| group.map(|m| m.as_str().to_string()))
+                .collect(),
         }
     }
 
@@ -68,6 +156,108 @@ impl SearchResult {
     pub fn length(&self) -> usize {
         self.end - self.start
     }
+
+    /// Returns the match with up to `radius_chars` characters of `text` on
+    /// either side, for listing matches in a results panel.
+    ///
+    /// Context never crosses a line break or the document edges. Returns
+    /// `None` if the match no longer lies on character boundaries of `text`.
+    pub fn context(&self, text: &str, radius_chars: usize) -> Option<MatchContext> {
+        text.get(self.start..self.end)?;
+
+        let before = &text[..self.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let from = before[line_start..]
+            .char_indices()
+            .rev()
+            .take(radius_chars)
+            .last()
+            .map_or(self.start, |(i, _)| line_start + i);
+
+        let after = &text[self.end..];
+        let line_end = after.find(['\r', '\n']).unwrap_or(after.len());
+        let to = self.end + after[..line_end].char_indices().nth(radius_chars).map_or(line_end, |(i, _)| i);
+
+        Some(MatchContext {
+            snippet: text[from..to].to_string(),
+            match_start: self.start - from,
+            match_end: self.end - from,
+            clipped_before: from > line_start,
+            clipped_after: to < self.end + line_end,
+            offset: self.start,
+        })
+    }
+
+    /// Expands `$n` and `${n}` in `replacement` to this match's capture
+    /// groups, and `$$` to a literal `$`. Groups that did not participate
+    /// expand to nothing; without captures the replacement is returned as is.
+    pub fn expand_replacement(&self, replacement: &str) -> String {
+        if self.captures.is_empty() {
+            return replacement.to_string();
+        }
+
+        let mut expanded = String::with_capacity(replacement.len());
+        let mut rest = replacement;
+        while let Some(dollar) = rest.find('$') {
+            expanded.push_str(&rest[..dollar]);
+            rest = &rest[dollar + 1..];
+
+            let (digits, consumed) = if let Some(braced) = rest.strip_prefix('{') {
+                match braced.find('}') {
+                    Some(close) if close > 0 && braced[..close].bytes().all(|b| b.is_ascii_digit()) => {
+                        (&braced[..close], close + 2)
+                    }
+                    _ => ("", 0),
+                }
+            } else {
+                let len = rest.bytes().take_while(u8::is_ascii_digit).count();
+                (&rest[..len], len)
+            };
+
+            if rest.starts_with('$') {
+                expanded.push('$');
+                rest = &rest[1..];
+            } else if consumed == 0 {
+                expanded.push('$');
+            } else {
+                let group = digits.parse::<usize>().ok().and_then(|i| self.captures.get(i));
+                if let Some(Some(text)) = group {
+                    expanded.push_str(text);
+                }
+                rest = &rest[consumed..];
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+}
+
+/// A single replacement planned by [`SearchResultSet::plan_replace_all`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaceEdit {
+    /// Start of the replaced range (byte offset)
+    pub offset: usize,
+    /// Length of the replaced range in bytes
+    pub length: usize,
+    /// Text to put in place of the range
+    pub replacement: String,
+}
+
+/// A match shown with its surrounding text, from [`SearchResult::context`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchContext {
+    /// The match and the text around it on the same line
+    pub snippet: String,
+    /// Start of the match within `snippet` (byte offset)
+    pub match_start: usize,
+    /// End of the match within `snippet` (byte offset)
+    pub match_end: usize,
+    /// Whether the line continues before the snippet, e.g. to show an ellipsis
+    pub clipped_before: bool,
+    /// Whether the line continues after the snippet
+    pub clipped_after: bool,
+    /// Start of the match in the document (byte offset)
+    pub offset: usize,
 }
 
 /// Collection of all search results
@@ -82,6 +272,9 @@ pub struct SearchResultSet {
     /// Currently selected match index
     #[serde(default)]
     pub current_index: Option<usize>,
+    /// Options the set was built with, used to refine it incrementally
+    #[serde(skip)]
+    options: Option<SearchOptions>,
 }
 
 impl SearchResultSet {
@@ -91,6 +284,7 @@ impl SearchResultSet {
             results: Vec::new(),
             total_count: 0,
             current_index: None,
+            options: None,
         }
     }
 
@@ -101,6 +295,7 @@ impl SearchResultSet {
             results,
             total_count,
             current_index: None,
+            options: None,
         }
     }
 
@@ -118,26 +313,222 @@ impl SearchResultSet {
     pub fn is_empty(&self) -> bool {
         self.results.is_empty()
     }
+
+    /// Finds all matches of `options` in `text`, scanning forward once.
+    ///
+    /// A regex query is compiled once for the whole scan; an invalid pattern
+    /// is reported as [`FindError::InvalidPattern`].
+    pub fn build(text: &str, options: &SearchOptions) -> Result<Self, FindError> {
+        let mut set = Self::scan(text, options)?;
+        set.options = Some(options.clone());
+        Ok(set)
+    }
+
+    fn scan(text: &str, options: &SearchOptions) -> Result<Self, FindError> {
+        if options.query.is_empty() || text.is_empty() {
+            return Ok(SearchResultSet::new());
+        }
+
+        if let Some(pattern) = options.pattern() {
+            let re = build_regex(&pattern, options.case_sensitive, options.whole_word)?;
+            let results = re
+                .captures_iter(text)
+                .map(|captures| SearchResult::from_captures(&captures, 0))
+                .map(|result| without_wildcard_captures(result, options))
+                .collect();
+            return Ok(SearchResultSet::from_results(results));
+        }
+
+        let mut results = Vec::new();
+        let mut pos = 0usize;
+        while let Some(result) = simple_search(
+            text,
+            &options.query,
+            pos,
+            Folding::from(options),
+            options.whole_word,
+            false,
+            false,
+        ) {
+            pos = result.end;
+            results.push(result);
+        }
+
+        Ok(SearchResultSet::from_results(results))
+    }
+
+    /// Returns every match with up to `radius_chars` characters of context,
+    /// in result order; see [`SearchResult::context`]. Matches that no longer
+    /// fit `text` are skipped.
+    pub fn with_context(&self, text: &str, radius_chars: usize) -> Vec<MatchContext> {
+        self.results
+            .iter()
+            .filter_map(|result| result.context(text, radius_chars))
+            .collect()
+    }
+
+    /// Plans replacing every match with `replacement` without touching the text.
+    ///
+    /// Capture references are expanded per match. Edits come in ascending
+    /// offset order and never overlap (a match overlapping an earlier one is
+    /// skipped), so applying them back to front keeps every offset valid.
+    pub fn plan_replace_all(&self, replacement: &str) -> Vec<ReplaceEdit> {
+        let mut matches: Vec<&SearchResult> = self.results.iter().collect();
+        matches.sort_by_key(|result| (result.start, result.end));
+
+        let mut edits: Vec<ReplaceEdit> = Vec::with_capacity(matches.len());
+        for result in matches {
+            if edits.last().is_some_and(|last| result.start < last.offset + last.length) {
+                continue;
+            }
+            edits.push(ReplaceEdit {
+                offset: result.start,
+                length: result.length(),
+                replacement: result.expand_replacement(replacement),
+            });
+        }
+        edits
+    }
+
+    /// Finds all matches of `options` in `tree` without materializing its text.
+    ///
+    /// Plain queries stream the tree's chunks through a buffer holding just
+    /// enough of the preceding text to catch matches that straddle piece
+    /// boundaries; offsets are document-absolute. Regex queries cannot be
+    /// streamed and search the whole text.
+    pub fn search_piece_tree(tree: &PieceTree, options: &SearchOptions) -> Result<Self, FindError> {
+        if options.mode() != SearchMode::Plain {
+            return SearchResultSet::build(&tree.get_text(), options);
+        }
+
+        let query = &options.query;
+        // A match spans at most as many characters as the folded query has
+        let folding = Folding::from(options);
+        let window = fold_query(query, folding).chars().count();
+        let mut results = Vec::new();
+        let mut buffer = String::new();
+        // Document offset of `buffer`, and the buffer offset the next match may start at
+        let mut base = 0usize;
+        let mut next = 0usize;
+
+        let mut chunks = tree.chunks().peekable();
+        while let Some(chunk) = chunks.next() {
+            buffer.push_str(chunk);
+            let last = chunks.peek().is_none();
+
+            // A match touching the buffer end may still fail the whole-word check
+            while let Some(result) = simple_search(&buffer, query, next, folding, options.whole_word, false, false) {
+                if !last && result.end >= buffer.len() {
+                    break;
+                }
+                next = result.end;
+                results.push(SearchResult::new(base + result.start, base + result.end, result.matched_text));
+            }
+            if last {
+                break;
+            }
+
+            // Keep where a later match may start, plus one character of context before it
+            let keep = next.max(buffer.char_indices().nth_back(window.saturating_sub(1)).map_or(0, |(i, _)| i));
+            next = keep;
+            let drain = buffer[..keep].char_indices().next_back().map_or(0, |(i, _)| i);
+            buffer.drain(..drain);
+            base += drain;
+            next -= drain;
+        }
+
+        let mut set = SearchResultSet::from_results(results);
+        set.options = Some(options.clone());
+        Ok(set)
+    }
+
+    /// Re-runs this search on `text` with a new `query`, as when the user
+    /// types another character.
+    ///
+    /// When `query` extends the previous plain query, only the positions of
+    /// the existing matches (and the occurrences they overlapped) are checked
+    /// instead of rescanning `text`, which must be unchanged since the set
+    /// was built. Otherwise, and for regex or whole-word searches, this falls
+    /// back to a full search. Yields the same matches as a fresh search,
+    /// except where a character of `text` folds to several (such as the "ﬅ"
+    /// ligature) and only the longer query covers all of it.
+    pub fn refine(&self, text: &str, query: &str) -> SearchResultSet {
+        let mut options = self.options.clone().unwrap_or_default();
+        let previous = std::mem::replace(&mut options.query, query.to_string());
+
+        let extends = self.options.is_some()
+            && options.mode() == SearchMode::Plain
+            && !options.whole_word
+            && !previous.is_empty()
+            && query.len() > previous.len()
+            && query.starts_with(&previous);
+        if !extends {
+            return SearchResultSet::build(text, &options).unwrap_or_default();
+        }
+
+        // Every match of the longer query starts at an occurrence of the old one:
+        // either an existing match or one skipped because a match overlapped it
+        let folding = Folding::from(&options);
+        let folded_query = fold_query(query, folding);
+        let matches_at = |pos: usize| {
+            let end = if folding.is_exact() {
+                let end = pos + query.len();
+                (text.get(pos..end)? == query).then_some(end)?
+            } else {
+                pos + folded_prefix_len(&text[pos..], &folded_query, folding)?
+            };
+            Some(SearchResult::new(pos, end, text[pos..end].to_string()))
+        };
+
+        let mut results: Vec<SearchResult> = Vec::new();
+        for old in &self.results {
+            let overlapped = (old.start..old.end).filter(|&pos| text.is_char_boundary(pos));
+            for pos in overlapped {
+                if results.last().is_some_and(|last| pos < last.end) {
+                    continue;
+                }
+                if let Some(result) = matches_at(pos) {
+                    results.push(result);
+                }
+            }
+        }
+
+        let mut set = SearchResultSet::from_results(results);
+        set.options = Some(options);
+        set
+    }
 }
 
-/// Helper function to check if a position is at a word boundary
+/// Helper function to check if a position is at a word boundary, i.e. it
+/// does not fall inside a word. A word is a run of Unicode letters, digits
+/// and underscores; a position splitting a grapheme cluster (such as a letter
+/// and its combining accent) is never a boundary.
 fn is_word_boundary(text: &str, byte_offset: usize) -> bool {
     if byte_offset == 0 || byte_offset >= text.len() {
         return true;
     }
 
-    let prev_char = text[..byte_offset].chars().last();
-    let next_char = text[byte_offset..].chars().next();
+    let is_grapheme_boundary = GraphemeCursor::new(byte_offset, text.len(), true)
+        .is_boundary(text, 0)
+        .unwrap_or(true);
+    if !is_grapheme_boundary {
+        return false;
+    }
+
+    let prev_is_word = text[..byte_offset].chars().next_back().is_some_and(is_word_char);
+    let next_is_word = text[byte_offset..].chars().next().is_some_and(is_word_char);
 
-    let prev_is_word = prev_char.map(is_word_char).unwrap_or(false);
-    let next_is_word = next_char.map(is_word_char).unwrap_or(false);
+    !(prev_is_word && next_is_word)
+}
 
-    prev_is_word != next_is_word
+/// Checks if `text[start..end]` is a whole word: neither end cuts into a word
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    is_word_boundary(text, start) && is_word_boundary(text, end)
 }
 
-/// Check if a character is a word character (letter, digit, or underscore)
+/// Check if a character is a word character (letter or digit in any script, or underscore)
 fn is_word_char(c: char) -> bool {
-    c.is_alphanumeric() || c == '_' || c.is_whitespace() == false && c != '\n' && c != '\r' && c != '\t'
+    c.is_alphanumeric() || c == '_'
 }
 
 /// Helper function to escape regex special characters if not using regex mode
@@ -155,12 +546,153 @@ fn escape_regex_pattern(query: &str) -> String {
     result
 }
 
-/// Performs a simple (non-regex) search with options
+/// Translates a wildcard query into a regex: `?` matches one character and `*`
+/// the shortest run of characters that completes the match
+fn wildcard_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len());
+    let mut literal = String::new();
+    for c in query.chars() {
+        let wildcard = match c {
+            '?' => ".",
+            '*' => ".*?",
+            _ => {
+                literal.push(c);
+                continue;
+            }
+        };
+        pattern.push_str(&regex::escape(&literal));
+        literal.clear();
+        pattern.push_str(wildcard);
+    }
+    pattern.push_str(&regex::escape(&literal));
+    pattern
+}
+
+/// Drops the capture groups of a wildcard match, so `$` in its replacement stays literal
+fn without_wildcard_captures(mut result: SearchResult, options: &SearchOptions) -> SearchResult {
+    if options.mode() == SearchMode::Wildcard {
+        result.captures.clear();
+    }
+    result
+}
+
+/// How text and query are normalized before plain-text matching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Folding {
+    /// Compare full Unicode case folds
+    ignore_case: bool,
+    /// Decompose to NFD and drop combining marks
+    ignore_diacritics: bool,
+}
+
+impl Folding {
+    fn new(case_sensitive: bool, ignore_diacritics: bool) -> Self {
+        Folding { ignore_case: !case_sensitive, ignore_diacritics }
+    }
+
+    /// Whether text is compared as-is
+    fn is_exact(self) -> bool {
+        !self.ignore_case && !self.ignore_diacritics
+    }
+
+    /// Appends the folded form of `chars` to `out`
+    fn fold_into(self, chars: impl Iterator<Item = char>, out: &mut String) {
+        let is_base = |c: &char| !is_combining_mark(*c);
+        match (self.ignore_case, self.ignore_diacritics) {
+            // Case folding can introduce marks again ("İ" folds to "i" and a dot above)
+            (true, true) => out.extend(chars.nfd().filter(is_base).default_case_fold().nfd().filter(is_base)),
+            (true, false) => out.extend(chars.default_case_fold()),
+            (false, true) => out.extend(chars.nfd().filter(is_base)),
+            (false, false) => out.extend(chars),
+        }
+    }
+}
+
+impl From<&SearchOptions> for Folding {
+    fn from(options: &SearchOptions) -> Self {
+        Folding::new(options.case_sensitive, options.ignore_diacritics)
+    }
+}
+
+/// Folds `query` for comparison, borrowing it when the search is exact
+fn fold_query(query: &str, folding: Folding) -> Cow<'_, str> {
+    if folding.is_exact() {
+        return Cow::Borrowed(query);
+    }
+    let mut folded = String::with_capacity(query.len());
+    folding.fold_into(query.chars(), &mut folded);
+    Cow::Owned(folded)
+}
+
+/// Byte length of the prefix of `text` whose fold is exactly `folded_query`
+fn folded_prefix_len(text: &str, folded_query: &str, folding: Folding) -> Option<usize> {
+    let mut folded = String::new();
+    for (offset, c) in text.char_indices() {
+        folding.fold_into(iter::once(c), &mut folded);
+        if !folded_query.starts_with(folded.as_str()) {
+            return None;
+        }
+        if folded.len() == folded_query.len() {
+            return Some(offset + c.len_utf8());
+        }
+    }
+    None
+}
+
+/// Text prepared for matching, mapping its byte offsets back to the original.
+///
+/// Case-insensitive searches compare full Unicode case folds ("ß" folds to
+/// "ss", "ς" to "σ") and diacritic-insensitive ones drop combining marks, both
+/// of which can change byte lengths, so `starts[i]` records the original
+/// offset of the character whose fold produced byte `i`. A character folding
+/// to nothing, such as a lone combining accent, joins the character before it.
+struct SearchText<'a> {
+    text: Cow<'a, str>,
+    starts: Option<Vec<usize>>,
+}
+
+impl<'a> SearchText<'a> {
+    fn new(text: &'a str, folding: Folding) -> Self {
+        if folding.is_exact() {
+            return SearchText { text: Cow::Borrowed(text), starts: None };
+        }
+
+        let mut folded = String::with_capacity(text.len());
+        let mut starts = Vec::with_capacity(text.len() + 1);
+        for (offset, c) in text.char_indices() {
+            folding.fold_into(iter::once(c), &mut folded);
+            starts.resize(folded.len(), offset);
+        }
+        starts.push(text.len());
+        SearchText { text: Cow::Owned(folded), starts: Some(starts) }
+    }
+
+    /// Original offset of byte `pos` of the searched text
+    fn original(&self, pos: usize) -> usize {
+        self.starts.as_ref().map_or(pos, |starts| starts[pos])
+    }
+
+    /// Searched-text offset of the first character at or after original offset `pos`
+    fn folded(&self, pos: usize) -> usize {
+        self.starts.as_ref().map_or(pos, |starts| starts.partition_point(|&start| start < pos))
+    }
+
+    /// Whether byte `pos` of the searched text falls between two original
+    /// characters, so a match may start or end there
+    fn is_boundary(&self, pos: usize) -> bool {
+        self.starts.as_ref().is_none_or(|starts| pos == 0 || starts[pos - 1] != starts[pos])
+    }
+}
+
+/// Performs a simple (non-regex) search comparing `folding` forms of text and query.
+///
+/// Case-insensitive matching uses full Unicode case folding; matches must
+/// cover whole characters of `text` and are reported at their offsets there.
 fn simple_search(
     text: &str,
     query: &str,
     from: usize,
-    case_sensitive: bool,
+    folding: Folding,
     whole_word: bool,
     wrap_around: bool,
     backward: bool,
@@ -169,87 +701,87 @@ fn simple_search(
         return None;
     }
 
-    let search_text = if case_sensitive {
-        text.to_string()
-    } else {
-        text.to_lowercase()
-    };
-
-    let search_query = if case_sensitive {
-        query.to_string()
-    } else {
-        query.to_lowercase()
-    };
+    let haystack = SearchText::new(text, folding);
+    let search_text: &str = &haystack.text;
+    let search_query = fold_query(query, folding);
+    let search_query: &str = &search_query;
 
     let query_bytes = search_query.len();
+    let last_char_len = search_query.chars().next_back().map_or(1, char::len_utf8);
 
-    if backward {
-        // Search backward
-        let search_range = if from == 0 {
-            0..0
-        } else {
-            0..from
-        };
+    // Maps a match in the searched text back to `text`, if it is usable there
+    let accept = |pos: usize| {
+        let end_pos = pos + query_bytes;
+        if !haystack.is_boundary(pos) || !haystack.is_boundary(end_pos) {
+            return None;
+        }
+        let (start, end) = (haystack.original(pos), haystack.original(end_pos));
+        if whole_word && !is_whole_word(text, start, end) {
+            return None;
+        }
+        Some(SearchResult::new(start, end, text[start..end].to_string()))
+    };
 
-        // If wrap_around is enabled and we don't find anything in the backward search,
-        // wrap around to the end
-        let mut found = None;
-        let mut start_pos = search_range.start;
-
-        while start_pos < search_range.end {
-            if let Some(pos) = search_text[start_pos..search_range.end].rfind(&search_query) {
-                let absolute_pos = start_pos + pos;
-                
-                // Check whole word boundary
-                if whole_word && !is_word_boundary(text, absolute_pos) {
-                    // Not a word boundary, continue searching before this match
-                    if absolute_pos == 0 {
-                        break;
-                    }
-                    start_pos = text[..absolute_pos].len().saturating_sub(1);
-                    continue;
-                }
+    if backward {
+        // Find the last match starting before `from`
+        let mut search_end = (haystack.folded(from) + query_bytes - 1).min(search_text.len());
+        while !search_text.is_char_boundary(search_end) {
+            search_end -= 1;
+        }
 
-                let end_pos = absolute_pos + query_bytes;
-                let matched_text = text[absolute_pos..end_pos].to_string();
-                found = Some(SearchResult::new(absolute_pos, end_pos, matched_text));
-                break;
+        while let Some(absolute_pos) = search_text[..search_end].rfind(search_query) {
+            if let Some(result) = accept(absolute_pos) {
+                return Some(result);
             }
-            break;
+            // Not usable, continue searching before this match
+            search_end = absolute_pos + query_bytes - last_char_len;
         }
 
-        if found.is_none() && wrap_around && from > 0 {
+        if wrap_around && from > 0 {
             // Wrap around to end and search backward
-            return simple_search(text, query, text.len(), case_sensitive, whole_word, false, true);
+            return simple_search(text, query, text.len(), folding, whole_word, false, true);
         }
 
-        found
+        None
     } else {
         // Search forward
-        let search_start = from.min(text.len());
-        let remaining = &search_text[search_start..];
+        let mut search_start = haystack.folded(from.min(text.len()));
 
-        if let Some(pos) = remaining.find(&search_query) {
+        while let Some(pos) = search_text[search_start..].find(search_query) {
             let absolute_pos = search_start + pos;
-            
-            // Check whole word boundary
-            if whole_word && !is_word_boundary(text, absolute_pos) {
-                // Not a word boundary, continue searching after this match
-                return simple_search(text, query, absolute_pos + 1, case_sensitive, whole_word, false, backward);
+            if let Some(result) = accept(absolute_pos) {
+                return Some(result);
             }
+            // Not usable, continue searching after this match's first character
+            search_start = absolute_pos + search_text[absolute_pos..].chars().next().map_or(1, char::len_utf8);
+        }
 
-            let end_pos = absolute_pos + query_bytes;
-            let matched_text = text[absolute_pos..end_pos].to_string();
-            Some(SearchResult::new(absolute_pos, end_pos, matched_text))
-        } else if wrap_around && from > 0 {
+        if wrap_around && from > 0 {
             // Wrap around to beginning
-            simple_search(text, query, 0, case_sensitive, whole_word, false, backward)
+            simple_search(text, query, 0, folding, whole_word, false, backward)
         } else {
             None
         }
     }
 }
 
+/// Compiles a search query into a regex with the requested flags
+fn build_regex(query: &str, case_sensitive: bool, whole_word: bool) -> Result<Regex, FindError> {
+    let re_str = if whole_word {
+        format!(r"\b(?:{})\b", query)
+    } else {
+        query.to_string()
+    };
+
+    let re_str = if case_sensitive {
+        re_str
+    } else {
+        format!(r"(?i){}", re_str)
+    };
+
+    Regex::new(&re_str).map_err(|err| FindError::InvalidPattern(err.to_string()))
+}
+
 /// Performs a regex search with options
 fn regex_search(
     text: &str,
@@ -264,81 +796,41 @@ fn regex_search(
         return None;
     }
 
-    // Build regex with appropriate flags
-    let re_str = if whole_word {
-        format!(r"\b{}\b", query)
-    } else {
-        query.to_string()
-    };
-
-    let re_str = if case_sensitive {
-        re_str
-    } else {
-        format!(r"(?i){}", re_str)
-    };
-
-    let re = match Regex::new(&re_str) {
-        Ok(r) => r,
-        Err(_) => return None,
-    };
-
-    let search_start = from.min(text.len());
+    let re = build_regex(query, case_sensitive, whole_word).ok()?;
+    let mut search_start = from.min(text.len());
+    while !text.is_char_boundary(search_start) {
+        search_start -= 1;
+    }
 
     if backward {
-        // Find all matches and get the last one before 'from'
-        let mut matches: Vec<regex::Match> = re.find_iter(text).collect();
-        
-        // Filter to only matches before 'from'
-        matches.retain(|m| m.start() < search_start);
-        
-        if let Some(m) = matches.last() {
-            Some(SearchResult::new(
-                m.start(),
-                m.end(),
-                m.as_str().to_string(),
-            ))
-        } else if wrap_around && from > 0 {
-            // Wrap around to end and search backward
-            let all_matches: Vec<regex::Match> = re.find_iter(text).collect();
-            all_matches.last().map(|m| SearchResult::new(
-                m.start(),
-                m.end(),
-                m.as_str().to_string(),
-            ))
-        } else {
-            None
-        }
-    } else {
-        // Find the first match at or after 'from'
-        if let Some(m) = re.find(text) {
-            if m.start() >= search_start {
-                Some(SearchResult::new(
-                    m.start(),
-                    m.end(),
-                    m.as_str().to_string(),
-                ))
+        // Find the last match starting before 'from'
+        let before = |limit: usize| {
+            re.captures_iter(text)
+                .take_while(|c| c.get(0).is_some_and(|m| m.start() < limit))
+                .last()
+                .map(|c| SearchResult::from_captures(&c, 0))
+        };
+
+        before(search_start).or_else(|| {
+            if wrap_around && from > 0 {
+                // Wrap around to end and search backward
+                before(usize::MAX)
             } else {
-                // Need to find next match
-                let remaining = &text[search_start..];
-                re.find(remaining).map(|m: regex::Match| {
-                    let absolute_pos = search_start + m.start();
-                    SearchResult::new(
-                        absolute_pos,
-                        absolute_pos + m.end() - m.start(),
-                        m.as_str().to_string(),
-                    )
-                })
+                None
             }
-        } else if wrap_around && from > 0 {
-            // Wrap around to beginning
-            re.find(text).map(|m: regex::Match| SearchResult::new(
-                m.start(),
-                m.end(),
-                m.as_str().to_string(),
-            ))
-        } else {
-            None
-        }
+        })
+    } else {
+        // Find the first match at or after 'from'
+        re.captures_at(text, search_start)
+            .map(|c| SearchResult::from_captures(&c, 0))
+            .or_else(|| {
+                if wrap_around && from > 0 {
+                    // Wrap around to beginning
+                    re.captures(text).map(|c| SearchResult::from_captures(&c, 0))
+                } else {
+                    None
+                }
+            })
     }
 }
 
@@ -352,22 +844,23 @@ pub fn search(
         return None;
     }
 
-    if options.regex {
+    if let Some(pattern) = options.pattern() {
         regex_search(
             text,
-            &options.query,
+            &pattern,
             from,
             options.case_sensitive,
             options.whole_word,
             options.wrap_around,
             options.search_backward,
         )
+        .map(|result| without_wildcard_captures(result, options))
     } else {
         simple_search(
             text,
             &options.query,
             from,
-            options.case_sensitive,
+            Folding::from(options),
             options.whole_word,
             options.wrap_around,
             options.search_backward,
@@ -375,29 +868,9 @@ pub fn search(
     }
 }
 
-/// Finds all matches in text
+/// Finds all matches in text; an invalid regex pattern finds nothing
 pub fn find_all_in_text(text: &str, options: &SearchOptions) -> SearchResultSet {
-    if options.query.is_empty() || text.is_empty() {
-        return SearchResultSet::new();
-    }
-
-    let mut results = Vec::new();
-    let mut pos = 0usize;
-
-    while let Some(result) = search(text, options, pos) {
-        results.push(result.clone());
-        pos = result.end;
-        
-        // Avoid infinite loop for empty matches
-        if result.length() == 0 {
-            pos = pos.saturating_add(1);
-            if pos >= text.len() {
-                break;
-            }
-        }
-    }
-
-    SearchResultSet::from_results(results)
+    SearchResultSet::build(text, options).unwrap_or_default()
 }
 
 /// Applies regex replacement with capture groups
@@ -413,6 +886,19 @@ pub fn apply_regex_replacement(text: &str, pattern: &str, replacement: &str) ->
 mod tests {
     use super::*;
 
+    /// Plain search with only case folding, as most tests need
+    fn simple_search(
+        text: &str,
+        query: &str,
+        from: usize,
+        case_sensitive: bool,
+        whole_word: bool,
+        wrap_around: bool,
+        backward: bool,
+    ) -> Option<SearchResult> {
+        super::simple_search(text, query, from, Folding::new(case_sensitive, false), whole_word, wrap_around, backward)
+    }
+
     #[test]
     fn test_search_options_default() {
         let options = SearchOptions::default();
@@ -529,6 +1015,333 @@ mod tests {
         assert_eq!(results.results[1].start, 12);
     }
 
+    #[test]
+    fn test_regex_digits() {
+        let options = SearchOptions {
+            query: r"\d+".to_string(),
+            regex: true,
+            ..Default::default()
+        };
+
+        let results = SearchResultSet::build("a1 b22 c333", &options).unwrap();
+        let matched: Vec<&str> = results.results.iter().map(|r| r.matched_text.as_str()).collect();
+        assert_eq!(matched, vec!["1", "22", "333"]);
+        assert_eq!((results.results[1].start, results.results[1].end), (4, 6));
+    }
+
+    #[test]
+    fn test_regex_capture_replacement() {
+        let options = SearchOptions {
+            query: r"(\w+)@(\w+)".to_string(),
+            regex: true,
+            ..Default::default()
+        };
+
+        let results = SearchResultSet::build("mail alice@example now", &options).unwrap();
+        let result = &results.results[0];
+        assert_eq!(result.captures, vec![
+            Some("alice@example".to_string()),
+            Some("alice".to_string()),
+            Some("example".to_string()),
+        ]);
+        assert_eq!(result.expand_replacement("$2 at ${1}, $$5"), "example at alice, $5");
+        assert_eq!(result.expand_replacement("$9|$|${x}"), "|$|${x}");
+
+        // Plain matches keep the replacement verbatim
+        assert_eq!(SearchResult::new(0, 1, "a".to_string()).expand_replacement("$1"), "$1");
+    }
+
+    #[test]
+    fn test_regex_invalid_pattern() {
+        let options = SearchOptions {
+            query: "(unclosed".to_string(),
+            regex: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            SearchResultSet::build("text", &options),
+            Err(FindError::InvalidPattern(_))
+        ));
+        assert!(find_all_in_text("text", &options).is_empty());
+        assert!(search("text", &options, 0).is_none());
+    }
+
+    #[test]
+    fn test_regex_search_from_offset() {
+        let text = "a1 b22 c333";
+        assert_eq!(regex_search(text, r"\d+", 2, true, false, false, false).map(|r| r.start), Some(4));
+        assert_eq!(regex_search(text, r"\d+", 8, true, false, false, true).map(|r| r.start), Some(4));
+        assert_eq!(regex_search(text, r"\d+", 11, true, false, true, false).map(|r| r.start), Some(1));
+        assert_eq!(regex_search(text, r"b(\d)", 0, true, false, false, false).map(|r| r.captures), Some(vec![
+            Some("b2".to_string()),
+            Some("2".to_string()),
+        ]));
+    }
+
+    fn plain(query: &str) -> SearchOptions {
+        SearchOptions {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive_full_folding() {
+        // "ß" folds to "ss", so the offsets cover the original two-byte "ß"
+        let text = "Die straße ist lang";
+        let result = simple_search(text, "STRASSE", 0, false, false, false, false).unwrap();
+        assert_eq!((result.start, result.end), (4, 11));
+        assert_eq!(result.matched_text, "straße");
+        let result = simple_search(text, "STRASSE", text.len(), false, false, false, true).unwrap();
+        assert_eq!(result.matched_text, "straße");
+
+        // Part of a folded character never matches
+        assert!(simple_search("straße", "stras", 0, false, false, false, false).is_none());
+
+        // Final sigma folds like medial sigma
+        let result = simple_search("ΟΔΟΣ οδος", "οδοσ", 0, false, false, false, false).unwrap();
+        assert_eq!(result.matched_text, "ΟΔΟΣ");
+        let result = simple_search("ΟΔΟΣ οδος", "οδοσ", result.end, false, false, false, false).unwrap();
+        assert_eq!(result.matched_text, "οδος");
+    }
+
+    #[test]
+    fn test_case_insensitive_dotted_capital_i() {
+        // Default (non-Turkic) folding maps "İ" to "i" plus a combining dot above
+        let text = "İstanbul, ISTANBUL";
+        let options = SearchOptions { query: "i\u{307}stanbul".to_string(), ..Default::default() };
+        let set = SearchResultSet::build(text, &options).unwrap();
+        assert_eq!(set.results.len(), 1);
+        assert_eq!((set.results[0].start, set.results[0].end), (0, 9));
+
+        // So "istanbul" only matches the dotless spelling, and "ı" stays distinct from "i"
+        let options = SearchOptions { query: "istanbul".to_string(), ..Default::default() };
+        let set = SearchResultSet::build(text, &options).unwrap();
+        assert_eq!(set.results.len(), 1);
+        assert_eq!(set.results[0].matched_text, "ISTANBUL");
+        assert!(simple_search("ıstanbul", "istanbul", 0, false, false, false, false).is_none());
+    }
+
+    #[test]
+    fn test_case_insensitive_folding_offsets_after_expansion() {
+        // Folding "İ" grows it by a byte; later matches must still use original offsets
+        let text = "İİ cat";
+        let result = simple_search(text, "CAT", 0, false, false, false, false).unwrap();
+        assert_eq!(&text[result.start..result.end], "cat");
+        assert_eq!(simple_search(text, "cat", result.start + 1, false, false, true, false).unwrap().start, result.start);
+
+        let tree = PieceTree::new(text.to_string());
+        let options = SearchOptions { query: "CAT".to_string(), ..Default::default() };
+        let set = SearchResultSet::search_piece_tree(&tree, &options).unwrap();
+        assert_eq!(set.results[0].start, result.start);
+
+        let set = SearchResultSet::build("STRASSE straße", &SearchOptions { query: "stra".to_string(), ..Default::default() }).unwrap();
+        let refined = set.refine("STRASSE straße", "strasse");
+        assert_eq!(refined.results.len(), 2);
+        assert_eq!(refined.results[1].matched_text, "straße");
+    }
+
+    #[test]
+    fn test_ignore_diacritics() {
+        let options = SearchOptions { query: "cafe".to_string(), ignore_diacritics: true, ..Default::default() };
+        let text = "Un café, un CAFÉ";
+        let set = SearchResultSet::build(text, &options).unwrap();
+        let matched: Vec<&str> = set.results.iter().map(|r| &text[r.start..r.end]).collect();
+        assert_eq!(matched, vec!["café", "CAFÉ"]);
+        assert_eq!((set.results[0].start, set.results[0].end), (3, 8));
+
+        // Accents in the query are ignored too
+        let options = SearchOptions { query: "résumé".to_string(), ..options };
+        assert_eq!(find_all_in_text("my resume", &options).results[0].matched_text, "resume");
+
+        // Without the option the accent must match
+        let plain = SearchOptions { query: "cafe".to_string(), ..Default::default() };
+        assert!(find_all_in_text("café", &plain).results.is_empty());
+    }
+
+    #[test]
+    fn test_ignore_diacritics_decomposed_text() {
+        // A combining accent stays part of the match that precedes it
+        let text = "cafe\u{301} noir";
+        let options = SearchOptions {
+            query: "CAFE".to_string(),
+            ignore_diacritics: true,
+            whole_word: true,
+            ..Default::default()
+        };
+        let result = search(text, &options, 0).unwrap();
+        assert_eq!((result.start, result.end), (0, 6));
+        assert_eq!(result.matched_text, "cafe\u{301}");
+
+        let options = SearchOptions { search_backward: true, ..options };
+        assert_eq!(search(text, &options, text.len()).unwrap().end, 6);
+
+        let tree = PieceTree::new(text.to_string());
+        let options = SearchOptions { query: "e".to_string(), ignore_diacritics: true, ..Default::default() };
+        let set = SearchResultSet::search_piece_tree(&tree, &options).unwrap();
+        assert_eq!(set.results.iter().map(|r| r.end).collect::<Vec<_>>(), vec![6]);
+    }
+
+    #[test]
+    fn test_refine_matches_fresh_search() {
+        let text = "fo foo food Foo fofoo f";
+        let set = SearchResultSet::build(text, &plain("fo")).unwrap();
+        assert_eq!(set.total_count, 6);
+
+        let refined = set.refine(text, "foo");
+        assert_eq!(refined, SearchResultSet::build(text, &plain("foo")).unwrap());
+        assert_eq!(refined.total_count, 4);
+
+        let refined = refined.refine(text, "food");
+        assert_eq!(refined.results.iter().map(|r| r.start).collect::<Vec<_>>(), vec![7]);
+    }
+
+    #[test]
+    fn test_refine_finds_overlapped_occurrences() {
+        // "aab" starts at 1, inside the first "aa" match
+        let text = "aaab aab";
+        let set = SearchResultSet::build(text, &plain("aa")).unwrap();
+        assert_eq!(set.results.iter().map(|r| r.start).collect::<Vec<_>>(), vec![0, 5]);
+
+        let refined = set.refine(text, "aab");
+        assert_eq!(refined, SearchResultSet::build(text, &plain("aab")).unwrap());
+        assert_eq!(refined.results.iter().map(|r| r.start).collect::<Vec<_>>(), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_refine_falls_back_to_full_search() {
+        let text = "foo fo food";
+        let set = SearchResultSet::build(text, &plain("foo")).unwrap();
+
+        // Deleting a character is not an extension
+        assert_eq!(set.refine(text, "fo"), SearchResultSet::build(text, &plain("fo")).unwrap());
+        assert_eq!(set.refine(text, "bar"), SearchResultSet::build(text, &plain("bar")).unwrap());
+
+        // Whole-word matches of "fo" are not a superset of those of "foo"
+        let options = SearchOptions { whole_word: true, ..plain("fo") };
+        let set = SearchResultSet::build(text, &options).unwrap();
+        let refined = set.refine(text, "foo");
+        assert_eq!(refined.results.iter().map(|r| r.start).collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_search_piece_tree_across_pieces() {
+        // Inserting in the middle of a word leaves it in three pieces
+        let mut tree = PieceTree::new("foobar bar".to_string());
+        tree.insert(3, "baz".to_string());
+        assert_eq!(tree.get_text(), "foobazbar bar");
+        assert!(tree.chunks().count() >= 3);
+
+        for query in ["obaz", "zb", "foobazbar", "bar", "azba", "r b", "nothing"] {
+            let options = plain(query);
+            let streamed = SearchResultSet::search_piece_tree(&tree, &options).unwrap();
+            assert_eq!(streamed, SearchResultSet::build(&tree.get_text(), &options).unwrap(), "query {:?}", query);
+        }
+
+        let streamed = SearchResultSet::search_piece_tree(&tree, &plain("zb")).unwrap();
+        assert_eq!((streamed.results[0].start, streamed.results[0].end), (5, 7));
+    }
+
+    #[test]
+    fn test_search_piece_tree_options() {
+        let mut tree = PieceTree::new("Cat category cat".to_string());
+        tree.insert(14, "|".to_string());
+        tree.insert(4, "ca".to_string());
+        let text = tree.get_text();
+
+        let whole_word = SearchOptions { whole_word: true, ..plain("cat") };
+        let case_sensitive = SearchOptions { case_sensitive: true, ..plain("cat") };
+        let regex = SearchOptions { regex: true, ..plain(r"c\w+") };
+        for options in [whole_word, case_sensitive, regex] {
+            let streamed = SearchResultSet::search_piece_tree(&tree, &options).unwrap();
+            assert_eq!(streamed, SearchResultSet::build(&text, &options).unwrap(), "{:?} in {:?}", options, text);
+        }
+    }
+
+    fn apply_back_to_front(text: &str, edits: &[ReplaceEdit]) -> String {
+        let mut text = text.to_string();
+        for edit in edits.iter().rev() {
+            text.replace_range(edit.offset..edit.offset + edit.length, &edit.replacement);
+        }
+        text
+    }
+
+    #[test]
+    fn test_plan_replace_all() {
+        let text = "one cat, two cats";
+        let set = SearchResultSet::build(text, &plain("cat")).unwrap();
+
+        let edits = set.plan_replace_all("dog");
+        assert_eq!(edits, vec![
+            ReplaceEdit { offset: 4, length: 3, replacement: "dog".to_string() },
+            ReplaceEdit { offset: 13, length: 3, replacement: "dog".to_string() },
+        ]);
+        assert_eq!(apply_back_to_front(text, &edits), "one dog, two dogs");
+
+        // Planning leaves the set untouched
+        assert_eq!(set.total_count, 2);
+        assert!(SearchResultSet::new().plan_replace_all("x").is_empty());
+    }
+
+    #[test]
+    fn test_plan_replace_all_captures_and_overlaps() {
+        let text = "2024-01-31 and 1999-12-25";
+        let options = SearchOptions { regex: true, ..plain(r"(\d+)-(\d+)-(\d+)") };
+        let edits = SearchResultSet::build(text, &options).unwrap().plan_replace_all("$3.$2.$1");
+        assert_eq!(apply_back_to_front(text, &edits), "31.01.2024 and 25.12.1999");
+
+        // Overlapping matches are skipped, out-of-order ones sorted
+        let set = SearchResultSet::from_results(vec![
+            SearchResult::new(4, 7, "bbb".to_string()),
+            SearchResult::new(0, 3, "aaa".to_string()),
+            SearchResult::new(2, 5, "aba".to_string()),
+        ]);
+        let edits = set.plan_replace_all("_");
+        assert_eq!(edits.iter().map(|e| e.offset).collect::<Vec<_>>(), vec![0, 4]);
+        assert_eq!(apply_back_to_front("aaa bbb", &edits), "_ _");
+    }
+
+    #[test]
+    fn test_whole_word_search() {
+        let options = SearchOptions {
+            query: "cat".to_string(),
+            whole_word: true,
+            ..Default::default()
+        };
+
+        let results = find_all_in_text("the cat sat", &options);
+        assert_eq!(results.total_count, 1);
+        assert_eq!((results.results[0].start, results.results[0].end), (4, 7));
+
+        assert!(find_all_in_text("category", &options).is_empty());
+        assert!(find_all_in_text("bobcat", &options).is_empty());
+        assert!(find_all_in_text("cat_food", &options).is_empty());
+        assert_eq!(find_all_in_text("cat, category, (cat)", &options).total_count, 2);
+    }
+
+    #[test]
+    fn test_whole_word_unicode() {
+        // Non-ASCII letters are word characters
+        assert!(simple_search("naïve", "na", 0, false, true, false, false).is_none());
+        assert!(simple_search("straße", "stra", 0, false, true, false, false).is_none());
+        assert_eq!(simple_search("über cat", "über", 0, false, true, false, false).map(|r| r.end), Some(5));
+
+        // A combining accent belongs to the preceding letter
+        assert!(simple_search("cafe\u{301}", "cafe", 0, false, true, false, false).is_none());
+        assert!(simple_search("cafe\u{301} cafe", "cafe", 0, false, true, false, false).is_some_and(|r| r.start == 7));
+    }
+
+    #[test]
+    fn test_whole_word_search_backward() {
+        let text = "cat category cat";
+        let result = simple_search(text, "cat", text.len(), false, true, false, true);
+        assert_eq!(result.map(|r| r.start), Some(13));
+        let result = simple_search(text, "cat", 13, false, true, false, true);
+        assert_eq!(result.map(|r| r.start), Some(0));
+        assert!(simple_search("category", "cat", 8, false, true, false, true).is_none());
+    }
+
     #[test]
     fn test_word_boundary() {
         assert!(is_word_boundary("hello", 0));
@@ -536,4 +1349,73 @@ mod tests {
         assert!(!is_word_boundary("hello", 2));
         assert!(is_word_boundary("hello world", 5));
     }
+
+    #[test]
+    fn test_match_context_near_document_start() {
+        let text = "Héllo wörld, the quick brown fox";
+        let set = SearchResultSet::build(text, &plain("wörld")).unwrap();
+
+        let contexts = set.with_context(text, 4);
+        assert_eq!(contexts.len(), 1);
+        let context = &contexts[0];
+        assert_eq!(context.snippet, "llo wörld, th");
+        assert_eq!(&context.snippet[context.match_start..context.match_end], "wörld");
+        assert_eq!((context.match_start, context.match_end), (4, 10));
+        assert!(context.clipped_before);
+        assert!(context.clipped_after);
+        assert_eq!(context.offset, 7);
+
+        // A wide radius stops at the document start instead of running past it
+        let context = &set.with_context(text, 20)[0];
+        assert!(context.snippet.starts_with("Héllo "));
+        assert_eq!(context.match_start, 7);
+        assert!(!context.clipped_before);
+    }
+
+    #[test]
+    fn test_match_context_stops_at_line_breaks() {
+        let text = "first line\r\nthe cat sat\nlast";
+        let set = SearchResultSet::build(text, &plain("cat")).unwrap();
+
+        let context = &set.with_context(text, 50)[0];
+        assert_eq!(context.snippet, "the cat sat");
+        assert_eq!((context.match_start, context.match_end), (4, 7));
+        assert!(!context.clipped_before && !context.clipped_after);
+
+        // Stale results are skipped rather than sliced out of bounds
+        let stale = SearchResultSet::from_results(vec![SearchResult::new(40, 43, "cat".to_string())]);
+        assert!(stale.with_context(text, 5).is_empty());
+    }
+
+    fn wildcard(query: &str) -> SearchOptions {
+        SearchOptions { wildcard: true, ..plain(query) }
+    }
+
+    fn matched(text: &str, options: &SearchOptions) -> Vec<String> {
+        find_all_in_text(text, options).results.into_iter().map(|r| r.matched_text).collect()
+    }
+
+    #[test]
+    fn test_wildcard_question_mark_matches_one_character() {
+        assert_eq!(matched("cat cot cart ct", &wildcard("c?t")), ["cat", "cot"]);
+        assert_eq!(wildcard("c?t").mode(), SearchMode::Wildcard);
+    }
+
+    #[test]
+    fn test_wildcard_star_matches_any_run() {
+        assert_eq!(matched("carpet", &wildcard("c*t")), ["carpet"]);
+        // The shortest run wins, and runs stay within a line
+        assert_eq!(matched("cat sat\nct", &wildcard("c*t")), ["cat", "ct"]);
+        assert_eq!(search("the carpet", &wildcard("c*t"), 0).map(|r| r.start), Some(4));
+    }
+
+    #[test]
+    fn test_wildcard_escapes_regex_metacharacters() {
+        assert_eq!(matched("a.b axb (1+2)", &wildcard("a.b")), ["a.b"]);
+        assert_eq!(matched("(1+2) (3+4)", &wildcard("(?+?)")), ["(1+2)", "(3+4)"]);
+
+        // Replacements are literal, with no capture group expansion
+        let set = find_all_in_text("cat", &wildcard("c?t"));
+        assert_eq!(set.results[0].expand_replacement("$0$"), "$0$");
+    }
 }
@@ -7,17 +7,17 @@ pub mod text_shaping;
 pub mod page_layout;
 pub mod undo_redo;
 
-pub use piece_tree::{BufferId, Piece, PieceTree, TextAttributes};
-pub use line_breaking::{BreakType, Line, LineBreaker};
-pub use line_layout::{DocumentLayout, LineLayout, ParagraphLayout};
-pub use ooxml::{parse_ooxml, ParsedDocument, OoxmlError};
-pub use find::{SearchOptions, SearchResult, SearchResultSet};
-pub use page_layout::{PageConfig, PageLayout, RenderedPage, RenderedLine, Rect, PaginationConfig};
+pub use piece_tree::{BufferId, Piece, PieceTree, TextAttributes, UndoGranularity};
+pub use line_breaking::{BreakOpportunity, BreakType, Line, LineBreaker};
+pub use line_layout::{DocumentLayout, LineLayout, ParagraphLayout, PositionedRun};
+pub use ooxml::{parse_ooxml, parse_ooxml_with_progress, ParseProgress, ParsedDocument, OoxmlError};
+pub use find::{FindError, MatchContext, ReplaceEdit, SearchMode, SearchOptions, SearchResult, SearchResultSet};
+pub use page_layout::{PageConfig, PageLayout, RenderedPage, RenderedLine, Rect, PaginationConfig, VerticalAlign};
 pub use undo_redo::{
     Command, CommandError, CommandMetadata, CommandRecord,
-    InsertCommand, DeleteCommand,
-    UndoRedoManager, CommandExecution, OperationType,
-    DEFAULT_MAX_HISTORY_SIZE, DEFAULT_MERGE_WINDOW_MS,
+    InsertCommand, DeleteCommand, CompositeCommand,
+    UndoRedoManager, CommandExecution, HistoryStep, OperationType,
+    DEFAULT_MAX_HISTORY_SIZE, DEFAULT_MAX_HISTORY_BYTES, DEFAULT_MERGE_WINDOW_MS,
 };
 
 mod bridge_generated;
@@ -4,10 +4,49 @@
 //! This module provides efficient line breaking with hyphenation support.
 
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::Arc;
-use crate::text_shaping::TextShaper;
+use hyphenation::{Hyphenator, Language, Load, Standard};
+use once_cell::sync::Lazy;
+use crate::text_shaping::{CapsStyle, TextShaper};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Appended to a truncated line when the layout asks for an ellipsis
+pub const ELLIPSIS: &str = "\u{2026}";
+
+/// Hyphenation dictionary embedded by the hyphenation crate's `embed_en-us` feature
+static EN_US_DICTIONARY: Lazy<Option<Standard>> = Lazy::new(|| Standard::from_embedded(Language::EnglishUS).ok());
+
+/// Looks up the dictionary for a BCP 47 language tag (e.g. "en", "en-US", "en_us")
+fn hyphenation_dictionary(language: &str) -> Option<&'static Standard> {
+    match language.to_ascii_lowercase().replace('_', "-").as_str() {
+        "en" | "en-us" => EN_US_DICTIONARY.as_ref(),
+        _ => None,
+    }
+}
 
+/// Returns the byte offsets where `text` may be hyphenated in `language`
+///
+/// Only the dictionary lookup is performed; no width or break decision is made,
+/// so renderers that draw their own hyphens can choose among the offsets.
+/// Unsupported languages yield no points.
+pub fn hyphenation_points(text: &str, language: &str) -> Vec<usize> {
+    let dictionary = match hyphenation_dictionary(language) {
+        Some(dictionary) => dictionary,
+        None => return Vec::new(),
+    };
+
+    text.split_word_bound_indices()
+        .filter(|(_, word)| word.chars().all(char::is_alphabetic))
+        .flat_map(|(start, word)| {
+            dictionary
+                .hyphenate(word)
+                .breaks
+                .into_iter()
+                .map(move |offset| start + offset)
+        })
+        .collect()
+}
 /// Represents the type of line break
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BreakType {
@@ -19,6 +58,19 @@ pub enum BreakType {
     Hyphenated,
 }
 
+/// The kind of opportunity a soft break was taken at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakOpportunity {
+    /// After a space or tab; the trailing whitespace can be suppressed when justifying
+    Space,
+    /// After a hyphen or dash that stays visible at the line end
+    Hyphen,
+    /// Between ideographs (or an ideograph and other text), with no separator character
+    Ideographic,
+    /// After a separator inside a URL or file path; it stays visible and no hyphen is added
+    Separator,
+}
+
 /// Represents a single line after breaking
 #[derive(Debug, Clone)]
 pub struct Line {
@@ -30,6 +82,10 @@ pub struct Line {
     pub width: f32,
     /// Type of break that ended this line
     pub break_type: BreakType,
+    /// Opportunity a `SoftBreak` was taken at; None for other break types
+    pub opportunity: Option<BreakOpportunity>,
+    /// Whether text after this line was cut off by a line limit
+    pub truncated: bool,
 }
 
 impl Line {
@@ -41,9 +97,18 @@ impl Line {
             end,
             width,
             break_type,
+            opportunity: None,
+            truncated: false,
         }
     }
 
+    /// Sets the opportunity a soft break was taken at
+    #[inline]
+    pub fn with_opportunity(mut self, opportunity: Option<BreakOpportunity>) -> Self {
+        self.opportunity = opportunity;
+        self
+    }
+
     /// Returns the length of the line in characters
     #[inline]
     pub fn len(&self) -> usize {
@@ -77,12 +142,141 @@ const MAX_LOOKAHEAD: usize = 200;
 
 /// Penalties for various line break situations
 const PENALTY_HYPHEN: i32 = 50;
-const PENALTY_HARD: i32 = 100;
 
 /// Demerits multipliers
 const DEMERITS_FLAGGED: f32 = 100.0;
 const DEMERITS_DOUBLE: f32 = 50.0;
 const DEMERITS_HYPHEN: f32 = 30.0;
+const DEMERITS_OVERFULL: f32 = 1000.0;
+
+/// Line breaking classes from UAX #14, reduced to the ones the breaker distinguishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BreakClass {
+    /// Ordinary alphabetic text and symbols (AL)
+    Alphabetic,
+    /// Digits (NU)
+    Numeric,
+    /// Ideographs and kana; breakable on either side (ID)
+    Ideographic,
+    /// Space (SP)
+    Space,
+    /// Non-breaking glue such as U+00A0 NO-BREAK SPACE (GL)
+    Glue,
+    /// Opening punctuation; no break after (OP)
+    Open,
+    /// Closing punctuation and brackets; no break before (CL, CP)
+    Close,
+    /// Exclamation and interrogation; no break before (EX)
+    Exclamation,
+    /// Infix separators such as "." and ","; no break before (IS)
+    InfixSeparator,
+    /// Hyphen-minus (HY)
+    Hyphen,
+    /// Break opportunity after, e.g. tabs and dashes (BA)
+    BreakAfter,
+}
+
+/// Characters a URL or file path may break after when `LineBreakerConfig::break_urls` is set
+const URL_SEPARATORS: [char; 6] = ['/', '?', '&', '.', '-', '\\'];
+
+/// Checks whether a whitespace-delimited token looks like a URL or file path
+fn is_url_like(token: &str) -> bool {
+    token.contains("://")
+        || token.starts_with("www.")
+        || ["/", "./", "../", "~/"].iter().any(|prefix| token.starts_with(prefix))
+        || token.get(1..3) == Some(":\\")
+}
+
+/// Byte offsets just after the separators inside URL-like tokens of `text`
+///
+/// Runs of separators such as "://" stay together, and a token never breaks
+/// after its last character.
+fn url_break_positions(text: &str) -> Vec<usize> {
+    let mut positions = Vec::new();
+
+    for token in text.split(char::is_whitespace).filter(|token| is_url_like(token)) {
+        let token_start = token.as_ptr() as usize - text.as_ptr() as usize;
+        let mut chars = token.char_indices().peekable();
+
+        while let Some((_, ch)) = chars.next() {
+            let Some(&(next_index, next)) = chars.peek() else {
+                break;
+            };
+            if URL_SEPARATORS.contains(&ch) && !URL_SEPARATORS.contains(&next) {
+                positions.push(token_start + next_index);
+            }
+        }
+    }
+
+    positions
+}
+
+/// Classifies a character for line breaking
+pub(crate) fn break_class(ch: char) -> BreakClass {
+    match ch {
+        ' ' => BreakClass::Space,
+        '\u{00A0}' | '\u{202F}' | '\u{2007}' | '\u{2060}' | '\u{FEFF}' => BreakClass::Glue,
+        '(' | '[' | '{' => BreakClass::Open,
+        ')' | ']' | '}' => BreakClass::Close,
+        '!' | '?' => BreakClass::Exclamation,
+        ',' | '.' | ':' | ';' => BreakClass::InfixSeparator,
+        '-' => BreakClass::Hyphen,
+        '\t' | '\u{00AD}' | '\u{2010}' | '–' | '—' => BreakClass::BreakAfter,
+        '0'..='9' => BreakClass::Numeric,
+        // CJK brackets and punctuation (kinsoku line-start and line-end rules)
+        '（' | '〔' | '【' | '〈' | '《' | '「' | '『' | '〖' | '〘' | '［' | '｛' => BreakClass::Open,
+        '）' | '〕' | '】' | '〉' | '》' | '」' | '』' | '〗' | '〙' | '］' | '｝' => BreakClass::Close,
+        '，' | '。' | '．' | '、' | '：' | '；' => BreakClass::Close,
+        '？' | '！' => BreakClass::Exclamation,
+        _ if is_ideographic(ch) => BreakClass::Ideographic,
+        _ => BreakClass::Alphabetic,
+    }
+}
+
+/// Checks if a character is an ideograph, kana, hangul or fullwidth form
+fn is_ideographic(ch: char) -> bool {
+    matches!(ch as u32,
+        0x4E00..=0x9FFF        // CJK Unified Ideographs
+        | 0x3400..=0x4DBF      // CJK Unified Ideographs Extension A
+        | 0x20000..=0x2A6DF    // CJK Unified Ideographs Extension B
+        | 0x3000..=0x303F      // CJK Symbols and Punctuation
+        | 0x3040..=0x30FF      // Hiragana and Katakana
+        | 0xAC00..=0xD7AF      // Hangul Syllables
+        | 0xFF00..=0xFFEF      // Halfwidth and Fullwidth Forms
+    )
+}
+
+/// Decides whether a line may break between `before` and `after` (UAX #14 pair rules).
+///
+/// `last_non_space` is the class of the nearest non-space character at or before
+/// `before`, so that rules spanning runs of spaces (e.g. `OP SP* ×`) can apply.
+pub(crate) fn is_break_opportunity(last_non_space: BreakClass, before: BreakClass, after: BreakClass) -> bool {
+    use BreakClass::*;
+
+    match (before, after) {
+        // LB7: do not break before spaces
+        (_, Space) => false,
+        // LB12, LB12a: glue binds to both neighbours, unless preceded by a space or hyphen
+        (Glue, _) => false,
+        (Space | Hyphen | BreakAfter, Glue) => true,
+        (_, Glue) => false,
+        // LB13: do not break before closing punctuation, "!" or separators, even after spaces
+        (_, Close | Exclamation | InfixSeparator) => false,
+        // LB14: do not break after opening punctuation, even after spaces
+        _ if last_non_space == Open => false,
+        // LB18: break after spaces
+        (Space, _) => true,
+        // LB21: do not break before hyphens
+        (_, Hyphen | BreakAfter) => false,
+        // LB25: keep a minus sign with the following number
+        (Hyphen, Numeric) => false,
+        (Hyphen | BreakAfter, _) => true,
+        // LB31: ideographs break on either side
+        (Ideographic, _) | (_, Ideographic) => true,
+        // LB28-LB30: keep words, numbers and attached punctuation together
+        _ => false,
+    }
+}
 
 /// Line breaker configuration
 #[derive(Debug, Clone)]
@@ -91,10 +285,16 @@ pub struct LineBreakerConfig {
     pub max_width: f32,
     /// Hyphenation enabled
     pub hyphenation_enabled: bool,
+    /// Language of the hyphenation dictionary (e.g. "en-US"); None disables dictionary breaks
+    pub hyphenation_language: Option<String>,
     /// Tab width in abstract units
     pub tab_width: f32,
     /// Word spacing adjustment
     pub word_spacing: f32,
+    /// Allow breaks after `/`, `?`, `&`, `.`, `-` and `\\` inside URLs and file paths
+    pub break_urls: bool,
+    /// Capitalization the text is measured with
+    pub caps: CapsStyle,
 }
 
 impl Default for LineBreakerConfig {
@@ -102,8 +302,11 @@ impl Default for LineBreakerConfig {
         LineBreakerConfig {
             max_width: 500.0,
             hyphenation_enabled: true,
+            hyphenation_language: None,
             tab_width: 40.0,
             word_spacing: 4.0,
+            break_urls: false,
+            caps: CapsStyle::Normal,
         }
     }
 }
@@ -125,6 +328,8 @@ struct BreakPoint {
     penalty: i32,
     /// Whether this break is flagged (problematic)
     flagged: bool,
+    /// Opportunity a soft break is taken at
+    opportunity: Option<BreakOpportunity>,
 }
 
 /// Box for use in binary heap (needs to be Clone for BinaryHeap)
@@ -160,7 +365,7 @@ impl PartialOrd for BreakBox {
 #[derive(Debug, Clone)]
 pub struct LineBreaker {
     pub config: LineBreakerConfig,
-    shaper: Arc<TextShaper<'static>>,
+    shaper: Arc<TextShaper>,
 }
 
 impl Default for LineBreaker {
@@ -188,6 +393,18 @@ impl LineBreaker {
         }
     }
 
+    /// Creates a line breaker that measures text with the given shaper
+    ///
+    /// Use this to break lines with the document's own font and size, so wraps match
+    /// the rendered glyph advances (kerning and ligatures included).
+    #[inline]
+    pub fn with_shaper(shaper: Arc<TextShaper>) -> Self {
+        LineBreaker {
+            config: LineBreakerConfig::default(),
+            shaper,
+        }
+    }
+
     /// Creates a line breaker for a specific width
     #[inline]
     pub fn with_width(max_width: f32) -> Self {
@@ -202,15 +419,28 @@ impl LineBreaker {
         self.config.max_width = max_width;
     }
 
-    /// Enables or disables hyphenation
+    /// Enables dictionary hyphenation for a language (e.g. "en-US"), or disables it with None
+    ///
+    /// Words are then also breakable at syllable points, producing `BreakType::Hyphenated`
+    /// lines. Languages without a dictionary keep breaking only at spaces and punctuation.
+    #[inline]
+    pub fn set_hyphenation(&mut self, lang: Option<&str>) {
+        self.config.hyphenation_enabled = lang.is_some();
+        self.config.hyphenation_language = lang.map(str::to_string);
+    }
+
+    /// Lets long URLs and file paths wrap after their separators instead of overflowing
+    ///
+    /// Breaks are only added inside tokens that look like a URL (`scheme://`,
+    /// `www.`) or a path (`/`, `./`, `../`, `~/`, `C:\\`); no hyphen is inserted.
     #[inline]
-    pub fn set_hyphenation(&mut self, enabled: bool) {
-        self.config.hyphenation_enabled = enabled;
+    pub fn set_break_urls(&mut self, enabled: bool) {
+        self.config.break_urls = enabled;
     }
 
     /// Calculates the width of a substring
     fn text_width(&mut self, text: &str) -> f32 {
-        self.shaper.measure_width(text)
+        self.shaper.measure_width_with_caps(text, self.config.caps)
     }
 
     /// Clears the width cache (No-op in new engine)
@@ -219,60 +449,49 @@ impl LineBreaker {
         // shaper handles caching internally if needed
     }
 
-    /// Checks if a character is a valid break point (after the character)
-    #[inline]
-    fn is_break_after(&self, ch: char) -> bool {
-        matches!(
-            ch,
-            ' ' | '\t' | '-' | '–' | '—' | ';' | ':' | ',' | '.' | '!' | '?' | ')' | ']' | '}'
-        )
-    }
-
     /// Checks if a character can be hyphenated
     #[inline]
     fn can_hyphenate(&self, ch: char) -> bool {
         ch.is_alphabetic() && !ch.is_ascii() || ch.is_ascii_alphabetic()
     }
 
-    /// Checks if a character is CJK
-    #[inline]
-    fn is_cjk(&self, ch: char) -> bool {
-        let code = ch as u32;
-        // CJK Unified Ideographs
-        (code >= 0x4E00 && code <= 0x9FFF)
-        || // CJK Unified Ideographs Extension A
-        (code >= 0x3400 && code <= 0x4DBF)
-        || // CJK Symbols and Punctuation
-        (code >= 0x3000 && code <= 0x303F)
-        || // Hiragana
-        (code >= 0x3040 && code <= 0x309F)
-        || // Katakana
-        (code >= 0x30A0 && code <= 0x30FF)
-    }
-
     /// Gets break points for a line using HarfBuzz shaping
     pub(crate) fn get_break_points(&mut self, text: &str) -> Vec<BreakPoint> {
         let mut break_points: Vec<BreakPoint> = Vec::new();
         let len = text.len();
 
         // 1. Shape the entire text to get accurate glyph positions
-        let (total_width, glyphs) = self.shaper.shape(text);
+        let (total_width, glyphs) = self.shaper.shape_with_caps(text, self.config.caps);
 
         // 2. Build a map of char_index -> x_position in pixels
-        let mut char_pos: HashMap<usize, f32> = HashMap::new();
-
-        // For simple ASCII text, estimate position based on accumulated width
-        // HarfBuzz glyphs have cluster indices that map to character positions
+        // HarfBuzz clusters are byte offsets, so map them back to character indices
+        let byte_to_char: HashMap<usize, usize> = text
+            .char_indices()
+            .enumerate()
+            .map(|(char_idx, (byte_idx, _))| (byte_idx, char_idx))
+            .collect();
+        let mut cluster_pos: HashMap<usize, f32> = HashMap::new();
         let mut current_pos_px: f32 = 0.0;
         for glyph in &glyphs {
-            let char_idx = glyph.cluster as usize;
-            // Only set if not already set (first glyph for this cluster wins)
-            if !char_pos.contains_key(&char_idx) {
-                char_pos.insert(char_idx, current_pos_px);
+            if let Some(&char_idx) = byte_to_char.get(&(glyph.cluster as usize)) {
+                // Only set if not already set (first glyph for this cluster wins)
+                cluster_pos.entry(char_idx).or_insert(current_pos_px);
             }
             current_pos_px += glyph.x_advance;
         }
 
+        // Characters inside a multi-character cluster (e.g. an "ffi" ligature) have no
+        // glyph of their own; they share the position of the cluster that contains them
+        let mut char_pos: HashMap<usize, f32> = HashMap::new();
+        let mut cluster_start_px = 0.0;
+        for char_idx in 0..byte_to_char.len() {
+            if let Some(&pos) = cluster_pos.get(&char_idx) {
+                cluster_start_px = pos;
+            }
+            char_pos.insert(char_idx, cluster_start_px);
+        }
+        char_pos.insert(byte_to_char.len(), total_width);
+
         // Add start break point
         break_points.push(BreakPoint {
             position: 0,
@@ -282,90 +501,99 @@ impl LineBreaker {
             is_hyphenated: false,
             penalty: 0,
             flagged: false,
+            opportunity: None,
         });
 
-        // 3. Iterate through characters to find break points
+        // 3. Iterate through characters to find UAX #14 break opportunities
         let chars: Vec<char> = text.chars().collect();
         let char_count = chars.len();
+        let classes: Vec<BreakClass> = chars.iter().map(|&ch| break_class(ch)).collect();
+        let byte_offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).chain(std::iter::once(len)).collect();
+        let mut last_non_space = BreakClass::Space;
+
+        for char_idx in 0..char_count.saturating_sub(1) {
+            let class = classes[char_idx];
+            if class != BreakClass::Space {
+                last_non_space = class;
+            }
 
-        for (char_idx, ch) in chars.iter().enumerate() {
-            // Get current width from char_pos, or estimate if not available
-            let current_width = char_pos.get(&char_idx).copied().unwrap_or_else(|| {
-                // Fallback: estimate width based on character count
-                let estimated_char_width = total_width / char_count.max(1) as f32;
-                estimated_char_width * char_idx as f32
+            if !is_break_opportunity(last_non_space, class, classes[char_idx + 1]) {
+                continue;
+            }
+
+            // Width up to and including this character
+            let width_after = char_pos.get(&(char_idx + 1)).copied().unwrap_or_else(|| {
+                total_width / char_count.max(1) as f32 * (char_idx + 1) as f32
             });
 
-            // Calculate width after this character (for the next break point)
-            let char_width = char_pos.get(&char_idx).copied()
-                .and_then(|w| char_pos.get(&(char_idx + 1)).copied().map(|next_w| next_w - w))
-                .unwrap_or_else(|| total_width / char_count.max(1) as f32);
-            let width_after = current_width + char_width;
-
-            // Handle CJK characters - each can be a break point
-            if self.is_cjk(*ch) {
-                // Find byte index of next character
-                let next_byte_idx = if char_idx + 1 < char_count {
-                    text.char_indices().nth(char_idx + 1).map(|(i, _)| i).unwrap_or(len)
-                } else {
-                    len
-                };
+            // Breaking after a dash is less desirable than after a space
+            let penalty = match chars[char_idx] {
+                '-' | '–' | '—' | '\u{2010}' => PENALTY_HYPHEN,
+                _ => 0,
+            };
+            let opportunity = match class {
+                BreakClass::Space => BreakOpportunity::Space,
+                BreakClass::BreakAfter if chars[char_idx] == '\t' => BreakOpportunity::Space,
+                BreakClass::Hyphen | BreakClass::BreakAfter => BreakOpportunity::Hyphen,
+                _ => BreakOpportunity::Ideographic,
+            };
+
+            break_points.push(BreakPoint {
+                position: byte_offsets[char_idx + 1],
+                char_offset: char_idx + 1,
+                width: width_after,
+                break_type: BreakType::SoftBreak,
+                is_hyphenated: false,
+                penalty,
+                flagged: false,
+                opportunity: Some(opportunity),
+            });
+        }
 
-                break_points.push(BreakPoint {
-                    position: next_byte_idx,
-                    char_offset: char_idx + 1,
-                    width: width_after,
-                    break_type: BreakType::SoftBreak,
-                    is_hyphenated: false,
-                    penalty: 0,
-                    flagged: false,
-                });
-                continue;
-            }
+        // Add breaks after separators in URLs and paths, unless one is already there
+        if self.config.break_urls {
+            for position in url_break_positions(text) {
+                if break_points.iter().any(|bp| bp.position == position) {
+                    continue;
+                }
 
-            // Handle ASCII/whitespace - allow break after spaces and tabs
-            if *ch == ' ' || *ch == '\t' {
-                let next_byte_idx = if char_idx + 1 < char_count {
-                    text.char_indices().nth(char_idx + 1).map(|(i, _)| i).unwrap_or(len)
-                } else {
-                    len
-                };
+                let char_idx = text[..position].chars().count();
+                let width = char_pos.get(&char_idx).copied().unwrap_or_else(|| {
+                    total_width / char_count.max(1) as f32 * char_idx as f32
+                });
 
                 break_points.push(BreakPoint {
-                    position: next_byte_idx,
-                    char_offset: char_idx + 1,
-                    width: width_after,
+                    position,
+                    char_offset: char_idx,
+                    width,
                     break_type: BreakType::SoftBreak,
                     is_hyphenated: false,
-                    penalty: 0,
+                    penalty: PENALTY_HYPHEN,
                     flagged: false,
+                    opportunity: Some(BreakOpportunity::Separator),
                 });
-                continue;
             }
+        }
 
-            // Check if we can break after this character
-            if self.is_break_after(*ch) {
-                let next_byte_idx = if char_idx + 1 < char_count {
-                    text.char_indices().nth(char_idx + 1).map(|(i, _)| i).unwrap_or(len)
-                } else {
-                    len
-                };
-
-                // Calculate penalty based on character
-                let penalty = match *ch {
-                    '-' | '–' | '—' => PENALTY_HYPHEN,
-                    '!' | '?' => PENALTY_HARD,
-                    _ => 0,
-                };
+        // Add syllable break points; the line then also carries the width of a hyphen
+        let hyphenation_points = self.get_hyphenation_points(text);
+        if !hyphenation_points.is_empty() {
+            let hyphen_width = self.shaper.measure_width("-");
+            for position in hyphenation_points {
+                let char_idx = text[..position].chars().count();
+                let width = char_pos.get(&char_idx).copied().unwrap_or_else(|| {
+                    total_width / char_count.max(1) as f32 * char_idx as f32
+                });
 
                 break_points.push(BreakPoint {
-                    position: next_byte_idx,
-                    char_offset: char_idx + 1,
-                    width: width_after,
-                    break_type: BreakType::SoftBreak,
-                    is_hyphenated: false,
-                    penalty,
+                    position,
+                    char_offset: char_idx,
+                    width: width + hyphen_width,
+                    break_type: BreakType::Hyphenated,
+                    is_hyphenated: true,
+                    penalty: PENALTY_HYPHEN,
                     flagged: false,
+                    opportunity: None,
                 });
             }
         }
@@ -381,6 +609,7 @@ impl LineBreaker {
             is_hyphenated: false,
             penalty: 0,
             flagged: false,
+            opportunity: None,
         };
 
         // Remove duplicates (SoftBreaks) but keep HardBreaks
@@ -388,6 +617,11 @@ impl LineBreaker {
             // Keep if position is different, or if it's a HardBreak
             bp.position != len || bp.break_type == BreakType::HardBreak
         });
+
+        // Never break inside a grapheme cluster: a base and its combining marks,
+        // an emoji ZWJ sequence or a flag stay on one line even when they overflow
+        let graphemes: HashSet<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+        break_points.retain(|bp| graphemes.contains(&bp.position));
         break_points.push(end_break);
 
         // Sort by position
@@ -396,26 +630,14 @@ impl LineBreaker {
         break_points
     }
 
-    /// Syllable-based hyphenation using the hyphenation crate
+    /// Syllable-based hyphenation using the hyphenation crate, as byte offsets
     fn get_hyphenation_points(&self, text: &str) -> Vec<usize> {
-        if !self.config.hyphenation_enabled {
-            return Vec::new();
-        }
-
-        // TODO: Load hyphenation dictionary properly
-        // For now, we keep the fallback logic or use standard english if successfully loaded in future
-        // Use standard_english implementation if we had the file
-        
-        let mut points = Vec::new();
-        let chars: Vec<char> = text.chars().collect();
-        let len = chars.len();
-
-        // Fallback robust logic for now to pass tests
-        if len > 4 {
-             points.push(len - 2);
+        match self.config.hyphenation_language {
+            Some(ref language) if self.config.hyphenation_enabled => {
+                hyphenation_points(text, language)
+            }
+            _ => Vec::new(),
         }
-
-        points
     }
 
     /// Calculates demerits for a line based on its ratio to max width
@@ -448,6 +670,7 @@ impl LineBreaker {
         let _candidates: BinaryHeap<BreakBox> = BinaryHeap::new();
         let mut active_breaks: Vec<(usize, BreakPoint, f32)> = Vec::new(); // (line_number, break_point, total_demerits)
         let mut chosen_breaks: HashMap<usize, (usize, BreakPoint)> = HashMap::new(); // position -> (prev_position, break_point)
+        let mut best_totals: HashMap<usize, f32> = HashMap::new(); // position -> lowest demerits reaching it
 
         // Initialize with first break point
         if let Some(first) = break_points.first() {
@@ -499,6 +722,11 @@ impl LineBreaker {
                 // Add penalty based on character
                 total += current.penalty as f32;
 
+                // Overfull lines (only possible at the paragraph end) lose to any break that fits
+                if line_width > max_width {
+                    total += DEMERITS_OVERFULL * (line_width / max_width);
+                }
+
                 // Add demerits for double penalties (too many hyphenated lines)
                 if *line_num > 0 {
                     // Check previous line's hyphenation
@@ -528,9 +756,12 @@ impl LineBreaker {
                         chosen_breaks.insert(current.position, (prev_break.position, current.clone()));
                     }
                 } else if line_width <= max_width {
-                    // Valid break point
-                    new_candidates.push((*line_num + 1, current.clone(), total));
-                    chosen_breaks.insert(current.position, (prev_break.position, current.clone()));
+                    // Valid break point; keep only the cheapest way of reaching it
+                    if best_totals.get(&current.position).is_none_or(|best| total < *best) {
+                        best_totals.insert(current.position, total);
+                        new_candidates.push((*line_num + 1, current.clone(), total));
+                        chosen_breaks.insert(current.position, (prev_break.position, current.clone()));
+                    }
                 } else if current.break_type == BreakType::HardBreak && line_width <= max_width * 2.0 {
                     // Hard break that exceeds max_width but is still usable (for final fallback)
                     if total < best_demerits {
@@ -580,14 +811,18 @@ impl LineBreaker {
             self.config.max_width = width;
         }
 
-        // Split by explicit newlines first
+        // Split by explicit newlines first; line offsets stay relative to `text`
         let paragraphs: Vec<&str> = text.split('\n').collect();
         let mut lines = Vec::new();
+        let mut paragraph_start = 0usize;
 
         for paragraph in paragraphs {
+            let base = paragraph_start;
+            paragraph_start += paragraph.len() + 1;
+
             if paragraph.is_empty() {
                 // Empty line - add a hard break
-                lines.push(Line::new(0, 0, 0.0, BreakType::HardBreak));
+                lines.push(Line::new(base, base, 0.0, BreakType::HardBreak));
                 continue;
             }
 
@@ -601,8 +836,14 @@ impl LineBreaker {
 
                 if end > start {
                     let line_text = &paragraph[start..end];
-                    let width = self.text_width(line_text);
-                    lines.push(Line::new(start, end, width, bp.break_type));
+                    let width = if bp.break_type == BreakType::Hyphenated {
+                        self.text_width(&format!("{}-", line_text))
+                    } else {
+                        self.text_width(line_text)
+                    };
+                    lines.push(
+                        Line::new(base + start, base + end, width, bp.break_type).with_opportunity(bp.opportunity),
+                    );
                 }
 
                 prev_end = end;
@@ -612,10 +853,51 @@ impl LineBreaker {
         lines
     }
 
+    /// Breaks text into at most `max_lines` lines, marking the last one truncated if text was cut
+    pub fn break_lines_truncated(&mut self, text: &str, max_width: Option<f32>, max_lines: usize) -> Vec<Line> {
+        let mut lines = self.break_lines(text, max_width);
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+            if let Some(last) = lines.last_mut() {
+                last.truncated = true;
+            }
+        }
+        lines
+    }
+
+    /// Shortens `line` so that its text followed by [`ELLIPSIS`] fits the maximum width
+    ///
+    /// Trailing whitespace and then trailing characters are dropped as needed; the
+    /// line's width afterwards includes the ellipsis.
+    pub fn fit_ellipsis(&mut self, text: &str, line: &mut Line) {
+        let budget = self.config.max_width - self.text_width(ELLIPSIS);
+        let mut end = line.end;
+
+        loop {
+            let kept = text[line.start..end].trim_end();
+            end = line.start + kept.len();
+            if kept.is_empty() || self.text_width(kept) <= budget {
+                break;
+            }
+            end -= kept.graphemes(true).next_back().map_or(0, str::len);
+        }
+
+        line.width = self.text_width(&text[line.start..end]) + self.text_width(ELLIPSIS);
+        line.end = end;
+        line.break_type = BreakType::SoftBreak;
+        line.opportunity = None;
+    }
+
     /// Calculates the width of text
     pub fn calculate_text_width(&mut self, text: &str) -> f32 {
         self.text_width(text)
     }
+
+    /// Gets the shaper used for measurement
+    #[inline]
+    pub fn shaper(&self) -> &TextShaper {
+        &self.shaper
+    }
 }
 
 /// Extension trait for byte offset calculation
@@ -753,4 +1035,312 @@ mod tests {
             assert!(line.width <= 200.0 + 50.0, "Line width {} exceeds max", line.width);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_hyphenation_breaks_at_syllable() {
+        let allowed = hyphenation_points("typography", "en-US");
+        assert!(!allowed.is_empty());
+
+        let mut breaker = LineBreaker::new();
+        breaker.set_hyphenation(Some("en-US"));
+        let max_width = breaker.calculate_text_width("typogra-") + 1.0;
+        let lines = breaker.break_lines("typography", Some(max_width));
+
+        assert!(lines.len() > 1, "Should have hyphenated: {:?}", lines);
+        assert_eq!(lines[0].break_type, BreakType::Hyphenated);
+        assert!(allowed.contains(&lines[0].end), "Broke mid-syllable at {}", lines[0].end);
+        assert_eq!(lines.last().map(|line| line.end), Some("typography".len()));
+    }
+
+    #[test]
+    fn test_hyphenation_unknown_language_falls_back() {
+        let mut breaker = LineBreaker::new();
+        breaker.set_hyphenation(Some("xx"));
+        let max_width = breaker.calculate_text_width("typogra-") + 1.0;
+        let lines = breaker.break_lines("typography", Some(max_width));
+
+        assert!(lines.iter().all(|line| line.break_type != BreakType::Hyphenated));
+    }
+
+    #[test]
+    fn test_hyphenation_can_be_disabled() {
+        let mut breaker = LineBreaker::new();
+        breaker.set_hyphenation(Some("en-US"));
+        breaker.set_hyphenation(None);
+        let max_width = breaker.calculate_text_width("typogra-") + 1.0;
+        let lines = breaker.break_lines("typography", Some(max_width));
+
+        assert!(lines.iter().all(|line| line.break_type != BreakType::Hyphenated));
+    }
+
+    #[test]
+    fn test_shaper_widths_with_ligature() {
+        // The fixture font forms an "ffi" ligature as wide as a single letter
+        let font = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fonts/VelumTest.ttf");
+        let shaper = Arc::new(TextShaper::with_search_paths(&[font], 12.0).unwrap());
+        let mut breaker = LineBreaker::with_shaper(shaper.clone());
+        let text = "office office office office";
+
+        // Shaped widths account for the "ffi" ligature; summing per-character
+        // advances (the naive path) overestimates the first two words
+        let fitting = "office office ";
+        let shaped_width = shaper.measure_width(fitting);
+        let naive_width: f32 = fitting.chars().map(|c| shaper.measure_width(&c.to_string())).sum();
+        let max_width = shaped_width + 0.1;
+
+        let lines = breaker.break_lines(text, Some(max_width));
+        assert_eq!(lines[0].end, fitting.len(), "{:?}", lines);
+        assert!(naive_width > max_width, "naive {} vs shaped {}", naive_width, shaped_width);
+    }
+
+    fn break_positions(text: &str) -> Vec<usize> {
+        LineBreaker::new()
+            .get_break_points(text)
+            .iter()
+            .filter(|bp| bp.break_type == BreakType::SoftBreak && bp.position > 0)
+            .map(|bp| bp.position)
+            .collect()
+    }
+
+    #[test]
+    fn test_break_classes() {
+        assert_eq!(break_class(' '), BreakClass::Space);
+        assert_eq!(break_class('\u{00A0}'), BreakClass::Glue);
+        assert_eq!(break_class('('), BreakClass::Open);
+        assert_eq!(break_class(')'), BreakClass::Close);
+        assert_eq!(break_class('（'), BreakClass::Open);
+        assert_eq!(break_class('，'), BreakClass::Close);
+        assert_eq!(break_class('中'), BreakClass::Ideographic);
+        assert_eq!(break_class('a'), BreakClass::Alphabetic);
+    }
+
+    #[test]
+    fn test_no_break_before_close_paren() {
+        let text = "see (fig. 1)";
+        let positions = break_positions(text);
+
+        // Breaks only after "see " and after "fig. "; never inside "(fig" or before ")"
+        assert_eq!(positions, vec![4, 10]);
+        assert!(!positions.contains(&text.find(')').unwrap()));
+
+        let mut breaker = LineBreaker::new();
+        let max_width = breaker.calculate_text_width("see (fig. 1") + 1.0;
+        let lines = breaker.break_lines(text, Some(max_width));
+        assert!(lines.iter().all(|line| &text[line.start..line.end] != ")"), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_no_break_space_keeps_together() {
+        let text = "distance 10\u{00A0}km";
+        assert_eq!(break_positions(text), vec![9]);
+
+        let mut breaker = LineBreaker::new();
+        let max_width = breaker.calculate_text_width("distance 10") + 1.0;
+        let lines = breaker.break_lines(text, Some(max_width));
+        assert!(lines.iter().any(|line| &text[line.start..line.end] == "10\u{00A0}km"), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_mixed_script_breaks() {
+        // Ideographs break on either side, but not before the fullwidth comma
+        let text = "Velum是编辑器，好";
+        let positions = break_positions(text);
+        let comma = text.find('，').unwrap();
+        assert!(positions.contains(&"Velum".len()));
+        assert!(!positions.contains(&comma));
+        assert!(positions.contains(&(comma + '，'.len_utf8())));
+    }
+
+    #[test]
+    fn test_soft_break_opportunity() {
+        let mut breaker = LineBreaker::new();
+
+        let text = "well-known";
+        let max_width = breaker.calculate_text_width("well-") + 1.0;
+        let lines = breaker.break_lines(text, Some(max_width));
+        assert_eq!(&text[lines[0].start..lines[0].end], "well-", "{:?}", lines);
+        assert_eq!(lines[0].opportunity, Some(BreakOpportunity::Hyphen));
+
+        let text = "alpha beta";
+        let max_width = breaker.calculate_text_width("alpha ") + 1.0;
+        let lines = breaker.break_lines(text, Some(max_width));
+        assert_eq!(&text[lines[0].start..lines[0].end], "alpha ", "{:?}", lines);
+        assert_eq!(lines[0].break_type, BreakType::SoftBreak);
+        assert_eq!(lines[0].opportunity, Some(BreakOpportunity::Space));
+
+        let last = lines.last().unwrap();
+        assert_eq!(last.break_type, BreakType::HardBreak);
+        assert_eq!(last.opportunity, None);
+    }
+
+    #[test]
+    fn test_soft_break_opportunity_ideographic() {
+        let mut breaker = LineBreaker::with_width(100.0);
+        let lines = breaker.break_lines("这是一个测试文本用于测试中文分行", None);
+        assert!(lines.len() > 1);
+        assert_eq!(lines[0].opportunity, Some(BreakOpportunity::Ideographic));
+    }
+
+    #[test]
+    fn test_line_offsets_relative_to_whole_text() {
+        let mut breaker = LineBreaker::with_width(1000.0);
+        let text = "Line one\n\nLine two";
+        let lines = breaker.break_lines(text, None);
+
+        let segments: Vec<&str> = lines.iter().map(|line| &text[line.start..line.end]).collect();
+        assert_eq!(segments, vec!["Line one", "", "Line two"]);
+        assert_eq!((lines[1].start, lines[1].end), (9, 9));
+    }
+
+    #[test]
+    fn test_break_lines_truncated_limits_line_count() {
+        let mut breaker = LineBreaker::new();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(10);
+
+        let all = breaker.break_lines(&text, Some(150.0));
+        assert!(all.len() > 2);
+
+        let lines = breaker.break_lines_truncated(&text, Some(150.0), 2);
+        assert_eq!(lines.len(), 2);
+        assert!(!lines[0].truncated);
+        assert!(lines[1].truncated);
+        assert_eq!(lines[1].end, all[1].end);
+    }
+
+    #[test]
+    fn test_break_lines_truncated_short_text_untouched() {
+        let mut breaker = LineBreaker::new();
+        let lines = breaker.break_lines_truncated("Short", Some(150.0), 2);
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].truncated);
+    }
+
+    #[test]
+    fn test_fit_ellipsis_within_width() {
+        let mut breaker = LineBreaker::new();
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(10);
+
+        let mut lines = breaker.break_lines_truncated(&text, Some(150.0), 2);
+        let original_end = lines[1].end;
+        breaker.fit_ellipsis(&text, &mut lines[1]);
+
+        let last = &lines[1];
+        let shown = format!("{}{}", &text[last.start..last.end], ELLIPSIS);
+        assert!(shown.ends_with('\u{2026}'));
+        assert!(last.end < original_end);
+        assert!(!text[last.start..last.end].ends_with(' '));
+        assert!(last.width <= 150.0);
+        assert!((breaker.calculate_text_width(&shown) - last.width).abs() < 1.0);
+    }
+
+    const URL_TEXT: &str = "See https://example.com/docs/reference/line-breaking?lang=en&page=2 for details";
+
+    #[test]
+    fn test_url_stays_whole_without_break_urls() {
+        let mut breaker = LineBreaker::with_width(150.0);
+        let lines = breaker.break_lines(URL_TEXT, None);
+
+        let url_start = URL_TEXT.find("https").unwrap();
+        let url_end = URL_TEXT.find(" for").unwrap();
+        assert!(lines.iter().all(|line| line.end <= url_start || line.end >= url_end));
+    }
+
+    #[test]
+    fn test_break_urls_wraps_after_separators() {
+        let mut breaker = LineBreaker::with_width(150.0);
+        breaker.set_break_urls(true);
+        let lines = breaker.break_lines(URL_TEXT, None);
+
+        let url_start = URL_TEXT.find("https").unwrap();
+        let url_end = URL_TEXT.find(" for").unwrap();
+        let inside: Vec<&Line> = lines.iter()
+            .filter(|line| line.end > url_start && line.end < url_end)
+            .collect();
+
+        assert!(inside.len() >= 2, "URL should wrap: {:?}", lines);
+        for line in inside {
+            let before = URL_TEXT[..line.end].chars().next_back().unwrap();
+            let after = URL_TEXT[line.end..].chars().next().unwrap();
+            assert!(URL_SEPARATORS.contains(&before), "break after {:?}", before);
+            assert!(!URL_SEPARATORS.contains(&after), "break before {:?}", after);
+            assert_eq!(line.opportunity, Some(BreakOpportunity::Separator));
+            assert_eq!(line.break_type, BreakType::SoftBreak);
+            assert!(line.width <= 150.0);
+        }
+    }
+
+    #[test]
+    fn test_url_break_positions() {
+        let text = "go to https://a.b/c?d=1 or C:\\dir\\file.txt, not e.g. well-known";
+        let breaks: Vec<&str> = url_break_positions(text).into_iter().map(|pos| &text[..pos]).collect();
+
+        assert_eq!(breaks, [
+            "go to https://",
+            "go to https://a.",
+            "go to https://a.b/",
+            "go to https://a.b/c?",
+            "go to https://a.b/c?d=1 or C:\\",
+            "go to https://a.b/c?d=1 or C:\\dir\\",
+            "go to https://a.b/c?d=1 or C:\\dir\\file.",
+        ]);
+        assert_eq!(url_break_positions("/usr/"), [1]);
+        assert!(url_break_positions("plain-text words.").is_empty());
+    }
+
+    fn assert_breaks_keep_graphemes(breaker: &mut LineBreaker, text: &str, lines: &[Line]) {
+        let boundaries: HashSet<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+        for point in breaker.get_break_points(text) {
+            assert!(point.position == text.len() || boundaries.contains(&point.position), "break inside a cluster: {:?}", point);
+        }
+        for line in lines {
+            assert!(boundaries.contains(&line.start), "line starts inside a cluster: {:?}", line);
+            assert!(line.end == text.len() || boundaries.contains(&line.end), "line ends inside a cluster: {:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_zwj_family_emoji_never_split() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("日{family}本{family}語{family}", family = family);
+        let mut breaker = LineBreaker::new();
+        let max_width = breaker.calculate_text_width(&text) / 3.0;
+        let lines = breaker.break_lines(&text, Some(max_width));
+
+        assert!(lines.len() > 1, "Should have broken: {:?}", lines);
+        assert_breaks_keep_graphemes(&mut breaker, &text, &lines);
+        let whole: usize = lines.iter().map(|line| text[line.start..line.end].matches(family).count()).sum();
+        assert_eq!(whole, 3);
+    }
+
+    #[test]
+    fn test_combining_marks_stay_with_base() {
+        // Kana with a combining voiced mark, and a Latin base with a combining acute
+        let text = "か\u{3099}き\u{3099}く\u{3099} e\u{0301}/e\u{0301}";
+        let mut breaker = LineBreaker::new();
+        breaker.set_break_urls(true);
+        let max_width = breaker.calculate_text_width(text) / 4.0;
+        let lines = breaker.break_lines(text, Some(max_width));
+
+        assert!(lines.len() > 1, "Should have broken: {:?}", lines);
+        assert_breaks_keep_graphemes(&mut breaker, text, &lines);
+        for line in &lines {
+            let first = text[line.start..].chars().next().unwrap();
+            assert!(first != '\u{3099}' && first != '\u{0301}', "mark starts a line: {:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_ellipsis_drops_whole_clusters() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let text = format!("ab{}{}{}", family, family, family);
+        let mut breaker = LineBreaker::new();
+        let budget = breaker.calculate_text_width("ab") + breaker.calculate_text_width(ELLIPSIS) + 0.5;
+        breaker.set_max_width(budget);
+        let width = breaker.calculate_text_width(&text);
+        let mut line = Line::new(0, text.len(), width, BreakType::HardBreak);
+        breaker.fit_ellipsis(&text, &mut line);
+
+        assert!(line.end >= 2);
+        assert_eq!((line.end - 2) % family.len(), 0, "cut inside a family emoji at {}", line.end);
+    }
+}
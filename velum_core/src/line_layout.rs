@@ -3,11 +3,36 @@
 //! Provides higher-level text layout functionality including paragraph layout
 //! and bidirectional text support.
 
-use crate::line_breaking::{BreakType, LineBreaker};
+use crate::line_breaking::{self, BreakType, LineBreaker, ELLIPSIS};
+use crate::ooxml::{Caps, DocumentSettings};
+use crate::piece_tree::count_words;
+use crate::text_shaping::{CapsStyle, GlyphInfo, ShapeOptions, TextDirection, TextShaper};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Returns the runs of `range` in visual order, as byte ranges into the analyzed text
+fn visual_runs(bidi: &BidiInfo, range: Range<usize>) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    for paragraph in &bidi.paragraphs {
+        let start = range.start.max(paragraph.range.start);
+        let end = range.end.min(paragraph.range.end);
+        if start >= end {
+            continue;
+        }
+
+        let (_, level_runs) = bidi.visual_runs(paragraph, start..end);
+        runs.extend(level_runs.into_iter().map(|run| (run.start, run.end)));
+    }
+    runs
+}
 
 /// Line spacing rule enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum LineSpacingRule {
     Single,     // 单倍行距 (1.0)
     OneAndHalf, // 1.5倍行距
@@ -18,7 +43,7 @@ pub enum LineSpacingRule {
 }
 
 /// Text alignment enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Alignment {
     Left,
     Right,
@@ -26,6 +51,36 @@ pub enum Alignment {
     Justify,
 }
 
+/// How the text following a tab lines up with its tab stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TabAlignment {
+    /// Text starts at the stop
+    #[default]
+    Left,
+    /// Text is centered on the stop
+    Center,
+    /// Text ends at the stop
+    Right,
+    /// The first `.` sits on the stop; text without one ends at the stop
+    Decimal,
+}
+
+/// A tab stop, positioned from the left edge of the layout width (before indentation)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TabStop {
+    /// Distance from the left edge in abstract units
+    pub position: f32,
+    /// Alignment of the text following the tab
+    pub alignment: TabAlignment,
+}
+
+impl TabStop {
+    /// Creates a tab stop at `position` with the given alignment
+    pub fn new(position: f32, alignment: TabAlignment) -> Self {
+        TabStop { position, alignment }
+    }
+}
+
 /// Default implementation for Alignment
 impl Default for Alignment {
     fn default() -> Self {
@@ -80,6 +135,30 @@ pub struct LineLayoutInfo {
     pub offset_x: f32,
     /// Actual line height used
     pub line_height: f32,
+    /// Left edge of the line after indentation and alignment
+    pub aligned_x: f32,
+    /// Extra space added to each inter-word space when justified
+    pub word_spacing: f32,
+    /// Advance of each tab on the line, in order, after resolving tab stops
+    #[serde(default)]
+    pub tab_advances: Vec<f32>,
+    /// Whether a hyphen glyph is drawn at the end of the line; its advance is included in `width`
+    #[serde(default)]
+    pub show_hyphen: bool,
+}
+
+/// Glyphs of one directional run of a line, positioned for drawing
+#[derive(Debug, Clone)]
+pub struct PositionedRun {
+    /// Index of the line the run belongs to (0-based)
+    pub line_index: usize,
+    /// Left edge of the run
+    pub x: f32,
+    /// Top of the run's line, from the top of the paragraph
+    pub y: f32,
+    /// Glyphs in visual order; clusters are byte offsets into the paragraph text,
+    /// and advances include justification and resolved tab stops
+    pub glyphs: Vec<GlyphInfo>,
 }
 
 /// Paragraph properties for layout customization
@@ -99,8 +178,15 @@ pub struct ParagraphProperties {
     pub line_spacing: f32,
     /// Line spacing rule
     pub line_spacing_rule: LineSpacingRule,
-    /// Text alignment
-    pub alignment: Alignment,
+    /// Text alignment; None falls back to the layout config's alignment
+    pub alignment: Option<Alignment>,
+    /// Keep on the same page as the start of the following paragraph
+    pub keep_with_next: bool,
+    /// Keep all lines of the paragraph on one page
+    pub keep_together: bool,
+    /// Capitalization the paragraph's text is measured with
+    #[serde(default)]
+    pub caps: CapsStyle,
 }
 
 impl Default for ParagraphProperties {
@@ -113,8 +199,32 @@ impl Default for ParagraphProperties {
             space_after: 0.0,
             line_spacing: 1.0,
             line_spacing_rule: LineSpacingRule::Single,
-            alignment: Alignment::default(),
+            alignment: None,
+            keep_with_next: false,
+            keep_together: false,
+            caps: CapsStyle::Normal,
+        }
+    }
+}
+
+impl Hash for ParagraphProperties {
+    /// Hashes lengths by their bit patterns, so the layout cache keys on exact values
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for length in [
+            self.indent_left,
+            self.indent_right,
+            self.indent_first_line,
+            self.space_before,
+            self.space_after,
+            self.line_spacing,
+        ] {
+            length.to_bits().hash(state);
         }
+        self.line_spacing_rule.hash(state);
+        self.alignment.hash(state);
+        self.keep_with_next.hash(state);
+        self.keep_together.hash(state);
+        self.caps.hash(state);
     }
 }
 
@@ -135,7 +245,7 @@ impl ParagraphProperties {
     #[inline]
     pub fn with_alignment(alignment: Alignment) -> Self {
         ParagraphProperties {
-            alignment,
+            alignment: Some(alignment),
             ..Default::default()
         }
     }
@@ -150,6 +260,25 @@ impl ParagraphProperties {
         }
     }
 
+    /// Creates paragraph properties with pagination keep flags
+    #[inline]
+    pub fn with_keep(keep_with_next: bool, keep_together: bool) -> Self {
+        ParagraphProperties {
+            keep_with_next,
+            keep_together,
+            ..Default::default()
+        }
+    }
+
+    /// Creates paragraph properties with a capitalization style
+    #[inline]
+    pub fn with_caps(caps: CapsStyle) -> Self {
+        ParagraphProperties {
+            caps,
+            ..Default::default()
+        }
+    }
+
     /// Creates paragraph properties with full customization
     #[inline]
     pub fn new(
@@ -170,7 +299,10 @@ impl ParagraphProperties {
             space_after,
             line_spacing,
             line_spacing_rule,
-            alignment,
+            alignment: Some(alignment),
+            keep_with_next: false,
+            keep_together: false,
+            caps: CapsStyle::Normal,
         }
     }
 }
@@ -196,6 +328,12 @@ pub struct ParagraphLayout {
     pub has_bidi: bool,
     /// Paragraph properties used
     pub properties: ParagraphProperties,
+    /// Words in the paragraph, as counted by [`count_words`]
+    #[serde(default)]
+    pub word_count: usize,
+    /// Characters (Unicode scalar values) in the paragraph
+    #[serde(default)]
+    pub char_count: usize,
 }
 
 /// Complete document layout result
@@ -209,6 +347,12 @@ pub struct DocumentLayout {
     pub total_height: f32,
     /// Line height
     pub line_height: f32,
+    /// Words across all paragraphs
+    #[serde(default)]
+    pub word_count: usize,
+    /// Characters across all paragraphs, excluding the separating newlines
+    #[serde(default)]
+    pub char_count: usize,
 }
 
 /// Configuration for line layout
@@ -216,14 +360,24 @@ pub struct DocumentLayout {
 pub struct LineLayoutConfig {
     /// Line height in abstract units
     pub line_height: f32,
-    /// Tab size in spaces
+    /// Tab size in spaces; past the last tab stop, tabs advance to multiples of it
     pub tab_size: usize,
+    /// Explicit tab stops, sorted by position
+    pub tab_stops: Vec<TabStop>,
+    /// Interval between default tab stops past the last explicit one; `None` uses `tab_size` spaces
+    pub default_tab_stop: Option<f32>,
     /// Default font size
     pub font_size: f32,
     /// Enable bidirectional support
     pub bidi_enabled: bool,
     /// Trim trailing whitespace
     pub trim_trailing: bool,
+    /// Alignment for paragraphs whose properties leave it unset
+    pub alignment: Alignment,
+    /// Maximum number of lines per paragraph; later lines are dropped
+    pub max_lines: Option<usize>,
+    /// Shorten a truncated last line so an ellipsis fits after it
+    pub ellipsis: bool,
 }
 
 impl Default for LineLayoutConfig {
@@ -231,9 +385,34 @@ impl Default for LineLayoutConfig {
         LineLayoutConfig {
             line_height: 1.2,
             tab_size: 4,
+            tab_stops: Vec::new(),
+            default_tab_stop: None,
             font_size: 14.0,
             bidi_enabled: true,
             trim_trailing: true,
+            alignment: Alignment::Left,
+            max_lines: None,
+            ellipsis: false,
+        }
+    }
+}
+
+impl From<Caps> for CapsStyle {
+    fn from(caps: Caps) -> Self {
+        match caps {
+            Caps::Normal => CapsStyle::Normal,
+            Caps::AllCaps => CapsStyle::AllCaps,
+            Caps::SmallCaps => CapsStyle::SmallCaps,
+        }
+    }
+}
+
+impl From<&DocumentSettings> for LineLayoutConfig {
+    /// Takes the default tab stop interval from a document's settings
+    fn from(settings: &DocumentSettings) -> Self {
+        LineLayoutConfig {
+            default_tab_stop: settings.default_tab_stop_points(),
+            ..LineLayoutConfig::default()
         }
     }
 }
@@ -243,8 +422,15 @@ impl Default for LineLayoutConfig {
 pub struct LineLayout {
     config: LineLayoutConfig,
     breaker: LineBreaker,
+    /// Bumped whenever the configuration or breaker may have changed
+    config_revision: u64,
+    /// Paragraph layouts keyed by (text and properties hash, max width bits, config revision)
+    cache: HashMap<(u64, u32, u64), ParagraphLayout>,
 }
 
+/// Cached paragraph layouts kept before the cache is flushed
+const MAX_CACHED_PARAGRAPHS: usize = 4096;
+
 impl Default for LineLayout {
     fn default() -> Self {
         LineLayout::new()
@@ -258,6 +444,8 @@ impl LineLayout {
         LineLayout {
             config: LineLayoutConfig::default(),
             breaker: LineBreaker::new(),
+            config_revision: 0,
+            cache: HashMap::new(),
         }
     }
 
@@ -267,6 +455,8 @@ impl LineLayout {
         LineLayout {
             config,
             breaker: LineBreaker::new(),
+            config_revision: 0,
+            cache: HashMap::new(),
         }
     }
 
@@ -274,18 +464,52 @@ impl LineLayout {
     #[inline]
     pub fn set_line_height(&mut self, height: f32) {
         self.config.line_height = height;
+        self.config_changed();
     }
 
     /// Sets the tab size
     #[inline]
     pub fn set_tab_size(&mut self, size: usize) {
         self.config.tab_size = size;
+        self.config_changed();
+    }
+
+    /// Replaces the explicit tab stops
+    pub fn set_tab_stops(&mut self, mut stops: Vec<TabStop>) {
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        self.config.tab_stops = stops;
+        self.config_changed();
+    }
+
+    /// Sets the interval between default tab stops, e.g. a document's `defaultTabStop`
+    pub fn set_default_tab_stop(&mut self, interval: Option<f32>) {
+        self.config.default_tab_stop = interval;
+        self.config_changed();
     }
 
     /// Enables or disables bidirectional support
     #[inline]
     pub fn set_bidi(&mut self, enabled: bool) {
         self.config.bidi_enabled = enabled;
+        self.config_changed();
+    }
+
+    /// Limits paragraphs to `max_lines` lines, optionally ending a cut paragraph with an ellipsis
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>, ellipsis: bool) {
+        self.config.max_lines = max_lines;
+        self.config.ellipsis = ellipsis;
+        self.config_changed();
+    }
+
+    /// Discards all cached paragraph layouts
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Invalidates cached layouts after a configuration change
+    fn config_changed(&mut self) {
+        self.config_revision += 1;
+        self.cache.clear();
     }
 
     /// Calculates the line height based on spacing rule
@@ -300,6 +524,121 @@ impl LineLayout {
         }
     }
 
+    /// Calculates the alignment shift and justification spacing for a line
+    ///
+    /// Trailing whitespace hangs past the margin, so only the visible width is aligned.
+    /// Justified lines stretch their inter-word spaces, except the paragraph's last line.
+    fn calculate_alignment(
+        &self,
+        alignment: Alignment,
+        line_text: &str,
+        visible_width: f32,
+        content_width: f32,
+        is_last_line: bool,
+    ) -> (f32, f32) {
+        let slack = (content_width - visible_width).max(0.0);
+        match alignment {
+            Alignment::Left => (0.0, 0.0),
+            Alignment::Right => (slack, 0.0),
+            Alignment::Center => (slack / 2.0, 0.0),
+            Alignment::Justify => {
+                let gaps = line_text.trim_end().matches(' ').count();
+                if is_last_line || gaps == 0 {
+                    (0.0, 0.0)
+                } else {
+                    (0.0, slack / gaps as f32)
+                }
+            }
+        }
+    }
+
+    /// The first tab stop past `x`, falling back to the default stops every
+    /// `default_tab_stop` units, or every `tab_size` spaces when that is unset
+    fn next_tab_stop(&mut self, x: f32) -> TabStop {
+        const EPSILON: f32 = 0.001;
+
+        if let Some(stop) = self.config.tab_stops.iter().find(|stop| stop.position > x + EPSILON) {
+            return *stop;
+        }
+
+        let interval = match self.config.default_tab_stop {
+            Some(interval) => interval,
+            None => self.config.tab_size as f32 * self.breaker.calculate_text_width(" "),
+        };
+        if interval <= 0.0 {
+            return TabStop::new(x, TabAlignment::Left);
+        }
+        TabStop::new(((x + EPSILON) / interval).floor() * interval + interval, TabAlignment::Left)
+    }
+
+    /// Resolves the tabs of a line that starts at `start_x`
+    ///
+    /// Returns the advance of each tab, the line's width and its trailing
+    /// whitespace width. Text never moves backwards: when the text after a
+    /// tab would overlap what precedes it, the tab's advance is zero.
+    ///
+    /// Line breaking measures a tab as a single glyph, before stops are known.
+    /// When the resolved tabs push the visible text past `max_width`, the
+    /// advances of the last visible tabs shrink until it fits, so the line
+    /// keeps the break the breaker chose.
+    fn layout_tabs(&mut self, line_text: &str, start_x: f32, max_width: f32) -> (Vec<f32>, f32, f32) {
+        let visible_len = if self.config.trim_trailing {
+            line_text.trim_end().len()
+        } else {
+            line_text.len()
+        };
+
+        let mut advances = Vec::new();
+        let mut visible_tabs = 0;
+        let mut pen = start_x;
+        let mut visible_end = start_x;
+        let mut segment_start = 0;
+
+        for (index, segment) in line_text.split('\t').enumerate() {
+            // Only the visible part of the segment lines up with the stop
+            let visible = &segment[..visible_len.saturating_sub(segment_start).min(segment.len())];
+
+            if index > 0 {
+                let stop = self.next_tab_stop(pen);
+                let lead = match stop.alignment {
+                    TabAlignment::Left => 0.0,
+                    TabAlignment::Center => self.breaker.calculate_text_width(visible) / 2.0,
+                    TabAlignment::Right => self.breaker.calculate_text_width(visible),
+                    TabAlignment::Decimal => match visible.find('.') {
+                        Some(point) => self.breaker.calculate_text_width(&visible[..point]),
+                        None => self.breaker.calculate_text_width(visible),
+                    },
+                };
+                let start = (stop.position - lead).max(pen);
+                advances.push(start - pen);
+                pen = start;
+                if segment_start <= visible_len {
+                    visible_tabs += 1;
+                }
+            }
+
+            if segment_start <= visible_len {
+                visible_end = pen + self.breaker.calculate_text_width(visible);
+            }
+            pen += self.breaker.calculate_text_width(segment);
+            segment_start += segment.len() + 1;
+        }
+
+        let mut overflow = visible_end - start_x - max_width;
+        for advance in advances[..visible_tabs].iter_mut().rev() {
+            if overflow <= 0.0 {
+                break;
+            }
+            let shrink = advance.min(overflow);
+            *advance -= shrink;
+            overflow -= shrink;
+            pen -= shrink;
+            visible_end -= shrink;
+        }
+
+        (advances, pen - start_x, pen - visible_end)
+    }
+
     /// Calculates the left offset for a line based on indentation
     fn calculate_line_offset(&self, line_index: usize, props: ParagraphProperties) -> f32 {
         let left_indent = props.indent_left;
@@ -320,11 +659,40 @@ impl LineLayout {
     }
 
     /// Layouts a single paragraph with custom properties
+    ///
+    /// Results are cached, so re-laying out an unchanged paragraph at the same width
+    /// skips line breaking entirely.
     pub fn layout_paragraph_with_props(
         &mut self,
         text: &str,
         max_width: f32,
         props: ParagraphProperties,
+    ) -> ParagraphLayout {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        props.hash(&mut hasher);
+        let key = (hasher.finish(), max_width.to_bits(), self.config_revision);
+
+        if let Some(layout) = self.cache.get(&key) {
+            if layout.text == text && layout.properties == props {
+                return layout.clone();
+            }
+        }
+
+        let layout = self.compute_paragraph_layout(text, max_width, props);
+        if self.cache.len() >= MAX_CACHED_PARAGRAPHS {
+            self.cache.clear();
+        }
+        self.cache.insert(key, layout.clone());
+        layout
+    }
+
+    /// Breaks and measures a paragraph without consulting the cache
+    fn compute_paragraph_layout(
+        &mut self,
+        text: &str,
+        max_width: f32,
+        props: ParagraphProperties,
     ) -> ParagraphLayout {
         // Calculate content width (accounting for left and right indent)
         // Convert twips to abstract units (assuming 1440 twips per inch)
@@ -335,12 +703,23 @@ impl LineLayout {
 
         // Set breaker max width to content width
         self.breaker.set_max_width(content_width);
-
-        let lines = self.breaker.break_lines(text, None);
+        self.breaker.config.caps = props.caps;
+
+        let mut lines = match self.config.max_lines {
+            Some(max_lines) => self.breaker.break_lines_truncated(text, None, max_lines),
+            None => self.breaker.break_lines(text, None),
+        };
+        if self.config.ellipsis {
+            if let Some(last) = lines.last_mut().filter(|line| line.truncated) {
+                self.breaker.fit_ellipsis(text, last);
+            }
+        }
         let mut layout_lines = Vec::new();
 
         let mut has_bidi = false;
-        let mut char_offset = 0usize;
+
+        // Resolve embedding levels once per paragraph; lines only slice them
+        let bidi = if self.config.bidi_enabled { Some(BidiInfo::new(text, None)) } else { None };
 
         // Calculate base line height
         let base_line_height = self.config.line_height * self.config.font_size;
@@ -348,12 +727,14 @@ impl LineLayout {
         // Calculate actual line height based on spacing rule
         let actual_line_height = self.calculate_line_height(base_line_height, props);
 
+        let alignment = props.alignment.unwrap_or(self.config.alignment);
+
         for (i, line) in lines.iter().enumerate() {
             if line.is_empty() {
                 layout_lines.push(LineLayoutInfo {
                     line_number: i,
-                    start: char_offset,
-                    end: char_offset,
+                    start: line.start,
+                    end: line.end,
                     width: 0.0,
                     break_type: "HardBreak".to_string(),
                     char_count: 0,
@@ -361,6 +742,10 @@ impl LineLayout {
                     trailing_whitespace: 0.0,
                     offset_x: left_indent_units,
                     line_height: actual_line_height,
+                    aligned_x: left_indent_units,
+                    word_spacing: 0.0,
+                    tab_advances: Vec::new(),
+                    show_hyphen: false,
                 });
                 continue;
             }
@@ -368,27 +753,11 @@ impl LineLayout {
             let line_text = &text[line.start..line.end];
             let char_count = line_text.chars().count();
 
-            // Check for bidirectional text
-            let is_bidi = if self.config.bidi_enabled {
-                let has_rtl = line_text.chars().any(|c| {
-                    matches!(
-                        c,
-                        '\u{0590}'..='\u{05FF}' |  // Hebrew
-                        '\u{0600}'..='\u{06FF}' |  // Arabic
-                        '\u{0750}'..='\u{077F}' |  // Arabic Supplement
-                        '\u{08A0}'..='\u{08FF}' |  // Arabic Extended-A
-                        '\u{FB50}'..='\u{FDFF}' |  // Arabic Presentation Forms-A
-                        '\u{FE70}'..='\u{FEFF}' |  // Arabic Presentation Forms-B
-                        '\u{10800}'..='\u{10FFF}'  // Private Use Area (some RTL scripts)
-                    )
-                });
-                if has_rtl {
-                    has_bidi = true;
-                }
-                has_rtl
-            } else {
-                false
-            };
+            // Check for bidirectional text: any right-to-left level on the line
+            let is_bidi = bidi
+                .as_ref()
+                .is_some_and(|bidi| bidi.levels[line.start..line.end].iter().any(|level| level.is_rtl()));
+            has_bidi |= is_bidi;
 
             // Calculate trailing whitespace
             let trailing_ws = if self.config.trim_trailing {
@@ -406,7 +775,28 @@ impl LineLayout {
             // Calculate line offset based on indentation
             let offset_x = self.calculate_line_offset(i, props);
 
+            // A word broken by hyphenation ends with a drawn hyphen the breaker already measured
+            let show_hyphen = line.break_type == BreakType::Hyphenated && !line.truncated;
+
+            // Tabs advance to stops measured from the left edge, so they depend on where the line starts
+            let (tab_advances, width, trailing_ws) = if line_text.contains('\t') {
+                let (advances, width, trailing) = self.layout_tabs(line_text, offset_x, content_width);
+                let hyphen = if show_hyphen { self.breaker.calculate_text_width("-") } else { 0.0 };
+                (advances, width + hyphen, trailing)
+            } else {
+                (Vec::new(), line.width, trailing_ws)
+            };
+
+            let (alignment_shift, word_spacing) = self.calculate_alignment(
+                alignment,
+                line_text,
+                width - trailing_ws,
+                content_width,
+                i + 1 == lines.len(),
+            );
+
             let break_type_str = match line.break_type {
+                _ if line.truncated => "Truncated",
                 BreakType::HardBreak => "HardBreak",
                 BreakType::SoftBreak => "SoftBreak",
                 BreakType::Hyphenated => "Hyphenated",
@@ -416,16 +806,18 @@ impl LineLayout {
                 line_number: i,
                 start: line.start,
                 end: line.end,
-                width: line.width,
+                width,
                 break_type: break_type_str.to_string(),
                 char_count,
                 is_bidi,
                 trailing_whitespace: trailing_ws,
                 offset_x,
                 line_height: actual_line_height,
+                aligned_x: offset_x + alignment_shift,
+                word_spacing,
+                tab_advances,
+                show_hyphen,
             });
-
-            char_offset = line.end;
         }
 
         // Calculate total height: lines * line_height + space_before + space_after
@@ -444,6 +836,8 @@ impl LineLayout {
             actual_line_height,
             has_bidi,
             properties: props,
+            word_count: count_words(text),
+            char_count: text.chars().count(),
         }
     }
 
@@ -479,6 +873,8 @@ impl LineLayout {
         }
 
         DocumentLayout {
+            word_count: all_paragraphs.iter().map(|p| p.word_count).sum(),
+            char_count: all_paragraphs.iter().map(|p| p.char_count).sum(),
             paragraphs: all_paragraphs,
             total_width,
             total_height,
@@ -503,21 +899,170 @@ impl LineLayout {
         serde_json::to_string(&layout).unwrap_or_else(|_| "{}".to_string())
     }
 
-    /// Calculates the visual order for a bidirectional line
-    #[allow(dead_code)]
+    /// Calculates the visual order of the directional runs in a line of text
+    ///
+    /// Returns byte ranges into `text`, left to right as displayed. Runs at an
+    /// odd (right-to-left) level are drawn with their characters reversed.
     pub fn calculate_visual_order(&self, text: &str) -> Vec<(usize, usize)> {
         if text.is_empty() {
             return Vec::new();
         }
 
-        // Simple implementation - returns the text as-is for LTR
-        // Full bidirectional reordering would require more complex handling
-        vec![(0, text.len())]
+        let bidi = BidiInfo::new(text, None);
+        visual_runs(&bidi, 0..text.len())
+    }
+
+    /// Text to draw for a laid-out line of `text`, ending in an ellipsis when it was truncated
+    pub fn display_text(&self, text: &str, line: &LineLayoutInfo) -> String {
+        let shown = &text[line.start..line.end];
+        if self.config.ellipsis && line.break_type == "Truncated" {
+            format!("{}{}", shown, ELLIPSIS)
+        } else {
+            shown.to_string()
+        }
+    }
+
+    /// Builds the visual representation of a laid-out line of `text` (the paragraph
+    /// the line belongs to), including the run order when the line is bidirectional
+    pub fn visual_line(&self, text: &str, line: &LineLayoutInfo) -> LayoutLine {
+        let visual_order = if line.is_bidi {
+            Some(visual_runs(&BidiInfo::new(text, None), line.start..line.end))
+        } else {
+            None
+        };
+
+        LayoutLine {
+            start: line.start,
+            end: line.end,
+            width: line.width,
+            break_type: line.break_type.clone(),
+            visual_order,
+            is_bidi: line.is_bidi,
+        }
+    }
+
+    /// Lays out a paragraph and shapes each line into positioned glyph runs
+    ///
+    /// Runs follow the visual order of bidirectional lines. A hyphen or ellipsis
+    /// ending a line is its own run, with clusters at the line's end offset.
+    pub fn layout_glyphs(&mut self, text: &str, max_width: f32, shaper: &TextShaper) -> Vec<PositionedRun> {
+        let layout = self.layout_paragraph(text, max_width);
+        let bidi = layout.has_bidi.then(|| BidiInfo::new(text, None));
+
+        let mut runs = Vec::new();
+        let mut y = layout.properties.space_before * max_width / 1440.0;
+        for (line_index, line) in layout.lines.iter().enumerate() {
+            let segments = match bidi.as_ref().filter(|_| line.is_bidi) {
+                Some(bidi) => visual_runs(bidi, line.start..line.end)
+                    .into_iter()
+                    .map(|(start, end)| (start, end, bidi.levels[start].is_rtl()))
+                    .collect(),
+                None => vec![(line.start, line.end, false)],
+            };
+
+            let mut tab_advances = line.tab_advances.iter();
+            let mut x = line.aligned_x;
+            for (start, end, rtl) in segments {
+                if start == end {
+                    continue;
+                }
+                let direction = if rtl { TextDirection::RightToLeft } else { TextDirection::LeftToRight };
+                let (_, mut glyphs) = shaper.shape_with_options(&text[start..end], &ShapeOptions::new().with_direction(direction));
+                let origin = x;
+                for glyph in &mut glyphs {
+                    match text[start + glyph.cluster as usize..].chars().next() {
+                        Some('\t') => glyph.x_advance = tab_advances.next().copied().unwrap_or(glyph.x_advance),
+                        Some(' ') => glyph.x_advance += line.word_spacing,
+                        _ => {}
+                    }
+                    glyph.cluster += start as u32;
+                    x += glyph.x_advance;
+                }
+                runs.push(PositionedRun { line_index, x: origin, y, glyphs });
+            }
+
+            let marker = if line.show_hyphen {
+                Some("-")
+            } else if self.config.ellipsis && line.break_type == "Truncated" {
+                Some(ELLIPSIS)
+            } else {
+                None
+            };
+            if let Some(marker) = marker {
+                let (_, mut glyphs) = shaper.shape(marker);
+                for glyph in &mut glyphs {
+                    glyph.cluster = line.end as u32;
+                }
+                runs.push(PositionedRun { line_index, x, y, glyphs });
+            }
+
+            y += line.line_height;
+        }
+        runs
+    }
+
+    /// Returns the x position of every caret stop in a line, from 0 to the line width
+    ///
+    /// Stops fall on grapheme boundaries, so a base letter and its combining marks form
+    /// a single step; a ligature glyph's advance is shared evenly among its graphemes.
+    pub fn caret_positions(&self, line_text: &str) -> Vec<f32> {
+        let graphemes: Vec<usize> = line_text.grapheme_indices(true).map(|(start, _)| start).collect();
+        let mut widths = vec![0.0f32; graphemes.len()];
+
+        let (_, glyphs) = self.breaker.shaper().shape(line_text);
+        let mut cluster_starts: Vec<usize> = glyphs.iter().map(|glyph| glyph.cluster as usize).collect();
+        cluster_starts.sort_unstable();
+        cluster_starts.dedup();
+
+        for glyph in &glyphs {
+            let start = glyph.cluster as usize;
+            let end = cluster_starts
+                .iter()
+                .find(|&&cluster| cluster > start)
+                .copied()
+                .unwrap_or(line_text.len());
+
+            // Graphemes covered by this glyph's cluster: the one containing it onwards
+            let first = graphemes.partition_point(|&grapheme| grapheme <= start).saturating_sub(1);
+            let last = graphemes.partition_point(|&grapheme| grapheme < end).max(first + 1);
+            let share = glyph.x_advance / (last - first) as f32;
+            for width in widths.iter_mut().take(last).skip(first) {
+                *width += share;
+            }
+        }
+
+        std::iter::once(0.0)
+            .chain(widths.iter().scan(0.0, |x, width| {
+                *x += width;
+                Some(*x)
+            }))
+            .collect()
+    }
+
+    /// Returns the char offset of the caret stop nearest to `x` in a line
+    ///
+    /// Clicking on the left half of a character places the caret before it, the right
+    /// half after it. Positions outside the line clamp to its start or end.
+    pub fn hit_test(&self, line_text: &str, x: f32) -> usize {
+        let positions = self.caret_positions(line_text);
+        let stop = positions
+            .windows(2)
+            .position(|pair| x < (pair[0] + pair[1]) / 2.0)
+            .unwrap_or(positions.len().saturating_sub(1));
+
+        line_text
+            .grapheme_indices(true)
+            .take(stop)
+            .map(|(_, grapheme)| grapheme.chars().count())
+            .sum()
     }
 
     /// Gets the line breaker for direct access
+    ///
+    /// The caller may reconfigure the breaker, so cached layouts are invalidated.
     #[inline]
     pub fn breaker_mut(&mut self) -> &mut LineBreaker {
+        self.config_changed();
         &mut self.breaker
     }
 
@@ -526,6 +1071,13 @@ impl LineLayout {
     pub fn breaker(&self) -> &LineBreaker {
         &self.breaker
     }
+
+    /// Returns the byte offsets where `text` may be hyphenated in `language`
+    ///
+    /// See [`line_breaking::hyphenation_points`]; unsupported languages yield no points.
+    pub fn hyphenation_points(text: &str, language: &str) -> Vec<usize> {
+        line_breaking::hyphenation_points(text, language)
+    }
 }
 
 /// Utility functions for text measurement
@@ -644,6 +1196,48 @@ mod tests {
         assert!(!order.is_empty());
     }
 
+    #[test]
+    fn test_visual_order_mixed_hebrew_latin() {
+        let layout = LineLayout::new();
+        let text = "abc אבג def";
+        let hebrew = text.find('א').unwrap();
+        let hebrew_end = hebrew + "אבג".len();
+
+        // LTR paragraph: the Hebrew run stays between the Latin runs
+        assert_eq!(
+            layout.calculate_visual_order(text),
+            vec![(0, hebrew), (hebrew, hebrew_end), (hebrew_end, text.len())]
+        );
+    }
+
+    #[test]
+    fn test_visual_order_rtl_paragraph() {
+        let layout = LineLayout::new();
+        let text = "אבג abc";
+        let latin = text.find('a').unwrap();
+
+        // RTL paragraph: the embedded Latin run is displayed first (leftmost)
+        let order = layout.calculate_visual_order(text);
+        assert_eq!(order.first(), Some(&(latin, text.len())));
+        assert_eq!(order.last(), Some(&(0, latin)));
+    }
+
+    #[test]
+    fn test_layout_populates_bidi() {
+        let mut layout = LineLayout::new();
+        let text = "abc אבג def";
+        let result = layout.layout_paragraph(text, 1000.0);
+        assert!(result.has_bidi);
+        assert!(result.lines[0].is_bidi);
+
+        let line = layout.visual_line(text, &result.lines[0]);
+        assert_eq!(line.visual_order.map(|order| order.len()), Some(3));
+
+        let result = layout.layout_paragraph("plain text", 1000.0);
+        assert!(!result.has_bidi);
+        assert_eq!(layout.visual_line("plain text", &result.lines[0]).visual_order, None);
+    }
+
     #[test]
     fn test_line_layout_info() {
         let mut layout = LineLayout::new();
@@ -676,7 +1270,7 @@ mod tests {
         assert_eq!(props.indent_left, 0.0);
         assert_eq!(props.indent_right, 0.0);
         assert_eq!(props.indent_first_line, 0.0);
-        assert_eq!(props.alignment, Alignment::Left);
+        assert_eq!(props.alignment, None);
         assert_eq!(props.line_spacing_rule, LineSpacingRule::Single);
     }
 
@@ -741,25 +1335,25 @@ mod tests {
     #[test]
     fn test_alignment_left() {
         let props = ParagraphProperties::with_alignment(Alignment::Left);
-        assert_eq!(props.alignment, Alignment::Left);
+        assert_eq!(props.alignment, Some(Alignment::Left));
     }
 
     #[test]
     fn test_alignment_right() {
         let props = ParagraphProperties::with_alignment(Alignment::Right);
-        assert_eq!(props.alignment, Alignment::Right);
+        assert_eq!(props.alignment, Some(Alignment::Right));
     }
 
     #[test]
     fn test_alignment_center() {
         let props = ParagraphProperties::with_alignment(Alignment::Center);
-        assert_eq!(props.alignment, Alignment::Center);
+        assert_eq!(props.alignment, Some(Alignment::Center));
     }
 
     #[test]
     fn test_alignment_justify() {
         let props = ParagraphProperties::with_alignment(Alignment::Justify);
-        assert_eq!(props.alignment, Alignment::Justify);
+        assert_eq!(props.alignment, Some(Alignment::Justify));
     }
 
     #[test]
@@ -782,7 +1376,7 @@ mod tests {
         assert_eq!(props.space_after, 144.0);
         assert_eq!(props.line_spacing, 1.5);
         assert_eq!(props.line_spacing_rule, LineSpacingRule::Multiple);
-        assert_eq!(props.alignment, Alignment::Center);
+        assert_eq!(props.alignment, Some(Alignment::Center));
     }
 
     #[test]
@@ -894,7 +1488,7 @@ mod tests {
 
         for &align in &alignments {
             let props = ParagraphProperties::with_alignment(align);
-            assert_eq!(props.alignment, align);
+            assert_eq!(props.alignment, Some(align));
         }
     }
 
@@ -934,4 +1528,426 @@ mod tests {
             assert_eq!(para.properties.line_spacing_rule, LineSpacingRule::Double);
         }
     }
+
+    #[test]
+    fn test_hyphenation_points_english_word() {
+        // hy-phen-a-tion
+        assert_eq!(LineLayout::hyphenation_points("hyphenation", "en-US"), vec![2, 6, 7]);
+    }
+
+    #[test]
+    fn test_hyphenation_points_none() {
+        assert!(LineLayout::hyphenation_points("cat", "en-US").is_empty());
+        assert!(LineLayout::hyphenation_points("hyphenation", "xx").is_empty());
+    }
+
+    #[test]
+    fn test_hyphenation_points_paragraph_offsets() {
+        let text = "The hyphenation table";
+        let points = LineLayout::hyphenation_points(text, "en");
+
+        assert_eq!(points, vec![6, 10, 11]);
+        assert!(points.iter().all(|&p| text.is_char_boundary(p)));
+    }
+
+    #[test]
+    fn test_right_alignment_offset() {
+        let mut layout = LineLayout::with_config(LineLayoutConfig {
+            alignment: Alignment::Right,
+            ..Default::default()
+        });
+        let result = layout.layout_paragraph("Hello world", 1000.0);
+        let line = &result.lines[0];
+        assert!((line.aligned_x - (1000.0 - line.width)).abs() < 0.001);
+        assert_eq!(line.word_spacing, 0.0);
+    }
+
+    #[test]
+    fn test_explicit_left_alignment_overrides_config() {
+        let mut layout = LineLayout::with_config(LineLayoutConfig {
+            alignment: Alignment::Right,
+            ..Default::default()
+        });
+        let props = ParagraphProperties::with_alignment(Alignment::Left);
+        let result = layout.layout_paragraph_with_props("Hello world", 1000.0, props);
+        assert_eq!(result.lines[0].aligned_x, 0.0);
+    }
+
+    #[test]
+    fn test_center_alignment_from_paragraph_properties() {
+        let mut layout = LineLayout::new();
+        let props = ParagraphProperties::with_alignment(Alignment::Center);
+        let result = layout.layout_paragraph_with_props("Hello world", 1000.0, props);
+        let line = &result.lines[0];
+        assert!((line.aligned_x - (1000.0 - line.width) / 2.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_justify_stretches_all_but_last_line() {
+        let mut layout = LineLayout::new();
+        let props = ParagraphProperties::with_alignment(Alignment::Justify);
+        let text = "The quick brown fox jumps over the lazy dog and keeps running far away";
+        let result = layout.layout_paragraph_with_props(text, 150.0, props);
+        assert!(result.lines.len() > 1);
+
+        let (last, rest) = result.lines.split_last().unwrap();
+        for line in rest {
+            let line_text = &text[line.start..line.end];
+            let gaps = line_text.trim_end().matches(' ').count() as f32;
+            assert!(line.word_spacing > 0.0, "{:?}", line);
+            let filled = line.width - line.trailing_whitespace + gaps * line.word_spacing;
+            assert!((filled - result.content_width).abs() < 0.01, "{} vs {}", filled, result.content_width);
+            assert_eq!(line.aligned_x, line.offset_x);
+        }
+        assert_eq!(last.word_spacing, 0.0);
+    }
+
+    #[test]
+    fn test_caret_positions() {
+        let layout = LineLayout::new();
+        let positions = layout.caret_positions("abc");
+        assert_eq!(positions.len(), 4);
+        assert_eq!(positions[0], 0.0);
+        assert!(positions.windows(2).all(|pair| pair[1] > pair[0]));
+
+        let width = layout.breaker().shaper().measure_width("abc");
+        assert!((positions[3] - width).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_hit_test_rounds_at_midpoint() {
+        let layout = LineLayout::new();
+        let positions = layout.caret_positions("abc");
+        let middle_of_b = (positions[1] + positions[2]) / 2.0;
+
+        assert_eq!(layout.hit_test("abc", middle_of_b - 0.1), 1);
+        assert_eq!(layout.hit_test("abc", middle_of_b + 0.1), 2);
+        assert_eq!(layout.hit_test("abc", -5.0), 0);
+        assert_eq!(layout.hit_test("abc", positions[3] + 5.0), 3);
+    }
+
+    #[test]
+    fn test_caret_positions_combining_marks() {
+        let layout = LineLayout::new();
+        // "e" + COMBINING ACUTE ACCENT is one caret step
+        let text = "e\u{0301}x";
+        let positions = layout.caret_positions(text);
+        assert_eq!(positions.len(), 3);
+
+        let offsets: Vec<usize> = (0..=40).map(|i| layout.hit_test(text, i as f32)).collect();
+        assert!(!offsets.contains(&1), "caret stopped between a letter and its mark");
+        assert!(offsets.contains(&2));
+    }
+
+    #[test]
+    fn test_caret_positions_ligature() {
+        let layout = LineLayout::new();
+        let positions = layout.caret_positions("office");
+        assert_eq!(positions.len(), 7);
+        assert!(positions.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    #[test]
+    fn test_layout_cache_reuses_unchanged_paragraphs() {
+        let mut layout = LineLayout::new();
+        let text = (0..500)
+            .map(|i| format!("Paragraph {} has a few words that wrap across lines", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let first = layout.layout_document(&text, 200.0);
+        assert_eq!(layout.cache.len(), 500);
+
+        // Mark every cached entry; a re-layout served from the cache carries the mark
+        for cached in layout.cache.values_mut() {
+            cached.total_height += 1000.0;
+        }
+        let second = layout.layout_document(&text, 200.0);
+        assert_eq!(layout.cache.len(), 500);
+        for (a, b) in first.paragraphs.iter().zip(&second.paragraphs) {
+            assert_eq!(a.total_height + 1000.0, b.total_height);
+            assert_eq!(a.text, b.text);
+            assert_eq!(a.lines.len(), b.lines.len());
+        }
+
+        layout.clear_cache();
+        let third = layout.layout_document(&text, 200.0);
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&third).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_layout_cache_invalidated_by_config() {
+        let mut layout = LineLayout::new();
+        let before = layout.layout_paragraph("Hello world", 1000.0);
+        layout.set_line_height(2.0);
+        assert!(layout.cache.is_empty());
+
+        let after = layout.layout_paragraph("Hello world", 1000.0);
+        assert!(after.actual_line_height > before.actual_line_height);
+
+        // A different width is a different entry
+        layout.layout_paragraph("Hello world", 500.0);
+        assert_eq!(layout.cache.len(), 2);
+    }
+
+    #[test]
+    fn test_layout_cache_keys_on_property_values() {
+        let mut layout = LineLayout::new();
+        let indented = ParagraphProperties::with_indent(720.0, 0.0, 0.0);
+        layout.layout_paragraph_with_props("Hello world", 1000.0, ParagraphProperties::default());
+        layout.layout_paragraph_with_props("Hello world", 1000.0, indented);
+        assert_eq!(layout.cache.len(), 2);
+
+        // Equal properties hash alike, so the indented layout is served from the cache
+        let again = layout.layout_paragraph_with_props("Hello world", 1000.0, ParagraphProperties::with_indent(720.0, 0.0, 0.0));
+        assert_eq!(layout.cache.len(), 2);
+        assert_eq!(again.properties, indented);
+        assert!((again.content_width - 500.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_blank_line_offsets_between_cjk_paragraphs() {
+        let mut layout = LineLayout::new();
+        let text = "中文段落\n\n日本語の段落";
+        let result = layout.layout_paragraph(text, 1000.0);
+
+        let segments: Vec<&str> = result.lines.iter().map(|line| &text[line.start..line.end]).collect();
+        assert_eq!(segments, vec!["中文段落", "", "日本語の段落"]);
+
+        // The blank line sits right after the first newline, in bytes
+        assert_eq!(result.lines[1].start, "中文段落\n".len());
+        assert!(result
+            .lines
+            .windows(2)
+            .all(|pair| pair[0].end <= pair[1].start && pair[1].start <= pair[1].end));
+    }
+
+    #[test]
+    fn test_paragraph_word_count_ignores_punctuation() {
+        let mut layout = LineLayout::new();
+        let paragraph = layout.layout_paragraph("Hello, world! It's 3.5 times - really.", 1000.0);
+        assert_eq!(paragraph.word_count, 6);
+        assert_eq!(paragraph.char_count, 38);
+    }
+
+    #[test]
+    fn test_paragraph_word_count_cjk() {
+        let mut layout = LineLayout::new();
+        // Each ideograph is its own word under Unicode segmentation
+        let paragraph = layout.layout_paragraph("你好，世界。Rust", 1000.0);
+        assert_eq!(paragraph.word_count, 5);
+        assert_eq!(paragraph.char_count, 10);
+    }
+
+    #[test]
+    fn test_whitespace_paragraph_has_no_words() {
+        let mut layout = LineLayout::new();
+        let paragraph = layout.layout_paragraph(" \t  ", 1000.0);
+        assert_eq!(paragraph.word_count, 0);
+        assert_eq!(paragraph.char_count, 4);
+    }
+
+    #[test]
+    fn test_document_layout_count_totals() {
+        let mut layout = LineLayout::new();
+        let document = layout.layout_document("one two\n\n三四 five", 1000.0);
+        assert_eq!(document.paragraphs.len(), 3);
+        assert_eq!(document.word_count, 5);
+        assert_eq!(document.char_count, 14);
+    }
+
+    #[test]
+    fn test_max_lines_with_ellipsis() {
+        let mut layout = LineLayout::new();
+        layout.set_max_lines(Some(2), true);
+        let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore.";
+
+        let paragraph = layout.layout_paragraph(text, 120.0);
+        assert_eq!(paragraph.lines.len(), 2);
+        assert_eq!(paragraph.lines[0].break_type, "SoftBreak");
+        assert_eq!(paragraph.lines[1].break_type, "Truncated");
+        assert!(paragraph.lines[1].width <= 120.0);
+
+        let shown: Vec<String> = paragraph.lines.iter().map(|line| layout.display_text(text, line)).collect();
+        assert!(!shown[0].ends_with('\u{2026}'));
+        assert!(shown[1].ends_with('\u{2026}'));
+        assert!(!shown[1].ends_with(" \u{2026}"));
+    }
+
+    #[test]
+    fn test_max_lines_not_reached_has_no_ellipsis() {
+        let mut layout = LineLayout::new();
+        layout.set_max_lines(Some(2), true);
+
+        let paragraph = layout.layout_paragraph("Short text", 500.0);
+        assert_eq!(paragraph.lines.len(), 1);
+        assert_eq!(layout.display_text("Short text", &paragraph.lines[0]), "Short text");
+    }
+
+    #[test]
+    fn test_right_tab_stop_ends_text_at_stop() {
+        let mut layout = LineLayout::new();
+        layout.set_tab_stops(vec![TabStop::new(300.0, TabAlignment::Right)]);
+        let name = layout.breaker_mut().calculate_text_width("Name");
+        let value = layout.breaker_mut().calculate_text_width("42");
+
+        let result = layout.layout_paragraph("Name\t42", 1000.0);
+        let line = &result.lines[0];
+
+        assert_eq!(line.tab_advances.len(), 1);
+        assert!((line.tab_advances[0] - (300.0 - value - name)).abs() < 0.01);
+        assert!((line.width - 300.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_decimal_tab_stop_aligns_numbers_on_point() {
+        let mut layout = LineLayout::new();
+        layout.set_tab_stops(vec![
+            TabStop::new(200.0, TabAlignment::Decimal),
+            TabStop::new(50.0, TabAlignment::Left),
+        ]);
+
+        for (text, integer_part) in [("Item\t\t3.5", "3"), ("Total\t\t1234.25", "1234"), ("Count\t\t17", "17")] {
+            let integer_width = layout.breaker_mut().calculate_text_width(integer_part);
+            let result = layout.layout_paragraph(text, 1000.0);
+            let line = &result.lines[0];
+            let label = layout.breaker_mut().calculate_text_width(text.split('\t').next().unwrap());
+
+            // Stops are sorted, so the first tab reaches 50 and the second the decimal stop
+            assert_eq!(line.tab_advances.len(), 2);
+            assert!((label + line.tab_advances[0] - 50.0).abs() < 0.01, "{}", text);
+            let point_x = 50.0 + line.tab_advances[1] + integer_width;
+            assert!((point_x - 200.0).abs() < 0.01, "{}: decimal point at {}", text, point_x);
+        }
+    }
+
+    #[test]
+    fn test_tabs_past_last_stop_use_default_interval() {
+        let mut layout = LineLayout::new();
+        let interval = 4.0 * layout.breaker_mut().calculate_text_width(" ");
+        let a = layout.breaker_mut().calculate_text_width("a");
+
+        let result = layout.layout_paragraph("a\tb\t", 1000.0);
+        let line = &result.lines[0];
+
+        assert_eq!(line.tab_advances.len(), 2);
+        assert!((line.tab_advances[0] - (interval - a)).abs() < 0.01);
+        // The trailing tab hangs past the visible text like trailing spaces
+        let b = layout.breaker_mut().calculate_text_width("b");
+        assert!((line.width - line.trailing_whitespace - (interval + b)).abs() < 0.01);
+        assert!((line.width - 2.0 * interval).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_tab_text_never_moves_backwards() {
+        let mut layout = LineLayout::new();
+        layout.set_tab_stops(vec![TabStop::new(40.0, TabAlignment::Right)]);
+
+        let result = layout.layout_paragraph("x\tA long right-aligned cell", 1000.0);
+        assert_eq!(result.lines[0].tab_advances, vec![0.0]);
+    }
+
+    #[test]
+    fn test_small_caps_run_reaches_line_breaking() {
+        let run = crate::ooxml::RunProperties { small_caps: Some(true), ..Default::default() };
+        let text = "Small caps text";
+        let mut layout = LineLayout::new();
+
+        let props = ParagraphProperties::with_caps(run.caps_style().into());
+        let small_caps = layout.layout_paragraph_with_props(text, 1000.0, props);
+        let all_caps = layout.layout_paragraph_with_props(text, 1000.0, ParagraphProperties::with_caps(CapsStyle::AllCaps));
+
+        let expected = layout.breaker().shaper().measure_width_with_caps(text, CapsStyle::SmallCaps);
+        assert_eq!(small_caps.properties.caps, CapsStyle::SmallCaps);
+        assert!((small_caps.lines[0].width - expected).abs() < 0.01);
+        assert!(small_caps.lines[0].width < all_caps.lines[0].width);
+    }
+
+    #[test]
+    fn test_tabs_shrink_to_keep_the_breakers_line() {
+        let mut layout = LineLayout::new();
+        layout.set_tab_stops(vec![TabStop::new(300.0, TabAlignment::Left)]);
+        let max_width = layout.breaker_mut().calculate_text_width("ab cd ef") + 1.0;
+
+        // The breaker measures the tab as one glyph, so the line is not split at it
+        let result = layout.layout_paragraph("ab\tcd", max_width);
+        assert_eq!(result.lines.len(), 1);
+
+        // Reaching the stop would overflow; the tab shrinks until the text fits
+        let line = &result.lines[0];
+        let text = layout.breaker_mut().calculate_text_width("abcd");
+        assert!((line.width - max_width).abs() < 0.01, "{}", line.width);
+        assert!((line.tab_advances[0] - (max_width - text)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_hyphenated_line_includes_hyphen_width() {
+        let mut layout = LineLayout::new();
+        layout.breaker_mut().set_hyphenation(Some("en-US"));
+        let max_width = layout.breaker_mut().calculate_text_width("typogra-") + 1.0;
+        let paragraph = layout.layout_paragraph("typography", max_width);
+
+        let first = &paragraph.lines[0];
+        assert_eq!(first.break_type, "Hyphenated");
+        assert!(first.show_hyphen);
+        let text = &"typography"[first.start..first.end];
+        let expected = layout.breaker_mut().calculate_text_width(&format!("{}-", text));
+        assert!((first.width - expected).abs() < 0.01, "{} vs {}", first.width, expected);
+        assert!(paragraph.lines[1..].iter().all(|line| !line.show_hyphen));
+    }
+
+    #[test]
+    fn test_default_tab_stop_interval_overrides_tab_size() {
+        let mut layout = LineLayout::new();
+        layout.set_default_tab_stop(Some(50.0));
+        let paragraph = layout.layout_paragraph("\ta\tb", 1000.0);
+
+        let advances = &paragraph.lines[0].tab_advances;
+        assert!((advances[0] - 50.0).abs() < 0.01);
+        let a_width = layout.breaker_mut().calculate_text_width("a");
+        assert!((advances[1] - (50.0 - a_width)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_config_from_document_settings() {
+        let settings = DocumentSettings { default_tab_stop: Some(708), ..DocumentSettings::default() };
+        let mut layout = LineLayout::with_config(LineLayoutConfig::from(&settings));
+
+        // Without explicit stops, a tab advances to the document's default stop
+        let paragraph = layout.layout_paragraph("a\tb", 1000.0);
+        let before_tab = layout.breaker_mut().calculate_text_width("a");
+        assert!((paragraph.lines[0].tab_advances[0] - (35.4 - before_tab)).abs() < 0.01);
+
+        assert_eq!(LineLayoutConfig::from(&DocumentSettings::default()).default_tab_stop, None);
+    }
+
+    #[test]
+    fn test_layout_glyphs_positions_two_lines() {
+        let mut layout = LineLayout::new();
+        let shaper = TextShaper::new();
+        let max_width = layout.breaker_mut().calculate_text_width("first line ") + 1.0;
+        let runs = layout.layout_glyphs("first line second", max_width, &shaper);
+
+        let lines: Vec<usize> = runs.iter().map(|run| run.line_index).collect();
+        assert_eq!(lines, [0, 1]);
+        assert!(runs[1].y > runs[0].y);
+
+        for run in &runs {
+            let origins: Vec<f32> = run.glyphs.iter()
+                .scan(run.x, |x, glyph| {
+                    let origin = *x;
+                    *x += glyph.x_advance;
+                    Some(origin)
+                })
+                .collect();
+            assert!(!origins.is_empty());
+            assert!(origins.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", origins);
+        }
+
+        // Clusters point back into the paragraph text
+        assert_eq!(runs[1].glyphs[0].cluster, "first line ".len() as u32);
+    }
 }
@@ -105,12 +105,18 @@ mod tests {
             theme: None,
             core_properties: None,
             tables: Vec::new(),
+            body: Vec::new(),
+            sections: Vec::new(),
             images: Vec::new(),
             headers: Vec::new(),
             footers: Vec::new(),
-            footnotes: Vec::new(),
-            endnotes: Vec::new(),
-            numbering: Vec::new(),
+            footnotes: HashMap::new(),
+            endnotes: HashMap::new(),
+            numbering: None,
+            settings: None,
+            preserved_parts: Vec::new(),
+            package_relationships: Vec::new(),
+            document_relationships: Vec::new(),
         };
 
         // Create a paragraph with mixed formatting
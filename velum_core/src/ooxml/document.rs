@@ -1,18 +1,41 @@
 //! WordProcessingML document parser
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use super::opc::OpcPackage;
 use super::types::{
     Paragraph, ParagraphProperties, Run, RunProperties, Style, Theme, ThemeFonts,
-    Table, TableRow, TableCell, TableProperties, TableRowProperties,
-    TableBorders, TableBorder, Header, Footer, Footnote, Endnote, Numbering,
-    AbstractNumDef, ListLevel, NumInstance, DocumentImage,
+    BodyElement, Table, TableRow, TableCell, TableProperties, TableRowProperties,
+    TableBorders, TableBorder, Header, Footer, Numbering,
+    AbstractNumDef, ListLevel, NumInstance, LevelOverride, DocumentImage,
+    PageOrientation, SectionProperties, VertAlign, PackagePart, Relationship, NoteReference, Symbol,
+    DocumentSettings,
 };
+use super::writer::REGENERATED_PARTS;
 use super::error::OoxmlError;
+use crate::find::{find_all_in_text, SearchOptions};
+use once_cell::sync::Lazy;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use regex::Regex;
+
+/// Toggle run properties: present unless `w:val` turns them off
+static CAPS_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<w:caps(?:\s+w:val="([^"]*)")?\s*/>"#).unwrap());
+static SMALL_CAPS_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<w:smallCaps(?:\s+w:val="([^"]*)")?\s*/>"#).unwrap());
+static STRIKE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<w:strike(?:\s+w:val="([^"]*)")?\s*/>"#).unwrap());
+
+/// DrawingML pictures: each `<w:drawing>`, the blip it embeds, its extent in EMUs and its `wp:docPr` text
+static DRAWING_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?s)<w:drawing\b.*?</w:drawing>"#).unwrap());
+static BLIP_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<a:blip\b[^>]*\br:embed="([^"]*)""#).unwrap());
+static EXTENT_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<wp:extent\b[^>]*\bcx="(\d+)"[^>]*\bcy="(\d+)""#).unwrap());
+static DOC_PR_TITLE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<wp:docPr\b[^>]*\btitle="([^"]*)""#).unwrap());
+static DOC_PR_DESCR_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r#"<wp:docPr\b[^>]*\bdescr="([^"]*)""#).unwrap());
 
 /// WordProcessingML document parser
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct WordDocument {
     /// Extracted text content
     pub text: String,
@@ -26,18 +49,37 @@ pub struct WordDocument {
     pub core_properties: Option<CoreProperties>,
     /// Tables in the document
     pub tables: Vec<Table>,
+    /// Top-level paragraphs and tables of the body in document order
+    pub body: Vec<BodyElement>,
+    /// Section page setups in document order; the last one is the body's final `<w:sectPr>`
+    pub sections: Vec<SectionProperties>,
     /// Images in the document
     pub images: Vec<DocumentImage>,
     /// Headers in the document
     pub headers: Vec<Header>,
     /// Footers in the document
     pub footers: Vec<Footer>,
-    /// Footnotes in the document
-    pub footnotes: Vec<Footnote>,
-    /// Endnotes in the document
-    pub endnotes: Vec<Endnote>,
+    /// Footnote paragraphs keyed by the `w:id` their references use
+    pub footnotes: HashMap<i32, Vec<Paragraph>>,
+    /// Endnote paragraphs keyed by the `w:id` their references use
+    pub endnotes: HashMap<i32, Vec<Paragraph>>,
     /// Numbering definitions (list styles)
-    pub numbering: Vec<Numbering>,
+    pub numbering: Option<Numbering>,
+    /// Document-wide settings from `word/settings.xml`
+    pub settings: Option<DocumentSettings>,
+    /// Package parts the writer does not regenerate, kept verbatim and sorted by name
+    pub preserved_parts: Vec<PackagePart>,
+    /// Package-level relationships (`_rels/.rels`)
+    pub package_relationships: Vec<Relationship>,
+    /// Relationships of the main document part (`word/_rels/document.xml.rels`)
+    pub document_relationships: Vec<Relationship>,
+}
+
+/// A parsed paragraph with where its `<w:p>` starts in the part
+struct LocatedParagraph {
+    position: u64,
+    in_table: bool,
+    paragraph: Paragraph,
 }
 
 /// Core document properties
@@ -53,9 +95,170 @@ pub struct CoreProperties {
     pub modified: Option<String>,
 }
 
+/// A search match located by paragraph and run rather than global offset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocFind {
+    /// Index of the paragraph containing the match
+    pub paragraph_index: usize,
+    /// Index of the run in which the match starts
+    pub run_index: usize,
+    /// Byte range of the match within the paragraph's text
+    pub byte_range_in_para: Range<usize>,
+}
+
+/// Options for exporting a document as plain text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextExportOptions {
+    /// Separator inserted between paragraphs (e.g. `"\n"`, `"\r\n"`, `"\n\n"`)
+    pub paragraph_separator: String,
+    /// Skip paragraphs with no text instead of emitting consecutive separators
+    pub collapse_empty_paragraphs: bool,
+}
+
+impl Default for TextExportOptions {
+    fn default() -> Self {
+        TextExportOptions {
+            paragraph_separator: "\n".to_string(),
+            collapse_empty_paragraphs: false,
+        }
+    }
+}
+
+/// Progress reported while parsing a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseProgress {
+    /// Parsing of a package part has started
+    Part { name: String },
+    /// A body paragraph has been parsed; `parsed` is the running total
+    Paragraph { parsed: usize },
+}
+
+/// Progress callback and cancellation flag threaded through parsing
+#[derive(Default)]
+pub(crate) struct ParseMonitor<'a> {
+    progress: Option<&'a mut dyn FnMut(ParseProgress)>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> ParseMonitor<'a> {
+    pub(crate) fn new(progress: &'a mut dyn FnMut(ParseProgress), cancel: &'a AtomicBool) -> Self {
+        ParseMonitor {
+            progress: Some(progress),
+            cancel: Some(cancel),
+        }
+    }
+
+    /// Fails with `Cancelled` once the cancel flag is set
+    pub(crate) fn check(&self) -> Result<(), OoxmlError> {
+        match self.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => Err(OoxmlError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks for cancellation, then reports `progress`
+    fn report(&mut self, progress: ParseProgress) -> Result<(), OoxmlError> {
+        self.check()?;
+        if let Some(callback) = self.progress.as_mut() {
+            callback(progress);
+        }
+        Ok(())
+    }
+
+    fn part(&mut self, name: &str) -> Result<(), OoxmlError> {
+        self.report(ParseProgress::Part { name: name.to_string() })
+    }
+}
+
 impl WordDocument {
+    /// Build the document's plain text from its paragraphs
+    pub fn to_plain_text(&self, opts: TextExportOptions) -> String {
+        self.paragraphs
+            .iter()
+            .filter(|p| !(opts.collapse_empty_paragraphs && p.text.is_empty()))
+            .map(|p| p.text.as_str())
+            .collect::<Vec<_>>()
+            .join(&opts.paragraph_separator)
+    }
+
+    /// Search every paragraph, reporting matches in structural coordinates
+    pub fn find(&self, opts: &SearchOptions) -> Vec<DocFind> {
+        let mut found = Vec::new();
+
+        for (paragraph_index, paragraph) in self.paragraphs.iter().enumerate() {
+            let matches = find_all_in_text(&paragraph.text, opts);
+
+            for result in matches.results {
+                // Runs concatenate to the paragraph text; locate the run holding the match start
+                let mut run_end = 0usize;
+                let run_index = paragraph.runs
+                    .iter()
+                    .position(|run| {
+                        run_end += run.text.len();
+                        result.start < run_end
+                    })
+                    .unwrap_or(paragraph.runs.len().saturating_sub(1));
+
+                found.push(DocFind {
+                    paragraph_index,
+                    run_index,
+                    byte_range_in_para: result.start..result.end,
+                });
+            }
+        }
+
+        found
+    }
+
+    /// Resolve a style's effective formatting by walking its `based_on` chain
+    ///
+    /// Ancestor properties are applied first and overlaid by each descendant's
+    /// explicit values. Cycles and missing parents end the chain.
+    pub fn resolve_style(&self, id: &str) -> Style {
+        let mut chain: Vec<&Style> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut current = self.styles.get(id);
+
+        while let Some(style) = current {
+            if !visited.insert(style.id.as_str()) {
+                break;
+            }
+            chain.push(style);
+            current = style.based_on.as_deref().and_then(|parent| self.styles.get(parent));
+        }
+
+        let mut resolved = match chain.first() {
+            Some(style) => (*style).clone(),
+            None => return Style { id: id.to_string(), ..Style::default() },
+        };
+        resolved.run_properties = RunProperties::default();
+        resolved.paragraph_properties = ParagraphProperties::default();
+
+        for style in chain.iter().rev() {
+            resolved.run_properties.overlay(&style.run_properties);
+            resolved.paragraph_properties.overlay(&style.paragraph_properties);
+        }
+
+        resolved
+    }
+
+    /// Look up a footnote's paragraphs by the `w:id` its references use
+    pub fn footnote(&self, id: i32) -> Option<&[Paragraph]> {
+        self.footnotes.get(&id).map(Vec::as_slice)
+    }
+
+    /// Look up an endnote's paragraphs by the `w:id` its references use
+    pub fn endnote(&self, id: i32) -> Option<&[Paragraph]> {
+        self.endnotes.get(&id).map(Vec::as_slice)
+    }
+
     /// Create a new WordDocument by parsing the OPC package
     pub fn parse(package: &OpcPackage) -> Result<Self, OoxmlError> {
+        Self::parse_monitored(package, &mut ParseMonitor::default())
+    }
+
+    /// Parse the OPC package, reporting progress and honoring cancellation through `monitor`
+    pub(crate) fn parse_monitored(package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<Self, OoxmlError> {
         let mut document = WordDocument {
             text: String::new(),
             paragraphs: Vec::new(),
@@ -63,74 +266,77 @@ impl WordDocument {
             theme: None,
             core_properties: None,
             tables: Vec::new(),
+            body: Vec::new(),
+            sections: Vec::new(),
             images: Vec::new(),
             headers: Vec::new(),
             footers: Vec::new(),
-            footnotes: Vec::new(),
-            endnotes: Vec::new(),
-            numbering: Vec::new(),
+            footnotes: HashMap::new(),
+            endnotes: HashMap::new(),
+            numbering: None,
+            settings: None,
+            preserved_parts: Vec::new(),
+            package_relationships: Vec::new(),
+            document_relationships: Vec::new(),
         };
 
-        document.parse_main_document(package)?;
-        document.parse_styles(package)?;
-        document.parse_theme(package)?;
-        document.parse_core_properties(package)?;
-        document.parse_numbering(package)?;
-        document.parse_headers_footers(package)?;
-        document.parse_footnotes_endnotes(package)?;
+        document.parse_main_document(package, monitor)?;
+        document.parse_styles(package, monitor)?;
+        document.parse_theme(package, monitor)?;
+        document.parse_core_properties(package, monitor)?;
+        document.parse_numbering(package, monitor)?;
+        document.parse_headers_footers(package, monitor)?;
+        document.parse_footnotes_endnotes(package, monitor)?;
+        document.parse_settings(package, monitor)?;
+        monitor.check()?;
+        document.preserve_unmodeled_parts(package);
 
         Ok(document)
     }
 
+    /// Keep the parts and relationships `to_docx_bytes` needs to write the package back losslessly
+    fn preserve_unmodeled_parts(&mut self, package: &OpcPackage) {
+        self.preserved_parts = package.parts()
+            .filter(|part| !REGENERATED_PARTS.contains(&part.name.as_str()))
+            .cloned()
+            .collect();
+        self.preserved_parts.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.package_relationships = package.root_relationships.clone();
+        self.document_relationships = package.get_relationships("/word/document.xml")
+            .cloned()
+            .unwrap_or_default();
+    }
+
     /// Parse the main document body (word/document.xml)
-    fn parse_main_document(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_main_document(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         let main_part_name = "/word/document.xml".to_string();
 
         let main_part = package.get_part(&main_part_name)
             .ok_or_else(|| OoxmlError::PartNotFound(main_part_name.clone()))?;
+        monitor.part(&main_part.name)?;
 
         let xml_str = String::from_utf8_lossy(&main_part.data);
 
-        // First, extract and parse all tables
-        self.parse_tables(&xml_str, package);
-
-        // Then parse paragraphs (excluding those inside tables)
-        // We need to handle paragraphs outside tables
-        let para_pattern = regex::Regex::new(r#"<w:p[^>]*>(.*?)</w:p>"#).unwrap();
+        // First, extract and parse all top-level tables
+        let table_positions = self.parse_tables(&xml_str)?;
 
-        // Track positions to skip table content
-        let table_pattern = regex::Regex::new(r#"<w:tbl[^>]*>.*?</w:tbl>"#).unwrap();
-        let mut last_end = 0usize;
-
-        for table_cap in table_pattern.captures(&xml_str) {
-            let table_range = match table_cap.get(0) {
-                Some(m) => m.start()..m.end(),
-                None => continue,
-            };
-
-            // Parse paragraphs before this table
-            let before_table = &xml_str[last_end..table_range.start];
-            for para_cap in para_pattern.captures(before_table) {
-                if let Some(para_xml) = para_cap.get(1) {
-                    if let Some(para) = self.parse_paragraph(para_xml.as_str()) {
-                        self.paragraphs.push(para);
-                    }
-                }
-            }
+        // Then parse every paragraph in document order, including those nested in tables
+        let located = Self::parse_located_paragraphs(&xml_str, monitor)?;
 
-            // Skip table content (already parsed)
-            last_end = table_range.end;
-        }
+        // Interleave body paragraphs and tables by where they start
+        let mut body: Vec<(u64, BodyElement)> = located.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.in_table)
+            .map(|(index, p)| (p.position, BodyElement::Paragraph(index)))
+            .chain(table_positions.into_iter().enumerate().map(|(index, pos)| (pos, BodyElement::Table(index))))
+            .collect();
+        body.sort_by_key(|(pos, _)| *pos);
+        self.body = body.into_iter().map(|(_, element)| element).collect();
 
-        // Parse paragraphs after last table
-        let after_tables = &xml_str[last_end..];
-        for para_cap in para_pattern.captures(after_tables) {
-            if let Some(para_xml) = para_cap.get(1) {
-                if let Some(para) = self.parse_paragraph(para_xml.as_str()) {
-                    self.paragraphs.push(para);
-                }
-            }
-        }
+        self.paragraphs = located.into_iter().map(|p| p.paragraph).collect();
+        self.sections = Self::parse_sections(&xml_str);
+        self.resolve_hyperlinks(package, &main_part_name);
 
         // Parse inline images in the document
         self.parse_inline_images(&xml_str, package);
@@ -144,98 +350,362 @@ impl WordDocument {
         Ok(())
     }
 
-    /// Parse a single paragraph from XML
+    /// Parse a single paragraph from the inner XML of a `<w:p>` element
     fn parse_paragraph(&self, para_xml: &str) -> Option<Paragraph> {
-        let mut paragraph = Paragraph::default();
+        Self::parse_paragraphs(&format!("<w:p>{}</w:p>", para_xml), &mut ParseMonitor::default())
+            .ok()?
+            .into_iter()
+            .next()
+    }
 
-        // Parse runs within paragraph
-        let run_pattern = regex::Regex::new(r#"<w:r[^>]*>(.*?)</w:r>"#).unwrap();
-        for run_cap in run_pattern.captures(para_xml) {
-            let run_xml = match run_cap.get(1) {
-                Some(m) => m.as_str(),
-                None => continue,
-            };
+    /// Parse all `<w:p>` elements in `xml`, at any nesting depth, in document order.
+    ///
+    /// Each open paragraph keeps its own run state, so runs are always attached to
+    /// the innermost paragraph that owns them (e.g. text boxes inside a run).
+    fn parse_paragraphs(xml: &str, monitor: &mut ParseMonitor) -> Result<Vec<Paragraph>, OoxmlError> {
+        Ok(Self::parse_located_paragraphs(xml, monitor)?
+            .into_iter()
+            .map(|p| p.paragraph)
+            .collect())
+    }
 
-            let mut run = Run::default();
+    /// Like [`Self::parse_paragraphs`], also recording where each paragraph starts
+    /// and whether it lies inside a `<w:tbl>`
+    fn parse_located_paragraphs(xml: &str, monitor: &mut ParseMonitor) -> Result<Vec<LocatedParagraph>, OoxmlError> {
+        /// A paragraph being built, with its slot in the output and its open run
+        struct OpenParagraph {
+            slot: usize,
+            position: u64,
+            in_table: bool,
+            paragraph: Paragraph,
+            run: Option<Run>,
+            in_text: bool,
+            /// Where the open `<w:t>` starts in the run's text, unless it has `xml:space="preserve"`
+            trim_from: Option<usize>,
+            /// Relationship ID (or `#anchor`) of the enclosing `<w:hyperlink>`
+            hyperlink: Option<String>,
+        }
 
-            // Parse text in run
-            let text_pattern = regex::Regex::new(r#"<w:t[^>]*>([^<]*)</w:t>"#).unwrap();
-            for text_cap in text_pattern.captures(run_xml) {
-                if let Some(text_match) = text_cap.get(1) {
-                    run.text = text_match.as_str().to_string();
-                    break;
+        let mut reader = Reader::from_str(xml);
+        let mut slots: Vec<Option<LocatedParagraph>> = Vec::new();
+        let mut open: Vec<OpenParagraph> = Vec::new();
+        let mut parsed = 0usize;
+        let mut table_depth = 0usize;
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => match e.name().as_ref() {
+                    "w:tbl" => table_depth += 1,
+                    "w:p" => {
+                        open.push(OpenParagraph {
+                            slot: slots.len(),
+                            position: reader.buffer_position(),
+                            in_table: table_depth > 0,
+                            paragraph: Paragraph::default(),
+                            run: None,
+                            in_text: false,
+                            trim_from: None,
+                            hyperlink: None,
+                        });
+                        slots.push(None);
+                    }
+                    "w:r" => {
+                        if let Some(current) = open.last_mut() {
+                            current.run = Some(Run {
+                                hyperlink: current.hyperlink.clone(),
+                                ..Run::default()
+                            });
+                        }
+                    }
+                    "w:hyperlink" => {
+                        if let Some(current) = open.last_mut() {
+                            current.hyperlink = match Self::attribute(&e, "r:id")? {
+                                Some(id) => Some(id),
+                                None => Self::attribute(&e, "w:anchor")?.map(|anchor| format!("#{}", anchor)),
+                            };
+                        }
+                    }
+                    "w:pPr" => {
+                        if let Some(current) = open.last_mut().filter(|p| p.run.is_none()) {
+                            let span = reader.read_to_end(e.name())?;
+                            let ppr_xml = &xml[span.start as usize..span.end as usize];
+                            Self::parse_paragraph_properties(ppr_xml, &mut current.paragraph.properties);
+                        }
+                    }
+                    "w:rPr" => {
+                        if let Some(run) = open.last_mut().and_then(|p| p.run.as_mut()) {
+                            let span = reader.read_to_end(e.name())?;
+                            let rpr_xml = &xml[span.start as usize..span.end as usize];
+                            Self::parse_run_properties(rpr_xml, &mut run.properties);
+                        }
+                    }
+                    "w:t" => {
+                        if let Some(current) = open.last_mut() {
+                            current.in_text = current.run.is_some();
+                            let preserve = Self::attribute(&e, "xml:space")?.as_deref() == Some("preserve");
+                            current.trim_from = current.run.as_ref()
+                                .filter(|_| !preserve)
+                                .map(|run| run.text.len());
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Empty(e) => {
+                    if let Some(run) = open.last_mut().and_then(|p| p.run.as_mut()) {
+                        match e.name().as_ref() {
+                            "w:tab" => run.text.push('\t'),
+                            "w:br" | "w:cr" => run.text.push('\n'),
+                            "w:footnoteReference" => {
+                                run.note_reference = Self::note_id(&e)?.map(NoteReference::Footnote);
+                            }
+                            "w:endnoteReference" => {
+                                run.note_reference = Self::note_id(&e)?.map(NoteReference::Endnote);
+                            }
+                            "w:sym" => {
+                                let font = Self::attribute(&e, "w:font")?.unwrap_or_default();
+                                let char_code = Self::attribute(&e, "w:char")?
+                                    .and_then(|code| u32::from_str_radix(code.trim(), 16).ok());
+                                run.symbol = char_code.map(|char_code| Symbol { font, char_code });
+                            }
+                            _ => {}
+                        }
+                    }
                 }
-            }
-
-            // Parse run properties
-            let rpr_pattern = regex::Regex::new(r#"<w:rPr[^>]*>(.*?)</w:rPr>"#).unwrap();
-            if let Some(rpr_cap) = rpr_pattern.captures(run_xml) {
-                if let Some(rpr_xml) = rpr_cap.get(1) {
-                    Self::parse_run_properties(rpr_xml.as_str(), &mut run.properties);
+                Event::Text(e) => {
+                    if let Some(current) = open.last_mut().filter(|p| p.in_text) {
+                        if let Some(run) = current.run.as_mut() {
+                            run.text.push_str(&e.xml10_content());
+                        }
+                    }
                 }
-            }
-
-            if !run.text.is_empty() || !run.properties.is_default() {
-                paragraph.runs.push(run);
+                Event::GeneralRef(e) => {
+                    if let Some(current) = open.last_mut().filter(|p| p.in_text) {
+                        if let Some(run) = current.run.as_mut() {
+                            if let Some(ch) = e.resolve_char_ref()? {
+                                run.text.push(ch);
+                            } else if let Some(entity) = resolve_predefined_entity(&e) {
+                                run.text.push_str(entity);
+                            }
+                        }
+                    }
+                }
+                Event::End(e) => match e.name().as_ref() {
+                    "w:tbl" => table_depth = table_depth.saturating_sub(1),
+                    "w:p" => {
+                        if let Some(mut finished) = open.pop() {
+                            if !finished.paragraph.runs.is_empty() {
+                                finished.paragraph.text = finished.paragraph.runs
+                                    .iter()
+                                    .map(|r| r.text.as_str())
+                                    .collect();
+                                slots[finished.slot] = Some(LocatedParagraph {
+                                    position: finished.position,
+                                    in_table: finished.in_table,
+                                    paragraph: finished.paragraph,
+                                });
+                                parsed += 1;
+                                monitor.report(ParseProgress::Paragraph { parsed })?;
+                            }
+                        }
+                    }
+                    "w:r" => {
+                        if let Some(current) = open.last_mut() {
+                            if let Some(run) = current.run.take() {
+                                if !run.text.is_empty() || !run.properties.is_default() || run.note_reference.is_some() || run.symbol.is_some() {
+                                    current.paragraph.runs.push(run);
+                                }
+                            }
+                        }
+                    }
+                    "w:hyperlink" => {
+                        if let Some(current) = open.last_mut() {
+                            current.hyperlink = None;
+                        }
+                    }
+                    "w:t" => {
+                        if let Some(current) = open.last_mut() {
+                            current.in_text = false;
+                            // Without xml:space="preserve", edge whitespace is insignificant
+                            if let (Some(start), Some(run)) = (current.trim_from.take(), current.run.as_mut()) {
+                                let trimmed = run.text[start..].trim_matches([' ', '\t', '\r', '\n']).to_string();
+                                run.text.truncate(start);
+                                run.text.push_str(&trimmed);
+                            }
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
             }
         }
 
-        if paragraph.runs.is_empty() {
-            return None;
-        }
+        Ok(slots.into_iter().flatten().collect())
+    }
 
-        paragraph.text = paragraph.runs
-            .iter()
-            .map(|r| r.text.clone())
-            .collect();
-        Some(paragraph)
+    /// Read and unescape an attribute of a start tag
+    fn attribute(e: &BytesStart, name: &str) -> Result<Option<String>, OoxmlError> {
+        match e.try_get_attribute(name).map_err(quick_xml::Error::from)? {
+            Some(attr) => Ok(Some(attr.normalized_value(XmlVersion::Implicit1_0)?.into_owned())),
+            None => Ok(None),
+        }
     }
 
-    /// Parse tables from document XML
-    fn parse_tables(&mut self, xml_str: &str, _package: &OpcPackage) {
-        let table_pattern = regex::Regex::new(r#"<w:tbl[^>]*>(.*?)</w:tbl>"#).unwrap();
+    /// Replace hyperlink relationship IDs on runs with their targets
+    ///
+    /// Internal `#anchor` links are kept as-is; IDs missing from the part's
+    /// relationships are dropped.
+    fn resolve_hyperlinks(&mut self, package: &OpcPackage, part_name: &str) {
+        let relationships = package.get_relationships(part_name);
 
-        for table_cap in table_pattern.captures(xml_str) {
-            let table_xml = match table_cap.get(1) {
-                Some(m) => m.as_str(),
-                None => continue,
+        for run in self.paragraphs.iter_mut().flat_map(|p| p.runs.iter_mut()) {
+            let Some(link) = run.hyperlink.take() else {
+                continue;
             };
 
-            let mut table = Table::default();
-            table.properties = self.parse_table_properties(table_xml);
+            run.hyperlink = if link.starts_with('#') {
+                Some(link)
+            } else {
+                relationships
+                    .and_then(|rels| rels.iter().find(|rel| rel.id == link))
+                    .map(|rel| rel.target.clone())
+            };
+        }
+    }
 
-            // Parse table rows
-            let row_pattern = regex::Regex::new(r#"<w:tr[^>]*>(.*?)</w:tr>"#).unwrap();
-            for row_cap in row_pattern.captures(table_xml) {
-                let row_xml = match row_cap.get(1) {
-                    Some(m) => m.as_str(),
-                    None => continue,
-                };
+    /// Parse the top-level `<w:tbl>` elements of the document, returning where each starts
+    ///
+    /// Nested tables are skipped as part of their enclosing cell; their paragraphs
+    /// are still picked up by the paragraph pass.
+    fn parse_tables(&mut self, xml: &str) -> Result<Vec<u64>, OoxmlError> {
+        let mut reader = Reader::from_str(xml);
+        let mut positions = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) if e.name().as_ref() == "w:tbl" => {
+                    let position = reader.buffer_position();
+                    let span = reader.read_to_end(e.name())?;
+                    let table = self.parse_table(&xml[span.start as usize..span.end as usize])?;
+                    if !table.rows.is_empty() {
+                        self.tables.push(table);
+                        positions.push(position);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
 
-                let mut row = TableRow::default();
-                row.properties = self.parse_table_row_properties(row_xml);
+        Ok(positions)
+    }
 
-                // Parse table cells
-                let cell_pattern = regex::Regex::new(r#"<w:tc[^>]*>(.*?)</w:tc>"#).unwrap();
-                for cell_cap in cell_pattern.captures(row_xml) {
-                    let cell_xml = match cell_cap.get(1) {
-                        Some(m) => m.as_str(),
-                        None => continue,
-                    };
+    /// Parse every `<w:sectPr>` in document order; missing values keep Word's defaults
+    fn parse_sections(xml: &str) -> Vec<SectionProperties> {
+        let section_pattern = regex::Regex::new(r#"(?s)<w:sectPr(?:\s[^>]*)?(?:/>|>(.*?)</w:sectPr>)"#).unwrap();
+
+        section_pattern.captures_iter(xml)
+            .map(|caps| {
+                let section_xml = caps.get(1).map_or("", |m| m.as_str());
+                let mut section = SectionProperties::default();
+                let attr = |element: &str, attr: &str| Self::xml_attr(section_xml, element, attr);
+
+                if let Some(width) = attr("pgSz", "w").and_then(|v| v.parse().ok()) {
+                    section.page_width = width;
+                }
+                if let Some(height) = attr("pgSz", "h").and_then(|v| v.parse().ok()) {
+                    section.page_height = height;
+                }
+                if attr("pgSz", "orient").as_deref() == Some("landscape") {
+                    section.orientation = PageOrientation::Landscape;
+                }
+
+                if let Some(top) = attr("pgMar", "top").and_then(|v| v.parse().ok()) {
+                    section.margin_top = top;
+                }
+                if let Some(bottom) = attr("pgMar", "bottom").and_then(|v| v.parse().ok()) {
+                    section.margin_bottom = bottom;
+                }
+                if let Some(left) = attr("pgMar", "left").or_else(|| attr("pgMar", "start")).and_then(|v| v.parse().ok()) {
+                    section.margin_left = left;
+                }
+                if let Some(right) = attr("pgMar", "right").or_else(|| attr("pgMar", "end")).and_then(|v| v.parse().ok()) {
+                    section.margin_right = right;
+                }
+                if let Some(header) = attr("pgMar", "header").and_then(|v| v.parse().ok()) {
+                    section.header_distance = header;
+                }
+                if let Some(footer) = attr("pgMar", "footer").and_then(|v| v.parse().ok()) {
+                    section.footer_distance = footer;
+                }
 
-                    let cell = self.parse_table_cell(cell_xml);
-                    row.cells.push(cell);
+                if let Some(columns) = attr("cols", "num").and_then(|v| v.parse().ok()) {
+                    section.columns = columns;
+                }
+                if let Some(gap) = attr("cols", "space").and_then(|v| v.parse().ok()) {
+                    section.column_gap = gap;
                 }
 
-                if !row.cells.is_empty() {
-                    table.rows.push(row);
+                section
+            })
+            .collect()
+    }
+
+    /// Parse the content of a `<w:tbl>` element
+    fn parse_table(&self, table_xml: &str) -> Result<Table, OoxmlError> {
+        let mut table = Table::default();
+        let mut reader = Reader::from_str(table_xml);
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => {
+                    let span = reader.read_to_end(e.name())?;
+                    let inner = &table_xml[span.start as usize..span.end as usize];
+                    match e.name().as_ref() {
+                        "w:tblPr" => table.properties = self.parse_table_properties(inner),
+                        "w:tr" => {
+                            let row = self.parse_table_row(inner)?;
+                            if !row.cells.is_empty() {
+                                table.rows.push(row);
+                            }
+                        }
+                        _ => {}
+                    }
                 }
+                Event::Eof => break,
+                _ => {}
             }
+        }
 
-            if !table.rows.is_empty() {
-                self.tables.push(table);
+        Ok(table)
+    }
+
+    /// Parse the content of a `<w:tr>` element
+    fn parse_table_row(&self, row_xml: &str) -> Result<TableRow, OoxmlError> {
+        let mut row = TableRow::default();
+        let mut reader = Reader::from_str(row_xml);
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => {
+                    let span = reader.read_to_end(e.name())?;
+                    let inner = &row_xml[span.start as usize..span.end as usize];
+                    match e.name().as_ref() {
+                        "w:trPr" => {
+                            row.properties = self.parse_table_row_properties(inner);
+                            row.height = row.properties.height;
+                        }
+                        "w:tc" => row.cells.push(self.parse_table_cell(inner)?),
+                        _ => {}
+                    }
+                }
+                Event::Empty(e) if e.name().as_ref() == "w:tc" => row.cells.push(TableCell::default()),
+                Event::Eof => break,
+                _ => {}
             }
         }
+
+        Ok(row)
     }
 
     /// Parse table properties from XML
@@ -324,126 +794,173 @@ impl WordDocument {
             }
         }
 
-        // Check if header row
-        props.is_header = row_xml.contains("<w:tblHeader");
+        // Check if header row (repeated at the top of each page)
+        if let Some(caps) = regex::Regex::new(r#"<w:tblHeader(?:\s+w:val="([^"]*)")?\s*/>"#).unwrap().captures(row_xml) {
+            props.is_header = caps.get(1).is_none_or(|m| Self::is_on(m.as_str()));
+        }
 
         props
     }
 
-    /// Parse table cell from XML
-    fn parse_table_cell(&self, cell_xml: &str) -> TableCell {
+    /// Parse the content of a `<w:tc>` element
+    fn parse_table_cell(&self, cell_xml: &str) -> Result<TableCell, OoxmlError> {
         let mut cell = TableCell::default();
-
-        // Parse cell width
-        if let Some(caps) = regex::Regex::new(r#"<w:tcW[^>]*w:w="([^"]*)""#).unwrap().captures(cell_xml) {
-            if let Some(m) = caps.get(1) {
-                cell.width = m.as_str().parse().ok();
+        let mut reader = Reader::from_str(cell_xml);
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => {
+                    let span = reader.read_to_end(e.name())?;
+                    let inner = &cell_xml[span.start as usize..span.end as usize];
+                    match e.name().as_ref() {
+                        "w:tcPr" => Self::parse_table_cell_properties(inner, &mut cell),
+                        "w:p" => {
+                            if let Some(para) = self.parse_paragraph(inner) {
+                                cell.paragraphs.push(para);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
             }
         }
 
-        // Parse vertical merge
-        if let Some(caps) = regex::Regex::new(r#"<w:vMerge[^>]*w:val="([^"]*)""#).unwrap().captures(cell_xml) {
-            if let Some(m) = caps.get(1) {
-                cell.vertical_merge = Some(match m.as_str() {
-                    "restart" => 1,
-                    "continue" => -1,
-                    _ => 0,
-                });
-            }
-        }
+        Ok(cell)
+    }
 
-        // Parse horizontal merge
-        if let Some(caps) = regex::Regex::new(r#"<w:hMerge[^>]*w:val="([^"]*)""#).unwrap().captures(cell_xml) {
-            if let Some(m) = caps.get(1) {
-                cell.horizontal_merge = Some(match m.as_str() {
-                    "restart" => 1,
-                    "continue" => -1,
-                    _ => 0,
-                });
-            }
-        }
+    /// Parse cell properties (the content of `<w:tcPr>`) from XML
+    fn parse_table_cell_properties(xml: &str, cell: &mut TableCell) {
+        let merge = |val: Option<String>| match val.as_deref() {
+            Some("restart") => 1,
+            // A bare `<w:vMerge/>` continues the merge above
+            Some("continue") | None => -1,
+            Some(_) => 0,
+        };
 
-        // Parse paragraphs in cell
-        let para_pattern = regex::Regex::new(r#"<w:p[^>]*>(.*?)</w:p>"#).unwrap();
-        for para_cap in para_pattern.captures(cell_xml) {
-            if let Some(para_xml) = para_cap.get(1) {
-                if let Some(para) = self.parse_paragraph(para_xml.as_str()) {
-                    cell.paragraphs.push(para);
-                }
-            }
+        if let Some(width) = Self::xml_attr(xml, "tcW", "w") {
+            cell.width = width.parse().ok();
+            cell.properties.width = cell.width;
         }
-
-        cell
+        if let Some(span) = Self::xml_attr(xml, "gridSpan", "val") {
+            cell.grid_span = span.parse().ok();
+        }
+        if Self::has_element(xml, "vMerge") {
+            cell.vertical_merge = Some(merge(Self::xml_attr(xml, "vMerge", "val")));
+        }
+        if Self::has_element(xml, "hMerge") {
+            cell.horizontal_merge = Some(merge(Self::xml_attr(xml, "hMerge", "val")));
+        }
+        cell.properties.vertical_alignment = Self::xml_attr(xml, "vAlign", "val");
+        cell.properties.text_direction = Self::xml_attr(xml, "textDirection", "val");
+        cell.properties.shading_color = Self::xml_attr(xml, "shd", "fill")
+            .filter(|fill| fill != "auto");
     }
 
-    /// Parse inline images from document XML
+    /// Parse the pictures embedded by `<w:drawing>` elements, in document order
     fn parse_inline_images(&mut self, xml_str: &str, package: &OpcPackage) {
-        // Parse drawing elements with inline pictures
-        let drawing_pattern = regex::Regex::new(
-            r#"<w:drawing[^>]*>.*?<wp:inline[^>]*>.*?(?:<wp:blipFill>.*?<a:blip[^>]*r:embed="([^"]*)"[^>]*>.*?</wp:blipFill>).*?</wp:inline>.*?</w:drawing>"#
-        ).unwrap();
+        let capture = |pattern: &Regex, xml: &str| {
+            pattern.captures(xml).and_then(|caps| caps.get(1)).map(|m| m.as_str().to_string())
+        };
 
-        for cap in drawing_pattern.captures(xml_str) {
-            if let Some(embed_id) = cap.get(1) {
-                let embed_id = embed_id.as_str().to_string();
+        for drawing in DRAWING_PATTERN.find_iter(xml_str) {
+            let drawing = drawing.as_str();
+            let Some(embed_id) = capture(&BLIP_PATTERN, drawing) else {
+                continue;
+            };
+            let Some(mut image) = self.resolve_image_reference(package, &embed_id) else {
+                continue;
+            };
 
-                // Look up the image in relationships
-                let image = self.resolve_image_reference(package, &embed_id);
-                if let Some(img) = image {
-                    self.images.push(img);
-                }
+            if let Some(extent) = EXTENT_PATTERN.captures(drawing) {
+                image.desired_width = extent[1].parse().ok();
+                image.desired_height = extent[2].parse().ok();
             }
+            image.title = capture(&DOC_PR_TITLE_PATTERN, drawing);
+            image.alt_description = capture(&DOC_PR_DESCR_PATTERN, drawing);
+            self.images.push(image);
         }
     }
 
     /// Resolve image reference from relationships
     fn resolve_image_reference(&self, package: &OpcPackage, embed_id: &str) -> Option<DocumentImage> {
-        // Get document relationships
-        let doc_rels_part = "/word/_rels/document.xml.rels";
+        let image_part = package.image_for_relationship("/word/document.xml", embed_id)?;
+
+        Some(DocumentImage {
+            id: embed_id.to_string(),
+            path: image_part.name.trim_start_matches("/word/").to_string(),
+            original_width: None,
+            original_height: None,
+            desired_width: None,
+            desired_height: None,
+            scale_x: None,
+            scale_y: None,
+            title: None,
+            alt_description: None,
+            is_linked: false,
+        })
+    }
 
-        let rels_part = if let Some(part) = package.get_part(doc_rels_part) {
-            part
-        } else {
-            return None;
-        };
+    /// Parse paragraph properties (the content of `<w:pPr>`) from XML
+    fn parse_paragraph_properties(xml: &str, props: &mut ParagraphProperties) {
+        // Alignment
+        if let Some(val) = Self::xml_attr(xml, "jc", "val") {
+            let alignment = match val.as_str() {
+                "start" => "left".to_string(),
+                "end" => "right".to_string(),
+                _ => val,
+            };
+            props.alignment = Some(alignment);
+        }
 
-        let rels_xml = String::from_utf8_lossy(&rels_part.data);
+        // Indentation (w:start/w:end are the newer names for w:left/w:right)
+        if let Some(left) = Self::xml_attr(xml, "ind", "left").or_else(|| Self::xml_attr(xml, "ind", "start")) {
+            props.indent_left = left.parse().ok();
+        }
+        if let Some(right) = Self::xml_attr(xml, "ind", "right").or_else(|| Self::xml_attr(xml, "ind", "end")) {
+            props.indent_right = right.parse().ok();
+        }
+        if let Some(first_line) = Self::xml_attr(xml, "ind", "firstLine") {
+            props.indent_first_line = first_line.parse().ok();
+        } else if let Some(hanging) = Self::xml_attr(xml, "ind", "hanging") {
+            props.indent_first_line = hanging.parse::<i32>().ok().map(|h| -h);
+        }
 
-        // Find the relationship with this ID
-        // Escape special regex characters in embed_id
-        let escaped_id = regex::escape(embed_id);
-        let rel_pattern = format!(
-            r#"<Relationship[^>]*Id="{}"[^>]*Target="([^"]*)""#,
-            escaped_id
-        );
+        // Spacing
+        if let Some(before) = Self::xml_attr(xml, "spacing", "before") {
+            props.spacing_before = before.parse().ok();
+        }
+        if let Some(after) = Self::xml_attr(xml, "spacing", "after") {
+            props.spacing_after = after.parse().ok();
+        }
+        if let Some(line) = Self::xml_attr(xml, "spacing", "line") {
+            props.spacing_line = line.parse().ok();
+        }
 
-        if let Some(caps) = regex::Regex::new(&rel_pattern).unwrap().captures(&rels_xml) {
-            if let Some(target) = caps.get(1) {
-                let target = target.as_str().to_string();
-                let image_path = format!("/word/{}", target);
-
-                // Get the image part
-                if let Some(image_part) = package.get_part(&image_path) {
-                    let _content_type = &image_part.content_type;
-
-                    return Some(DocumentImage {
-                        id: embed_id.to_string(),
-                        path: target,
-                        original_width: None,
-                        original_height: None,
-                        desired_width: None,
-                        desired_height: None,
-                        scale_x: None,
-                        scale_y: None,
-                        title: None,
-                        alt_description: None,
-                        is_linked: false,
-                    });
-                }
-            }
+        // List membership
+        if let Some(num_id) = Self::xml_attr(xml, "numId", "val") {
+            props.num_id = Some(num_id);
         }
+        if let Some(ilvl) = Self::xml_attr(xml, "ilvl", "val") {
+            props.ilvl = ilvl.parse().ok();
+        }
+    }
+
+    /// Read the `w:{attr}` attribute of the first `<w:{element}>` in `xml`
+    fn xml_attr(xml: &str, element: &str, attr: &str) -> Option<String> {
+        let pattern = format!(r#"<w:{}\b[^>]*\bw:{}="([^"]*)""#, element, attr);
+        regex::Regex::new(&pattern)
+            .ok()?
+            .captures(xml)
+            .and_then(|caps| caps.get(1))
+            .map(|m| m.as_str().to_string())
+    }
 
-        None
+    /// Check whether `xml` contains a `<w:{element}>` start or empty tag
+    fn has_element(xml: &str, element: &str) -> bool {
+        regex::Regex::new(&format!(r#"<w:{}[\s/>]"#, element))
+            .is_ok_and(|re| re.is_match(xml))
     }
 
     /// Parse run properties from XML
@@ -472,8 +989,9 @@ impl WordDocument {
         // Font size
         if let Some(caps) = regex::Regex::new(r#"<w:sz[^>]*val="(\d+)""#).unwrap().captures(xml) {
             if let Some(m) = caps.get(1) {
+                // Kept in half-points so e.g. 23 (11.5pt) isn't rounded down
                 if let Ok(size) = m.as_str().parse::<i32>() {
-                    props.font_size = Some(size / 2);
+                    props.font_size = Some(size);
                 }
             }
         }
@@ -491,10 +1009,64 @@ impl WordDocument {
                 props.font_name = Some(m.as_str().to_string());
             }
         }
+
+        // Background: explicit shading wins over a named highlight
+        let shading = Self::xml_attr(xml, "shd", "fill")
+            .filter(|fill| fill != "auto");
+        let highlight = Self::xml_attr(xml, "highlight", "val")
+            .and_then(|name| Self::highlight_to_hex(&name))
+            .map(str::to_string);
+        if let Some(color) = shading.or(highlight) {
+            props.background_color = Some(color);
+        }
+
+        // All caps / small caps (toggle properties, on unless w:val is false)
+        if let Some(caps) = CAPS_PATTERN.captures(xml) {
+            props.caps = Some(caps.get(1).is_none_or(|m| Self::is_on(m.as_str())));
+        }
+        if let Some(caps) = SMALL_CAPS_PATTERN.captures(xml) {
+            props.small_caps = Some(caps.get(1).is_none_or(|m| Self::is_on(m.as_str())));
+        }
+        if let Some(caps) = STRIKE_PATTERN.captures(xml) {
+            props.strikethrough = Some(caps.get(1).is_none_or(|m| Self::is_on(m.as_str())));
+        }
+
+        // Superscript / subscript
+        if let Some(align) = Self::xml_attr(xml, "vertAlign", "val").and_then(|v| VertAlign::from_string(&v)) {
+            props.vert_align = Some(align);
+        }
+    }
+
+    /// Hex RGB for an `ST_HighlightColor` name, or None for `none` and unknown names
+    fn highlight_to_hex(name: &str) -> Option<&'static str> {
+        Some(match name {
+            "black" => "000000",
+            "blue" => "0000FF",
+            "cyan" => "00FFFF",
+            "green" => "00FF00",
+            "magenta" => "FF00FF",
+            "red" => "FF0000",
+            "yellow" => "FFFF00",
+            "white" => "FFFFFF",
+            "darkBlue" => "000080",
+            "darkCyan" => "008080",
+            "darkGreen" => "008000",
+            "darkMagenta" => "800080",
+            "darkRed" => "800000",
+            "darkYellow" => "808000",
+            "darkGray" => "808080",
+            "lightGray" => "C0C0C0",
+            _ => return None,
+        })
+    }
+
+    /// Interpret an `ST_OnOff` attribute value
+    fn is_on(val: &str) -> bool {
+        !matches!(val, "0" | "false" | "off")
     }
 
     /// Parse styles (word/styles.xml)
-    fn parse_styles(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_styles(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         let styles_part_name = "/word/styles.xml";
         
         let styles_part = if let Some(part) = package.get_part(styles_part_name) {
@@ -502,15 +1074,21 @@ impl WordDocument {
         } else {
             return Ok(());
         };
+        monitor.part(&styles_part.name)?;
 
         let xml_str = String::from_utf8_lossy(&styles_part.data);
         
         // Parse style elements
         let style_pattern = regex::Regex::new(
-            r#"<w:style[^>]*w:styleId="([^"]*)"[^>]*w:type="([^"]*)"[^>]*>(.*?)</w:style>"#
+            r#"(?s)<w:style[^>]*w:styleId="([^"]*)"[^>]*w:type="([^"]*)"[^>]*>(.*?)</w:style>"#
         ).unwrap();
+        let rpr_pattern = regex::Regex::new(r"(?s)<w:rPr>(.*?)</w:rPr>").unwrap();
+        let ppr_pattern = regex::Regex::new(r"(?s)<w:pPr>(.*?)</w:pPr>").unwrap();
+        let name_pattern = regex::Regex::new(r#"<w:name[^>]*w:val="([^"]*)""#).unwrap();
+        let based_on_pattern = regex::Regex::new(r#"<w:basedOn[^>]*w:val="([^"]*)""#).unwrap();
+        let default_pattern = regex::Regex::new(r#"w:default="1""#).unwrap();
         
-        for cap in style_pattern.captures(&xml_str) {
+        for cap in style_pattern.captures_iter(&xml_str) {
             let style_id = match cap.get(1) {
                 Some(m) => m.as_str().to_string(),
                 None => continue,
@@ -537,24 +1115,32 @@ impl WordDocument {
             };
             
             // Get style name
-            if let Some(name_cap) = regex::Regex::new(r#"<w:name[^>]*w:val="([^"]*)""#).unwrap().captures(style_xml) {
+            if let Some(name_cap) = name_pattern.captures(style_xml) {
                 if let Some(m) = name_cap.get(1) {
                     style.name = Some(m.as_str().to_string());
                 }
             }
             
             // Get basedOn
-            if let Some(based_cap) = regex::Regex::new(r#"<w:basedOn[^>]*w:val="([^"]*)""#).unwrap().captures(style_xml) {
+            if let Some(based_cap) = based_on_pattern.captures(style_xml) {
                 if let Some(m) = based_cap.get(1) {
                     style.based_on = Some(m.as_str().to_string());
                 }
             }
             
             // Check if default
-            if regex::Regex::new(r#"w:default="1""#).unwrap().is_match(style_xml) {
+            if default_pattern.is_match(style_xml) {
                 style.is_default = true;
             }
             
+            // Formatting defined by the style itself
+            if let Some(rpr) = rpr_pattern.captures(style_xml) {
+                Self::parse_run_properties(&rpr[1], &mut style.run_properties);
+            }
+            if let Some(ppr) = ppr_pattern.captures(style_xml) {
+                Self::parse_paragraph_properties(&ppr[1], &mut style.paragraph_properties);
+            }
+            
             self.styles.insert(style_id, style);
         }
 
@@ -562,19 +1148,17 @@ impl WordDocument {
     }
 
     /// Parse theme (word/theme/theme1.xml)
-    fn parse_theme(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_theme(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         let theme_part_names = ["/word/theme/theme1.xml", "/word/theme/theme.xml", "/word/themes/theme1.xml"];
         
-        let theme_part = theme_part_names.iter()
-            .find(|&name| package.get_part(name).is_some())
-            .and_then(|name| package.get_part(name));
-
-        if theme_part.is_none() {
-            return Ok(());
-        }
+        let theme_part = match theme_part_names.iter().find_map(|name| package.get_part(name)) {
+            Some(part) => part,
+            None => return Ok(()),
+        };
+        monitor.part(&theme_part.name)?;
 
-        let _theme_part = theme_part.unwrap();
-        let theme = Theme {
+        let xml_str = String::from_utf8_lossy(&theme_part.data);
+        let mut theme = Theme {
             name: "Office Theme".to_string(),
             colors: HashMap::new(),
             fonts: ThemeFonts {
@@ -584,12 +1168,43 @@ impl WordDocument {
             },
         };
 
+        if let Some(caps) = regex::Regex::new(r#"<a:theme[^>]*\sname="([^"]*)""#).unwrap().captures(&xml_str) {
+            theme.name = caps[1].to_string();
+        }
+
+        // Color scheme: each slot holds either an explicit sRGB value or a system color
+        // whose last computed value is recorded in lastClr
+        let scheme_names = [
+            "dk1", "lt1", "dk2", "lt2",
+            "accent1", "accent2", "accent3", "accent4", "accent5", "accent6",
+            "hlink", "folHlink",
+        ];
+        for name in scheme_names {
+            let pattern = format!(
+                r#"(?s)<a:{name}>\s*<a:(?:srgbClr\s+val|sysClr[^>]*lastClr)="([0-9A-Fa-f]{{6}})""#
+            );
+            if let Some(caps) = regex::Regex::new(&pattern).unwrap().captures(&xml_str) {
+                theme.colors.insert(name.to_string(), caps[1].to_uppercase());
+            }
+        }
+
+        // Font scheme: the latin typeface of the major (headings) and minor (body) fonts
+        let font_pattern = regex::Regex::new(
+            r#"(?s)<a:(majorFont|minorFont)>\s*<a:latin[^>]*typeface="([^"]*)""#
+        ).unwrap();
+        for caps in font_pattern.captures_iter(&xml_str) {
+            match &caps[1] {
+                "majorFont" => theme.fonts.major_font = caps[2].to_string(),
+                _ => theme.fonts.minor_font = caps[2].to_string(),
+            }
+        }
+
         self.theme = Some(theme);
         Ok(())
     }
 
     /// Parse core properties (docProps/core.xml)
-    fn parse_core_properties(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_core_properties(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         let core_part_name = "/docProps/core.xml";
         
         let core_part = if let Some(part) = package.get_part(core_part_name) {
@@ -597,6 +1212,7 @@ impl WordDocument {
         } else {
             return Ok(());
         };
+        monitor.part(&core_part.name)?;
 
         let xml_str = String::from_utf8_lossy(&core_part.data);
         let mut props = CoreProperties::default();
@@ -633,8 +1249,49 @@ impl WordDocument {
         Ok(())
     }
 
+    /// Parse document settings (word/settings.xml)
+    fn parse_settings(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
+        let settings_part = if let Some(part) = package.get_part("/word/settings.xml") {
+            part
+        } else {
+            return Ok(());
+        };
+        monitor.part(&settings_part.name)?;
+
+        let xml_str = String::from_utf8_lossy(&settings_part.data);
+        let mut settings = DocumentSettings::default();
+
+        if let Some(caps) = regex::Regex::new(r#"<w:defaultTabStop[^>]*\sw:val="(-?\d+)""#).unwrap().captures(&xml_str) {
+            settings.default_tab_stop = caps[1].parse().ok();
+        }
+
+        if let Some(caps) = regex::Regex::new(r#"<w:proofState\b[^>]*>"#).unwrap().captures(&xml_str) {
+            let attribute = |name: &str| {
+                regex::Regex::new(&format!(r#"\sw:{}="([^"]*)""#, name)).unwrap()
+                    .captures(&caps[0])
+                    .map(|value| value[1].to_string())
+            };
+            settings.spelling_state = attribute("spelling");
+            settings.grammar_state = attribute("grammar");
+        }
+
+        if let Some(caps) = regex::Regex::new(r#"<w:themeFontLang[^>]*\sw:val="([^"]*)""#).unwrap().captures(&xml_str) {
+            settings.language = Some(caps[1].to_string());
+        }
+
+        let compat_pattern = regex::Regex::new(
+            r#"<w:compatSetting[^>]*\sw:name="compatibilityMode"[^>]*\sw:val="(\d+)""#
+        ).unwrap();
+        if let Some(caps) = compat_pattern.captures(&xml_str) {
+            settings.compatibility_mode = caps[1].parse().ok();
+        }
+
+        self.settings = Some(settings);
+        Ok(())
+    }
+
     /// Parse numbering definitions (word/numbering.xml)
-    fn parse_numbering(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_numbering(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         let numbering_part_name = "/word/numbering.xml";
 
         let numbering_part = if let Some(part) = package.get_part(numbering_part_name) {
@@ -642,16 +1299,21 @@ impl WordDocument {
         } else {
             return Ok(());
         };
+        monitor.part(&numbering_part.name)?;
 
         let xml_str = String::from_utf8_lossy(&numbering_part.data);
         let mut numbering = Numbering::default();
 
         // Parse abstract numbering definitions
         let abstract_num_pattern = regex::Regex::new(
-            r#"<w:abstractNum[^>]*w:abstractNumId="([^"]*)"[^>]*>(.*?)</w:abstractNum>"#
+            r#"(?s)<w:abstractNum\s[^>]*w:abstractNumId="([^"]*)"[^>]*>(.*?)</w:abstractNum>"#
         ).unwrap();
+        let lvl_pattern = regex::Regex::new(r#"(?s)<w:lvl\s[^>]*w:ilvl="([^"]*)"[^>]*>(.*?)</w:lvl>"#).unwrap();
+        let num_fmt_pattern = regex::Regex::new(r#"<w:numFmt[^>]*w:val="([^"]*)""#).unwrap();
+        let lvl_text_pattern = regex::Regex::new(r#"<w:lvlText[^>]*w:val="([^"]*)""#).unwrap();
+        let start_pattern = regex::Regex::new(r#"<w:start\s[^>]*w:val="([^"]*)""#).unwrap();
 
-        for cap in abstract_num_pattern.captures(&xml_str) {
+        for cap in abstract_num_pattern.captures_iter(&xml_str) {
             let abstract_num_id = match cap.get(1) {
                 Some(m) => m.as_str().to_string(),
                 None => continue,
@@ -668,8 +1330,7 @@ impl WordDocument {
             };
 
             // Parse list levels (lvl)
-            let lvl_pattern = regex::Regex::new(r#"<w:lvl[^>]*w:ilvl="([^"]*)"[^>]*>(.*?)</w:lvl>"#).unwrap();
-            for lvl_cap in lvl_pattern.captures(abstract_num_xml) {
+            for lvl_cap in lvl_pattern.captures_iter(abstract_num_xml) {
                 let level_idx = match lvl_cap.get(1) {
                     Some(m) => m.as_str().parse().unwrap_or(0),
                     None => 0,
@@ -690,21 +1351,21 @@ impl WordDocument {
                 };
 
                 // Parse format
-                if let Some(caps) = regex::Regex::new(r#"<w:numFmt[^>]*w:val="([^"]*)""#).unwrap().captures(lvl_xml) {
+                if let Some(caps) = num_fmt_pattern.captures(lvl_xml) {
                     if let Some(m) = caps.get(1) {
                         level.format = m.as_str().to_string();
                     }
                 }
 
                 // Parse text
-                if let Some(caps) = regex::Regex::new(r#"<w:lvlText[^>]*w:val="([^"]*)""#).unwrap().captures(lvl_xml) {
+                if let Some(caps) = lvl_text_pattern.captures(lvl_xml) {
                     if let Some(m) = caps.get(1) {
                         level.text = m.as_str().to_string();
                     }
                 }
 
                 // Parse start value
-                if let Some(caps) = regex::Regex::new(r#"<w:startOverride[^>]*w:val="([^"]*)""#).unwrap().captures(lvl_xml) {
+                if let Some(caps) = start_pattern.captures(lvl_xml) {
                     if let Some(m) = caps.get(1) {
                         level.start_value = m.as_str().parse().unwrap_or(1);
                     }
@@ -717,8 +1378,10 @@ impl WordDocument {
         }
 
         // Parse numbering instances
-        let num_pattern = regex::Regex::new(r#"<w:num[^>]*w:numId="([^"]*)"[^>]*>(.*?)</w:num>"#).unwrap();
-        for cap in num_pattern.captures(&xml_str) {
+        let num_pattern = regex::Regex::new(r#"(?s)<w:num\s[^>]*w:numId="([^"]*)"[^>]*>(.*?)</w:num>"#).unwrap();
+        let abstract_num_id_pattern = regex::Regex::new(r#"<w:abstractNumId[^>]*w:val="([^"]*)""#).unwrap();
+        let override_pattern = regex::Regex::new(r#"(?s)<w:lvlOverride\s[^>]*w:ilvl="([^"]*)"[^>]*>(.*?)</w:lvlOverride>"#).unwrap();
+        for cap in num_pattern.captures_iter(&xml_str) {
             let num_id = match cap.get(1) {
                 Some(m) => m.as_str().to_string(),
                 None => continue,
@@ -736,21 +1399,32 @@ impl WordDocument {
             };
 
             // Parse abstract num ID reference
-            if let Some(caps) = regex::Regex::new(r#"<w:abstractNumId[^>]*w:val="([^"]*)""#).unwrap().captures(num_xml) {
+            if let Some(caps) = abstract_num_id_pattern.captures(num_xml) {
                 if let Some(m) = caps.get(1) {
                     num_instance.abstract_num_id = m.as_str().to_string();
                 }
             }
 
+            // Parse per-level overrides
+            for override_cap in override_pattern.captures_iter(num_xml) {
+                let override_xml = override_cap.get(2).map_or("", |m| m.as_str());
+                num_instance.overrides.push(LevelOverride {
+                    level: override_cap[1].parse().unwrap_or(0),
+                    start_value: Self::xml_attr(override_xml, "startOverride", "val")
+                        .and_then(|v| v.parse().ok()),
+                    text: Self::xml_attr(override_xml, "lvlText", "val"),
+                });
+            }
+
             numbering.num_instances.push(num_instance);
         }
 
-        self.numbering.push(numbering);
+        self.numbering = Some(numbering);
         Ok(())
     }
 
     /// Parse headers and footers
-    fn parse_headers_footers(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
+    fn parse_headers_footers(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
         // Get document relationships to find header/footer references
         let doc_rels_part = "/word/_rels/document.xml.rels";
 
@@ -780,6 +1454,7 @@ impl WordDocument {
 
             let header_part_name = format!("/word/{}", target);
             if let Some(header_part) = package.get_part(&header_part_name) {
+                monitor.part(&header_part.name)?;
                 let header_xml = String::from_utf8_lossy(&header_part.data);
                 let paragraphs = self.parse_header_footer_content(&header_xml);
 
@@ -811,6 +1486,7 @@ impl WordDocument {
 
             let footer_part_name = format!("/word/{}", target);
             if let Some(footer_part) = package.get_part(&footer_part_name) {
+                monitor.part(&footer_part.name)?;
                 let footer_xml = String::from_utf8_lossy(&footer_part.data);
                 let paragraphs = self.parse_header_footer_content(&footer_xml);
 
@@ -865,112 +1541,54 @@ impl WordDocument {
         paragraphs
     }
 
-    /// Parse footnotes and endnotes
-    fn parse_footnotes_endnotes(&mut self, package: &OpcPackage) -> Result<(), OoxmlError> {
-        // Parse footnotes
-        let footnote_part_names = ["/word/footnotes.xml", "/word/footnote.xml"];
-
-        for part_name in footnote_part_names.iter() {
-            if let Some(footnote_part) = package.get_part(part_name) {
-                let xml_str = String::from_utf8_lossy(&footnote_part.data);
-
-                let footnote_pattern = regex::Regex::new(
-                    r#"<w:footnote[^>]*w:id="([^"]*)"[^>]*>(.*?)</w:footnote>"#
-                ).unwrap();
-
-                for cap in footnote_pattern.captures(&xml_str) {
-                    let footnote_id = match cap.get(1) {
-                        Some(m) => m.as_str().to_string(),
-                        None => continue,
-                    };
-
-                    let footnote_xml = match cap.get(2) {
-                        Some(m) => m.as_str(),
-                        None => continue,
-                    };
-
-                    let mut footnote = Footnote {
-                        id: footnote_id,
-                        footnote_type: None,
-                        paragraphs: Vec::new(),
-                    };
-
-                    // Determine footnote type
-                    if footnote_xml.contains("<w:footnoteRef") {
-                        footnote.footnote_type = Some("normal".to_string());
-                    } else if footnote_xml.contains("<w:separator") {
-                        footnote.footnote_type = Some("separator".to_string());
-                    } else if footnote_xml.contains("<w:continuationSeparator") {
-                        footnote.footnote_type = Some("continuationSeparator".to_string());
-                    }
-
-                    // Parse paragraphs in footnote
-                    let para_pattern = regex::Regex::new(r#"<w:p[^>]*>(.*?)</w:p>"#).unwrap();
-                    for para_cap in para_pattern.captures(footnote_xml) {
-                        if let Some(para_xml) = para_cap.get(1) {
-                            if let Some(para) = self.parse_paragraph(para_xml.as_str()) {
-                                footnote.paragraphs.push(para);
-                            }
-                        }
-                    }
+    /// Read the numeric `w:id` of a note or note reference
+    fn note_id(e: &BytesStart) -> Result<Option<i32>, OoxmlError> {
+        Ok(Self::attribute(e, "w:id")?.and_then(|id| id.trim().parse().ok()))
+    }
 
-                    self.footnotes.push(footnote);
-                }
+    /// Parse footnotes and endnotes
+    fn parse_footnotes_endnotes(&mut self, package: &OpcPackage, monitor: &mut ParseMonitor) -> Result<(), OoxmlError> {
+        for part_name in ["/word/footnotes.xml", "/word/footnote.xml"] {
+            if let Some(part) = package.get_part(part_name) {
+                monitor.part(&part.name)?;
+                let xml = String::from_utf8_lossy(&part.data);
+                self.footnotes.extend(Self::parse_notes(&xml, "w:footnote")?);
             }
         }
 
-        // Parse endnotes
-        let endnote_part_names = ["/word/endnotes.xml", "/word/endnote.xml"];
-
-        for part_name in endnote_part_names.iter() {
-            if let Some(endnote_part) = package.get_part(part_name) {
-                let xml_str = String::from_utf8_lossy(&endnote_part.data);
-
-                let endnote_pattern = regex::Regex::new(
-                    r#"<w:endnote[^>]*w:id="([^"]*)"[^>]*>(.*?)</w:endnote>"#
-                ).unwrap();
-
-                for cap in endnote_pattern.captures(&xml_str) {
-                    let endnote_id = match cap.get(1) {
-                        Some(m) => m.as_str().to_string(),
-                        None => continue,
-                    };
-
-                    let endnote_xml = match cap.get(2) {
-                        Some(m) => m.as_str(),
-                        None => continue,
-                    };
-
-                    let mut endnote = Endnote {
-                        id: endnote_id,
-                        endnote_type: None,
-                        paragraphs: Vec::new(),
-                    };
+        for part_name in ["/word/endnotes.xml", "/word/endnote.xml"] {
+            if let Some(part) = package.get_part(part_name) {
+                monitor.part(&part.name)?;
+                let xml = String::from_utf8_lossy(&part.data);
+                self.endnotes.extend(Self::parse_notes(&xml, "w:endnote")?);
+            }
+        }
 
-                    // Determine endnote type
-                    if endnote_xml.contains("<w:endnoteRef") {
-                        endnote.endnote_type = Some("normal".to_string());
-                    } else if endnote_xml.contains("<w:separator") {
-                        endnote.endnote_type = Some("separator".to_string());
-                    } else if endnote_xml.contains("<w:continuationSeparator") {
-                        endnote.endnote_type = Some("continuationSeparator".to_string());
-                    }
+        Ok(())
+    }
 
-                    // Parse paragraphs in endnote
-                    for para_cap in endnote_pattern.captures(endnote_xml) {
-                        if let Some(para_xml) = para_cap.get(1) {
-                            if let Some(para) = self.parse_paragraph(para_xml.as_str()) {
-                                endnote.paragraphs.push(para);
-                            }
-                        }
+    /// Parse each `element` (`w:footnote` or `w:endnote`) of a notes part into its paragraphs,
+    /// keyed by its numeric `w:id`; separator notes are kept under their own IDs
+    fn parse_notes(xml: &str, element: &str) -> Result<Vec<(i32, Vec<Paragraph>)>, OoxmlError> {
+        let mut reader = Reader::from_str(xml);
+        let mut notes = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) if e.name().as_ref() == element => {
+                    let id = Self::note_id(&e)?;
+                    let span = reader.read_to_end(e.name())?;
+                    if let Some(id) = id {
+                        let note_xml = &xml[span.start as usize..span.end as usize];
+                        notes.push((id, Self::parse_paragraphs(note_xml, &mut ParseMonitor::default())?));
                     }
-
-                    self.endnotes.push(endnote);
                 }
+                Event::Eof => break,
+                _ => {}
             }
         }
 
-        Ok(())
+        Ok(notes)
     }
 }
 
@@ -984,5 +1602,874 @@ impl RunProperties {
             && self.font_name.is_none() 
             && self.color.is_none() 
             && self.background_color.is_none()
+            && self.caps.is_none()
+            && self.small_caps.is_none()
+            && self.strikethrough.is_none()
+            && self.vert_align.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::types::{Caps, ContentType, PackagePart, Relationship, RelationshipType};
+
+    fn package_with_document(document_xml: &str) -> OpcPackage {
+        let mut package = OpcPackage::default();
+        package.parts.insert("/word/document.xml".to_string(), PackagePart {
+            name: "/word/document.xml".to_string(),
+            content_type: ContentType::MainDocument,
+            data: document_xml.as_bytes().to_vec(),
+        });
+        package
+    }
+
+    #[test]
+    fn test_parse_main_document_two_paragraphs() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p>
+      <w:pPr><w:jc w:val="center"/></w:pPr>
+      <w:r><w:rPr><w:b w:val="1"/></w:rPr><w:t>First</w:t></w:r>
+    </w:p>
+    <w:p><w:r><w:t xml:space="preserve">Second </w:t></w:r><w:r><w:t>paragraph</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        assert_eq!(document.paragraphs.len(), 2);
+        assert_eq!(document.paragraphs[0].text, "First");
+        assert_eq!(document.paragraphs[0].runs[0].properties.bold, Some(true));
+        assert_eq!(document.paragraphs[1].text, "Second paragraph");
+        assert_eq!(document.paragraphs[1].runs.len(), 2);
+        assert_eq!(document.text, "First\nSecond paragraph");
+    }
+
+    #[test]
+    fn test_parse_main_document_table_paragraphs() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Before</w:t></w:r></w:p>
+<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Cell &amp; text</w:t></w:r></w:p></w:tc></w:tr></w:tbl>
+<w:p><w:r><w:t>After</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let texts: Vec<&str> = document.paragraphs.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, vec!["Before", "Cell & text", "After"]);
+    }
+
+    #[test]
+    fn test_parse_table_header_rows() {
+        let row = |header: &str, text: &str| format!(
+            "<w:tr><w:trPr>{}</w:trPr><w:tc><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:tc></w:tr>",
+            header, text
+        );
+        let xml = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:tbl>{}{}{}{}</w:tbl></w:body></w:document>"#,
+            row("<w:tblHeader/>", "Title"),
+            row(r#"<w:tblHeader w:val="true"/>"#, "Columns"),
+            row(r#"<w:tblHeader w:val="0"/>"#, "Body"),
+            row("", "More"),
+        );
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(&xml), &mut ParseMonitor::default()).unwrap();
+
+        let table = &document.tables[0];
+        let flags: Vec<bool> = table.rows.iter().map(|r| r.properties.is_header).collect();
+        assert_eq!(flags, vec![true, true, false, false]);
+        assert_eq!(table.header_row_count(), 2);
+    }
+
+    #[test]
+    fn test_parse_two_by_two_table() {
+        let cell = |text: &str| format!("<w:tc><w:tcPr><w:tcW w:w=\"2000\" w:type=\"dxa\"/></w:tcPr><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:tc>", text);
+        let xml = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Before</w:t></w:r></w:p>
+<w:tbl><w:tblPr><w:tblW w:w="4000" w:type="dxa"/></w:tblPr><w:tblGrid><w:gridCol w:w="2000"/><w:gridCol w:w="2000"/></w:tblGrid>
+<w:tr>{}{}</w:tr><w:tr>{}{}</w:tr></w:tbl>
+<w:p><w:r><w:t>After</w:t></w:r></w:p>
+</w:body></w:document>"#,
+            cell("A1"), cell("B1"), cell("A2"), cell("B2"),
+        );
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(&xml), &mut ParseMonitor::default()).unwrap();
+
+        assert_eq!(document.tables.len(), 1);
+        let table = &document.tables[0];
+        assert_eq!(table.properties.width, Some(4000));
+        let texts: Vec<Vec<&str>> = table.rows.iter()
+            .map(|row| row.cells.iter().map(|c| c.paragraphs[0].text.as_str()).collect())
+            .collect();
+        assert_eq!(texts, vec![vec!["A1", "B1"], vec!["A2", "B2"]]);
+        assert_eq!(table.rows[0].cells[0].width, Some(2000));
+
+        assert_eq!(document.body, vec![
+            BodyElement::Paragraph(0),
+            BodyElement::Table(0),
+            BodyElement::Paragraph(5),
+        ]);
+        assert_eq!(document.paragraphs[5].text, "After");
+    }
+
+    #[test]
+    fn test_parse_table_cell_spans() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:tbl>
+<w:tr><w:tc><w:tcPr><w:gridSpan w:val="2"/></w:tcPr><w:p><w:r><w:t>Wide</w:t></w:r></w:p></w:tc><w:tc><w:tcPr><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>Tall</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p/></w:tc><w:tc><w:p/></w:tc><w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc></w:tr>
+</w:tbl></w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let rows = &document.tables[0].rows;
+        assert_eq!(rows[0].cells[0].grid_span, Some(2));
+        assert_eq!(rows[0].cells[0].column_span(), 2);
+        assert_eq!(rows[0].cells[1].vertical_merge, Some(1));
+        assert_eq!(rows[1].cells.len(), 3);
+        assert_eq!(rows[1].cells[0].column_span(), 1);
+        assert_eq!(rows[1].cells[2].vertical_merge, Some(-1));
+    }
+
+    #[test]
+    fn test_parse_nested_table_keeps_outer_structure() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:tbl>
+<w:tr><w:tc><w:tbl><w:tr><w:tc><w:p><w:r><w:t>Inner</w:t></w:r></w:p></w:tc></w:tr></w:tbl><w:p><w:r><w:t>Outer</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p><w:r><w:t>Second row</w:t></w:r></w:p></w:tc></w:tr>
+</w:tbl></w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        assert_eq!(document.tables.len(), 1);
+        let rows = &document.tables[0].rows;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].cells[0].paragraphs[0].text, "Outer");
+        assert_eq!(rows[1].cells[0].paragraphs[0].text, "Second row");
+        assert_eq!(document.body, vec![BodyElement::Table(0)]);
+    }
+
+    #[test]
+    fn test_parse_letter_section_properties() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Body</w:t></w:r></w:p>
+<w:sectPr><w:pgSz w:w="12240" w:h="15840"/><w:pgMar w:top="1440" w:right="1440" w:bottom="1440" w:left="1440" w:header="708" w:footer="708" w:gutter="0"/><w:cols w:space="708"/></w:sectPr>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        assert_eq!(document.sections.len(), 1);
+        let section = &document.sections[0];
+        assert_eq!((section.page_width, section.page_height), (12240, 15840));
+        assert_eq!(section.orientation, PageOrientation::Portrait);
+        assert_eq!(
+            (section.margin_top, section.margin_right, section.margin_bottom, section.margin_left),
+            (1440, 1440, 1440, 1440)
+        );
+        assert_eq!((section.header_distance, section.footer_distance), (708, 708));
+        assert_eq!(section.columns, 1);
+    }
+
+    #[test]
+    fn test_parse_landscape_two_column_section() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Body</w:t></w:r></w:p>
+<w:sectPr><w:pgSz w:w="16838" w:h="11906" w:orient="landscape"/><w:pgMar w:top="-720" w:bottom="1000" w:left="900" w:right="900"/><w:cols w:num="2" w:space="360"/></w:sectPr>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let section = &document.sections[0];
+        assert_eq!(section.orientation, PageOrientation::Landscape);
+        assert_eq!(section.margin_top, -720);
+        assert_eq!((section.columns, section.column_gap), (2, 360));
+    }
+
+    #[test]
+    fn test_parse_half_point_font_size() {
+        let mut props = RunProperties::default();
+        WordDocument::parse_run_properties(r#"<w:sz w:val="23"/>"#, &mut props);
+
+        assert_eq!(props.font_size, Some(23));
+        assert_eq!(props.font_size_points(), Some(11.5));
+    }
+
+    #[test]
+    fn test_parse_run_decodes_entities() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph("<w:r><w:t>Tom &amp; Jerry &lt;&quot;&#169;&#x263A;&quot;&gt;</w:t></w:r>")
+            .unwrap();
+
+        assert_eq!(para.text, "Tom & Jerry <\"\u{a9}\u{263a}\">");
+    }
+
+    #[test]
+    fn test_parse_run_preserves_space_only_when_asked() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph(r#"<w:r><w:t xml:space="preserve">Two  </w:t></w:r><w:r><w:t xml:space="preserve">  spaces</w:t></w:r><w:r><w:t>  trimmed  </w:t></w:r>"#)
+            .unwrap();
+
+        let runs: Vec<&str> = para.runs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(runs, vec!["Two  ", "  spaces", "trimmed"]);
+        assert_eq!(para.text, "Two    spacestrimmed");
+    }
+
+    #[test]
+    fn test_parse_run_keeps_non_breaking_space() {
+        let document = WordDocument::default();
+        let para = document.parse_paragraph("<w:r><w:t>&#160;</w:t></w:r>").unwrap();
+
+        assert_eq!(para.text, "\u{a0}");
+    }
+
+    #[test]
+    fn test_parse_highlight_background() {
+        let mut props = RunProperties::default();
+        WordDocument::parse_run_properties(r#"<w:highlight w:val="yellow"/>"#, &mut props);
+        assert_eq!(props.background_color.as_deref(), Some("FFFF00"));
+
+        let mut props = RunProperties::default();
+        WordDocument::parse_run_properties(r#"<w:highlight w:val="none"/>"#, &mut props);
+        assert_eq!(props.background_color, None);
+    }
+
+    #[test]
+    fn test_parse_shading_background() {
+        let mut props = RunProperties::default();
+        WordDocument::parse_run_properties(r#"<w:shd w:val="clear" w:color="auto" w:fill="A5C8FF"/>"#, &mut props);
+        assert_eq!(props.background_color.as_deref(), Some("A5C8FF"));
+
+        // Shading takes precedence over a highlight on the same run
+        let mut props = RunProperties::default();
+        WordDocument::parse_run_properties(
+            r#"<w:highlight w:val="yellow"/><w:shd w:val="clear" w:fill="00FF00"/>"#,
+            &mut props,
+        );
+        assert_eq!(props.background_color.as_deref(), Some("00FF00"));
+    }
+
+    #[test]
+    fn test_parse_strikethrough_run() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph(r#"<w:r><w:rPr><w:strike/></w:rPr><w:t>gone</w:t></w:r><w:r><w:rPr><w:strike w:val="false"/></w:rPr><w:t>kept</w:t></w:r>"#)
+            .unwrap();
+
+        assert_eq!(para.runs[0].properties.strikethrough, Some(true));
+        assert_eq!(para.runs[1].properties.strikethrough, Some(false));
+        assert_eq!(para.runs[0].properties.vert_align, None);
+    }
+
+    #[test]
+    fn test_parse_superscript_run() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph(r#"<w:r><w:t>x</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:t>2</w:t></w:r>"#)
+            .unwrap();
+
+        assert!(para.runs[0].properties.is_default());
+        assert_eq!(para.runs[1].properties.vert_align, Some(VertAlign::Superscript));
+        assert_eq!(para.runs[1].properties.strikethrough, None);
+        assert!(!para.runs[1].properties.is_default());
+    }
+
+    #[test]
+    fn test_parse_paragraph_tab_between_text() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph("<w:r><w:t>A</w:t><w:tab/><w:t>B</w:t></w:r>")
+            .unwrap();
+
+        assert_eq!(para.text, "A\tB");
+        assert_eq!(para.runs[0].text, "A\tB");
+    }
+
+    #[test]
+    fn test_parse_paragraph_line_break() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph(r#"<w:r><w:t xml:space="preserve">Line one</w:t><w:br/><w:t>Line two</w:t><w:br w:type="page"/></w:r>"#)
+            .unwrap();
+
+        assert_eq!(para.text, "Line one\nLine two\n");
+    }
+
+    #[test]
+    fn test_parse_small_caps_run() {
+        use crate::text_shaping::{CapsStyle, TextShaper, SMALL_CAPS_SCALE};
+
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph("<w:r><w:rPr><w:smallCaps/></w:rPr><w:t>Small</w:t></w:r><w:r><w:rPr><w:caps w:val=\"0\"/></w:rPr><w:t>x</w:t></w:r>")
+            .unwrap();
+
+        let props = &para.runs[0].properties;
+        assert_eq!(props.small_caps, Some(true));
+        assert_eq!(props.caps_style(), Caps::SmallCaps);
+        assert_eq!(para.runs[1].properties.caps, Some(false));
+        assert_eq!(para.runs[1].properties.caps_style(), Caps::Normal);
+
+        let shaper = TextShaper::new();
+        let (width, glyphs) = shaper.shape_with_caps(&para.runs[0].text, CapsStyle::from(props.caps_style()));
+        let (_, upper_glyphs) = shaper.shape("SMALL");
+        let codepoints: Vec<u32> = glyphs.iter().map(|g| g.codepoint).collect();
+        let upper_codepoints: Vec<u32> = upper_glyphs.iter().map(|g| g.codepoint).collect();
+        assert_eq!(codepoints, upper_codepoints);
+        assert_eq!(glyphs[0].scale, 1.0);
+        assert!(glyphs[1..].iter().all(|g| g.scale == SMALL_CAPS_SCALE));
+        assert!(width < shaper.measure_width("SMALL"));
+    }
+
+    #[test]
+    fn test_find_reports_paragraph_coordinates() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:r><w:t>Introduction</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">The quick </w:t></w:r><w:r><w:t>brown fox</w:t></w:r></w:p>
+<w:p><w:r><w:t>Conclusion</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+        assert_eq!(document.paragraphs.len(), 3);
+
+        let opts = SearchOptions {
+            query: "brown".to_string(),
+            ..Default::default()
+        };
+        let found = document.find(&opts);
+
+        assert_eq!(found, vec![DocFind {
+            paragraph_index: 1,
+            run_index: 1,
+            byte_range_in_para: 10..15,
+        }]);
+        assert_eq!(&document.paragraphs[1].text[10..15], "brown");
+    }
+
+    #[test]
+    fn test_find_multiple_matches_across_paragraphs() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:r><w:t>one two one</w:t></w:r></w:p>
+<w:p><w:r><w:t>none</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let opts = SearchOptions {
+            query: "one".to_string(),
+            ..Default::default()
+        };
+        let ranges: Vec<(usize, Range<usize>)> = document
+            .find(&opts)
+            .into_iter()
+            .map(|f| (f.paragraph_index, f.byte_range_in_para))
+            .collect();
+
+        assert_eq!(ranges, vec![(0, 0..3), (0, 8..11), (1, 1..4)]);
+    }
+
+    #[test]
+    fn test_parse_paragraph_properties_centered() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:pPr><w:jc w:val="center"/><w:spacing w:before="240" w:after="120" w:line="360"/></w:pPr><w:r><w:t>Title</w:t></w:r></w:p>
+<w:p><w:pPr><w:jc w:val="both"/></w:pPr><w:r><w:t>Body</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let props = &document.paragraphs[0].properties;
+        assert_eq!(props.alignment.as_deref(), Some("center"));
+        assert_eq!(props.spacing_before, Some(240));
+        assert_eq!(props.spacing_after, Some(120));
+        assert_eq!(props.spacing_line, Some(360));
+        assert_eq!(document.paragraphs[1].properties.alignment.as_deref(), Some("both"));
+    }
+
+    #[test]
+    fn test_parse_paragraph_properties_left_indent() {
+        let xml = r#"<w:document><w:body>
+<w:p><w:pPr><w:ind w:left="720" w:hanging="360"/><w:rPr><w:b/></w:rPr></w:pPr><w:r><w:t>Indented</w:t></w:r></w:p>
+<w:p><w:r><w:t>Plain</w:t></w:r></w:p>
+</w:body></w:document>"#;
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package_with_document(xml), &mut ParseMonitor::default()).unwrap();
+
+        let props = &document.paragraphs[0].properties;
+        assert_eq!(props.indent_left, Some(720));
+        assert_eq!(props.indent_right, None);
+        assert_eq!(props.indent_first_line, Some(-360));
+        assert!(props.alignment.is_none());
+        assert_eq!(document.paragraphs[1].properties.indent_left, None);
+    }
+
+    fn document_with_paragraphs(texts: &[&str]) -> WordDocument {
+        let mut document = WordDocument::default();
+        document.paragraphs = texts
+            .iter()
+            .map(|text| Paragraph {
+                text: text.to_string(),
+                runs: vec![Run { text: text.to_string(), ..Default::default() }],
+                ..Default::default()
+            })
+            .collect();
+        document
+    }
+
+    #[test]
+    fn test_to_plain_text_crlf_separator() {
+        let document = document_with_paragraphs(&["First", "Second", "", "Fourth"]);
+        let opts = TextExportOptions {
+            paragraph_separator: "\r\n".to_string(),
+            ..Default::default()
+        };
+
+        let text = document.to_plain_text(opts);
+        assert_eq!(text.matches("\r\n").count(), document.paragraphs.len() - 1);
+        assert_eq!(text, "First\r\nSecond\r\n\r\nFourth");
+    }
+
+    #[test]
+    fn test_to_plain_text_collapse_empty() {
+        let document = document_with_paragraphs(&["First", "", "", "Last"]);
+        let opts = TextExportOptions {
+            paragraph_separator: "\n\n".to_string(),
+            collapse_empty_paragraphs: true,
+        };
+
+        assert_eq!(document.to_plain_text(opts), "First\n\nLast");
+        assert_eq!(document.to_plain_text(TextExportOptions::default()), "First\n\n\nLast");
+    }
+
+    fn style(id: &str, based_on: Option<&str>, run_properties: RunProperties) -> Style {
+        Style {
+            id: id.to_string(),
+            style_type: "paragraph".to_string(),
+            based_on: based_on.map(|s| s.to_string()),
+            run_properties,
+            ..Style::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve_style_inherits_based_on() {
+        let mut document = WordDocument::default();
+        document.styles.insert("Normal".to_string(), style("Normal", None, RunProperties {
+            bold: Some(true),
+            ..RunProperties::default()
+        }));
+        document.styles.insert("Heading1".to_string(), style("Heading1", Some("Normal"), RunProperties {
+            font_size: Some(16),
+            ..RunProperties::default()
+        }));
+
+        let resolved = document.resolve_style("Heading1");
+        assert_eq!(resolved.id, "Heading1");
+        assert_eq!(resolved.based_on.as_deref(), Some("Normal"));
+        assert_eq!(resolved.run_properties.bold, Some(true));
+        assert_eq!(resolved.run_properties.font_size, Some(16));
+
+        // The parent itself is untouched by the child's values
+        assert_eq!(document.resolve_style("Normal").run_properties.font_size, None);
+    }
+
+    #[test]
+    fn test_resolve_style_child_overrides_and_cycles() {
+        let mut document = WordDocument::default();
+        document.styles.insert("A".to_string(), style("A", Some("B"), RunProperties {
+            bold: Some(false),
+            ..RunProperties::default()
+        }));
+        document.styles.insert("B".to_string(), style("B", Some("A"), RunProperties {
+            bold: Some(true),
+            italic: Some(true),
+            ..RunProperties::default()
+        }));
+
+        let resolved = document.resolve_style("A");
+        assert_eq!(resolved.run_properties.bold, Some(false));
+        assert_eq!(resolved.run_properties.italic, Some(true));
+
+        let missing = document.resolve_style("Missing");
+        assert_eq!(missing.id, "Missing");
+        assert!(missing.run_properties.bold.is_none());
+    }
+
+    #[test]
+    fn test_parse_styles_reads_formatting() {
+        let xml = r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:styleId="Normal" w:type="paragraph">
+    <w:name w:val="Normal"/>
+    <w:rPr><w:b w:val="1"/></w:rPr>
+  </w:style>
+  <w:style w:styleId="Heading1" w:type="paragraph">
+    <w:name w:val="heading 1"/>
+    <w:basedOn w:val="Normal"/>
+    <w:pPr><w:jc w:val="center"/></w:pPr>
+    <w:rPr><w:sz w:val="32"/></w:rPr>
+  </w:style>
+</w:styles>"#;
+        let mut package = OpcPackage::default();
+        package.parts.insert("/word/styles.xml".to_string(), PackagePart {
+            name: "/word/styles.xml".to_string(),
+            content_type: ContentType::Styles,
+            data: xml.as_bytes().to_vec(),
+        });
+
+        let mut document = WordDocument::default();
+        document.parse_styles(&package, &mut ParseMonitor::default()).unwrap();
+        assert_eq!(document.styles.len(), 2);
+
+        let heading = document.resolve_style("Heading1");
+        assert_eq!(heading.name.as_deref(), Some("heading 1"));
+        assert_eq!(heading.run_properties.bold, Some(true));
+        assert_eq!(heading.run_properties.font_size, Some(32));
+        assert_eq!(heading.run_properties.font_size_points(), Some(16.0));
+        assert_eq!(heading.paragraph_properties.alignment.as_deref(), Some("center"));
+    }
+
+    #[test]
+    fn test_parse_theme_colors_and_fonts() {
+        let xml = r#"<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="Brand">
+  <a:themeElements>
+    <a:clrScheme name="Brand">
+      <a:dk1><a:sysClr val="windowText" lastClr="000000"/></a:dk1>
+      <a:lt1><a:sysClr val="window" lastClr="FFFFFF"/></a:lt1>
+      <a:dk2><a:srgbClr val="1F497D"/></a:dk2>
+      <a:lt2><a:srgbClr val="EEECE1"/></a:lt2>
+      <a:accent1><a:srgbClr val="FF0000"/></a:accent1>
+      <a:accent2><a:srgbClr val="c0504d"/></a:accent2>
+      <a:hlink><a:srgbClr val="0000FF"/></a:hlink>
+    </a:clrScheme>
+    <a:fontScheme name="Brand">
+      <a:majorFont><a:latin typeface="Cambria"/><a:ea typeface=""/></a:majorFont>
+      <a:minorFont><a:latin typeface="Georgia"/><a:ea typeface=""/></a:minorFont>
+    </a:fontScheme>
+  </a:themeElements>
+</a:theme>"#;
+        let mut package = OpcPackage::default();
+        package.parts.insert("/word/theme/theme1.xml".to_string(), PackagePart {
+            name: "/word/theme/theme1.xml".to_string(),
+            content_type: ContentType::Theme,
+            data: xml.as_bytes().to_vec(),
+        });
+
+        let mut document = WordDocument::default();
+        document.parse_theme(&package, &mut ParseMonitor::default()).unwrap();
+        let theme = document.theme.unwrap();
+
+        assert_eq!(theme.name, "Brand");
+        assert_eq!(theme.colors["accent1"], "FF0000");
+        assert_eq!(theme.colors["accent2"], "C0504D");
+        assert_eq!(theme.colors["dk1"], "000000");
+        assert_eq!(theme.colors["lt1"], "FFFFFF");
+        assert_eq!(theme.colors["hlink"], "0000FF");
+        assert!(!theme.colors.contains_key("accent3"));
+        assert_eq!(theme.fonts.major_font, "Cambria");
+        assert_eq!(theme.fonts.minor_font, "Georgia");
+    }
+
+    #[test]
+    fn test_parse_numbering_two_level_list() {
+        let numbering_xml = r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+    <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%2)"/></w:lvl>
+  </w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/><w:lvlText w:val="&#8226;"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2">
+    <w:abstractNumId w:val="1"/>
+    <w:lvlOverride w:ilvl="0"><w:startOverride w:val="3"/></w:lvlOverride>
+  </w:num>
+</w:numbering>"#;
+        let document_xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>First</w:t></w:r></w:p>
+<w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Nested</w:t></w:r></w:p>
+<w:p><w:r><w:t>Body</w:t></w:r></w:p>
+</w:body></w:document>"#;
+        let mut package = package_with_document(document_xml);
+        package.parts.insert("/word/numbering.xml".to_string(), PackagePart {
+            name: "/word/numbering.xml".to_string(),
+            content_type: ContentType::Numbering,
+            data: numbering_xml.as_bytes().to_vec(),
+        });
+
+        let document = WordDocument::parse(&package).unwrap();
+        let numbering = document.numbering.as_ref().unwrap();
+        assert_eq!(numbering.abstract_num_defs.len(), 2);
+        assert_eq!(numbering.num_instances.len(), 2);
+        assert_eq!(numbering.levels("1").unwrap().len(), 2);
+        assert_eq!(numbering.num_instances[1].overrides[0].start_value, Some(3));
+
+        let first = &document.paragraphs[0].properties;
+        assert_eq!(first.num_id.as_deref(), Some("1"));
+        assert_eq!(first.ilvl, Some(0));
+        let level = numbering.level("1", 0).unwrap();
+        assert_eq!(level.format, "decimal");
+        assert_eq!(level.text, "%1.");
+
+        let nested = &document.paragraphs[1].properties;
+        assert_eq!(nested.ilvl, Some(1));
+        let level = numbering.level(nested.num_id.as_deref().unwrap(), 1).unwrap();
+        assert_eq!(level.format, "lowerLetter");
+        assert_eq!(level.text, "%2)");
+
+        assert!(document.paragraphs[2].properties.num_id.is_none());
+        assert_eq!(numbering.level("2", 0).unwrap().format, "bullet");
+    }
+
+    #[test]
+    fn test_parse_drawing_pictures() {
+        const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let picture = |id: &str, descr: &str, cx: u32| format!(r#"
+      <w:r>
+        <w:drawing>
+          <wp:inline distT="0" distB="0" distL="0" distR="0">
+            <wp:extent cx="{cx}" cy="952500"/>
+            <wp:docPr id="1" name="Picture 1" descr="{descr}"/>
+            <a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">
+              <a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture">
+                <pic:pic xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture">
+                  <pic:blipFill>
+                    <a:blip r:embed="{id}"/>
+                    <a:stretch><a:fillRect/></a:stretch>
+                  </pic:blipFill>
+                </pic:pic>
+              </a:graphicData>
+            </a:graphic>
+          </wp:inline>
+        </w:drawing>
+      </w:r>"#);
+        let xml = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing">
+  <w:body>
+    <w:p>{}{}
+    </w:p>
+  </w:body>
+</w:document>"#,
+            picture("rId5", "A logo", 1905000),
+            picture("rId6", "A chart", 3810000),
+        );
+        let mut package = package_with_document(&xml);
+        let image = |id: &str, target: &str| Relationship {
+            id: id.to_string(),
+            relationship_type: RelationshipType::Image,
+            target: target.to_string(),
+            target_mode: None,
+        };
+        package.relationships.insert("/word/document.xml".to_string(), vec![
+            image("rId5", "media/image1.png"),
+            image("rId6", "media/image2.png"),
+        ]);
+        for name in ["/word/media/image1.png", "/word/media/image2.png"] {
+            package.parts.insert(name.to_string(), PackagePart {
+                name: name.to_string(),
+                content_type: ContentType::ImagePng,
+                data: PNG_BYTES.to_vec(),
+            });
+        }
+
+        let document = WordDocument::parse(&package).unwrap();
+
+        assert_eq!(document.images.len(), 2);
+        let logo = &document.images[0];
+        assert_eq!(logo.id, "rId5");
+        assert_eq!(logo.path, "media/image1.png");
+        assert_eq!(package.get_part(&format!("/word/{}", logo.path)).unwrap().data, PNG_BYTES);
+        assert_eq!(logo.desired_width, Some(1905000));
+        assert_eq!(logo.desired_height, Some(952500));
+        assert_eq!(logo.alt_description.as_deref(), Some("A logo"));
+        assert_eq!(document.images[1].path, "media/image2.png");
+        assert_eq!(document.images[1].desired_width, Some(3810000));
+    }
+
+    #[test]
+    fn test_parse_hyperlinked_run() {
+        let xml = r##"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><w:body>
+<w:p><w:r><w:t xml:space="preserve">Visit </w:t></w:r><w:hyperlink r:id="rId5"><w:r><w:rPr><w:u w:val="single"/></w:rPr><w:t>Velum</w:t></w:r></w:hyperlink><w:r><w:t xml:space="preserve"> or </w:t></w:r><w:hyperlink w:anchor="intro"><w:r><w:t>the intro</w:t></w:r></w:hyperlink></w:p>
+</w:body></w:document>"##;
+        let mut package = package_with_document(xml);
+        package.relationships.insert("/word/document.xml".to_string(), vec![Relationship {
+            id: "rId5".to_string(),
+            relationship_type: RelationshipType::Hyperlink,
+            target: "https://example.com/velum".to_string(),
+            target_mode: Some("External".to_string()),
+        }]);
+
+        let mut document = WordDocument::default();
+        document.parse_main_document(&package, &mut ParseMonitor::default()).unwrap();
+
+        let runs = &document.paragraphs[0].runs;
+        assert_eq!(document.paragraphs[0].text, "Visit Velum or the intro");
+        assert_eq!(runs[0].hyperlink, None);
+        assert_eq!(runs[1].text, "Velum");
+        assert_eq!(runs[1].hyperlink.as_deref(), Some("https://example.com/velum"));
+        assert_eq!(runs[2].hyperlink, None);
+        assert_eq!(runs[3].hyperlink.as_deref(), Some("#intro"));
+    }
+
+    #[test]
+    fn test_parse_footnote_and_reference() {
+        let document_xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Claim</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:footnoteReference w:id="1"/></w:r></w:p>
+</w:body></w:document>"#;
+        let footnotes_xml = r#"<w:footnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:footnote w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:footnote>
+  <w:footnote w:type="continuationSeparator" w:id="0"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>
+  <w:footnote w:id="1">
+    <w:p><w:r><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> See the appendix.</w:t></w:r></w:p>
+  </w:footnote>
+</w:footnotes>"#;
+        let mut package = package_with_document(document_xml);
+        package.parts.insert("/word/footnotes.xml".to_string(), PackagePart {
+            name: "/word/footnotes.xml".to_string(),
+            content_type: ContentType::Unknown(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml".to_string(),
+            ),
+            data: footnotes_xml.as_bytes().to_vec(),
+        });
+
+        let document = WordDocument::parse(&package).unwrap();
+
+        let runs = &document.paragraphs[0].runs;
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].note_reference, None);
+        assert_eq!(runs[1].note_reference, Some(NoteReference::Footnote(1)));
+
+        assert_eq!(document.footnotes.len(), 3);
+        assert!(document.footnotes.contains_key(&-1));
+        let footnote = document.footnote(1).unwrap();
+        assert_eq!(footnote.len(), 1);
+        assert_eq!(footnote[0].text, " See the appendix.");
+        assert!(document.footnote(2).is_none());
+    }
+
+    #[test]
+    fn test_parse_endnote_reference() {
+        let document_xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Claim</w:t></w:r><w:r><w:endnoteReference w:id="2"/></w:r></w:p>
+</w:body></w:document>"#;
+        let endnotes_xml = r#"<w:endnotes xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:endnote w:id="2"><w:p><w:r><w:t>First source.</w:t></w:r></w:p><w:p><w:r><w:t>Second source.</w:t></w:r></w:p></w:endnote>
+</w:endnotes>"#;
+        let mut package = package_with_document(document_xml);
+        package.parts.insert("/word/endnotes.xml".to_string(), PackagePart {
+            name: "/word/endnotes.xml".to_string(),
+            content_type: ContentType::Unknown(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.endnotes+xml".to_string(),
+            ),
+            data: endnotes_xml.as_bytes().to_vec(),
+        });
+
+        let document = WordDocument::parse(&package).unwrap();
+
+        assert_eq!(document.paragraphs[0].runs[1].note_reference, Some(NoteReference::Endnote(2)));
+        let endnote = document.endnote(2).unwrap();
+        let texts: Vec<&str> = endnote.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, ["First source.", "Second source."]);
+    }
+
+    #[test]
+    fn test_parse_settings_default_tab_stop() {
+        let settings_xml = r#"<w:settings xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:proofState w:spelling="clean" w:grammar="dirty"/>
+  <w:defaultTabStop w:val="708"/>
+  <w:compat><w:compatSetting w:name="compatibilityMode" w:uri="http://schemas.microsoft.com/office/word" w:val="15"/></w:compat>
+  <w:themeFontLang w:val="de-DE"/>
+</w:settings>"#;
+        let mut package = package_with_document(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body/></w:document>"#,
+        );
+        package.parts.insert("/word/settings.xml".to_string(), PackagePart {
+            name: "/word/settings.xml".to_string(),
+            content_type: ContentType::Settings,
+            data: settings_xml.as_bytes().to_vec(),
+        });
+
+        let document = WordDocument::parse(&package).unwrap();
+        let settings = document.settings.as_ref().unwrap();
+
+        assert_eq!(settings.default_tab_stop, Some(708));
+        assert_eq!(settings.default_tab_stop_points(), Some(35.4));
+        assert_eq!(settings.spelling_state.as_deref(), Some("clean"));
+        assert_eq!(settings.grammar_state.as_deref(), Some("dirty"));
+        assert_eq!(settings.language.as_deref(), Some("de-DE"));
+        assert_eq!(settings.compatibility_mode, Some(15));
+    }
+
+    #[test]
+    fn test_missing_settings_part() {
+        let package = package_with_document(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body/></w:document>"#,
+        );
+        let document = WordDocument::parse(&package).unwrap();
+
+        assert!(document.settings.is_none());
+    }
+
+    #[test]
+    fn test_progress_reports_parts_actually_parsed() {
+        let rels_xml = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Id="rId1" Target="header2.xml"/>
+  <Relationship Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer" Id="rId2" Target="footer1.xml"/>
+</Relationships>"#;
+        let band_xml = r#"<w:hdr xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:p><w:r><w:t>Band</w:t></w:r></w:p></w:hdr>"#;
+        let mut package = package_with_document(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body/></w:document>"#,
+        );
+        for (name, data) in [
+            ("/word/_rels/document.xml.rels", rels_xml),
+            ("/word/header2.xml", band_xml),
+            ("/word/footer1.xml", band_xml),
+        ] {
+            package.parts.insert(name.to_string(), PackagePart {
+                name: name.to_string(),
+                content_type: ContentType::Unknown(String::new()),
+                data: data.as_bytes().to_vec(),
+            });
+        }
+
+        let mut parts = Vec::new();
+        let mut progress = |p| {
+            if let ParseProgress::Part { name } = p {
+                parts.push(name);
+            }
+        };
+        let cancel = AtomicBool::new(false);
+        let document = WordDocument::parse_monitored(&package, &mut ParseMonitor::new(&mut progress, &cancel)).unwrap();
+
+        assert_eq!((document.headers.len(), document.footers.len()), (1, 1));
+        assert_eq!(parts, ["/word/document.xml", "/word/header2.xml", "/word/footer1.xml"]);
+    }
+
+    #[test]
+    fn test_parse_symbol_run() {
+        let document = WordDocument::default();
+        let para = document
+            .parse_paragraph(r#"<w:r><w:t xml:space="preserve">Next </w:t></w:r><w:r><w:sym w:font="Wingdings" w:char="F0E0"/></w:r>"#)
+            .unwrap();
+
+        assert_eq!(para.runs.len(), 2);
+        assert_eq!(para.runs[0].symbol, None);
+        let symbol = para.runs[1].symbol.as_ref().unwrap();
+        assert_eq!(symbol.font, "Wingdings");
+        assert_eq!(symbol.char_code, 0xF0E0);
+        assert!(para.runs[1].text.is_empty());
     }
 }
@@ -8,6 +8,15 @@ pub enum OoxmlError {
     #[error("ZIP archive error: {0}")]
     ZipError(#[from] zip::result::ZipError),
     
+    #[error("File is not a ZIP archive")]
+    NotAZip,
+    
+    #[error("Corrupt or truncated ZIP archive: {0}")]
+    CorruptArchive(String),
+    
+    #[error("XML error: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    
     #[error("Content-Type not found for part: {0}")]
     ContentTypeNotFound(String),
     
@@ -31,4 +40,7 @@ pub enum OoxmlError {
     
     #[error("Unsupported content type: {0}")]
     UnsupportedContentType(String),
+    
+    #[error("Parsing was cancelled")]
+    Cancelled,
 }
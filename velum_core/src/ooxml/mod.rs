@@ -23,6 +23,7 @@ mod opc;
 mod document;
 mod converter;
 mod serializer;
+mod writer;
 
 pub use error::OoxmlError;
 pub use converter::ooxml_to_piece_tree;
@@ -40,6 +41,8 @@ pub use types::{
     RelationshipType,
     Run,
     RunProperties,
+    Caps,
+    VertAlign,
     Style,
     Theme,
     ThemeFonts,
@@ -49,7 +52,11 @@ pub use types::{
     SourceRect,
     DocumentAnchor,
     AnchorPositionSpec,
+    // Section types
+    PageOrientation,
+    SectionProperties,
     // Table types
+    BodyElement,
     Table,
     TableRow,
     TableCell,
@@ -64,6 +71,10 @@ pub use types::{
     // Footnote/Endnote types
     Footnote,
     Endnote,
+    NoteReference,
+    Symbol,
+    // Settings types
+    DocumentSettings,
     // Numbering types
     Numbering,
     AbstractNumDef,
@@ -75,7 +86,11 @@ pub use types::{
     ContentControlProperties,
 };
 pub use opc::OpcPackage;
-pub use document::WordDocument;
+pub use document::{DocFind, ParseProgress, TextExportOptions, WordDocument};
+
+use std::sync::atomic::AtomicBool;
+use document::ParseMonitor;
+use crate::piece_tree::count_words;
 
 /// Serializable document structure for UI consumption
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -120,6 +135,10 @@ pub struct ParsedDocument {
     #[serde(default)]
     pub tables: Vec<Table>,
 
+    /// Section page setups (page size, margins, columns) in document order
+    #[serde(default)]
+    pub sections: Vec<SectionProperties>,
+
     /// Images in the document
     #[serde(default)]
     pub images: Vec<DocumentImage>,
@@ -132,17 +151,21 @@ pub struct ParsedDocument {
     #[serde(default)]
     pub footers: Vec<Footer>,
 
-    /// Footnotes in the document
+    /// Footnote paragraphs keyed by the `w:id` their references use
     #[serde(default)]
-    pub footnotes: Vec<Footnote>,
+    pub footnotes: std::collections::HashMap<i32, Vec<Paragraph>>,
 
-    /// Endnotes in the document
+    /// Endnote paragraphs keyed by the `w:id` their references use
     #[serde(default)]
-    pub endnotes: Vec<Endnote>,
+    pub endnotes: std::collections::HashMap<i32, Vec<Paragraph>>,
 
     /// Numbering definitions (list styles)
     #[serde(default)]
-    pub numbering: Vec<Numbering>,
+    pub numbering: Option<Numbering>,
+
+    /// Document-wide settings (default tab stop, proofing state, language)
+    #[serde(default)]
+    pub settings: Option<DocumentSettings>,
 }
 
 impl Default for ParsedDocument {
@@ -159,12 +182,14 @@ impl Default for ParsedDocument {
             modified_at: None,
             theme: None,
             tables: Vec::new(),
+            sections: Vec::new(),
             images: Vec::new(),
             headers: Vec::new(),
             footers: Vec::new(),
-            footnotes: Vec::new(),
-            endnotes: Vec::new(),
-            numbering: Vec::new(),
+            footnotes: std::collections::HashMap::new(),
+            endnotes: std::collections::HashMap::new(),
+            numbering: None,
+            settings: None,
         }
     }
 }
@@ -185,20 +210,49 @@ impl Default for ParsedDocument {
 /// # Errors
 ///
 /// Returns `OoxmlError` if:
-/// - The file is not a valid ZIP archive
+/// - The file is not a ZIP archive (`NotAZip`) or its directory is damaged (`CorruptArchive`)
 /// - Required parts are missing (e.g., [Content_Types].xml)
 /// - XML parsing fails
 /// - Content types are invalid
 pub fn parse_ooxml(file_data: &[u8]) -> Result<ParsedDocument, OoxmlError> {
+    parse_ooxml_with_progress(file_data, &mut |_| {}, &AtomicBool::new(false))
+}
+
+/// Parse OOXML document data, reporting progress and allowing cancellation
+///
+/// `progress` is called as each package part starts and after every body paragraph.
+/// `cancel` is checked at the same points; once it is set, parsing stops early.
+///
+/// # Arguments
+///
+/// * `file_data` - Raw bytes of the .docx file
+/// * `progress` - Callback receiving [`ParseProgress`] updates
+/// * `cancel` - Flag another thread can set to abort parsing
+///
+/// # Errors
+///
+/// Returns `OoxmlError::Cancelled` if `cancel` was set, otherwise the same errors
+/// as [`parse_ooxml`].
+pub fn parse_ooxml_with_progress(
+    file_data: &[u8],
+    progress: &mut dyn FnMut(ParseProgress),
+    cancel: &AtomicBool,
+) -> Result<ParsedDocument, OoxmlError> {
+    let mut monitor = ParseMonitor::new(progress, cancel);
+    monitor.check()?;
+    
+    // Reject non-archives and damaged archives before reading any parts
+    check_archive(file_data)?;
+    
     // Parse the OPC package
     let package = OpcPackage::new(file_data)?;
     
     // Parse the Word document
-    let word_doc = WordDocument::parse(&package)?;
+    let word_doc = WordDocument::parse_monitored(&package, &mut monitor)?;
     
     // Calculate statistics
     let char_count = word_doc.text.chars().count();
-    let word_count = word_doc.text.split_whitespace().count();
+    let word_count = count_words(&word_doc.text);
     
     // Extract core properties
     let (title, author, created_at, modified_at) = if let Some(props) = &word_doc.core_properties {
@@ -224,15 +278,35 @@ pub fn parse_ooxml(file_data: &[u8]) -> Result<ParsedDocument, OoxmlError> {
         modified_at,
         theme: word_doc.theme,
         tables: word_doc.tables,
+        sections: word_doc.sections,
         images: word_doc.images,
         headers: word_doc.headers,
         footers: word_doc.footers,
         footnotes: word_doc.footnotes,
         endnotes: word_doc.endnotes,
         numbering: word_doc.numbering,
+        settings: word_doc.settings,
     })
 }
 
+/// Pre-flight check distinguishing "not a zip" from "a damaged zip"
+///
+/// A ZIP file starts with a local file header signature (or the end of central
+/// directory signature when empty). Data carrying the signature that still can't
+/// be opened has a truncated or corrupt central directory.
+fn check_archive(file_data: &[u8]) -> Result<(), OoxmlError> {
+    const LOCAL_FILE_HEADER: &[u8] = b"PK\x03\x04";
+    const END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+
+    if !file_data.starts_with(LOCAL_FILE_HEADER) && !file_data.starts_with(END_OF_CENTRAL_DIRECTORY) {
+        return Err(OoxmlError::NotAZip);
+    }
+
+    zip::ZipArchive::new(std::io::Cursor::new(file_data))
+        .map(|_| ())
+        .map_err(|e| OoxmlError::CorruptArchive(e.to_string()))
+}
+
 /// Parse OOXML document from file path
 ///
 /// # Arguments
@@ -288,6 +362,97 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn fixture_path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn test_parse_ooxml_fixture() {
+        let data: Vec<u8> = fs::read(fixture_path("simple.docx")).unwrap();
+        let document = parse_ooxml(&data).unwrap();
+
+        assert_eq!(document.text, "Velum Fixture\nHello world");
+        assert_eq!(document.paragraph_count, 2);
+        assert_eq!(document.word_count, 4);
+        assert_eq!(document.title.as_deref(), Some("Fixture"));
+        assert_eq!(document.author.as_deref(), Some("Velum"));
+
+        let from_file = parse_ooxml_from_file(fixture_path("simple.docx").to_str().unwrap()).unwrap();
+        assert_eq!(from_file.text, document.text);
+    }
+
+    #[test]
+    fn test_parse_ooxml_with_progress_reports() {
+        let data = fs::read(fixture_path("simple.docx")).unwrap();
+        let mut events = Vec::new();
+        let document = parse_ooxml_with_progress(&data, &mut |p| events.push(p), &AtomicBool::new(false)).unwrap();
+
+        assert_eq!(document.paragraph_count, 2);
+        assert_eq!(events[0], ParseProgress::Part { name: "/word/document.xml".to_string() });
+        assert_eq!(events[1], ParseProgress::Paragraph { parsed: 1 });
+        assert_eq!(events[2], ParseProgress::Paragraph { parsed: 2 });
+
+        // Only parts present in the package are reported
+        let parts: Vec<&str> = events.iter().filter_map(|p| match p {
+            ParseProgress::Part { name } => Some(name.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(parts, ["/word/document.xml", "/word/styles.xml", "/docProps/core.xml"]);
+    }
+
+    #[test]
+    fn test_parse_ooxml_with_progress_cancelled() {
+        let data = fs::read(fixture_path("simple.docx")).unwrap();
+        let mut events = Vec::new();
+        let cancel = AtomicBool::new(true);
+
+        let result = parse_ooxml_with_progress(&data, &mut |p| events.push(p), &cancel);
+        assert!(matches!(result, Err(OoxmlError::Cancelled)));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_parse_ooxml_cancelled_mid_document() {
+        use std::sync::atomic::Ordering;
+
+        let data = fs::read(fixture_path("simple.docx")).unwrap();
+        let cancel = AtomicBool::new(false);
+        let mut paragraphs_seen = 0;
+
+        let result = parse_ooxml_with_progress(&data, &mut |p| {
+            if let ParseProgress::Paragraph { .. } = p {
+                paragraphs_seen += 1;
+                cancel.store(true, Ordering::Relaxed);
+            }
+        }, &cancel);
+
+        assert!(matches!(result, Err(OoxmlError::Cancelled)));
+        assert_eq!(paragraphs_seen, 1);
+    }
+
+    #[test]
+    fn test_parse_ooxml_not_a_zip() {
+        let data: Vec<u8> = (0..256u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+        assert!(matches!(parse_ooxml(&data), Err(OoxmlError::NotAZip)));
+    }
+
+    #[test]
+    fn test_parse_ooxml_truncated_central_directory() {
+        use std::io::Write;
+
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut cursor);
+            writer.start_file("word/document.xml", zip::write::FileOptions::default()).unwrap();
+            writer.write_all(b"<w:document/>").unwrap();
+            writer.finish().unwrap();
+        }
+        let mut data = cursor.into_inner();
+        data.truncate(data.len() - 30);
+
+        assert!(matches!(parse_ooxml(&data), Err(OoxmlError::CorruptArchive(_))));
+    }
+
     #[test]
     fn test_content_type_parsing() {
         // Test content type enum
@@ -328,12 +493,14 @@ mod tests {
             modified_at: None,
             theme: None,
             tables: Vec::new(),
+            sections: Vec::new(),
             images: Vec::new(),
             headers: Vec::new(),
             footers: Vec::new(),
-            footnotes: Vec::new(),
-            endnotes: Vec::new(),
-            numbering: Vec::new(),
+            footnotes: std::collections::HashMap::new(),
+            endnotes: std::collections::HashMap::new(),
+            numbering: None,
+            settings: None,
         };
 
         let json = document_to_json(&doc).unwrap();
@@ -471,12 +638,14 @@ mod tests {
             modified_at: Some("2024-01-02".to_string()),
             theme: None,
             tables: Vec::new(),
+            sections: Vec::new(),
             images: Vec::new(),
             headers: Vec::new(),
             footers: Vec::new(),
-            footnotes: Vec::new(),
-            endnotes: Vec::new(),
-            numbering: Vec::new(),
+            footnotes: std::collections::HashMap::new(),
+            endnotes: std::collections::HashMap::new(),
+            numbering: None,
+            settings: None,
         };
 
         assert_eq!(doc.text, "Test content");
@@ -47,6 +47,9 @@ impl OpcPackage {
         // Extract all parts from the archive
         package.extract_parts(&mut archive)?;
 
+        // Every related part must have a content type to be read
+        package.validate_content_types()?;
+
         Ok(package)
     }
 
@@ -125,20 +128,27 @@ impl OpcPackage {
         let mut relationships = Vec::new();
         
         // <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
-        let rel_pattern = regex::Regex::new(r#"<Relationship\s+Id="([^"]+)"\s+Type="([^"]+)"\s+Target="([^"]+)"\s*/>"#).unwrap();
+        // Attributes may appear in any order, and external targets add TargetMode="External"
+        let rel_pattern = regex::Regex::new(r#"<Relationship\s([^>]*?)/?>"#).unwrap();
+        let attr_pattern = regex::Regex::new(r#"(\w+)="([^"]*)""#).unwrap();
         
         for cap in rel_pattern.captures_iter(&xml_str) {
-            let id = cap[1].to_string();
-            let type_uri = cap[2].to_string();
-            let target = cap[3].to_string();
-            
-            let rel_type = RelationshipType::from_string(&type_uri);
+            let mut attrs: HashMap<&str, &str> = HashMap::new();
+            for attr in attr_pattern.captures_iter(cap.get(1).map_or("", |m| m.as_str())) {
+                if let (Some(name), Some(value)) = (attr.get(1), attr.get(2)) {
+                    attrs.insert(name.as_str(), value.as_str());
+                }
+            }
+
+            let (Some(id), Some(type_uri), Some(target)) = (attrs.get("Id"), attrs.get("Type"), attrs.get("Target")) else {
+                continue;
+            };
             
             relationships.push(Relationship {
-                id,
-                relationship_type: rel_type,
-                target,
-                target_mode: None,
+                id: id.to_string(),
+                relationship_type: RelationshipType::from_string(type_uri),
+                target: target.replace("&amp;", "&"),
+                target_mode: attrs.get("TargetMode").map(|m| m.to_string()),
             });
         }
 
@@ -147,34 +157,25 @@ impl OpcPackage {
 
     /// Parse relationships files for each part
     fn parse_all_relationships<R: Read + Seek>(&mut self, archive: &mut ZipArchive<R>) -> ZipResult<()> {
-        // Find all .rels files by looking at the content types
-        let mut rel_files: Vec<String> = self.content_types.keys()
-            .filter(|name| name.ends_with(".rels"))
-            .cloned()
+        // Part relationships live next to their source: word/_rels/document.xml.rels
+        let rel_files: Vec<String> = archive.file_names()
+            .filter(|name| name.ends_with(".rels") && name.trim_start_matches('/') != "_rels/.rels")
+            .map(|name| name.to_string())
             .collect();
 
-        // Also check for word/_rels/*.rels files
-        for part_name in self.content_types.keys().cloned().collect::<Vec<_>>() {
-            if part_name.starts_with("word/") && !part_name.ends_with(".rels") {
-                // This is a part that might have relationships
-                let rel_path = format!("{}/_rels/{}.rels", 
-                    part_name.rsplit_once('/').map_or("", |(p, _)| p),
-                    part_name.rsplit('/').next().unwrap_or(&part_name));
-                if !rel_files.contains(&rel_path) {
-                    rel_files.push(rel_path);
-                }
-            }
-        }
-
         for rel_file in rel_files {
             if let Some(xml_data) = Self::read_file_from_archive(archive, &[&rel_file]) {
                 let relationships = Self::parse_relationships_xml(&xml_data);
                 if !relationships.is_empty() {
-                    // Store relationships keyed by the source part (derive from .rels path)
-                    let source_part = rel_file
-                        .strip_suffix(".rels")
-                        .unwrap_or(&rel_file)
-                        .replace("/_rels/", "/");
+                    // Store relationships keyed by the absolute source part name
+                    let source_part = format!(
+                        "/{}",
+                        rel_file
+                            .trim_start_matches('/')
+                            .strip_suffix(".rels")
+                            .unwrap_or(&rel_file)
+                            .replace("_rels/", "")
+                    );
                     self.relationships.insert(source_part, relationships);
                 }
             }
@@ -194,15 +195,15 @@ impl OpcPackage {
                 continue;
             }
 
-            // Get content type from our map
-            let content_type = self.content_types.get(&name).cloned();
+            // Part names are absolute ("/word/document.xml") while ZIP entries are not
+            let part_name = format!("/{}", name.trim_start_matches('/'));
 
-            if let Some(ct) = content_type {
+            if let Some(ct) = self.content_type_for(&part_name) {
                 let mut data = Vec::new();
                 file.read_to_end(&mut data)?;
 
-                self.parts.insert(name.clone(), PackagePart {
-                    name: name.clone(),
+                self.parts.insert(part_name.clone(), PackagePart {
+                    name: part_name,
                     content_type: ct,
                     data,
                 });
@@ -217,6 +218,92 @@ impl OpcPackage {
         self.parts.get(name)
     }
 
+    /// Iterate over every part in the package, in no particular order
+    pub fn parts(&self) -> impl Iterator<Item = &PackagePart> {
+        self.parts.values()
+    }
+
+    /// Get the names of all parts in the package, sorted
+    pub fn part_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.parts.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Get all image parts (usually under `/word/media/`), sorted by name
+    pub fn media_parts(&self) -> Vec<&PackagePart> {
+        let mut media: Vec<&PackagePart> = self.parts()
+            .filter(|part| part.image_format().is_some())
+            .collect();
+        media.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        media
+    }
+
+    /// Get the image part an `r:embed` relationship id of `source` points at
+    pub fn image_for_relationship(&self, source: &str, id: &str) -> Option<&PackagePart> {
+        let relationship = self.get_relationships(source)?
+            .iter()
+            .find(|rel| rel.id == id && rel.relationship_type.is_image())?;
+        if relationship.target_mode.as_deref() == Some("External") {
+            return None;
+        }
+        self.get_part(&Self::resolve_target(source, &relationship.target))
+    }
+
+    /// Resolve a relationship target against the part that declares it
+    fn resolve_target(source: &str, target: &str) -> String {
+        if target.starts_with('/') {
+            return target.to_string();
+        }
+
+        let mut segments: Vec<&str> = source.split('/').filter(|s| !s.is_empty()).collect();
+        segments.pop();
+        for segment in target.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(segment),
+            }
+        }
+        format!("/{}", segments.join("/"))
+    }
+
+    /// Resolve the content type of a part: its `Override`, else the `Default` for its extension
+    pub fn content_type_for(&self, part_name: &str) -> Option<ContentType> {
+        if let Some(ct) = self.content_types.get(part_name) {
+            return Some(ct.clone());
+        }
+
+        // Extensions match case-insensitively (ECMA-376 Part 2, 10.1.2.2)
+        let file_name = part_name.rsplit('/').next().unwrap_or(part_name);
+        let extension = file_name.rsplit_once('.').map(|(_, ext)| ext)?;
+        self.content_types.get(&format!("/{}", extension))
+            .or_else(|| self.content_types.get(&format!("/{}", extension.to_ascii_lowercase())))
+            .cloned()
+    }
+
+    /// Check that every part referenced by an internal relationship has a resolvable content type
+    pub fn validate_content_types(&self) -> Result<(), OoxmlError> {
+        let sources = std::iter::once(("/", &self.root_relationships))
+            .chain(self.relationships.iter().map(|(source, rels)| (source.as_str(), rels)));
+
+        for (source, relationships) in sources {
+            for relationship in relationships {
+                if relationship.target_mode.as_deref() == Some("External") {
+                    continue;
+                }
+                let part_name = Self::resolve_target(source, &relationship.target);
+                if self.content_type_for(&part_name).is_none() {
+                    return Err(OoxmlError::InvalidContentType(part_name));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get content type for a part
     pub fn get_content_type(&self, name: &str) -> Option<ContentType> {
         self.content_types.get(name).cloned()
@@ -245,4 +332,130 @@ mod tests {
         assert_eq!(relationships[0].id, "rId1");
         assert_eq!(relationships[0].target, "word/document.xml");
     }
+
+    #[test]
+    fn test_enumerate_parts() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/simple.docx");
+        let package = OpcPackage::new(&std::fs::read(path).unwrap()).unwrap();
+
+        let names = package.part_names();
+        assert!(names.contains(&"/word/document.xml"));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(package.parts().count(), names.len());
+
+        let main = package.parts().find(|part| part.name == "/word/document.xml").unwrap();
+        assert_eq!(main.content_type, ContentType::MainDocument);
+        assert!(package.validate_content_types().is_ok());
+    }
+
+    const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    const CONTENT_TYPES: &str = r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+    <Default Extension="png" ContentType="image/png"/>
+    <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+    <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+</Types>"#;
+
+    fn docx_with_image() -> Vec<u8> {
+        docx_with_content_types(CONTENT_TYPES)
+    }
+
+    fn docx_with_content_types(content_types: &str) -> Vec<u8> {
+        use std::io::Write;
+
+        let files: [(&str, &[u8]); 5] = [
+            ("[Content_Types].xml", content_types.as_bytes()),
+            ("_rels/.rels", br#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+    <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>"#),
+            ("word/document.xml", br#"<w:document><w:body><w:p><w:r><w:drawing><a:blip r:embed="rId5"/></w:drawing></w:r></w:p></w:body></w:document>"#),
+            ("word/_rels/document.xml.rels", br#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+    <Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/>
+</Relationships>"#),
+            ("word/media/image1.png", PNG_BYTES),
+        ];
+
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut cursor);
+            for (name, data) in files {
+                writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+                writer.write_all(data).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn test_media_parts() {
+        let package = OpcPackage::new(&docx_with_image()).unwrap();
+
+        let media = package.media_parts();
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].name, "/word/media/image1.png");
+        assert!(!media[0].data.is_empty());
+        assert_eq!(media[0].data, PNG_BYTES);
+        assert_eq!(media[0].image_format(), Some(ContentType::ImagePng));
+    }
+
+    #[test]
+    fn test_image_for_relationship() {
+        let package = OpcPackage::new(&docx_with_image()).unwrap();
+
+        let image = package.image_for_relationship("/word/document.xml", "rId5").unwrap();
+        assert_eq!(image.name, "/word/media/image1.png");
+        assert!(package.image_for_relationship("/word/document.xml", "rId9").is_none());
+    }
+
+    #[test]
+    fn test_content_type_for() {
+        let package = OpcPackage::new(&docx_with_image()).unwrap();
+
+        assert_eq!(package.content_type_for("/word/document.xml"), Some(ContentType::MainDocument));
+        assert_eq!(package.content_type_for("/word/media/image1.png"), Some(ContentType::ImagePng));
+        assert_eq!(package.content_type_for("/word/media/IMAGE2.PNG"), Some(ContentType::ImagePng));
+        assert_eq!(package.content_type_for("/word/styles.xml"), None);
+    }
+
+    #[test]
+    fn test_validate_content_types() {
+        let package = OpcPackage::new(&docx_with_image()).unwrap();
+        assert!(package.validate_content_types().is_ok());
+    }
+
+    #[test]
+    fn test_validate_missing_main_document_override() {
+        let content_types = CONTENT_TYPES.lines()
+            .filter(|line| !line.contains("/word/document.xml"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(matches!(
+            OpcPackage::new(&docx_with_content_types(&content_types)),
+            Err(OoxmlError::InvalidContentType(part)) if part == "/word/document.xml"
+        ));
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        assert_eq!(OpcPackage::resolve_target("/word/document.xml", "media/image1.png"), "/word/media/image1.png");
+        assert_eq!(OpcPackage::resolve_target("/word/document.xml", "../customXml/item1.xml"), "/customXml/item1.xml");
+        assert_eq!(OpcPackage::resolve_target("/word/document.xml", "/word/media/a.png"), "/word/media/a.png");
+    }
+
+    #[test]
+    fn test_parse_relationships_xml_external_target() {
+        let xml = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+    <Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>
+    <Relationship Target="styles.xml" Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles"/>
+</Relationships>"#;
+
+        let relationships = OpcPackage::parse_relationships_xml(xml.as_bytes());
+        assert_eq!(relationships.len(), 2);
+        assert_eq!(relationships[0].relationship_type, RelationshipType::Hyperlink);
+        assert_eq!(relationships[0].target, "https://example.com/?a=1&b=2");
+        assert_eq!(relationships[0].target_mode.as_deref(), Some("External"));
+        assert_eq!(relationships[1].id, "rId1");
+        assert_eq!(relationships[1].target, "styles.xml");
+    }
 }
@@ -12,7 +12,7 @@ use zip::ZipWriter;
 use super::error::OoxmlError;
 use super::opc::OpcPackage;
 use super::types::{
-    ContentType, Paragraph, ParagraphProperties, Relationship, RelationshipType,
+    ContentType, NoteReference, Paragraph, ParagraphProperties, Relationship, RelationshipType,
     Run, RunProperties, Style, Theme, ThemeFonts,
 };
 use crate::piece_tree::{PieceTree, TextAttributes};
@@ -221,7 +221,7 @@ impl DocxSerializer {
 
         // Serialize each paragraph
         for para in &document.paragraphs {
-            body.push_str(&self.serialize_paragraph(para)?);
+            body.push_str(&serialize_paragraph(para)?);
         }
 
         // End document body
@@ -236,242 +236,16 @@ impl DocxSerializer {
         })
     }
 
-    /// Serialize a single paragraph
-    fn serialize_paragraph(&self, para: &Paragraph) -> Result<String, OoxmlError> {
-        let mut xml = String::new();
-
-        xml.push_str("<w:p>");
-
-        // Serialize paragraph properties
-        xml.push_str(&self.serialize_paragraph_properties(&para.properties));
-
-        // Serialize runs
-        for run in &para.runs {
-            xml.push_str(&self.serialize_run(run)?);
-        }
-
-        xml.push_str("</w:p>");
-
-        Ok(xml)
-    }
-
-    /// Serialize paragraph properties
-    fn serialize_paragraph_properties(&self, props: &ParagraphProperties) -> String {
-        let mut xml = String::new();
-
-        if props.indent_left.is_some()
-            || props.indent_right.is_some()
-            || props.indent_first_line.is_some()
-            || props.spacing_before.is_some()
-            || props.spacing_after.is_some()
-            || props.spacing_line.is_some()
-            || props.alignment.is_some()
-        {
-            xml.push_str("<w:pPr>");
-
-            if let Some(ref align) = props.alignment {
-                xml.push_str(&format!(r#"<w:jc w:val="{}"/>"#, escape_xml_attr(align)));
-            }
-
-            if let Some(left) = props.indent_left {
-                xml.push_str(&format!(r#"<w:ind w:left="{}"/>"#, left));
-            }
-
-            if let Some(right) = props.indent_right {
-                xml.push_str(&format!(r#"<w:ind w:right="{}"/>"#, right));
-            }
-
-            if let Some(first) = props.indent_first_line {
-                xml.push_str(&format!(r#"<w:ind w:firstLine="{}"/>"#, first));
-            }
-
-            if let Some(before) = props.spacing_before {
-                xml.push_str(&format!(r#"<w:spacing w:before="{}"/>"#, before));
-            }
-
-            if let Some(after) = props.spacing_after {
-                xml.push_str(&format!(r#"<w:spacing w:after="{}"/>"#, after));
-            }
-
-            if let Some(line) = props.spacing_line {
-                xml.push_str(&format!(r#"<w:spacing w:line="{}"/>"#, line));
-            }
-
-            xml.push_str("</w:pPr>");
-        }
-
-        xml
-    }
-
-    /// Serialize a run
-    fn serialize_run(&self, run: &Run) -> Result<String, OoxmlError> {
-        let mut xml = String::new();
-
-        xml.push_str("<w:r>");
-
-        // Serialize run properties
-        xml.push_str(&self.serialize_run_properties(&run.properties));
-
-        // Serialize text
-        if !run.text.is_empty() {
-            xml.push_str(&format!(
-                "<w:t>{}</w:t>",
-                escape_xml_text(&run.text)
-            ));
-        }
-
-        xml.push_str("</w:r>");
-
-        Ok(xml)
-    }
-
-    /// Serialize run properties
-    fn serialize_run_properties(&self, props: &RunProperties) -> String {
-        let mut xml = String::new();
-
-        if props.bold.is_some()
-            || props.italic.is_some()
-            || props.underline.is_some()
-            || props.font_size.is_some()
-            || props.font_name.is_some()
-            || props.color.is_some()
-            || props.background_color.is_some()
-        {
-            xml.push_str("<w:rPr>");
-
-            if let Some(bold) = props.bold {
-                xml.push_str(&format!(r#"<w:b w:val="{}"/>"#, if bold { "1" } else { "0" }));
-            }
-
-            if let Some(italic) = props.italic {
-                xml.push_str(&format!(r#"<w:i w:val="{}"/>"#, if italic { "1" } else { "0" }));
-            }
-
-            if let Some(ref underline) = props.underline {
-                xml.push_str(&format!(r#"<w:u w:val="{}"/>"#, escape_xml_attr(underline)));
-            }
-
-            if let Some(size) = props.font_size {
-                // Word uses half-points, so multiply by 2
-                xml.push_str(&format!(r#"<w:sz w:val="{}"/>"#, size * 2));
-            }
-
-            if let Some(ref name) = props.font_name {
-                xml.push_str(&format!(r#"<w:rFonts w:ascii="{}"/>"#, escape_xml_attr(name)));
-            }
-
-            if let Some(ref color) = props.color {
-                xml.push_str(&format!(r#"<w:color w:val="{}"/>"#, escape_xml_attr(color)));
-            }
-
-            if let Some(ref bg_color) = props.background_color {
-                xml.push_str(&format!(r#"<w:shd w:fill="{}"/>"#, escape_xml_attr(bg_color)));
-            }
-
-            xml.push_str("</w:rPr>");
-        }
-
-        xml
-    }
-
     /// Serialize styles
     fn serialize_styles(&self, styles: &HashMap<String, Style>) -> Result<SerializedPart, OoxmlError> {
-        let mut xml = String::new();
-
-        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
-        xml.push_str(
-            r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
-        );
-
-        // Serialize each style
-        for (_, style) in styles {
-            xml.push_str(&self.serialize_style(style)?);
-        }
-
-        // Add default styles if none exist
-        if styles.is_empty() {
-            xml.push_str(&self.get_default_styles());
-        }
-
-        xml.push_str("</w:styles>");
-
         Ok(SerializedPart {
             path: "/word/styles.xml".to_string(),
             content_type: ContentType::Styles,
-            data: xml.into_bytes(),
+            data: styles_xml(styles)?.into_bytes(),
             relationships: Vec::new(),
         })
     }
 
-    /// Serialize a single style
-    fn serialize_style(&self, style: &Style) -> Result<String, OoxmlError> {
-        let mut xml = String::new();
-
-        xml.push_str(&format!(r#"<w:style w:styleId="{}" w:type="{}""#,
-            escape_xml_attr(&style.id),
-            escape_xml_attr(&style.style_type)));
-
-        if style.is_default {
-            xml.push_str(r#" w:default="1""#);
-        }
-
-        xml.push_str(">");
-
-        // Style name
-        if let Some(ref name) = style.name {
-            xml.push_str(&format!(r#"<w:name w:val="{}"/>"#, escape_xml_attr(name)));
-        }
-
-        // Based on
-        if let Some(ref based_on) = style.based_on {
-            xml.push_str(&format!(r#"<w:basedOn w:val="{}"/>"#, escape_xml_attr(based_on)));
-        }
-
-        // Paragraph properties
-        xml.push_str(&self.serialize_paragraph_properties(&style.paragraph_properties));
-
-        // Run properties
-        xml.push_str(&self.serialize_run_properties(&style.run_properties));
-
-        xml.push_str("</w:style>");
-
-        Ok(xml)
-    }
-
-    /// Get default styles for a new document
-    fn get_default_styles(&self) -> String {
-        r#"<w:style w:styleId="Normal" w:type="paragraph" w:default="1">
-            <w:name w:val="Normal"/>
-            <w:rPr>
-                <w:sz w:val="22"/>
-                <w:lang w:val="en-US"/>
-            </w:rPr>
-        </w:style>
-        <w:style w:styleId="Heading1" w:type="paragraph">
-            <w:name w:val="Heading 1"/>
-            <w:basedOn w:val="Normal"/>
-            <w:pPr>
-                <w:spacing w:before="240" w:after="60"/>
-            </w:pPr>
-            <w:rPr>
-                <w:b w:val="1"/>
-                <w:sz w:val="32"/>
-            </w:rPr>
-        </w:style>
-        <w:style w:styleId="Heading2" w:type="paragraph">
-            <w:name w:val="Heading 2"/>
-            <w:basedOn w:val="Heading1"/>
-            <w:pPr>
-                <w:spacing w:before="200" w:after="40"/>
-            </w:pPr>
-            <w:rPr>
-                <w:b w:val="1"/>
-                <w:sz w:val="26"/>
-            </w:rPr>
-        </w:style>"#
-            .to_string()
-    }
-
     /// Serialize core properties
     fn serialize_core_properties(&self, document: &WordDocument) -> SerializedPart {
         let mut xml = String::new();
@@ -672,25 +446,9 @@ impl DocxSerializer {
         // Override types
         for (part_name, content_type) in content_types {
             if part_name.starts_with("/") {
-                let type_str = match content_type {
-                    ContentType::MainDocument => "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
-                    ContentType::Styles => "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml",
-                    ContentType::Theme => "application/vnd.openxmlformats-officedocument.theme+xml",
-                    ContentType::Settings => "application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml",
-                    ContentType::CoreProperties => "application/vnd.openxmlformats-package.core-properties+xml",
-                    ContentType::AppProperties => "application/vnd.openxmlformats-officedocument.extended-properties+xml",
-                    ContentType::Numbering => "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml",
-                    ContentType::WebSettings => "application/vnd.openxmlformats-officedocument.wordprocessingml.webSettings+xml",
-                    ContentType::ImagePng => "image/png",
-                    ContentType::ImageJpeg => "image/jpeg",
-                    ContentType::ImageGif => "image/gif",
-                    ContentType::ImageBmp => "image/bmp",
-                    ContentType::ImageSvg => "image/svg+xml",
-                    _ => "application/xml",
-                };
                 xml.push_str(&format!(
                     r#"<Override PartName="{}" ContentType="{}"/>"#,
-                    part_name, type_str
+                    part_name, escape_xml_attr(content_type.mime_type())
                 ));
             }
         }
@@ -719,21 +477,10 @@ impl DocxSerializer {
             } else {
                 format!("{}/{}", base_path, rel.target)
             };
-            let type_uri = match &rel.relationship_type {
-                RelationshipType::OfficeDocument => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument".to_string(),
-                RelationshipType::Document => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/mainDocument".to_string(),
-                RelationshipType::Styles => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles".to_string(),
-                RelationshipType::Theme => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme".to_string(),
-                RelationshipType::Settings => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/settings".to_string(),
-                RelationshipType::CoreProperties => "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties".to_string(),
-                RelationshipType::Image => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image".to_string(),
-                RelationshipType::Unknown(uri) => uri.clone(),
-                _ => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument".to_string(),
-            };
             xml.push_str(&format!(
                 r#"<Relationship Id="{}" Type="{}" Target="{}"/>"#,
                 escape_xml_attr(&rel.id),
-                type_uri,
+                escape_xml_attr(rel.relationship_type.uri()),
                 escape_xml_attr(&target)
             ));
         }
@@ -771,6 +518,298 @@ impl DocxSerializer {
     }
 }
 
+/// Serialize a single paragraph
+pub(super) fn serialize_paragraph(para: &Paragraph) -> Result<String, OoxmlError> {
+    let mut xml = String::new();
+
+    xml.push_str("<w:p>");
+
+    // Serialize paragraph properties
+    xml.push_str(&serialize_paragraph_properties(&para.properties));
+
+    // Serialize runs
+    for run in &para.runs {
+        xml.push_str(&serialize_run(run)?);
+    }
+
+    xml.push_str("</w:p>");
+
+    Ok(xml)
+}
+
+/// Serialize paragraph properties
+fn serialize_paragraph_properties(props: &ParagraphProperties) -> String {
+    let mut xml = String::new();
+
+    if props.indent_left.is_some()
+        || props.indent_right.is_some()
+        || props.indent_first_line.is_some()
+        || props.spacing_before.is_some()
+        || props.spacing_after.is_some()
+        || props.spacing_line.is_some()
+        || props.alignment.is_some()
+        || props.num_id.is_some()
+    {
+        xml.push_str("<w:pPr>");
+
+        if let Some(ref num_id) = props.num_id {
+            xml.push_str(&format!(
+                r#"<w:numPr><w:ilvl w:val="{}"/><w:numId w:val="{}"/></w:numPr>"#,
+                props.ilvl.unwrap_or(0),
+                escape_xml_attr(num_id)
+            ));
+        }
+
+        let mut spacing = String::new();
+        if let Some(before) = props.spacing_before {
+            spacing.push_str(&format!(r#" w:before="{}""#, before));
+        }
+        if let Some(after) = props.spacing_after {
+            spacing.push_str(&format!(r#" w:after="{}""#, after));
+        }
+        if let Some(line) = props.spacing_line {
+            spacing.push_str(&format!(r#" w:line="{}""#, line));
+        }
+        if !spacing.is_empty() {
+            xml.push_str(&format!("<w:spacing{}/>", spacing));
+        }
+
+        let mut indent = String::new();
+        if let Some(left) = props.indent_left {
+            indent.push_str(&format!(r#" w:left="{}""#, left));
+        }
+        if let Some(right) = props.indent_right {
+            indent.push_str(&format!(r#" w:right="{}""#, right));
+        }
+        if let Some(first) = props.indent_first_line {
+            indent.push_str(&format!(r#" w:firstLine="{}""#, first));
+        }
+        if !indent.is_empty() {
+            xml.push_str(&format!("<w:ind{}/>", indent));
+        }
+
+        if let Some(ref align) = props.alignment {
+            xml.push_str(&format!(r#"<w:jc w:val="{}"/>"#, escape_xml_attr(align)));
+        }
+
+        xml.push_str("</w:pPr>");
+    }
+
+    xml
+}
+
+/// Serialize a run
+fn serialize_run(run: &Run) -> Result<String, OoxmlError> {
+    let mut xml = String::new();
+
+    xml.push_str("<w:r>");
+
+    // Serialize run properties
+    xml.push_str(&serialize_run_properties(&run.properties));
+
+    match run.note_reference {
+        Some(NoteReference::Footnote(id)) => xml.push_str(&format!(r#"<w:footnoteReference w:id="{}"/>"#, id)),
+        Some(NoteReference::Endnote(id)) => xml.push_str(&format!(r#"<w:endnoteReference w:id="{}"/>"#, id)),
+        None => {}
+    }
+
+    if let Some(symbol) = &run.symbol {
+        xml.push_str(&format!(
+            r#"<w:sym w:font="{}" w:char="{:04X}"/>"#,
+            escape_xml_attr(&symbol.font),
+            symbol.char_code
+        ));
+    }
+
+    // Serialize text, with tabs and line breaks as their own elements
+    for (index, line) in run.text.split('\n').enumerate() {
+        if index > 0 {
+            xml.push_str("<w:br/>");
+        }
+        for (index, segment) in line.split('\t').enumerate() {
+            if index > 0 {
+                xml.push_str("<w:tab/>");
+            }
+            if segment.is_empty() {
+                continue;
+            }
+            // Readers trim unpreserved text, which would drop edge spaces
+            let preserve = segment.starts_with(char::is_whitespace) || segment.ends_with(char::is_whitespace);
+            xml.push_str(&format!(
+                "<w:t{}>{}</w:t>",
+                if preserve { r#" xml:space="preserve""# } else { "" },
+                escape_xml_text(segment)
+            ));
+        }
+    }
+
+    xml.push_str("</w:r>");
+
+    Ok(xml)
+}
+
+/// Serialize run properties
+fn serialize_run_properties(props: &RunProperties) -> String {
+    let mut xml = String::new();
+
+    if props.bold.is_some()
+        || props.italic.is_some()
+        || props.underline.is_some()
+        || props.font_size.is_some()
+        || props.font_name.is_some()
+        || props.color.is_some()
+        || props.background_color.is_some()
+        || props.caps.is_some()
+        || props.small_caps.is_some()
+        || props.strikethrough.is_some()
+        || props.vert_align.is_some()
+    {
+        xml.push_str("<w:rPr>");
+
+        if let Some(bold) = props.bold {
+            xml.push_str(&format!(r#"<w:b w:val="{}"/>"#, if bold { "1" } else { "0" }));
+        }
+
+        if let Some(italic) = props.italic {
+            xml.push_str(&format!(r#"<w:i w:val="{}"/>"#, if italic { "1" } else { "0" }));
+        }
+
+        if let Some(ref underline) = props.underline {
+            xml.push_str(&format!(r#"<w:u w:val="{}"/>"#, escape_xml_attr(underline)));
+        }
+
+        if let Some(size) = props.font_size {
+            // Already in half-points, as Word stores it
+            xml.push_str(&format!(r#"<w:sz w:val="{}"/>"#, size));
+        }
+
+        if let Some(ref name) = props.font_name {
+            xml.push_str(&format!(r#"<w:rFonts w:ascii="{}"/>"#, escape_xml_attr(name)));
+        }
+
+        if let Some(ref color) = props.color {
+            xml.push_str(&format!(r#"<w:color w:val="{}"/>"#, escape_xml_attr(color)));
+        }
+
+        if let Some(ref bg_color) = props.background_color {
+            xml.push_str(&format!(r#"<w:shd w:fill="{}"/>"#, escape_xml_attr(bg_color)));
+        }
+
+        if let Some(caps) = props.caps {
+            xml.push_str(&format!(r#"<w:caps w:val="{}"/>"#, if caps { "1" } else { "0" }));
+        }
+
+        if let Some(small_caps) = props.small_caps {
+            xml.push_str(&format!(r#"<w:smallCaps w:val="{}"/>"#, if small_caps { "1" } else { "0" }));
+        }
+
+        if let Some(strike) = props.strikethrough {
+            xml.push_str(&format!(r#"<w:strike w:val="{}"/>"#, if strike { "1" } else { "0" }));
+        }
+
+        if let Some(align) = props.vert_align {
+            xml.push_str(&format!(r#"<w:vertAlign w:val="{}"/>"#, align.as_str()));
+        }
+
+        xml.push_str("</w:rPr>");
+    }
+
+    xml
+}
+
+/// Serialize the styles part, falling back to built-in defaults when `styles` is empty
+pub(super) fn styles_xml(styles: &HashMap<String, Style>) -> Result<String, OoxmlError> {
+    let mut xml = String::new();
+
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+    xml.push_str(
+        r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+    );
+
+    // Serialize each style
+    for style in styles.values() {
+        xml.push_str(&serialize_style(style)?);
+    }
+
+    // Add default styles if none exist
+    if styles.is_empty() {
+        xml.push_str(&get_default_styles());
+    }
+
+    xml.push_str("</w:styles>");
+
+    Ok(xml)
+}
+
+/// Serialize a single style
+fn serialize_style(style: &Style) -> Result<String, OoxmlError> {
+    let mut xml = String::new();
+
+    xml.push_str(&format!(r#"<w:style w:styleId="{}" w:type="{}""#,
+        escape_xml_attr(&style.id),
+        escape_xml_attr(&style.style_type)));
+
+    if style.is_default {
+        xml.push_str(r#" w:default="1""#);
+    }
+
+    xml.push_str(">");
+
+    // Style name
+    if let Some(ref name) = style.name {
+        xml.push_str(&format!(r#"<w:name w:val="{}"/>"#, escape_xml_attr(name)));
+    }
+
+    // Based on
+    if let Some(ref based_on) = style.based_on {
+        xml.push_str(&format!(r#"<w:basedOn w:val="{}"/>"#, escape_xml_attr(based_on)));
+    }
+
+    // Paragraph properties
+    xml.push_str(&serialize_paragraph_properties(&style.paragraph_properties));
+
+    // Run properties
+    xml.push_str(&serialize_run_properties(&style.run_properties));
+
+    xml.push_str("</w:style>");
+
+    Ok(xml)
+}
+
+/// Get default styles for a new document
+fn get_default_styles() -> String {
+    r#"<w:style w:styleId="Normal" w:type="paragraph" w:default="1">
+        <w:name w:val="Normal"/>
+        <w:rPr>
+            <w:sz w:val="22"/>
+            <w:lang w:val="en-US"/>
+        </w:rPr>
+    </w:style>
+    <w:style w:styleId="Heading1" w:type="paragraph">
+        <w:name w:val="Heading 1"/>
+        <w:basedOn w:val="Normal"/>
+        <w:pPr>
+            <w:spacing w:before="240" w:after="60"/>
+        </w:pPr>
+        <w:rPr>
+            <w:b w:val="1"/>
+            <w:sz w:val="32"/>
+        </w:rPr>
+    </w:style>
+    <w:style w:styleId="Heading2" w:type="paragraph">
+        <w:name w:val="Heading 2"/>
+        <w:basedOn w:val="Heading1"/>
+        <w:pPr>
+            <w:spacing w:before="200" w:after="40"/>
+        </w:pPr>
+        <w:rPr>
+            <w:b w:val="1"/>
+            <w:sz w:val="26"/>
+        </w:rPr>
+    </w:style>"#
+        .to_string()
+}
+
 /// Convert PieceTree to WordDocument for serialization
 pub fn piece_tree_to_word_document(tree: &PieceTree) -> WordDocument {
     let mut paragraphs = Vec::new();
@@ -779,7 +818,7 @@ pub fn piece_tree_to_word_document(tree: &PieceTree) -> WordDocument {
     // Process all pieces
     for piece in &tree.pieces {
         let buffer_idx = PieceTree::buffer_idx(&piece.buffer_id);
-        if let Some(buffer) = tree.buffers.get(buffer_idx) {
+        if let Some(buffer) = tree.buffer(buffer_idx) {
             let piece_text = if piece.start + piece.length <= buffer.len() {
                 &buffer[piece.start..piece.start + piece.length]
             } else {
@@ -835,10 +874,14 @@ fn convert_attrs_to_run_props(attrs: &TextAttributes) -> RunProperties {
         bold: attrs.bold,
         italic: attrs.italic,
         underline: attrs.underline.map(|u| if u { "single".to_string() } else { "none".to_string() }),
-        font_size: attrs.font_size.map(|s| s as i32),
+        font_size: attrs.font_size.map(|s| s as i32 * 2),
         font_name: attrs.font_family.clone(),
         color: attrs.foreground.clone(),
         background_color: attrs.background.clone(),
+        caps: None,
+        small_caps: None,
+        strikethrough: None,
+        vert_align: None,
     }
 }
 
@@ -886,7 +929,7 @@ fn escape_xml_text(text: &str) -> String {
 }
 
 /// Escape special XML characters in attribute values
-fn escape_xml_attr(attr: &str) -> String {
+pub(super) fn escape_xml_attr(attr: &str) -> String {
     escape_xml_text(attr)
         .replace('\"', "&quot;")
         .replace('\'', "&apos;")
@@ -895,6 +938,7 @@ fn escape_xml_attr(attr: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::types::Symbol;
     use std::fs;
     use std::path::PathBuf;
 
@@ -923,6 +967,9 @@ mod tests {
             runs: vec![Run {
                 text: "Hello World".to_string(),
                 properties: RunProperties::default(),
+                hyperlink: None,
+                note_reference: None,
+                symbol: None,
             }],
         };
         doc.paragraphs.push(para);
@@ -951,6 +998,9 @@ mod tests {
                 bold: Some(true),
                 ..Default::default()
             },
+            hyperlink: None,
+            note_reference: None,
+            symbol: None,
         };
 
         let para = Paragraph {
@@ -989,6 +1039,9 @@ mod tests {
             runs: vec![Run {
                 text: "Heading".to_string(),
                 properties: RunProperties::default(),
+                hyperlink: None,
+                note_reference: None,
+                symbol: None,
             }],
         };
         doc.paragraphs.push(para);
@@ -1021,6 +1074,9 @@ mod tests {
                 runs: vec![Run {
                     text: format!("Paragraph {}", i),
                     properties: RunProperties::default(),
+                    hyperlink: None,
+                    note_reference: None,
+                    symbol: None,
                 }],
             };
             doc.paragraphs.push(para);
@@ -1048,6 +1104,9 @@ mod tests {
             runs: vec![Run {
                 text: "Special chars: <>&\"'".to_string(),
                 properties: RunProperties::default(),
+                hyperlink: None,
+                note_reference: None,
+                symbol: None,
             }],
         };
         doc.paragraphs.push(para);
@@ -1218,6 +1277,9 @@ mod tests {
                 runs: vec![Run {
                     text: format!("This is paragraph {}.", i),
                     properties: RunProperties::default(),
+                    hyperlink: None,
+                    note_reference: None,
+                    symbol: None,
                 }],
             };
             doc.paragraphs.push(para);
@@ -1235,4 +1297,15 @@ mod tests {
         // Check that data is non-empty (100 paragraphs should produce substantial output)
         assert!(!data.is_empty(), "Exported DOCX should not be empty");
     }
+
+    #[test]
+    fn test_serialize_symbol_run() {
+        let run = Run {
+            symbol: Some(Symbol { font: "Wingdings".to_string(), char_code: 0xF0E0 }),
+            ..Run::default()
+        };
+
+        let xml = serialize_run(&run).unwrap();
+        assert_eq!(xml, r#"<w:r><w:sym w:font="Wingdings" w:char="F0E0"/></w:r>"#);
+    }
 }
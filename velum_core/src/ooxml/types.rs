@@ -83,6 +83,50 @@ impl ContentType {
         )
     }
 
+    /// Detect an image content type from the leading bytes of the data
+    pub fn detect_image(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ContentType::ImagePng)
+        } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ContentType::ImageJpeg)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ContentType::ImageGif)
+        } else if data.starts_with(b"BM") {
+            Some(ContentType::ImageBmp)
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            Some(ContentType::ImageWebP)
+        } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+            Some(ContentType::ImageTiff)
+        } else {
+            None
+        }
+    }
+
+    /// MIME type written to `[Content_Types].xml`
+    pub fn mime_type(&self) -> &str {
+        match self {
+            ContentType::MainDocument => "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
+            ContentType::Styles => "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml",
+            ContentType::Theme => "application/vnd.openxmlformats-officedocument.theme+xml",
+            ContentType::Settings => "application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml",
+            ContentType::CoreProperties => "application/vnd.openxmlformats-package.core-properties+xml",
+            ContentType::AppProperties => "application/vnd.openxmlformats-officedocument.extended-properties+xml",
+            ContentType::WebSettings => "application/vnd.openxmlformats-officedocument.wordprocessingml.webSettings+xml",
+            ContentType::Numbering => "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml",
+            ContentType::CustomXml => "application/xml",
+            ContentType::Thumbnail => "image/jpeg",
+            ContentType::Relationships => "application/vnd.openxmlformats-package.relationships+xml",
+            ContentType::ImagePng => "image/png",
+            ContentType::ImageJpeg => "image/jpeg",
+            ContentType::ImageGif => "image/gif",
+            ContentType::ImageBmp => "image/bmp",
+            ContentType::ImageWebP => "image/webp",
+            ContentType::ImageTiff => "image/tiff",
+            ContentType::ImageSvg => "image/svg+xml",
+            ContentType::Unknown(mime) => mime,
+        }
+    }
+
     /// Get the part name for this content type
     pub fn default_part_name(&self) -> Option<&'static str> {
         match self {
@@ -120,6 +164,8 @@ pub enum RelationshipType {
     OfficeDocument,
     /// Image relationship
     Image,
+    /// Hyperlink relationship (usually with an external target)
+    Hyperlink,
     /// Unknown relationship type
     Unknown(String),
 }
@@ -138,10 +184,28 @@ impl RelationshipType {
             "http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail" => RelationshipType::Thumbnail,
             // Image relationships
             rel if rel.contains("relationships/image") => RelationshipType::Image,
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" => RelationshipType::Hyperlink,
             _ => RelationshipType::Unknown(s.to_string()),
         }
     }
 
+    /// Type URI written to a relationships part
+    pub fn uri(&self) -> &str {
+        match self {
+            RelationshipType::OfficeDocument => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument",
+            RelationshipType::Document => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/mainDocument",
+            RelationshipType::Styles => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles",
+            RelationshipType::Theme => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme",
+            RelationshipType::Settings => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/settings",
+            RelationshipType::CoreProperties => "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties",
+            RelationshipType::CustomXml => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/customXml",
+            RelationshipType::Thumbnail => "http://schemas.openxmlformats.org/package/2006/relationships/metadata/thumbnail",
+            RelationshipType::Image => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image",
+            RelationshipType::Hyperlink => "http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink",
+            RelationshipType::Unknown(uri) => uri,
+        }
+    }
+
     /// Check if this is an image relationship type
     pub fn is_image(&self) -> bool {
         matches!(self, RelationshipType::Image)
@@ -172,6 +236,14 @@ pub struct PackagePart {
     pub data: Vec<u8>,
 }
 
+impl PackagePart {
+    /// Image format of the part, detected from its bytes and falling back to the declared type
+    pub fn image_format(&self) -> Option<ContentType> {
+        ContentType::detect_image(&self.data)
+            .or_else(|| self.content_type.is_image().then(|| self.content_type.clone()))
+    }
+}
+
 /// Represents a parsed paragraph in the document
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Paragraph {
@@ -200,6 +272,25 @@ pub struct ParagraphProperties {
     pub spacing_after: Option<i32>,
     /// Line spacing
     pub spacing_line: Option<i32>,
+    /// Numbering instance this paragraph belongs to (`<w:numPr><w:numId>`)
+    pub num_id: Option<String>,
+    /// List level within the numbering instance (`<w:numPr><w:ilvl>`)
+    pub ilvl: Option<u32>,
+}
+
+impl ParagraphProperties {
+    /// Overlay the explicitly set values of `other` onto these properties
+    pub fn overlay(&mut self, other: &ParagraphProperties) {
+        if other.alignment.is_some() { self.alignment = other.alignment.clone(); }
+        if other.indent_left.is_some() { self.indent_left = other.indent_left; }
+        if other.indent_right.is_some() { self.indent_right = other.indent_right; }
+        if other.indent_first_line.is_some() { self.indent_first_line = other.indent_first_line; }
+        if other.spacing_before.is_some() { self.spacing_before = other.spacing_before; }
+        if other.spacing_after.is_some() { self.spacing_after = other.spacing_after; }
+        if other.spacing_line.is_some() { self.spacing_line = other.spacing_line; }
+        if other.num_id.is_some() { self.num_id = other.num_id.clone(); }
+        if other.ilvl.is_some() { self.ilvl = other.ilvl; }
+    }
 }
 
 /// Represents a run of text with common formatting
@@ -209,6 +300,83 @@ pub struct Run {
     pub text: String,
     /// Run properties
     pub properties: RunProperties,
+    /// Hyperlink target (URL, or `#bookmark` for internal anchors) when inside `<w:hyperlink>`
+    pub hyperlink: Option<String>,
+    /// Footnote or endnote anchored by this run (`<w:footnoteReference>` / `<w:endnoteReference>`)
+    #[serde(default)]
+    pub note_reference: Option<NoteReference>,
+    /// Symbol character inserted with `<w:sym>`, which carries no `<w:t>` text
+    #[serde(default)]
+    pub symbol: Option<Symbol>,
+}
+
+/// A symbol character drawn from a specific font, as in `<w:sym w:font="Wingdings" w:char="F0E0"/>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// Font the character code refers to
+    pub font: String,
+    /// Character code in the font, often offset into the private use area at `F000`
+    pub char_code: u32,
+}
+
+impl Symbol {
+    /// Unicode equivalent of the symbol, for the fonts and codes that have a well-known one
+    pub fn unicode(&self) -> Option<char> {
+        // Symbol fonts are addressed either directly or through the F000 private use block
+        let code = match self.char_code {
+            code @ 0xF000..=0xF0FF => code - 0xF000,
+            code => code,
+        };
+        let code = u8::try_from(code).ok()?;
+
+        match self.font.to_ascii_lowercase().as_str() {
+            "symbol" => Self::symbol_font_char(code),
+            "wingdings" => match code {
+                0x4A => Some('\u{263A}'),
+                0x4C => Some('\u{2639}'),
+                0x6C => Some('\u{25CF}'),
+                0xFB => Some('\u{2718}'),
+                0xFC => Some('\u{2714}'),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Map a code in the Symbol font, whose letters are Greek and whose upper half holds math signs
+    fn symbol_font_char(code: u8) -> Option<char> {
+        const GREEK_LOWER: &str = "αβχδεφγηιϕκλμνοπθρστυϖωξψζ";
+        const GREEK_UPPER: &str = "ΑΒΧΔΕΦΓΗΙϑΚΛΜΝΟΠΘΡΣΤΥςΩΞΨΖ";
+        match code {
+            b'a'..=b'z' => GREEK_LOWER.chars().nth((code - b'a') as usize),
+            b'A'..=b'Z' => GREEK_UPPER.chars().nth((code - b'A') as usize),
+            0xA3 => Some('≤'),
+            0xA5 => Some('∞'),
+            0xAC => Some('←'),
+            0xAE => Some('→'),
+            0xB0 => Some('°'),
+            0xB1 => Some('±'),
+            0xB3 => Some('≥'),
+            0xB4 => Some('×'),
+            0xB6 => Some('∂'),
+            0xB8 => Some('÷'),
+            0xB9 => Some('≠'),
+            0xBB => Some('≈'),
+            0xD6 => Some('√'),
+            0xE5 => Some('∑'),
+            0xF2 => Some('∫'),
+            _ => None,
+        }
+    }
+}
+
+/// A reference from a run to a note, by the note's `w:id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteReference {
+    /// References a `<w:footnote>` in `word/footnotes.xml`
+    Footnote(i32),
+    /// References a `<w:endnote>` in `word/endnotes.xml`
+    Endnote(i32),
 }
 
 /// Properties of a run (text formatting)
@@ -228,6 +396,88 @@ pub struct RunProperties {
     pub color: Option<String>,
     /// Background color (hex RGB)
     pub background_color: Option<String>,
+    /// All caps (`<w:caps/>`)
+    pub caps: Option<bool>,
+    /// Small caps (`<w:smallCaps/>`)
+    pub small_caps: Option<bool>,
+    /// Single strikethrough (`<w:strike/>`)
+    pub strikethrough: Option<bool>,
+    /// Vertical position relative to the baseline (`<w:vertAlign>`)
+    pub vert_align: Option<VertAlign>,
+}
+
+/// Capitalization of a run, resolved from `<w:caps/>` and `<w:smallCaps/>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Caps {
+    /// Text is displayed as written
+    #[default]
+    Normal,
+    /// All letters are displayed as capitals
+    AllCaps,
+    /// Lowercase letters are displayed as smaller capitals
+    SmallCaps,
+}
+
+/// Vertical alignment of a run (`ST_VerticalAlignRun`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VertAlign {
+    Baseline,
+    Superscript,
+    Subscript,
+}
+
+impl VertAlign {
+    /// Parse a `w:vertAlign` value
+    pub fn from_string(s: &str) -> Option<Self> {
+        match s {
+            "baseline" => Some(VertAlign::Baseline),
+            "superscript" => Some(VertAlign::Superscript),
+            "subscript" => Some(VertAlign::Subscript),
+            _ => None,
+        }
+    }
+
+    /// The `w:vertAlign` value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VertAlign::Baseline => "baseline",
+            VertAlign::Superscript => "superscript",
+            VertAlign::Subscript => "subscript",
+        }
+    }
+}
+
+impl RunProperties {
+    /// Font size in points, keeping half points (e.g. 23 half-points is 11.5pt)
+    pub fn font_size_points(&self) -> Option<f32> {
+        self.font_size.map(|half_points| half_points as f32 / 2.0)
+    }
+
+    /// Capitalization to apply when displaying this run's text
+    pub fn caps_style(&self) -> Caps {
+        if self.caps == Some(true) {
+            Caps::AllCaps
+        } else if self.small_caps == Some(true) {
+            Caps::SmallCaps
+        } else {
+            Caps::Normal
+        }
+    }
+
+    /// Overlay the explicitly set values of `other` onto these properties
+    pub fn overlay(&mut self, other: &RunProperties) {
+        if other.bold.is_some() { self.bold = other.bold; }
+        if other.italic.is_some() { self.italic = other.italic; }
+        if other.underline.is_some() { self.underline = other.underline.clone(); }
+        if other.font_size.is_some() { self.font_size = other.font_size; }
+        if other.font_name.is_some() { self.font_name = other.font_name.clone(); }
+        if other.color.is_some() { self.color = other.color.clone(); }
+        if other.background_color.is_some() { self.background_color = other.background_color.clone(); }
+        if other.caps.is_some() { self.caps = other.caps; }
+        if other.small_caps.is_some() { self.small_caps = other.small_caps; }
+        if other.strikethrough.is_some() { self.strikethrough = other.strikethrough; }
+        if other.vert_align.is_some() { self.vert_align = other.vert_align; }
+    }
 }
 
 /// Represents a style definition
@@ -354,10 +604,103 @@ pub struct AnchorPositionSpec {
     pub alignment: Option<String>,
 }
 
+// ============================================
+// Section types
+// ============================================
+
+/// Page orientation of a section (`w:orient`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PageOrientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Page setup of a section (`<w:sectPr>`), with all lengths in twips (1/20 of a point)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionProperties {
+    /// Page width (`w:pgSz/@w:w`)
+    pub page_width: u32,
+    /// Page height (`w:pgSz/@w:h`)
+    pub page_height: u32,
+    /// Page orientation
+    pub orientation: PageOrientation,
+    /// Top margin; negative values keep text from moving down for the header
+    pub margin_top: i32,
+    /// Bottom margin; negative values keep text from moving up for the footer
+    pub margin_bottom: i32,
+    /// Left margin
+    pub margin_left: u32,
+    /// Right margin
+    pub margin_right: u32,
+    /// Distance from the top edge of the page to the header
+    pub header_distance: u32,
+    /// Distance from the bottom edge of the page to the footer
+    pub footer_distance: u32,
+    /// Number of text columns
+    pub columns: u32,
+    /// Gap between columns
+    pub column_gap: u32,
+}
+
+impl Default for SectionProperties {
+    /// Word's defaults: US Letter with 1-inch margins
+    fn default() -> Self {
+        SectionProperties {
+            page_width: 12240,
+            page_height: 15840,
+            orientation: PageOrientation::Portrait,
+            margin_top: 1440,
+            margin_bottom: 1440,
+            margin_left: 1440,
+            margin_right: 1440,
+            header_distance: 720,
+            footer_distance: 720,
+            columns: 1,
+            column_gap: 720,
+        }
+    }
+}
+
+// ============================================
+// Settings types
+// ============================================
+
+/// Document-wide settings from `word/settings.xml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentSettings {
+    /// Interval between default tab stops in twips (`<w:defaultTabStop>`)
+    pub default_tab_stop: Option<i32>,
+    /// Spelling proofing state, "clean" or "dirty" (`<w:proofState w:spelling>`)
+    pub spelling_state: Option<String>,
+    /// Grammar proofing state, "clean" or "dirty" (`<w:proofState w:grammar>`)
+    pub grammar_state: Option<String>,
+    /// Default language of the document, e.g. "en-US" (`<w:themeFontLang w:val>`)
+    pub language: Option<String>,
+    /// Word compatibility mode, e.g. 15 for Word 2013 and later (`<w:compatSetting w:name="compatibilityMode">`)
+    pub compatibility_mode: Option<i32>,
+}
+
+impl DocumentSettings {
+    /// The default tab stop interval in points
+    pub fn default_tab_stop_points(&self) -> Option<f32> {
+        self.default_tab_stop.filter(|&twips| twips > 0).map(|twips| twips as f32 / 20.0)
+    }
+}
+
 // ============================================
 // Table types
 // ============================================
 
+/// A block-level element of the document body, in document order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyElement {
+    /// Index into the document's paragraphs
+    Paragraph(usize),
+    /// Index into the document's tables
+    Table(usize),
+}
+
 /// Represents a table in the document
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Table {
@@ -367,6 +710,16 @@ pub struct Table {
     pub properties: TableProperties,
 }
 
+impl Table {
+    /// Number of leading rows marked `<w:tblHeader/>`, repeated on each page the table spans
+    pub fn header_row_count(&self) -> usize {
+        self.rows
+            .iter()
+            .take_while(|row| row.properties.is_header)
+            .count()
+    }
+}
+
 /// Table row in a table
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TableRow {
@@ -389,10 +742,20 @@ pub struct TableCell {
     pub vertical_merge: Option<i32>,
     /// Horizontal merge information
     pub horizontal_merge: Option<i32>,
+    /// Number of grid columns the cell spans (`<w:gridSpan>`)
+    #[serde(default)]
+    pub grid_span: Option<u32>,
     /// Cell properties
     pub properties: TableCellProperties,
 }
 
+impl TableCell {
+    /// Number of grid columns the cell covers, 1 unless it has a `gridSpan`
+    pub fn column_span(&self) -> u32 {
+        self.grid_span.unwrap_or(1).max(1)
+    }
+}
+
 /// Table properties
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TableProperties {
@@ -529,6 +892,22 @@ pub struct Numbering {
     pub num_instances: Vec<NumInstance>,
 }
 
+impl Numbering {
+    /// Levels of the abstract definition used by numbering instance `num_id`
+    pub fn levels(&self, num_id: &str) -> Option<&[ListLevel]> {
+        let instance = self.num_instances.iter().find(|n| n.num_id == num_id)?;
+        self.abstract_num_defs
+            .iter()
+            .find(|a| a.abstract_num_id == instance.abstract_num_id)
+            .map(|a| a.levels.as_slice())
+    }
+
+    /// A single level of numbering instance `num_id`
+    pub fn level(&self, num_id: &str, ilvl: u32) -> Option<&ListLevel> {
+        self.levels(num_id)?.iter().find(|l| l.level == ilvl)
+    }
+}
+
 /// Abstract numbering definition
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AbstractNumDef {
@@ -652,6 +1031,9 @@ mod tests {
         para.runs.push(Run {
             text: "Hello".to_string(),
             properties: RunProperties::default(),
+            hyperlink: None,
+            note_reference: None,
+            symbol: None,
         });
         assert_eq!(para.runs.len(), 1);
     }
@@ -886,4 +1268,15 @@ mod tests {
 
         assert_eq!(fonts.major_font, "Calibri");
     }
+
+    #[test]
+    fn test_symbol_unicode_equivalent() {
+        let symbol = |font: &str, char_code| Symbol { font: font.to_string(), char_code };
+
+        assert_eq!(symbol("Symbol", 0xF070).unicode(), Some('π'));
+        assert_eq!(symbol("Symbol", 0x57).unicode(), Some('Ω'));
+        assert_eq!(symbol("Wingdings", 0xF0FC).unicode(), Some('✔'));
+        assert_eq!(symbol("Wingdings", 0xF0E0).unicode(), None);
+        assert_eq!(symbol("Calibri", 0x41).unicode(), None);
+    }
 }
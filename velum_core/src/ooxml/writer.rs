@@ -0,0 +1,652 @@
+//! DOCX writer - saves a parsed `WordDocument` back to a `.docx` package
+//!
+//! Unlike [`super::DocxSerializer`], which exports a flat list of paragraphs built
+//! from a `PieceTree`, this writes the structure produced by the parser: body
+//! paragraphs and tables in document order, the final section's page setup, and
+//! the document's styles. Package parts the parser does not model are copied
+//! through unchanged, along with the relationships that point at them.
+
+use std::io::{Cursor, Write};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use super::document::WordDocument;
+use super::error::OoxmlError;
+use super::serializer::{escape_xml_attr, serialize_paragraph, styles_xml};
+use super::types::{
+    BodyElement, ContentType, PageOrientation, Relationship, RelationshipType, SectionProperties,
+    Table, TableBorder, TableCell, TableRow,
+};
+
+/// Parts `to_docx_bytes` always writes itself; everything else in a parsed package is preserved
+pub(super) const REGENERATED_PARTS: [&str; 3] = [
+    "/word/document.xml",
+    "/word/styles.xml",
+    "/word/_rels/document.xml.rels",
+];
+
+impl WordDocument {
+    /// Write the document as a `.docx` package
+    ///
+    /// Paragraph and run formatting, tables, styles and the final section's page
+    /// setup are written from the model. Parts the parser does not model (images,
+    /// headers, numbering, ...) are copied from `preserved_parts` byte for byte,
+    /// and the package and document relationships are written back with their
+    /// original IDs so references to those parts still resolve.
+    pub fn to_docx_bytes(&self) -> Result<Vec<u8>, OoxmlError> {
+        let parts = [
+            ("[Content_Types].xml", self.content_types_xml()),
+            ("_rels/.rels", relationships_xml(&self.root_relationships())),
+            ("word/_rels/document.xml.rels", relationships_xml(&self.main_relationships())),
+            ("word/document.xml", self.document_xml()?),
+            ("word/styles.xml", styles_xml(&self.styles)?),
+        ];
+
+        let mut writer = Cursor::new(Vec::new());
+        {
+            let mut zip = ZipWriter::new(&mut writer);
+            let zip_options = FileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+
+            for (name, xml) in parts {
+                zip.start_file(name, zip_options)?;
+                zip.write_all(xml.as_bytes())?;
+            }
+
+            for part in &self.preserved_parts {
+                zip.start_file(part.name.trim_start_matches('/'), zip_options)?;
+                zip.write_all(&part.data)?;
+            }
+
+            zip.finish()?;
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    /// Serialize `[Content_Types].xml`, declaring every written part
+    fn content_types_xml(&self) -> String {
+        let mut xml = String::new();
+
+        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+        xml.push_str(r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#);
+        xml.push_str(&format!(r#"<Default Extension="rels" ContentType="{}"/>"#, ContentType::Relationships.mime_type()));
+        xml.push_str(r#"<Default Extension="xml" ContentType="application/xml"/>"#);
+
+        let overrides = [
+            ("/word/document.xml", &ContentType::MainDocument),
+            ("/word/styles.xml", &ContentType::Styles),
+        ];
+        let preserved = self.preserved_parts
+            .iter()
+            .filter(|part| part.content_type != ContentType::Relationships)
+            .map(|part| (part.name.as_str(), &part.content_type));
+
+        for (name, content_type) in overrides.into_iter().chain(preserved) {
+            xml.push_str(&format!(
+                r#"<Override PartName="{}" ContentType="{}"/>"#,
+                escape_xml_attr(name),
+                escape_xml_attr(content_type.mime_type())
+            ));
+        }
+
+        xml.push_str("</Types>");
+        xml
+    }
+
+    /// Package relationships, pointing at the main document when none were parsed
+    fn root_relationships(&self) -> Vec<Relationship> {
+        if !self.package_relationships.is_empty() {
+            return self.package_relationships.clone();
+        }
+
+        vec![Relationship {
+            id: "rId1".to_string(),
+            relationship_type: RelationshipType::OfficeDocument,
+            target: "word/document.xml".to_string(),
+            target_mode: None,
+        }]
+    }
+
+    /// Main document relationships, with the styles relationship targeting the written styles part
+    fn main_relationships(&self) -> Vec<Relationship> {
+        let mut relationships: Vec<Relationship> = self.document_relationships
+            .iter()
+            .filter(|rel| rel.relationship_type != RelationshipType::Styles)
+            .cloned()
+            .collect();
+
+        let id = self.document_relationships
+            .iter()
+            .find(|rel| rel.relationship_type == RelationshipType::Styles)
+            .map(|rel| rel.id.clone())
+            .unwrap_or_else(|| {
+                (1..)
+                    .map(|n| format!("rId{}", n))
+                    .find(|id| !relationships.iter().any(|rel| &rel.id == id))
+                    .unwrap_or_default()
+            });
+
+        relationships.push(Relationship {
+            id,
+            relationship_type: RelationshipType::Styles,
+            target: "styles.xml".to_string(),
+            target_mode: None,
+        });
+        relationships
+    }
+
+    /// Serialize the main document part (`word/document.xml`)
+    fn document_xml(&self) -> Result<String, OoxmlError> {
+        let mut xml = String::new();
+
+        xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+        xml.push_str(r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#);
+        xml.push_str("<w:body>");
+
+        if self.body.is_empty() {
+            // Documents built by hand may only fill in `paragraphs`
+            for para in &self.paragraphs {
+                xml.push_str(&serialize_paragraph(para)?);
+            }
+        }
+
+        for element in &self.body {
+            match *element {
+                BodyElement::Paragraph(index) => {
+                    if let Some(para) = self.paragraphs.get(index) {
+                        xml.push_str(&serialize_paragraph(para)?);
+                    }
+                }
+                BodyElement::Table(index) => {
+                    if let Some(table) = self.tables.get(index) {
+                        xml.push_str(&table_xml(table)?);
+                    }
+                }
+            }
+        }
+
+        if let Some(section) = self.sections.last() {
+            xml.push_str(&section_xml(section));
+        }
+
+        xml.push_str("</w:body>");
+        xml.push_str("</w:document>");
+
+        Ok(xml)
+    }
+}
+
+/// Serialize a relationships part
+fn relationships_xml(relationships: &[Relationship]) -> String {
+    let mut xml = String::new();
+
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#);
+    xml.push_str(r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#);
+
+    for rel in relationships {
+        xml.push_str(&format!(
+            r#"<Relationship Id="{}" Type="{}" Target="{}""#,
+            escape_xml_attr(&rel.id),
+            escape_xml_attr(rel.relationship_type.uri()),
+            escape_xml_attr(&rel.target)
+        ));
+        if let Some(ref mode) = rel.target_mode {
+            xml.push_str(&format!(r#" TargetMode="{}""#, escape_xml_attr(mode)));
+        }
+        xml.push_str("/>");
+    }
+
+    xml.push_str("</Relationships>");
+    xml
+}
+
+/// Serialize a `<w:tbl>` element
+fn table_xml(table: &Table) -> Result<String, OoxmlError> {
+    let props = &table.properties;
+    let mut xml = String::from("<w:tbl><w:tblPr>");
+
+    if let Some(width) = props.width {
+        xml.push_str(&format!(r#"<w:tblW w:w="{}" w:type="dxa"/>"#, width));
+    }
+    if let Some(ref alignment) = props.alignment {
+        xml.push_str(&format!(r#"<w:jc w:val="{}"/>"#, escape_xml_attr(alignment)));
+    }
+    if let Some(indent) = props.indent {
+        xml.push_str(&format!(r#"<w:tblInd w:w="{}" w:type="dxa"/>"#, indent));
+    }
+
+    let borders = [
+        ("top", &props.borders.top),
+        ("left", &props.borders.left),
+        ("bottom", &props.borders.bottom),
+        ("right", &props.borders.right),
+        ("insideH", &props.borders.inside_horizontal),
+        ("insideV", &props.borders.inside_vertical),
+    ];
+    if borders.iter().any(|(_, border)| border.is_some()) {
+        xml.push_str("<w:tblBorders>");
+        for (tag, border) in borders {
+            if let Some(border) = border {
+                xml.push_str(&border_xml(tag, border));
+            }
+        }
+        xml.push_str("</w:tblBorders>");
+    }
+
+    if let Some(layout) = &props.layout {
+        xml.push_str(&format!(r#"<w:tblLayout w:type="{}"/>"#, escape_xml_attr(layout)));
+    }
+    xml.push_str("</w:tblPr>");
+    xml.push_str(&grid_xml(table));
+
+    for row in &table.rows {
+        xml.push_str(&row_xml(row)?);
+    }
+
+    xml.push_str("</w:tbl>");
+    Ok(xml)
+}
+
+/// Serialize the `<w:tblGrid>` that `<w:tbl>` requires, derived from the cell widths
+fn grid_xml(table: &Table) -> String {
+    let columns = table
+        .rows
+        .iter()
+        .map(|row| row.cells.iter().map(|cell| cell.grid_span.unwrap_or(1).max(1) as usize).sum())
+        .max()
+        .unwrap_or(0);
+    let mut widths: Vec<Option<u32>> = vec![None; columns];
+
+    for row in &table.rows {
+        let mut column = 0;
+        for cell in &row.cells {
+            let span = cell.grid_span.unwrap_or(1).max(1) as usize;
+            if let Some(width) = cell.width.or(cell.properties.width) {
+                for slot in &mut widths[column..column + span] {
+                    slot.get_or_insert(width / span as u32);
+                }
+            }
+            column += span;
+        }
+    }
+
+    let mut xml = String::from("<w:tblGrid>");
+    for width in widths {
+        match width {
+            Some(width) => xml.push_str(&format!(r#"<w:gridCol w:w="{}"/>"#, width)),
+            None => xml.push_str("<w:gridCol/>"),
+        }
+    }
+    xml.push_str("</w:tblGrid>");
+    xml
+}
+
+/// Serialize a single border of `<w:tblBorders>`
+fn border_xml(tag: &str, border: &TableBorder) -> String {
+    let mut xml = format!(
+        r#"<w:{} w:val="{}" w:sz="{}""#,
+        tag,
+        escape_xml_attr(border.style.as_deref().unwrap_or("single")),
+        border.size.unwrap_or(4)
+    );
+    if let Some(ref color) = border.color {
+        xml.push_str(&format!(r#" w:color="{}""#, escape_xml_attr(color)));
+    }
+    xml.push_str("/>");
+    xml
+}
+
+/// Serialize a `<w:tr>` element
+fn row_xml(row: &TableRow) -> Result<String, OoxmlError> {
+    let props = &row.properties;
+    let mut xml = String::from("<w:tr>");
+
+    let height = props.height.or(row.height);
+    if height.is_some() || props.is_header {
+        xml.push_str("<w:trPr>");
+        if let Some(height) = height {
+            xml.push_str(&format!(r#"<w:trHeight w:h="{}""#, height));
+            if let Some(ref rule) = props.height_rule {
+                xml.push_str(&format!(r#" w:hrule="{}""#, escape_xml_attr(rule)));
+            }
+            xml.push_str("/>");
+        }
+        if props.is_header {
+            xml.push_str("<w:tblHeader/>");
+        }
+        xml.push_str("</w:trPr>");
+    }
+
+    for cell in &row.cells {
+        xml.push_str(&cell_xml(cell)?);
+    }
+
+    xml.push_str("</w:tr>");
+    Ok(xml)
+}
+
+/// Serialize a `<w:tc>` element
+fn cell_xml(cell: &TableCell) -> Result<String, OoxmlError> {
+    let merge = |tag: &str, merge: Option<i32>| match merge {
+        Some(1) => format!(r#"<w:{} w:val="restart"/>"#, tag),
+        Some(-1) => format!(r#"<w:{} w:val="continue"/>"#, tag),
+        _ => String::new(),
+    };
+
+    let mut props = String::new();
+    if let Some(width) = cell.width.or(cell.properties.width) {
+        props.push_str(&format!(r#"<w:tcW w:w="{}" w:type="dxa"/>"#, width));
+    }
+    if let Some(span) = cell.grid_span {
+        props.push_str(&format!(r#"<w:gridSpan w:val="{}"/>"#, span));
+    }
+    props.push_str(&merge("hMerge", cell.horizontal_merge));
+    props.push_str(&merge("vMerge", cell.vertical_merge));
+    if let Some(ref fill) = cell.properties.shading_color {
+        props.push_str(&format!(r#"<w:shd w:val="clear" w:fill="{}"/>"#, escape_xml_attr(fill)));
+    }
+    if let Some(ref direction) = cell.properties.text_direction {
+        props.push_str(&format!(r#"<w:textDirection w:val="{}"/>"#, escape_xml_attr(direction)));
+    }
+    if let Some(ref alignment) = cell.properties.vertical_alignment {
+        props.push_str(&format!(r#"<w:vAlign w:val="{}"/>"#, escape_xml_attr(alignment)));
+    }
+
+    let mut xml = String::from("<w:tc>");
+    if !props.is_empty() {
+        xml.push_str(&format!("<w:tcPr>{}</w:tcPr>", props));
+    }
+
+    // Every cell must end with a paragraph
+    if cell.paragraphs.is_empty() {
+        xml.push_str("<w:p/>");
+    }
+    for para in &cell.paragraphs {
+        xml.push_str(&serialize_paragraph(para)?);
+    }
+
+    xml.push_str("</w:tc>");
+    Ok(xml)
+}
+
+/// Serialize a `<w:sectPr>` element
+fn section_xml(section: &SectionProperties) -> String {
+    let orient = match section.orientation {
+        PageOrientation::Portrait => "",
+        PageOrientation::Landscape => r#" w:orient="landscape""#,
+    };
+
+    format!(
+        concat!(
+            r#"<w:sectPr>"#,
+            r#"<w:pgSz w:w="{}" w:h="{}"{}/>"#,
+            r#"<w:pgMar w:top="{}" w:right="{}" w:bottom="{}" w:left="{}" w:header="{}" w:footer="{}" w:gutter="0"/>"#,
+            r#"<w:cols w:num="{}" w:space="{}"/>"#,
+            r#"</w:sectPr>"#,
+        ),
+        section.page_width,
+        section.page_height,
+        orient,
+        section.margin_top,
+        section.margin_right,
+        section.margin_bottom,
+        section.margin_left,
+        section.header_distance,
+        section.footer_distance,
+        section.columns,
+        section.column_gap,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::opc::OpcPackage;
+    use super::super::types::{Paragraph, ParagraphProperties, Run, RunProperties, Style};
+
+    const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    const SOURCE_CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Default Extension="png" ContentType="image/png"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+  <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+  <Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>"#;
+
+    const SOURCE_ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>"#;
+
+    const SOURCE_DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+  <Relationship Id="rId5" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image1.png"/>
+  <Relationship Id="rId6" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/?a=1&amp;b=2" TargetMode="External"/>
+</Relationships>"#;
+
+    const SOURCE_CORE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Round trip</dc:title></cp:coreProperties>"#;
+
+    const SOURCE_DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p>
+      <w:pPr><w:jc w:val="center"/><w:spacing w:before="240" w:after="120"/></w:pPr>
+      <w:r><w:rPr><w:b w:val="1"/><w:sz w:val="32"/></w:rPr><w:t>Title &amp; more</w:t></w:r>
+    </w:p>
+    <w:p>
+      <w:pPr><w:ind w:left="720" w:firstLine="360"/></w:pPr>
+      <w:r><w:t xml:space="preserve">Plain </w:t></w:r>
+      <w:r><w:rPr><w:i w:val="1"/><w:color w:val="FF0000"/><w:rFonts w:ascii="Georgia"/></w:rPr><w:t>italic</w:t></w:r>
+      <w:r><w:tab/><w:t>after tab</w:t></w:r>
+    </w:p>
+    <w:tbl>
+      <w:tblPr><w:tblW w:w="4000" w:type="dxa"/></w:tblPr>
+      <w:tr>
+        <w:trPr><w:tblHeader/></w:trPr>
+        <w:tc><w:tcPr><w:tcW w:w="2000" w:type="dxa"/><w:vMerge w:val="restart"/></w:tcPr><w:p><w:r><w:t>A1</w:t></w:r></w:p></w:tc>
+        <w:tc><w:tcPr><w:tcW w:w="2000" w:type="dxa"/><w:shd w:val="clear" w:fill="D9D9D9"/></w:tcPr><w:p><w:r><w:rPr><w:u w:val="single"/></w:rPr><w:t>B1</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:tcPr><w:vMerge/></w:tcPr><w:p/></w:tc>
+        <w:tc><w:p><w:r><w:t>B2</w:t></w:r></w:p></w:tc>
+      </w:tr>
+    </w:tbl>
+    <w:p><w:r><w:t>Closing</w:t></w:r></w:p>
+    <w:sectPr>
+      <w:pgSz w:w="16838" w:h="11906" w:orient="landscape"/>
+      <w:pgMar w:top="1000" w:right="1100" w:bottom="1200" w:left="1300" w:header="500" w:footer="600" w:gutter="0"/>
+      <w:cols w:num="2" w:space="400"/>
+    </w:sectPr>
+  </w:body>
+</w:document>"#;
+
+    const SOURCE_STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Heading1">
+    <w:name w:val="heading 1"/>
+    <w:rPr><w:b w:val="1"/><w:sz w:val="32"/></w:rPr>
+  </w:style>
+</w:styles>"#;
+
+    fn source_docx() -> Vec<u8> {
+        let files: [(&str, &[u8]); 7] = [
+            ("[Content_Types].xml", SOURCE_CONTENT_TYPES.as_bytes()),
+            ("_rels/.rels", SOURCE_ROOT_RELS.as_bytes()),
+            ("docProps/core.xml", SOURCE_CORE.as_bytes()),
+            ("word/_rels/document.xml.rels", SOURCE_DOCUMENT_RELS.as_bytes()),
+            ("word/document.xml", SOURCE_DOCUMENT.as_bytes()),
+            ("word/styles.xml", SOURCE_STYLES.as_bytes()),
+            ("word/media/image1.png", PNG_BYTES),
+        ];
+
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = ZipWriter::new(&mut cursor);
+            for (name, data) in files {
+                writer.start_file(name, FileOptions::default()).unwrap();
+                writer.write_all(data).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        cursor.into_inner()
+    }
+
+    fn parse(data: &[u8]) -> WordDocument {
+        WordDocument::parse(&OpcPackage::new(data).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_text_and_formatting() {
+        let original = parse(&source_docx());
+        let written = original.to_docx_bytes().unwrap();
+        let reparsed = parse(&written);
+
+        assert_eq!(reparsed.text, original.text);
+        assert_eq!(reparsed.body, original.body);
+        assert_eq!(reparsed.paragraphs.len(), original.paragraphs.len());
+
+        for (before, after) in original.paragraphs.iter().zip(&reparsed.paragraphs) {
+            assert_eq!(after.text, before.text);
+            // Property structs have no PartialEq; their Debug output covers every field
+            assert_eq!(format!("{:?}", after.properties), format!("{:?}", before.properties));
+            assert_eq!(after.runs.len(), before.runs.len());
+            for (run_before, run_after) in before.runs.iter().zip(&after.runs) {
+                assert_eq!(run_after.text, run_before.text);
+                assert_eq!(format!("{:?}", run_after.properties), format!("{:?}", run_before.properties));
+            }
+        }
+
+        let title = &reparsed.paragraphs[0];
+        assert_eq!(title.text, "Title & more");
+        assert_eq!(title.properties.alignment.as_deref(), Some("center"));
+        assert_eq!(title.runs[0].properties.bold, Some(true));
+        assert_eq!(title.runs[0].properties.font_size, Some(32));
+
+        let body = &reparsed.paragraphs[1];
+        assert_eq!(body.text, "Plain italic\tafter tab");
+        assert_eq!(body.properties.indent_left, Some(720));
+        assert_eq!(body.properties.indent_first_line, Some(360));
+        assert_eq!(body.runs[1].properties.italic, Some(true));
+        assert_eq!(body.runs[1].properties.font_name.as_deref(), Some("Georgia"));
+    }
+
+    #[test]
+    fn test_round_trip_tables_sections_and_styles() {
+        let original = parse(&source_docx());
+        let reparsed = parse(&original.to_docx_bytes().unwrap());
+
+        assert_eq!(reparsed.tables.len(), 1);
+        let table = &reparsed.tables[0];
+        assert_eq!(table.properties.width, Some(4000));
+        assert_eq!(table.header_row_count(), 1);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0].cells[0].width, Some(2000));
+        assert_eq!(table.rows[0].cells[0].vertical_merge, Some(1));
+        assert_eq!(table.rows[1].cells[0].vertical_merge, Some(-1));
+        assert_eq!(table.rows[0].cells[1].properties.shading_color.as_deref(), Some("D9D9D9"));
+        assert_eq!(table.rows[0].cells[1].paragraphs[0].runs[0].properties.underline.as_deref(), Some("single"));
+        assert_eq!(table.rows[1].cells[1].paragraphs[0].text, "B2");
+
+        assert_eq!(reparsed.sections, original.sections);
+        assert_eq!(reparsed.sections.last().unwrap().orientation, PageOrientation::Landscape);
+        assert_eq!(reparsed.sections.last().unwrap().columns, 2);
+
+        let heading = &reparsed.styles["Heading1"];
+        assert_eq!(heading.run_properties.bold, Some(true));
+        assert_eq!(heading.run_properties.font_size, Some(32));
+    }
+
+    #[test]
+    fn test_written_properties_follow_schema_order() {
+        let written = parse(&source_docx()).to_docx_bytes().unwrap();
+        let package = OpcPackage::new(&written).unwrap();
+        let xml = String::from_utf8(package.get_part("/word/document.xml").unwrap().data.clone()).unwrap();
+
+        assert_eq!(xml.matches("<w:ind ").count(), 1);
+        assert!(xml.contains(r#"<w:ind w:left="720" w:firstLine="360"/>"#));
+        assert!(xml.contains(r#"<w:pPr><w:spacing w:before="240" w:after="120"/><w:jc w:val="center"/></w:pPr>"#));
+        assert!(xml.contains(r#"</w:tblPr><w:tblGrid><w:gridCol w:w="2000"/><w:gridCol w:w="2000"/></w:tblGrid><w:tr>"#));
+    }
+
+    #[test]
+    fn test_to_docx_bytes_without_body_writes_paragraphs() {
+        let mut document = WordDocument::default();
+        document.paragraphs.push(Paragraph {
+            text: "Line one\nLine two".to_string(),
+            properties: ParagraphProperties::default(),
+            runs: vec![Run {
+                text: "Line one\nLine two".to_string(),
+                properties: RunProperties { bold: Some(true), ..RunProperties::default() },
+                hyperlink: None,
+                note_reference: None,
+                symbol: None,
+            }],
+        });
+        document.styles.insert("Normal".to_string(), Style {
+            id: "Normal".to_string(),
+            style_type: "paragraph".to_string(),
+            ..Style::default()
+        });
+
+        let reparsed = parse(&document.to_docx_bytes().unwrap());
+
+        assert_eq!(reparsed.paragraphs.len(), 1);
+        assert_eq!(reparsed.paragraphs[0].text, "Line one\nLine two");
+        assert_eq!(reparsed.paragraphs[0].runs[0].properties.bold, Some(true));
+        assert!(reparsed.styles.contains_key("Normal"));
+        assert!(reparsed.sections.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip_preserves_unmodeled_parts() {
+        let original = parse(&source_docx());
+        let names: Vec<&str> = original.preserved_parts.iter().map(|part| part.name.as_str()).collect();
+        assert_eq!(names, ["/docProps/core.xml", "/word/media/image1.png"]);
+
+        let package = OpcPackage::new(&original.to_docx_bytes().unwrap()).unwrap();
+        assert!(package.validate_content_types().is_ok());
+
+        let image = package.image_for_relationship("/word/document.xml", "rId5").unwrap();
+        assert_eq!(image.name, "/word/media/image1.png");
+        assert_eq!(image.data, PNG_BYTES);
+        assert_eq!(image.content_type, ContentType::ImagePng);
+
+        let core = package.get_part("/docProps/core.xml").unwrap();
+        assert_eq!(core.data, SOURCE_CORE.as_bytes());
+        assert!(package.root_relationships
+            .iter()
+            .any(|rel| rel.id == "rId2" && rel.relationship_type == RelationshipType::CoreProperties));
+
+        let relationships = package.get_relationships("/word/document.xml").unwrap();
+        let hyperlink = relationships.iter().find(|rel| rel.id == "rId6").unwrap();
+        assert_eq!(hyperlink.target, "https://example.com/?a=1&b=2");
+        assert_eq!(hyperlink.target_mode.as_deref(), Some("External"));
+        let styles = relationships.iter().find(|rel| rel.relationship_type == RelationshipType::Styles).unwrap();
+        assert_eq!(styles.id, "rId3");
+        assert_eq!(relationships.len(), 3);
+    }
+
+    #[test]
+    fn test_main_relationships_adds_styles_with_free_id() {
+        let mut document = WordDocument::default();
+        document.document_relationships.push(Relationship {
+            id: "rId1".to_string(),
+            relationship_type: RelationshipType::Image,
+            target: "media/image1.png".to_string(),
+            target_mode: None,
+        });
+
+        let relationships = document.main_relationships();
+        assert_eq!(relationships.len(), 2);
+        assert_eq!(relationships[1].id, "rId2");
+        assert_eq!(relationships[1].relationship_type, RelationshipType::Styles);
+        assert_eq!(relationships[1].target, "styles.xml");
+    }
+}
@@ -5,10 +5,18 @@
 //! - Widow/orphan control
 //! - Multi-column layouts
 //! - Cross-page paragraph breaking
+//! - Repeating table header rows
+//! - Headers and footers with running page numbers
 
-use crate::line_layout::ParagraphLayout;
+use crate::line_layout::{DocumentLayout, LineLayout, ParagraphLayout};
+use crate::ooxml::SectionProperties;
+use crate::piece_tree::TextAttributes;
+use crate::text_shaping::TextShaper;
 use serde::{Deserialize, Serialize};
 use std::cmp::min;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::ops::Range;
 
 /// Represents a rectangle in 2D space
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -43,15 +51,62 @@ impl Rect {
     pub fn right(&self) -> f32 {
         self.x + self.width
     }
+
+    /// Checks if the point lies inside this rect. The left and top edges are
+    /// inside, the right and bottom edges are not; an empty rect contains nothing.
+    #[inline]
+    pub fn contains_point(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Checks if the two rects overlap with a positive area. Rects that only
+    /// touch along an edge, and empty rects, do not intersect anything.
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Gets the overlapping area of the two rects, if it is not empty
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let rect = Rect::new(
+            x,
+            y,
+            self.right().min(other.right()) - x,
+            self.bottom().min(other.bottom()) - y,
+        );
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// Gets the smallest rect enclosing both rects. Empty rects are ignored,
+    /// so the union with an empty rect is the other rect.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
 }
 
 /// Represents a rendered line with position information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RenderedLine {
     /// Line index within the page
     pub line_index: usize,
-    /// Source paragraph index
-    pub paragraph_index: usize,
+    /// Source paragraph index; None for header and footer lines
+    pub paragraph_index: Option<usize>,
     /// Source line index within the paragraph
     pub source_line_index: usize,
     /// Y position on the page
@@ -66,6 +121,8 @@ pub struct RenderedLine {
     pub start: usize,
     /// End byte offset in original text
     pub end: usize,
+    /// Literal text for lines not backed by a paragraph, such as headers and footers
+    pub text: Option<String>,
 }
 
 /// Page size and margin configuration
@@ -87,6 +144,31 @@ pub struct PageConfig {
     pub header_height: f32,
     /// Footer height in points
     pub footer_height: f32,
+    /// Header text; `{page}` and `{pages}` expand to the page number and page count
+    pub header_template: Option<String>,
+    /// Footer text; `{page}` and `{pages}` expand to the page number and page count
+    pub footer_template: Option<String>,
+    /// Number of text columns in the body
+    pub columns: usize,
+    /// Gap between columns in points
+    pub column_gap: f32,
+    /// Where the body's lines sit vertically when they do not fill a page
+    #[serde(default)]
+    pub vertical_align: VerticalAlign,
+}
+
+/// Vertical placement of a page's lines within the body region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VerticalAlign {
+    /// Lines start at the top of the body
+    #[default]
+    Top,
+    /// Lines are centered, with equal space above and below
+    Center,
+    /// Lines end at the bottom of the body
+    Bottom,
+    /// Lines span the body, with the extra space spread between paragraphs
+    Justify,
 }
 
 impl Default for PageConfig {
@@ -100,6 +182,11 @@ impl Default for PageConfig {
             margin_right: 72.0,
             header_height: 0.0,
             footer_height: 0.0,
+            header_template: None,
+            footer_template: None,
+            columns: 1,
+            column_gap: 24.0,
+            vertical_align: VerticalAlign::Top,
         }
     }
 }
@@ -123,6 +210,11 @@ impl PageConfig {
             margin_right: 72.0,
             header_height: 0.0,
             footer_height: 0.0,
+            header_template: None,
+            footer_template: None,
+            columns: 1,
+            column_gap: 24.0,
+            vertical_align: VerticalAlign::Top,
         }
     }
 
@@ -138,6 +230,48 @@ impl PageConfig {
         self.height - self.margin_top - self.margin_bottom - self.header_height - self.footer_height
     }
 
+    /// Gets the body region between the header and footer bands
+    #[inline]
+    pub fn content_region(&self) -> Rect {
+        Rect::new(
+            self.margin_left,
+            self.margin_top + self.header_height,
+            self.content_width(),
+            self.content_height(),
+        )
+    }
+
+    /// Gets a single column's width
+    #[inline]
+    pub fn column_width(&self) -> f32 {
+        if self.columns <= 1 {
+            self.content_width()
+        } else {
+            let total_gap = self.column_gap * (self.columns - 1) as f32;
+            (self.content_width() - total_gap) / self.columns as f32
+        }
+    }
+
+    /// Gets the body region of column `index`
+    #[inline]
+    pub fn column_region(&self, index: usize) -> Rect {
+        let body = self.content_region();
+        Rect::new(
+            body.x + index as f32 * (self.column_width() + self.column_gap),
+            body.y,
+            self.column_width(),
+            body.height,
+        )
+    }
+
+    /// Checks if a header or footer template needs the total page count
+    #[inline]
+    pub fn uses_page_count(&self) -> bool {
+        [&self.header_template, &self.footer_template]
+            .iter()
+            .any(|t| t.as_deref().is_some_and(|t| t.contains("{pages}")))
+    }
+
     /// Gets the header region
     #[inline]
     pub fn header_region(&self) -> Option<Rect> {
@@ -169,6 +303,24 @@ impl PageConfig {
     }
 }
 
+impl From<&SectionProperties> for PageConfig {
+    /// Converts a section's page setup from twips to points
+    fn from(section: &SectionProperties) -> Self {
+        let points = |twips: f32| twips / 20.0;
+        PageConfig {
+            width: points(section.page_width as f32),
+            height: points(section.page_height as f32),
+            margin_top: points(section.margin_top.unsigned_abs() as f32),
+            margin_bottom: points(section.margin_bottom.unsigned_abs() as f32),
+            margin_left: points(section.margin_left as f32),
+            margin_right: points(section.margin_right as f32),
+            columns: section.columns.max(1) as usize,
+            column_gap: points(section.column_gap as f32),
+            ..PageConfig::default()
+        }
+    }
+}
+
 /// A single page in the document
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Page {
@@ -188,25 +340,30 @@ pub struct Page {
     pub continued_on: Option<usize>,
     /// Previous page number indicator (for continuation)
     pub continued_from: Option<usize>,
+    /// Paragraphs starting on this page whose keep constraints could not be honored
+    pub keep_violations: Vec<usize>,
+    /// Header lines, positioned relative to `header_region`
+    pub header_lines: Vec<RenderedLine>,
+    /// Footer lines, positioned relative to `footer_region`
+    pub footer_lines: Vec<RenderedLine>,
 }
 
 /// Configuration for pagination control
 #[derive(Debug, Clone)]
 pub struct PaginationConfig {
-    /// Minimum lines to keep together at top of page (orphan control)
-    pub min_lines_orphan: u32,
-    /// Minimum lines to keep together at bottom of page (widow control)
-    pub min_lines_widow: u32,
+    /// Minimum lines of a split paragraph left at the bottom of a page (orphan control)
+    pub orphan_lines: u32,
+    /// Minimum lines of a split paragraph carried to the top of the next page (widow control)
+    pub widow_lines: u32,
     /// Enable widow/orphan control
     pub enable_widow_orphan: bool,
     /// Allow paragraph to be split across pages
     pub allow_page_breaks: bool,
     /// Keep paragraph together on one page if possible
     pub keep_with_next: bool,
-    /// Column count for multi-column layout
-    pub columns: u32,
-    /// Gap between columns
-    pub column_gap: f32,
+    /// Spread the final page's lines evenly across its columns instead of
+    /// filling them in turn
+    pub balance_last_page: bool,
     /// Line height for calculating page content
     pub line_height: f32,
     /// Font size for calculating line height
@@ -216,19 +373,41 @@ pub struct PaginationConfig {
 impl Default for PaginationConfig {
     fn default() -> Self {
         PaginationConfig {
-            min_lines_orphan: 2,
-            min_lines_widow: 2,
+            orphan_lines: 2,
+            widow_lines: 2,
             enable_widow_orphan: true,
             allow_page_breaks: true,
             keep_with_next: false,
-            columns: 1,
-            column_gap: 24.0,
+            balance_last_page: false,
             line_height: 1.2,
             font_size: 12.0,
         }
     }
 }
 
+/// A table laid out as consecutive paragraphs, one per row
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableLayoutInfo {
+    /// Paragraph indices of the table's rows
+    pub rows: Range<usize>,
+    /// Number of leading header rows, repeated at the top of continuation pages
+    pub header_rows: usize,
+}
+
+impl TableLayoutInfo {
+    /// Paragraph indices of the header rows
+    #[inline]
+    pub fn header_range(&self) -> Range<usize> {
+        self.rows.start..min(self.rows.start + self.header_rows, self.rows.end)
+    }
+
+    /// Checks if a paragraph is a non-header row of this table
+    #[inline]
+    pub fn is_body_row(&self, para_index: usize) -> bool {
+        para_index >= self.header_range().end && para_index < self.rows.end
+    }
+}
+
 /// Main page layout engine
 #[derive(Debug, Clone)]
 pub struct PageLayout {
@@ -240,6 +419,8 @@ pub struct PageLayout {
     pub pages: Vec<Page>,
     /// Total paragraph count
     pub paragraph_count: usize,
+    /// Tables whose header rows repeat across pages
+    pub tables: Vec<TableLayoutInfo>,
 }
 
 impl Default for PageLayout {
@@ -257,6 +438,7 @@ impl PageLayout {
             config: PaginationConfig::default(),
             pages: Vec::new(),
             paragraph_count: 0,
+            tables: Vec::new(),
         }
     }
 
@@ -268,19 +450,26 @@ impl PageLayout {
             config: PaginationConfig::default(),
             pages: Vec::new(),
             paragraph_count: 0,
+            tables: Vec::new(),
         }
     }
 
+    /// Registers a table spanning paragraphs `rows`, whose first `header_rows` rows
+    /// are re-emitted at the top of every page the table continues onto
+    pub fn add_table(&mut self, rows: Range<usize>, header_rows: usize) {
+        self.tables.push(TableLayoutInfo { rows, header_rows });
+    }
+
     /// Sets the number of columns
     #[inline]
-    pub fn set_columns(&mut self, columns: u32) {
-        self.config.columns = columns.max(1);
+    pub fn set_columns(&mut self, columns: usize) {
+        self.page_config.columns = columns.max(1);
     }
 
     /// Sets the column gap
     #[inline]
     pub fn set_column_gap(&mut self, gap: f32) {
-        self.config.column_gap = gap;
+        self.page_config.column_gap = gap;
     }
 
     /// Enables or disables widow/orphan control
@@ -289,15 +478,16 @@ impl PageLayout {
         self.config.enable_widow_orphan = enabled;
     }
 
+    /// Enables or disables column balancing on the final page
+    #[inline]
+    pub fn set_balance_last_page(&mut self, enabled: bool) {
+        self.config.balance_last_page = enabled;
+    }
+
     /// Gets a single column's width
     #[inline]
     fn column_width(&self) -> f32 {
-        if self.config.columns <= 1 {
-            self.page_config.content_width()
-        } else {
-            let total_gap = self.config.column_gap * (self.config.columns - 1) as f32;
-            (self.page_config.content_width() - total_gap) / self.config.columns as f32
-        }
+        self.page_config.column_width()
     }
 
     /// Gets the actual line height in points
@@ -312,18 +502,6 @@ impl PageLayout {
         self.page_config.content_height()
     }
 
-    /// Performs the first pass: collects all paragraphs and calculates total height
-    fn first_pass_collect(&self, paragraphs: &[ParagraphLayout]) -> Vec<(usize, f32)> {
-        let mut paragraph_heights = Vec::with_capacity(paragraphs.len());
-
-        for (idx, para) in paragraphs.iter().enumerate() {
-            let height = self.calculate_paragraph_height(para);
-            paragraph_heights.push((idx, height));
-        }
-
-        paragraph_heights
-    }
-
     /// Calculates the height needed for a paragraph
     fn calculate_paragraph_height(&self, para: &ParagraphLayout) -> f32 {
         if para.lines.is_empty() {
@@ -332,274 +510,368 @@ impl PageLayout {
         para.total_height
     }
 
-    /// Performs the second pass: allocates paragraphs to pages
-    fn second_pass_layout(&mut self, paragraphs: &[ParagraphLayout], paragraph_heights: &[(usize, f32)]) -> Vec<Page> {
-        let mut pages = Vec::new();
-        let column_width = self.column_width();
-        let line_height = self.actual_line_height();
-        let available_height = self.available_content_height();
-
-        let mut current_page = Page {
-            page_index: 0,
+    /// Creates an empty page with the configured bounds
+    fn blank_page(&self, page_index: usize) -> Page {
+        Page {
+            page_index,
             lines: Vec::new(),
-            content_bounds: Rect::new(
-                self.page_config.margin_left,
-                self.page_config.margin_top,
-                self.page_config.content_width(),
-                available_height,
-            ),
+            content_bounds: self.page_config.content_region(),
             header_region: self.page_config.header_region(),
             footer_region: self.page_config.footer_region(),
             column: 0,
             continued_on: None,
             continued_from: None,
-        };
+            keep_violations: Vec::new(),
+            header_lines: Vec::new(),
+            footer_lines: Vec::new(),
+        }
+    }
 
-        let mut current_y = 0.0f32;
-        let mut current_column = 0u32;
-        let mut current_x = 0.0f32;
+    /// Gets the x offset of a column within the content area
+    #[inline]
+    fn column_x(&self, column: usize) -> f32 {
+        self.page_config.column_region(column).x - self.page_config.margin_left
+    }
 
-        for (orig_idx, para_height) in paragraph_heights {
-            let para = &paragraphs[*orig_idx];
+    /// Converts a height into the number of whole lines it holds
+    #[inline]
+    fn lines_in(&self, height: f32) -> usize {
+        // Tolerate the rounding error accumulated by summing line heights
+        (height / self.actual_line_height() + 1e-3).floor().max(0.0) as usize
+    }
 
-            // Check if paragraph fits on current page
-            if current_y + para_height > available_height || current_column >= self.config.columns {
-                // Start a new page
-                if !current_page.lines.is_empty() {
-                    pages.push(current_page);
-                }
+    /// Number of whole lines that still fit in the cursor's column
+    #[inline]
+    fn lines_remaining(&self, cursor: &PageCursor) -> usize {
+        self.lines_in(self.available_content_height() - cursor.y)
+    }
 
-                current_page = Page {
-                    page_index: pages.len(),
-                    lines: Vec::new(),
-                    content_bounds: Rect::new(
-                        self.page_config.margin_left,
-                        self.page_config.margin_top,
-                        self.page_config.content_width(),
-                        available_height,
-                    ),
-                    header_region: self.page_config.header_region(),
-                    footer_region: self.page_config.footer_region(),
-                    column: 0,
-                    continued_on: None,
-                    continued_from: None,
-                };
-                current_y = 0.0;
-                current_column = 0;
-                current_x = 0.0;
+    /// Number of lines an empty column holds
+    #[inline]
+    fn lines_per_column(&self) -> usize {
+        self.lines_in(self.available_content_height())
+    }
+
+    /// Decides how many of a paragraph's `remaining` lines, starting at line
+    /// `first`, go into a column with room for `room` lines.
+    ///
+    /// Lines are pushed to the next column until at least `widow_lines` are
+    /// carried over; if that leaves fewer than `orphan_lines` behind, the
+    /// paragraph is moved whole, as is a `keep_together` paragraph that does
+    /// not fit. `fresh` marks a column nothing can be moved out of, where at
+    /// least one line is always placed.
+    fn lines_to_place(&self, first: usize, remaining: usize, room: usize, fresh: bool, keep_together: bool) -> usize {
+        if remaining <= room {
+            return remaining;
+        }
+
+        let mut take = room;
+        if first == 0 && (keep_together || !self.config.allow_page_breaks) {
+            take = 0;
+        } else if self.config.enable_widow_orphan {
+            let widow = self.config.widow_lines as usize;
+            let orphan = self.config.orphan_lines as usize;
+            if remaining - take < widow {
+                take = remaining.saturating_sub(widow);
+            }
+            if first == 0 && take < orphan {
+                take = 0;
             }
+        }
 
-            // Layout paragraph lines
-            self.layout_paragraph_to_page(
-                para,
-                *orig_idx,
-                &mut current_page,
-                &mut current_y,
-                &mut current_column,
-                &mut current_x,
-                column_width,
-                line_height,
-            );
+        if take == 0 && fresh {
+            take = room.clamp(1, remaining);
+        }
+        take
+    }
+
+    /// Checks whether the `keep_with_next` chain starting at `para_index` fits
+    /// in `room` lines: every chained paragraph whole, plus the opening lines
+    /// the following paragraph would place on its own
+    fn keep_chain_fits(&self, paragraphs: &[ParagraphLayout], para_index: usize, mut room: usize) -> bool {
+        for (index, para) in paragraphs.iter().enumerate().skip(para_index) {
+            let lines = para.lines.len();
+            if !para.properties.keep_with_next || index + 1 == paragraphs.len() {
+                return lines == 0 || self.lines_to_place(0, lines, room, false, para.properties.keep_together) > 0;
+            }
+            if lines > room {
+                return false;
+            }
+            room -= lines;
         }
+        true
+    }
 
-        // Add the last page if it has content
-        if !current_page.lines.is_empty() {
-            pages.push(current_page);
+    /// Places a paragraph at the cursor, splitting it across columns and pages
+    /// as needed. Returns the pages it completed.
+    ///
+    /// A `keep_with_next` or `keep_together` constraint that cannot be met even
+    /// in an empty column is dropped and the paragraph recorded in the page's
+    /// `keep_violations`.
+    fn place_paragraph(&self, cursor: &mut PageCursor, paragraphs: &[ParagraphLayout], para_index: usize) -> Vec<Page> {
+        let para = &paragraphs[para_index];
+        let mut finished = Vec::new();
+        let mut next_line = 0;
+        let mut advanced = false;
+
+        // A paragraph without lines still takes up a blank line
+        if para.lines.is_empty() {
+            cursor.y = (cursor.y + self.calculate_paragraph_height(para)).min(self.available_content_height());
+            return finished;
         }
 
-        pages
+        let keep_together = para.properties.keep_together && para.lines.len() <= self.lines_per_column();
+        let mut violated = para.properties.keep_together && !keep_together;
+
+        // Move the paragraph ahead when it would be separated from its successor
+        if para.properties.keep_with_next && !self.keep_chain_fits(paragraphs, para_index, self.lines_remaining(cursor)) {
+            if !self.keep_chain_fits(paragraphs, para_index, self.lines_per_column()) {
+                violated = true;
+            } else if cursor.y > 0.0 {
+                finished.extend(self.break_column(cursor, paragraphs, para_index, false));
+                advanced = true;
+            }
+        }
+        if violated {
+            cursor.page.keep_violations.push(para_index);
+        }
+
+        while next_line < para.lines.len() {
+            let remaining = para.lines.len() - next_line;
+            let room = self.lines_remaining(cursor);
+            let fresh = advanced || cursor.y <= 0.0;
+            let take = self.lines_to_place(next_line, remaining, room, fresh, keep_together);
+
+            self.place_lines(para, para_index, next_line..next_line + take, cursor);
+            next_line += take;
+            if next_line == para.lines.len() {
+                break;
+            }
+
+            finished.extend(self.break_column(cursor, paragraphs, para_index, next_line > 0));
+            advanced = true;
+        }
+
+        finished
     }
 
-    /// Layouts a single paragraph onto a page, handling column wrapping
-    fn layout_paragraph_to_page(
-        &self,
-        para: &ParagraphLayout,
-        para_index: usize,
-        page: &mut Page,
-        current_y: &mut f32,
-        current_column: &mut u32,
-        current_x: &mut f32,
-        column_width: f32,
-        line_height: f32,
-    ) {
-        let available_height = self.available_content_height();
-
-        for (line_idx, line_info) in para.lines.iter().enumerate() {
-            let line_height_actual = line_height;
-
-            // Check if line fits in current column
-            if *current_y + line_height_actual > available_height {
-                // Move to next column
-                *current_column += 1;
-                if *current_column >= self.config.columns {
-                    // Need new page - handled in caller
-                    break;
+    /// Continues paragraph `para_index` in the next column, marking the
+    /// continuation when the paragraph is `split` across the page break.
+    /// Returns the completed page, if any.
+    fn break_column(&self, cursor: &mut PageCursor, paragraphs: &[ParagraphLayout], para_index: usize, split: bool) -> Option<Page> {
+        let mut page = self.advance_column(cursor)?;
+        if split {
+            page.continued_on = Some(cursor.page.page_index);
+            cursor.page.continued_from = Some(page.page_index);
+        }
+
+        // A table split by this page break repeats its header rows first
+        if let Some(table) = self.tables.iter().find(|t| t.is_body_row(para_index)) {
+            for header_index in table.header_range() {
+                if let Some(header) = paragraphs.get(header_index) {
+                    self.place_lines(header, header_index, 0..header.lines.len(), cursor);
                 }
-                *current_y = 0.0;
-                *current_x = if *current_column == 0 {
-                    0.0
-                } else {
-                    self.page_config.content_width() - (self.config.columns - *current_column) as f32 * (column_width + self.config.column_gap)
-                };
+            }
+        }
+        Some(page)
+    }
+
+    /// Moves the cursor to the top of the next column, starting a new page
+    /// when the columns are exhausted. Returns the completed page, if any.
+    fn advance_column(&self, cursor: &mut PageCursor) -> Option<Page> {
+        cursor.y = 0.0;
+        cursor.column += 1;
+        if cursor.column < self.page_config.columns {
+            cursor.x = self.column_x(cursor.column);
+            return None;
+        }
+
+        cursor.column = 0;
+        cursor.x = 0.0;
+        cursor.placed = 0;
+        cursor.completed += 1;
+        Some(std::mem::replace(&mut cursor.page, self.blank_page(cursor.completed)))
+    }
+
+    /// Appends a paragraph's `lines` at the cursor without checking for overflow
+    fn place_lines(&self, para: &ParagraphLayout, para_index: usize, lines: Range<usize>, cursor: &mut PageCursor) {
+        let column_width = self.column_width();
+        let line_height = self.actual_line_height();
+
+        for line_idx in lines {
+            cursor.placed += 1;
+            if !cursor.render {
+                cursor.y += line_height;
+                continue;
             }
 
+            let line_info = &para.lines[line_idx];
             let rendered_line = RenderedLine {
-                line_index: page.lines.len(),
-                paragraph_index: para_index,
+                line_index: cursor.page.lines.len(),
+                paragraph_index: Some(para_index),
                 source_line_index: line_idx,
-                y: *current_y,
-                height: line_height_actual,
-                x: *current_x,
+                y: cursor.y,
+                height: line_height,
+                x: cursor.x,
                 width: line_info.width.min(column_width),
                 start: line_info.start,
                 end: line_info.end,
+                text: None,
             };
 
-            page.lines.push(rendered_line);
-            *current_y += line_height_actual;
+            cursor.page.lines.push(rendered_line);
+            cursor.y += line_height;
         }
     }
 
-    /// Applies widow/orphan control to adjust page breaks
-    fn apply_widow_orphan(&mut self, pages: &mut Vec<Page>) {
-        if !self.config.enable_widow_orphan || pages.len() < 2 {
-            return;
+    /// Lays out a header or footer template in a band of `band_height`,
+    /// one rendered line per template line that fits
+    fn layout_band(&self, template: &str, band_height: f32, page_number: usize, page_count: usize) -> Vec<RenderedLine> {
+        let text = template
+            .replace("{page}", &page_number.to_string())
+            .replace("{pages}", &page_count.to_string());
+        let line_height = self.actual_line_height();
+        let width = self.page_config.content_width();
+
+        text.lines()
+            .take(self.lines_in(band_height))
+            .enumerate()
+            .map(|(index, line)| RenderedLine {
+                line_index: index,
+                paragraph_index: None,
+                source_line_index: index,
+                y: index as f32 * line_height,
+                height: line_height,
+                x: 0.0,
+                width,
+                start: 0,
+                end: line.len(),
+                text: Some(line.to_string()),
+            })
+            .collect()
+    }
+
+    /// Fills a completed page's header and footer bands from their templates
+    fn apply_page_bands(&self, page: &mut Page, page_count: usize) {
+        let config = &self.page_config;
+        if let Some(template) = &config.header_template {
+            page.header_lines = self.layout_band(template, config.header_height, page.page_index + 1, page_count);
         }
+        if let Some(template) = &config.footer_template {
+            page.footer_lines = self.layout_band(template, config.footer_height, page.page_index + 1, page_count);
+        }
+    }
 
-        let min_widow = self.config.min_lines_widow as usize;
-        let min_orphan = self.config.min_lines_orphan as usize;
-
-        // Process from first to last page
-        let mut i = 0usize;
-        while i + 1 < pages.len() {
-            // Use indices instead of borrowing twice
-            let last_para_idx = pages[i].lines.last().map(|l| l.paragraph_index);
-            let first_para_idx = pages[i + 1].lines.first().map(|l| l.paragraph_index);
-
-            if last_para_idx == first_para_idx && last_para_idx.is_some() {
-                let para_idx = last_para_idx.unwrap();
-
-                // Count lines of this paragraph on each page
-                let current_para_lines: usize = pages[i].lines
-                    .iter()
-                    .filter(|l| l.paragraph_index == para_idx)
-                    .count();
-
-                let next_para_lines: usize = pages[i + 1].lines
-                    .iter()
-                    .filter(|l| l.paragraph_index == para_idx)
-                    .count();
-
-                // Widow: paragraph's last line(s) on new page alone
-                if next_para_lines <= min_widow && i > 0 {
-                    // Move lines from current page to next page
-                    self.move_lines_to_next_page(pages, i, min_widow);
-                }
-                // Orphan: paragraph's first line(s) on current page alone
-                else if current_para_lines <= min_orphan && i + 1 < pages.len() - 1 {
-                    // Move lines from next page to current page
-                    self.move_lines_from_next_page(pages, i, min_orphan);
-                }
+    /// Column of each of `lines`, in order, when they are restacked into columns
+    /// of at most `cap` lines under the usual widow, orphan and keep-together
+    /// rules. None if they need more columns than the page has.
+    fn column_assignment(&self, lines: &[RenderedLine], paragraphs: &[ParagraphLayout], cap: usize) -> Option<Vec<usize>> {
+        let mut assignment = Vec::with_capacity(lines.len());
+        let mut column = 0;
+        let mut used = 0;
+        while assignment.len() < lines.len() {
+            if column >= self.page_config.columns {
+                return None;
             }
 
-            i += 1;
+            let next = &lines[assignment.len()];
+            let remaining = lines[assignment.len()..]
+                .iter()
+                .take_while(|line| line.paragraph_index == next.paragraph_index)
+                .count();
+            let keep_together = next
+                .paragraph_index
+                .and_then(|index| paragraphs.get(index))
+                .is_some_and(|para| para.properties.keep_together && para.lines.len() <= cap);
+            let take = self.lines_to_place(next.source_line_index, remaining, cap - used, used == 0, keep_together);
+
+            assignment.extend(std::iter::repeat_n(column, take));
+            used += take;
+            if take < remaining {
+                column += 1;
+                used = 0;
+            }
         }
+        Some(assignment)
     }
 
-    /// Moves lines to the next page to fix widow
-    fn move_lines_to_next_page(&mut self, pages: &mut Vec<Page>, page_idx: usize, _min_lines: usize) {
-        if page_idx + 1 >= pages.len() {
+    /// Restacks the final page's lines so its columns end at about the same height
+    ///
+    /// The shortest column cap that still fits every line on the page wins. Blank
+    /// space between lines that share a column is kept; space at a column's top is not.
+    fn balance_columns(&self, page: &mut Page, paragraphs: &[ParagraphLayout]) {
+        let columns = self.page_config.columns;
+        if !self.config.balance_last_page || columns <= 1 || page.lines.is_empty() {
             return;
         }
 
-        let current_page_lines_count = pages[page_idx].lines.len();
-        let next_page_lines_count = pages[page_idx + 1].lines.len();
-
-        if current_page_lines_count == 0 || next_page_lines_count == 0 {
+        let Some(assignment) = (page.lines.len().div_ceil(columns)..=self.lines_per_column())
+            .find_map(|cap| self.column_assignment(&page.lines, paragraphs, cap))
+        else {
             return;
-        }
+        };
 
-        // Find the paragraph boundary in current page
-        if let Some(para_idx) = pages[page_idx].lines.last().map(|l| l.paragraph_index) {
-            // Count lines of this paragraph
-            let para_line_indices: Vec<usize> = pages[page_idx].lines
-                .iter()
-                .enumerate()
-                .filter(|(_, l)| l.paragraph_index == para_idx)
-                .map(|(idx, _)| idx)
-                .collect();
-
-            let para_line_count = para_line_indices.len();
-
-            if para_line_count > 2 {
-                // Move some lines to next page
-                let lines_to_move = para_line_count - 2; // Keep at least 2 lines
-                let move_indices: Vec<usize> = para_line_indices[..lines_to_move].to_vec();
-
-                // Calculate base Y for next page
-                let next_base_y = pages[page_idx + 1].lines
-                    .iter()
-                    .map(|l| l.y + l.height)
-                    .fold(0.0, f32::max);
-
-                // Move lines to next page
-                for (offset, &line_idx) in move_indices.iter().enumerate().rev() {
-                    let line = pages[page_idx].lines.remove(line_idx);
-                    let mut new_line = line;
-                    new_line.y = next_base_y + (offset as f32 * self.actual_line_height());
-                    pages[page_idx + 1].lines.insert(0, new_line);
+        let mut y = 0.0;
+        let mut previous: Option<(usize, f32, f32)> = None;
+        for (line, &column) in page.lines.iter_mut().zip(&assignment) {
+            match previous {
+                Some((prev_column, x, bottom)) if prev_column == column => {
+                    if x == line.x {
+                        y += (line.y - bottom).max(0.0);
+                    }
                 }
-
-                // Update continuation markers
-                pages[page_idx].continued_on = Some(page_idx + 1);
-                pages[page_idx + 1].continued_from = Some(page_idx);
+                _ => y = 0.0,
             }
-        }
-    }
+            previous = Some((column, line.x, line.y + line.height));
 
-    /// Moves lines from next page to current page to fix orphan
-    fn move_lines_from_next_page(&mut self, pages: &mut Vec<Page>, page_idx: usize, _min_lines: usize) {
-        if page_idx + 1 >= pages.len() {
-            return;
+            line.x = self.column_x(column);
+            line.y = y;
+            y += line.height;
         }
+    }
 
-        if pages[page_idx].lines.is_empty() || pages[page_idx + 1].lines.is_empty() {
+    /// Shifts each column's lines on a completed page according to the vertical alignment
+    fn align_page_content(&self, page: &mut Page) {
+        let body_height = self.available_content_height();
+        let align = self.page_config.vertical_align;
+        if align == VerticalAlign::Top {
             return;
         }
 
-        if let Some(para_idx) = pages[page_idx + 1].lines.first().map(|l| l.paragraph_index) {
-            let para_line_indices: Vec<usize> = pages[page_idx + 1].lines
-                .iter()
-                .enumerate()
-                .filter(|(_, l)| l.paragraph_index == para_idx)
-                .map(|(idx, _)| idx)
-                .collect();
-
-            let para_line_count = para_line_indices.len();
-
-            if para_line_count > 2 {
-                // Calculate new Y position for moved lines
-                let last_y = pages[page_idx].lines
-                    .iter()
-                    .map(|l| l.y + l.height)
-                    .fold(0.0, f32::max);
-
-                // Move some lines to current page
-                let lines_to_keep = para_line_count - 2; // Keep at least 2 on next page
-                let move_indices: Vec<usize> = para_line_indices[lines_to_keep..].to_vec();
-
-                for (offset, &line_idx) in move_indices.iter().enumerate() {
-                    let adjusted_idx = line_idx.saturating_sub(offset);
-                    if adjusted_idx < pages[page_idx + 1].lines.len() {
-                        let line = pages[page_idx + 1].lines.remove(adjusted_idx);
-                        let mut new_line = line;
-                        new_line.y = last_y + (offset as f32 * self.actual_line_height());
-                        pages[page_idx].lines.push(new_line);
+        let mut columns: Vec<f32> = page.lines.iter().map(|line| line.x).collect();
+        columns.sort_by(f32::total_cmp);
+        columns.dedup();
+
+        for x in columns {
+            let mut lines: Vec<&mut RenderedLine> = page.lines.iter_mut().filter(|line| line.x == x).collect();
+            let top = lines.iter().map(|line| line.y).fold(f32::INFINITY, f32::min);
+            let bottom = lines.iter().map(|line| line.y + line.height).fold(f32::NEG_INFINITY, f32::max);
+            let free = (body_height - (bottom - top)).max(0.0);
+
+            match align {
+                VerticalAlign::Top => {}
+                VerticalAlign::Center | VerticalAlign::Bottom => {
+                    let target = if align == VerticalAlign::Center { free / 2.0 } else { free };
+                    for line in lines.iter_mut() {
+                        line.y += target - top;
+                    }
+                }
+                VerticalAlign::Justify => {
+                    let gaps = lines.windows(2).filter(|pair| pair[0].paragraph_index != pair[1].paragraph_index).count();
+                    if gaps == 0 {
+                        continue;
+                    }
+                    let extra = (body_height - bottom).max(0.0) / gaps as f32;
+                    let mut shift = 0.0;
+                    let mut paragraph = lines[0].paragraph_index;
+                    for line in lines.iter_mut() {
+                        if line.paragraph_index != paragraph {
+                            paragraph = line.paragraph_index;
+                            shift += extra;
+                        }
+                        line.y += shift;
                     }
                 }
-
-                pages[page_idx].continued_on = Some(page_idx + 1);
-                pages[page_idx + 1].continued_from = Some(page_idx);
             }
         }
     }
@@ -612,68 +884,30 @@ impl PageLayout {
             return Vec::new();
         }
 
-        // First pass: collect paragraph heights
-        let paragraph_heights = self.first_pass_collect(paragraphs);
-
-        // Second pass: layout paragraphs to pages
-        let mut pages = self.second_pass_layout(paragraphs, &paragraph_heights);
-
-        // Third pass: apply widow/orphan control
-        self.apply_widow_orphan(&mut pages);
-
-        // Apply column adjustments
-        self.apply_column_adjustments(&mut pages);
+        let pages: Vec<Page> = PageStream::new(self, paragraphs).collect();
 
         self.pages = pages.clone();
         pages
     }
 
-    /// Adjusts line positions for multi-column layout
-    fn apply_column_adjustments(&self, pages: &mut Vec<Page>) {
-        let column_width = self.column_width();
-        let column_gap = self.config.column_gap;
-
-        for page in pages.iter_mut() {
-            // Group line indices by column
-            let mut column_line_indices: Vec<Vec<usize>> = vec![Vec::new(); self.config.columns as usize];
-
-            for (line_idx, line) in page.lines.iter().enumerate() {
-                // Determine which column this line belongs to based on X position
-                let col_idx = min(
-                    ((line.x + column_width / 2.0) / (column_width + column_gap)).floor() as usize,
-                    self.config.columns as usize - 1,
-                );
-                if col_idx < column_line_indices.len() {
-                    column_line_indices[col_idx].push(line_idx);
-                }
-            }
+    /// Lazily lays out paragraphs, yielding one rendered page at a time.
+    ///
+    /// Produces the same pages as [`PageLayout::layout_pages`], but only the
+    /// page being filled and the pages completed by the current paragraph are
+    /// held in memory. A `{pages}` token in a header or footer template costs
+    /// an extra layout pass to count the pages. The yielded pages are not
+    /// stored in `self.pages`.
+    pub fn pages_iter<'a>(&'a mut self, paragraphs: &'a [ParagraphLayout]) -> impl Iterator<Item = RenderedPage> + 'a {
+        self.paragraph_count = paragraphs.len();
 
-            // Recalculate Y positions for each column independently
-            let available_height = self.available_content_height();
-            for (col_idx, line_indices) in column_line_indices.iter().enumerate() {
-                let mut y_offset = 0.0f32;
-                let base_x = if col_idx == 0 {
-                    0.0
-                } else {
-                    col_idx as f32 * (column_width + column_gap)
-                };
+        let page_width = self.page_config.width;
+        let page_height = self.page_config.height;
 
-                for &line_idx in line_indices {
-                    if line_idx < page.lines.len() {
-                        page.lines[line_idx].x = base_x;
-                        page.lines[line_idx].y = y_offset;
-                        y_offset += page.lines[line_idx].height;
-
-                        // Check if line exceeds page height
-                        if y_offset > available_height {
-                            // This shouldn't happen, but handle gracefully
-                            page.lines[line_idx].height = (available_height - y_offset + page.lines[line_idx].height).max(self.actual_line_height());
-                            y_offset = available_height;
-                        }
-                    }
-                }
-            }
-        }
+        PageStream::new(self, paragraphs).map(move |page| RenderedPage {
+            page_width,
+            page_height,
+            ..RenderedPage::from(page)
+        })
     }
 
     /// Gets the page number for a given character offset
@@ -682,7 +916,7 @@ impl PageLayout {
 
         for (page_idx, page) in self.pages.iter().enumerate() {
             for line in &page.lines {
-                let para = paragraphs.get(line.paragraph_index)?;
+                let para = paragraphs.get(line.paragraph_index?)?;
                 let line_text = para.text.get(line.start..line.end)?;
 
                 if char_count + line_text.len() > offset {
@@ -696,15 +930,251 @@ impl PageLayout {
         None
     }
 
+    /// Maps a click at page coordinates `(x, y)` on page `page_index` to a
+    /// document byte offset, counting one separator byte between paragraphs
+    /// as `LineLayout::layout_document` splits them.
+    ///
+    /// The line whose column and vertical extent lie nearest to the point is
+    /// chosen, so clicks in margins or between lines clamp to the closest
+    /// line; the position within it comes from `line_layout`'s caret stops.
+    /// Returns None for an unknown page, a page without body lines, or a
+    /// line whose paragraph is missing from `paragraphs`.
+    pub fn hit_test(
+        &self,
+        page_index: usize,
+        x: f32,
+        y: f32,
+        paragraphs: &[ParagraphLayout],
+        line_layout: &LineLayout,
+    ) -> Option<usize> {
+        let page = self.pages.get(page_index)?;
+        let x = x - page.content_bounds.x;
+        let y = y - page.content_bounds.y;
+        let column_width = self.column_width();
+
+        let distance = |from: f32, start: f32, end: f32| (start - from).max(from - end).max(0.0);
+        let line = page.lines.iter().min_by(|a, b| {
+            let key = |line: &RenderedLine| {
+                (distance(x, line.x, line.x + column_width), distance(y, line.y, line.y + line.height))
+            };
+            key(a).partial_cmp(&key(b)).unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+
+        let paragraph_index = line.paragraph_index?;
+        let para = paragraphs.get(paragraph_index)?;
+        let line_text = para.text.get(line.start..line.end)?;
+        let indent = para.lines.get(line.source_line_index).map_or(0.0, |info| info.aligned_x);
+        let chars = line_layout.hit_test(line_text, x - line.x - indent);
+        let within = line_text.char_indices().nth(chars).map_or(line_text.len(), |(i, _)| i);
+
+        let para_start: usize = paragraphs[..paragraph_index].iter().map(|p| p.text.len() + 1).sum();
+        Some(para_start + line.start + within)
+    }
+
     /// Gets the total number of pages
     #[inline]
     pub fn page_count(&self) -> usize {
         self.pages.len()
     }
+
+    /// Counts the pages `layout` paginates to without rendering any lines.
+    ///
+    /// Agrees with the number of pages [`PageLayout::layout_pages`] produces
+    /// for the same paragraphs; `self.pages` is left untouched.
+    pub fn count_pages(&mut self, layout: &DocumentLayout) -> usize {
+        self.paragraph_count = layout.paragraphs.len();
+        PageStream::without_page_count(self, &layout.paragraphs, false).count_remaining()
+    }
+
+    /// Re-paginates after an edit to paragraph `start_paragraph` and later
+    /// ones, keeping the pages laid out before it.
+    ///
+    /// Pagination restarts at the top of the page holding the edited
+    /// paragraph (or an earlier one, when that page begins mid-paragraph or
+    /// inside a table), since earlier content can shift onto it. `self.pages`
+    /// must come from a previous layout of the same paragraphs before the
+    /// edit. Returns the index of the first recomputed page.
+    pub fn reflow_from(&mut self, layout: &DocumentLayout, start_paragraph: usize) -> usize {
+        let paragraphs = &layout.paragraphs;
+        self.paragraph_count = paragraphs.len();
+
+        let Some((page_index, para_index)) = self.reflow_start(paragraphs, start_paragraph) else {
+            self.pages = PageStream::new(self, paragraphs).collect();
+            return 0;
+        };
+
+        let mut stream = PageStream::resume(self, paragraphs, page_index, para_index);
+        let tail: Vec<Page> = std::iter::from_fn(|| stream.next_page()).collect();
+
+        let mut pages = std::mem::take(&mut self.pages);
+        pages.truncate(page_index);
+        pages.extend(tail);
+
+        // Header and footer bands of kept pages only change with the page count
+        let page_count = pages.len();
+        let refill = if self.page_config.uses_page_count() { 0 } else { page_index };
+        for page in &mut pages[refill..] {
+            self.apply_page_bands(page, page_count);
+        }
+        self.pages = pages;
+        page_index
+    }
+
+    /// Finds the page and paragraph pagination can restart from to pick up
+    /// changes from `start_paragraph` on, or None to start over
+    fn reflow_start(&self, paragraphs: &[ParagraphLayout], start_paragraph: usize) -> Option<(usize, usize)> {
+        // A keep-with-next chain is placed as a unit, so it is affected as a whole
+        let mut start = start_paragraph.min(paragraphs.len());
+        while start > 0 && paragraphs[start - 1].properties.keep_with_next {
+            start -= 1;
+        }
+
+        // First page with lines from the affected paragraphs, else the last page
+        let affected = self
+            .pages
+            .iter()
+            .position(|page| page.lines.iter().any(|line| line.paragraph_index.is_some_and(|index| index >= start)))
+            .unwrap_or(self.pages.len().saturating_sub(1));
+
+        // Restart at a page opening with the first line of a paragraph outside any table
+        (1..=affected).rev().find_map(|index| {
+            let page = &self.pages[index];
+            let first = page.lines.first()?;
+            let paragraph = first.paragraph_index?;
+            let clean = first.source_line_index == 0
+                && paragraph <= start
+                && page.continued_from.is_none()
+                && !self.tables.iter().any(|table| table.rows.contains(&paragraph));
+            clean.then_some((index, paragraph))
+        })
+    }
+}
+
+/// Working state for the page currently being filled
+#[derive(Debug)]
+struct PageCursor {
+    page: Page,
+    y: f32,
+    column: usize,
+    x: f32,
+    /// Number of pages already completed
+    completed: usize,
+    /// Number of lines placed on the current page
+    placed: usize,
+    /// Whether placed lines are rendered, or only counted
+    render: bool,
+}
+
+/// Pull-based paginator shared by the eager and lazy layout entry points
+struct PageStream<'a> {
+    layout: &'a PageLayout,
+    source: &'a [ParagraphLayout],
+    paragraphs: std::ops::Range<usize>,
+    /// Cursor for the page being filled, `None` once all paragraphs are placed
+    cursor: Option<PageCursor>,
+    /// Completed pages not yet yielded
+    pending: VecDeque<Page>,
+    /// Total page count substituted for `{pages}` in header and footer templates
+    page_count: usize,
+}
+
+impl<'a> PageStream<'a> {
+    fn new(layout: &'a PageLayout, paragraphs: &'a [ParagraphLayout]) -> Self {
+        let mut stream = Self::without_page_count(layout, paragraphs, true);
+        // `{pages}` needs a dry run to learn the total before the first page is yielded
+        if layout.page_config.uses_page_count() {
+            stream.page_count = Self::without_page_count(layout, paragraphs, false).count_remaining();
+        }
+        stream
+    }
+
+    /// Creates a stream that leaves header and footer templates unexpanded.
+    /// With `render` unset, pages are laid out without their lines.
+    fn without_page_count(layout: &'a PageLayout, paragraphs: &'a [ParagraphLayout], render: bool) -> Self {
+        PageStream {
+            layout,
+            source: paragraphs,
+            paragraphs: 0..paragraphs.len(),
+            cursor: Some(PageCursor {
+                page: layout.blank_page(0),
+                y: 0.0,
+                column: 0,
+                x: 0.0,
+                completed: 0,
+                placed: 0,
+                render,
+            }),
+            pending: VecDeque::new(),
+            page_count: 0,
+        }
+    }
+
+    /// Creates a stream that picks up at the top of page `page_index` with
+    /// paragraph `para_index`, leaving header and footer templates unexpanded
+    fn resume(layout: &'a PageLayout, paragraphs: &'a [ParagraphLayout], page_index: usize, para_index: usize) -> Self {
+        let mut stream = Self::without_page_count(layout, paragraphs, true);
+        stream.paragraphs = para_index..paragraphs.len();
+        if let Some(cursor) = stream.cursor.as_mut() {
+            cursor.page = layout.blank_page(page_index);
+            cursor.completed = page_index;
+        }
+        stream
+    }
+
+    /// Yields the next completed page, aligned but before its header and
+    /// footer are filled
+    fn next_page(&mut self) -> Option<Page> {
+        self.fill();
+
+        let mut page = self.pending.pop_front()?;
+        self.layout.align_page_content(&mut page);
+        Some(page)
+    }
+
+    /// Counts the pages still to come
+    fn count_remaining(&mut self) -> usize {
+        std::iter::from_fn(|| self.next_page()).count()
+    }
+
+    /// Lays out paragraphs until a page is completed or the input is exhausted
+    fn fill(&mut self) {
+        while self.pending.is_empty() {
+            let cursor = match self.cursor.as_mut() {
+                Some(cursor) => cursor,
+                None => return,
+            };
+
+            match self.paragraphs.next() {
+                Some(idx) => {
+                    let pages = self.layout.place_paragraph(cursor, self.source, idx);
+                    self.pending.extend(pages);
+                }
+                None => {
+                    // Add the last page if it has content
+                    if let Some(mut cursor) = self.cursor.take() {
+                        if cursor.placed > 0 {
+                            self.layout.balance_columns(&mut cursor.page, self.source);
+                            self.pending.push_back(cursor.page);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for PageStream<'_> {
+    type Item = Page;
+
+    fn next(&mut self) -> Option<Page> {
+        let mut page = self.next_page()?;
+        self.layout.apply_page_bands(&mut page, self.page_count);
+        Some(page)
+    }
 }
 
 /// Rendered page for external consumption (e.g., Flutter rendering)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RenderedPage {
     /// Page index
     pub page_index: usize,
@@ -719,6 +1189,12 @@ pub struct RenderedPage {
     /// Page dimensions
     pub page_width: f32,
     pub page_height: f32,
+    /// Paragraphs starting on this page whose keep constraints could not be honored
+    pub keep_violations: Vec<usize>,
+    /// Header lines, positioned relative to `header_region`
+    pub header_lines: Vec<RenderedLine>,
+    /// Footer lines, positioned relative to `footer_region`
+    pub footer_lines: Vec<RenderedLine>,
 }
 
 impl From<Page> for RenderedPage {
@@ -731,14 +1207,127 @@ impl From<Page> for RenderedPage {
             footer_region: page.footer_region,
             page_width: 0.0, // Will be set by caller
             page_height: 0.0,
+            keep_violations: page.keep_violations,
+            header_lines: page.header_lines,
+            footer_lines: page.footer_lines,
         }
     }
 }
 
+impl RenderedPage {
+    /// Renders the page as a standalone SVG document, for previews and tests.
+    ///
+    /// Body lines take their text from `paragraphs`, while header and footer
+    /// lines carry their own. `spans` optionally holds each paragraph's
+    /// formatting as sorted, paragraph-local byte ranges; formatted parts of a
+    /// line become `<tspan>`s placed at `shaper`'s measured advances.
+    pub fn to_svg(
+        &self,
+        paragraphs: &[ParagraphLayout],
+        spans: &[Vec<(Range<usize>, TextAttributes)>],
+        shaper: &TextShaper,
+    ) -> String {
+        let (width, height) = (self.page_width, self.page_height);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-size="{}">"#,
+            shaper.font_size()
+        );
+        let _ = writeln!(svg, r#"<rect width="{width}" height="{height}" fill="white"/>"#);
+
+        let ascent = shaper.metrics().ascent;
+        let bands = [
+            (Some(self.content_bounds), &self.lines),
+            (self.header_region, &self.header_lines),
+            (self.footer_region, &self.footer_lines),
+        ];
+        for (region, lines) in bands {
+            let Some(region) = region else { continue };
+            for line in lines {
+                let paragraph = line.paragraph_index.and_then(|index| {
+                    Some((paragraphs.get(index)?, spans.get(index).map_or(&[][..], Vec::as_slice)))
+                });
+                let (text, line_spans) = match (&line.text, paragraph) {
+                    (Some(text), _) => (text.as_str(), &[][..]),
+                    (None, Some((para, para_spans))) => (para.text.as_str(), para_spans),
+                    (None, None) => continue,
+                };
+                let x = region.x + line.x;
+                let y = region.y + line.y + ascent.min(line.height);
+                let _ = write!(svg, r#"<text x="{x}" y="{y}">"#);
+                for (range, attributes) in line_segments(line.start..line.end, line_spans) {
+                    let Some(segment) = text.get(range.clone()) else { continue };
+                    let segment = segment.trim_end_matches(['\n', '\r']);
+                    if segment.is_empty() {
+                        continue;
+                    }
+                    match attributes {
+                        Some(attributes) => {
+                            let offset = text.get(line.start..range.start).map_or(0.0, |prefix| shaper.measure_width(prefix));
+                            let _ = write!(svg, r#"<tspan x="{}"{}>{}</tspan>"#, x + offset, svg_style(attributes), escape_svg_text(segment));
+                        }
+                        None => svg.push_str(&escape_svg_text(segment)),
+                    }
+                }
+                svg.push_str("</text>\n");
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Splits `range` at the edges of `spans`, pairing each part with the
+/// attributes of the span covering it
+fn line_segments(
+    range: Range<usize>,
+    spans: &[(Range<usize>, TextAttributes)],
+) -> Vec<(Range<usize>, Option<&TextAttributes>)> {
+    let mut segments = Vec::new();
+    let mut pos = range.start;
+    for (span, attributes) in spans {
+        if span.end <= pos || span.start >= range.end {
+            continue;
+        }
+        if span.start > pos {
+            segments.push((pos..span.start, None));
+        }
+        let end = span.end.min(range.end);
+        segments.push((pos.max(span.start)..end, Some(attributes)));
+        pos = end;
+    }
+    if pos < range.end {
+        segments.push((pos..range.end, None));
+    }
+    segments
+}
+
+/// SVG presentation attributes for run formatting, each with a leading space
+fn svg_style(attributes: &TextAttributes) -> String {
+    let mut style = String::new();
+    if attributes.bold == Some(true) {
+        style.push_str(r#" font-weight="bold""#);
+    }
+    if attributes.italic == Some(true) {
+        style.push_str(r#" font-style="italic""#);
+    }
+    if attributes.underline == Some(true) {
+        style.push_str(r#" text-decoration="underline""#);
+    }
+    style
+}
+
+/// Escapes text for use as SVG character data
+fn escape_svg_text(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::line_layout::{LineLayoutInfo, LineLayout, ParagraphLayout, ParagraphProperties, LineSpacingRule, Alignment};
+    use crate::line_layout::{DocumentLayout, LineLayoutInfo, LineLayout, ParagraphLayout, ParagraphProperties, LineSpacingRule, Alignment};
 
     fn create_test_paragraphs() -> Vec<ParagraphLayout> {
         // Create test paragraphs without using LineLayout (to avoid HarfBuzz issues)
@@ -748,67 +1337,77 @@ mod tests {
                 max_width: 400.0,
                 content_width: 400.0,
                 lines: vec![
-                    LineLayoutInfo { line_number: 0, start: 0, end: 70, width: 350.0, break_type: "SoftBreak".to_string(), char_count: 70, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
+                    LineLayoutInfo { line_number: 0, start: 0, end: 70, width: 350.0, break_type: "SoftBreak".to_string(), char_count: 70, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
                 ],
                 total_height: 14.4,
                 base_line_height: 12.0,
                 actual_line_height: 14.4,
                 has_bidi: false,
                 properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
             },
             ParagraphLayout {
                 text: "Second paragraph here. This is used to verify that multiple paragraphs are handled correctly.".to_string(),
                 max_width: 400.0,
                 content_width: 400.0,
                 lines: vec![
-                    LineLayoutInfo { line_number: 0, start: 0, end: 95, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 95, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
+                    LineLayoutInfo { line_number: 0, start: 0, end: 95, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 95, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
                 ],
                 total_height: 14.4,
                 base_line_height: 12.0,
                 actual_line_height: 14.4,
                 has_bidi: false,
                 properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
             },
             ParagraphLayout {
                 text: "Third paragraph with some longer content that might span multiple lines when rendered.".to_string(),
                 max_width: 400.0,
                 content_width: 400.0,
                 lines: vec![
-                    LineLayoutInfo { line_number: 0, start: 0, end: 100, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 100, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
-                    LineLayoutInfo { line_number: 1, start: 100, end: 110, width: 50.0, break_type: "SoftBreak".to_string(), char_count: 10, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
+                    LineLayoutInfo { line_number: 0, start: 0, end: 100, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 100, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
+                    LineLayoutInfo { line_number: 1, start: 100, end: 110, width: 50.0, break_type: "SoftBreak".to_string(), char_count: 10, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
                 ],
                 total_height: 28.8,
                 base_line_height: 12.0,
                 actual_line_height: 14.4,
                 has_bidi: false,
                 properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
             },
             ParagraphLayout {
                 text: "Fourth short paragraph.".to_string(),
                 max_width: 400.0,
                 content_width: 400.0,
                 lines: vec![
-                    LineLayoutInfo { line_number: 0, start: 0, end: 25, width: 125.0, break_type: "SoftBreak".to_string(), char_count: 25, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
+                    LineLayoutInfo { line_number: 0, start: 0, end: 25, width: 125.0, break_type: "SoftBreak".to_string(), char_count: 25, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
                 ],
                 total_height: 14.4,
                 base_line_height: 12.0,
                 actual_line_height: 14.4,
                 has_bidi: false,
                 properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
             },
             ParagraphLayout {
                 text: "Fifth paragraph with even more content to test pagination behavior across multiple pages. This paragraph should be long enough to potentially span page boundaries.".to_string(),
                 max_width: 400.0,
                 content_width: 400.0,
                 lines: vec![
-                    LineLayoutInfo { line_number: 0, start: 0, end: 100, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 100, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
-                    LineLayoutInfo { line_number: 1, start: 100, end: 140, width: 200.0, break_type: "SoftBreak".to_string(), char_count: 40, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4 },
+                    LineLayoutInfo { line_number: 0, start: 0, end: 100, width: 400.0, break_type: "SoftBreak".to_string(), char_count: 100, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
+                    LineLayoutInfo { line_number: 1, start: 100, end: 140, width: 200.0, break_type: "SoftBreak".to_string(), char_count: 40, is_bidi: false, trailing_whitespace: 0.0, offset_x: 0.0, line_height: 14.4, aligned_x: 0.0, word_spacing: 0.0, tab_advances: Vec::new(), show_hyphen: false },
                 ],
                 total_height: 28.8,
                 base_line_height: 12.0,
                 actual_line_height: 14.4,
                 has_bidi: false,
                 properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
             },
         ]
     }
@@ -836,6 +1435,10 @@ mod tests {
                 trailing_whitespace: 0.0,
                 offset_x: 0.0,
                 line_height: 12.0,
+                aligned_x: 0.0,
+                word_spacing: 0.0,
+                tab_advances: Vec::new(),
+                show_hyphen: false,
             });
         }
 
@@ -849,6 +1452,8 @@ mod tests {
             actual_line_height: 12.0,
             has_bidi: false,
             properties: ParagraphProperties::default(),
+            word_count: 0,
+            char_count: 0,
         }
     }
 
@@ -875,6 +1480,28 @@ mod tests {
         assert_eq!(config.content_height(), expected);
     }
 
+    #[test]
+    fn test_page_config_from_section() {
+        let config = PageConfig::from(&SectionProperties::default());
+        assert_eq!((config.width, config.height), (612.0, 792.0));
+        assert_eq!(config.margin_left, 72.0);
+        assert_eq!(config.content_width(), 468.0);
+
+        let landscape = SectionProperties {
+            page_width: 16838,
+            page_height: 11906,
+            margin_top: -720,
+            columns: 2,
+            column_gap: 360,
+            ..SectionProperties::default()
+        };
+        let config = PageConfig::from(&landscape);
+        assert_eq!(config.width, 841.9);
+        assert_eq!(config.margin_top, 36.0);
+        assert_eq!(config.columns, 2);
+        assert_eq!(config.column_gap, 18.0);
+    }
+
     #[test]
     fn test_rect_operations() {
         let rect = Rect::new(10.0, 20.0, 100.0, 50.0);
@@ -886,6 +1513,67 @@ mod tests {
         assert!(empty.is_empty());
     }
 
+    #[test]
+    fn test_rect_overlapping() {
+        let a = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let b = Rect::new(50.0, 25.0, 100.0, 50.0);
+
+        assert!(a.intersects(&b));
+        assert!(b.intersects(&a));
+        assert_eq!(a.intersection(&b), Some(Rect::new(50.0, 25.0, 50.0, 25.0)));
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 150.0, 75.0));
+
+        // Containment is a special case of overlap
+        let inner = Rect::new(10.0, 10.0, 5.0, 5.0);
+        assert_eq!(a.intersection(&inner), Some(inner));
+        assert_eq!(a.union(&inner), a);
+    }
+
+    #[test]
+    fn test_rect_touching_edges() {
+        let a = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let right = Rect::new(100.0, 0.0, 20.0, 50.0);
+        let below = Rect::new(0.0, 50.0, 100.0, 20.0);
+
+        assert!(!a.intersects(&right));
+        assert!(!a.intersects(&below));
+        assert_eq!(a.intersection(&right), None);
+        assert_eq!(a.union(&right), Rect::new(0.0, 0.0, 120.0, 50.0));
+        assert_eq!(a.union(&below), Rect::new(0.0, 0.0, 100.0, 70.0));
+
+        // Left/top edges are inside, right/bottom edges belong to the neighbor
+        assert!(a.contains_point(0.0, 0.0));
+        assert!(!a.contains_point(100.0, 10.0));
+        assert!(!a.contains_point(10.0, 50.0));
+        assert!(right.contains_point(100.0, 10.0));
+    }
+
+    #[test]
+    fn test_rect_disjoint() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(30.0, 40.0, 10.0, 10.0);
+
+        assert!(!a.intersects(&b));
+        assert_eq!(a.intersection(&b), None);
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 40.0, 50.0));
+        assert!(a.contains_point(5.0, 5.0));
+        assert!(!a.contains_point(35.0, 45.0));
+    }
+
+    #[test]
+    fn test_rect_zero_area() {
+        let a = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let line = Rect::new(10.0, 10.0, 50.0, 0.0);
+        let point = Rect::new(20.0, 20.0, 0.0, 0.0);
+
+        assert!(!a.intersects(&line));
+        assert!(!line.intersects(&a));
+        assert_eq!(a.intersection(&point), None);
+        assert!(!point.contains_point(20.0, 20.0));
+        assert_eq!(a.union(&line), a);
+        assert_eq!(point.union(&line), point);
+    }
+
     #[test]
     fn test_single_page_layout() {
         let mut page_layout = PageLayout::new();
@@ -900,7 +1588,7 @@ mod tests {
         // All paragraphs should be on the page
         let page_para_indices: Vec<usize> = pages[0].lines
             .iter()
-            .map(|l| l.paragraph_index)
+            .filter_map(|l| l.paragraph_index)
             .collect();
         let unique_para_indices: std::collections::HashSet<usize> = page_para_indices.into_iter().collect();
         assert_eq!(unique_para_indices.len(), paragraphs.len());
@@ -920,6 +1608,11 @@ mod tests {
             margin_right: 2.0,
             header_height: 0.0,
             footer_height: 0.0,
+            header_template: None,
+            footer_template: None,
+            columns: 1,
+            column_gap: 24.0,
+            vertical_align: VerticalAlign::Top,
         };
 
         page_layout.page_config = config;
@@ -940,12 +1633,18 @@ mod tests {
                 trailing_whitespace: 0.0,
                 offset_x: 0.0,
                 line_height: 15.0,  // 15pt line
+                aligned_x: 0.0,
+                word_spacing: 0.0,
+                tab_advances: Vec::new(),
+                show_hyphen: false,
             }],
             total_height: 15.0,
             base_line_height: 15.0,
             actual_line_height: 15.0,
             has_bidi: false,
             properties: ParagraphProperties::default(),
+            word_count: 0,
+            char_count: 0,
         };
 
         let para2 = ParagraphLayout {
@@ -963,12 +1662,18 @@ mod tests {
                 trailing_whitespace: 0.0,
                 offset_x: 0.0,
                 line_height: 15.0,
+                aligned_x: 0.0,
+                word_spacing: 0.0,
+                tab_advances: Vec::new(),
+                show_hyphen: false,
             }],
             total_height: 15.0,
             base_line_height: 15.0,
             actual_line_height: 15.0,
             has_bidi: false,
             properties: ParagraphProperties::default(),
+            word_count: 0,
+            char_count: 0,
         };
 
         let para3 = ParagraphLayout {
@@ -986,12 +1691,18 @@ mod tests {
                 trailing_whitespace: 0.0,
                 offset_x: 0.0,
                 line_height: 15.0,
+                aligned_x: 0.0,
+                word_spacing: 0.0,
+                tab_advances: Vec::new(),
+                show_hyphen: false,
             }],
             total_height: 15.0,
             base_line_height: 15.0,
             actual_line_height: 15.0,
             has_bidi: false,
             properties: ParagraphProperties::default(),
+            word_count: 0,
+            char_count: 0,
         };
 
         let paragraphs = vec![para1, para2, para3];
@@ -1029,8 +1740,8 @@ mod tests {
     fn test_widow_orphan_control() {
         let mut page_layout = PageLayout::new();
         page_layout.config.enable_widow_orphan = true;
-        page_layout.config.min_lines_widow = 2;
-        page_layout.config.min_lines_orphan = 2;
+        page_layout.config.widow_lines = 2;
+        page_layout.config.orphan_lines = 2;
 
         let paragraphs = create_test_paragraphs();
         let pages = page_layout.layout_pages(&paragraphs);
@@ -1137,6 +1848,10 @@ mod tests {
                     trailing_whitespace: 0.0,
                     offset_x: 0.0,
                     line_height: 14.4,
+                    aligned_x: 0.0,
+                    word_spacing: 0.0,
+                    tab_advances: Vec::new(),
+                    show_hyphen: false,
                 },
             ],
             total_height: 14.4, // 1 line * 1.2 * 12.0 font_size
@@ -1144,6 +1859,8 @@ mod tests {
             actual_line_height: 14.4,
             has_bidi: false,
             properties: crate::line_layout::ParagraphProperties::default(),
+            word_count: 0,
+            char_count: 0,
         };
 
         let height = page_layout.calculate_paragraph_height(&para);
@@ -1228,4 +1945,739 @@ mod tests {
         assert_eq!(page_layout.page_count(), 0);
         assert_eq!(page_layout.paragraph_count, 0);
     }
+
+    fn create_paginated_layout() -> (PageLayout, Vec<ParagraphLayout>) {
+        let config = PageConfig {
+            width: 200.0,
+            height: 120.0,
+            margin_top: 10.0,
+            margin_bottom: 10.0,
+            margin_left: 10.0,
+            margin_right: 10.0,
+            header_height: 0.0,
+            footer_height: 0.0,
+            header_template: None,
+            footer_template: None,
+            columns: 1,
+            column_gap: 24.0,
+            vertical_align: VerticalAlign::Top,
+        };
+        let mut paragraphs = create_test_paragraphs();
+        paragraphs.push(create_long_paragraph());
+        paragraphs.extend(create_test_paragraphs());
+
+        (PageLayout::with_page_config(config), paragraphs)
+    }
+
+    #[test]
+    fn test_pages_iter_matches_layout_pages() {
+        let (mut page_layout, paragraphs) = create_paginated_layout();
+
+        let eager: Vec<RenderedPage> = page_layout
+            .layout_pages(&paragraphs)
+            .into_iter()
+            .map(|page| RenderedPage {
+                page_width: 200.0,
+                page_height: 120.0,
+                ..page.into()
+            })
+            .collect();
+        assert!(eager.len() > 2, "Expected several pages, got {}", eager.len());
+
+        let lazy: Vec<RenderedPage> = page_layout.pages_iter(&paragraphs).collect();
+        assert_eq!(lazy, eager);
+    }
+
+    #[test]
+    fn test_pages_iter_partial_consumption() {
+        let (mut page_layout, paragraphs) = create_paginated_layout();
+        let eager = page_layout.layout_pages(&paragraphs);
+
+        let first_two: Vec<RenderedPage> = page_layout.pages_iter(&paragraphs).take(2).collect();
+        assert_eq!(first_two.len(), 2);
+        for (rendered, page) in first_two.iter().zip(eager.iter()) {
+            assert_eq!(rendered.page_index, page.page_index);
+            assert_eq!(rendered.lines, page.lines);
+        }
+
+        let mut iter = page_layout.pages_iter(&paragraphs);
+        assert_eq!(iter.next().map(|p| p.page_index), Some(0));
+        assert_eq!(iter.next().map(|p| p.page_index), Some(1));
+    }
+
+    #[test]
+    fn test_pages_iter_empty() {
+        let mut page_layout = PageLayout::new();
+        assert_eq!(page_layout.pages_iter(&[]).count(), 0);
+    }
+
+    fn create_table_rows(count: usize) -> Vec<ParagraphLayout> {
+        (0..count)
+            .map(|i| {
+                let text = format!("Row {}", i);
+                ParagraphLayout {
+                    lines: vec![LineLayoutInfo {
+                        line_number: 0,
+                        start: 0,
+                        end: text.len(),
+                        width: 100.0,
+                        break_type: "HardBreak".to_string(),
+                        char_count: text.len(),
+                        is_bidi: false,
+                        trailing_whitespace: 0.0,
+                        offset_x: 0.0,
+                        line_height: 14.4,
+                        aligned_x: 0.0,
+                        word_spacing: 0.0,
+                        tab_advances: Vec::new(),
+                        show_hyphen: false,
+                    }],
+                    text,
+                    max_width: 400.0,
+                    content_width: 400.0,
+                    total_height: 14.4,
+                    base_line_height: 14.4,
+                    actual_line_height: 14.4,
+                    has_bidi: false,
+                    properties: ParagraphProperties::default(),
+                    word_count: 0,
+                    char_count: 0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_table_header_rows_repeat() {
+        // 2 header rows + 60 body rows; 48 lines fit on an A4 page
+        let rows = create_table_rows(62);
+        let mut layout = PageLayout::new();
+        layout.set_widow_orphan(false);
+        layout.add_table(0..rows.len(), 2);
+
+        let pages = layout.layout_pages(&rows);
+        assert_eq!(pages.len(), 2);
+
+        for page in &pages {
+            assert_eq!(page.lines[0].paragraph_index, Some(0));
+            assert_eq!(page.lines[1].paragraph_index, Some(1));
+            assert_eq!(page.lines[0].y, 0.0);
+        }
+
+        // Every body row is emitted exactly once
+        for body_index in 2..rows.len() {
+            let occurrences = pages
+                .iter()
+                .flat_map(|p| &p.lines)
+                .filter(|l| l.paragraph_index == Some(body_index))
+                .count();
+            assert_eq!(occurrences, 1, "row {} emitted {} times", body_index, occurrences);
+        }
+    }
+
+    #[test]
+    fn test_table_without_header_rows() {
+        let rows = create_table_rows(62);
+        let mut layout = PageLayout::new();
+        layout.set_widow_orphan(false);
+        layout.add_table(0..rows.len(), 0);
+
+        let pages = layout.layout_pages(&rows);
+        let total_lines: usize = pages.iter().map(|p| p.lines.len()).sum();
+        assert_eq!(total_lines, rows.len());
+        assert_ne!(pages[1].lines[0].paragraph_index, Some(0));
+    }
+
+    /// Paragraphs of the given line counts, 14.4pt per line
+    fn create_paragraphs_with_lines(line_counts: &[usize]) -> Vec<ParagraphLayout> {
+        line_counts
+            .iter()
+            .map(|&count| ParagraphLayout {
+                text: "line ".repeat(count),
+                max_width: 400.0,
+                content_width: 400.0,
+                lines: (0..count)
+                    .map(|i| LineLayoutInfo {
+                        line_number: i,
+                        start: i * 5,
+                        end: i * 5 + 5,
+                        width: 100.0,
+                        break_type: "SoftBreak".to_string(),
+                        char_count: 5,
+                        is_bidi: false,
+                        trailing_whitespace: 0.0,
+                        offset_x: 0.0,
+                        line_height: 14.4,
+                        aligned_x: 0.0,
+                        word_spacing: 0.0,
+                        tab_advances: Vec::new(),
+                        show_hyphen: false,
+                    })
+                    .collect(),
+                total_height: count as f32 * 14.4,
+                base_line_height: 12.0,
+                actual_line_height: 14.4,
+                has_bidi: false,
+                properties: ParagraphProperties::default(),
+                word_count: 0,
+                char_count: 0,
+            })
+            .collect()
+    }
+
+    /// A layout whose pages hold exactly five 14.4pt lines
+    fn create_five_line_layout() -> PageLayout {
+        PageLayout::with_page_config(PageConfig {
+            width: 500.0,
+            height: 72.0,
+            margin_top: 0.0,
+            margin_bottom: 0.0,
+            margin_left: 0.0,
+            margin_right: 0.0,
+            header_height: 0.0,
+            footer_height: 0.0,
+            header_template: None,
+            footer_template: None,
+            columns: 1,
+            column_gap: 24.0,
+            vertical_align: VerticalAlign::Top,
+        })
+    }
+
+    fn lines_of(page: &Page, para_index: usize) -> Vec<usize> {
+        page.lines
+            .iter()
+            .filter(|l| l.paragraph_index == Some(para_index))
+            .map(|l| l.source_line_index)
+            .collect()
+    }
+
+    #[test]
+    fn test_orphan_moves_short_paragraph_whole() {
+        // One line of room left for a 3-line paragraph
+        let mut layout = create_five_line_layout();
+        layout.config.orphan_lines = 2;
+        let paragraphs = create_paragraphs_with_lines(&[4, 3]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(pages.len(), 2);
+        assert!(lines_of(&pages[0], 1).is_empty());
+        assert_eq!(lines_of(&pages[1], 1), vec![0, 1, 2]);
+        assert_eq!(pages[1].lines[0].y, 0.0);
+        assert_eq!(pages[0].continued_on, None);
+    }
+
+    #[test]
+    fn test_paragraph_too_short_for_both_stays_whole() {
+        // Two lines of room: splitting would leave a single widow
+        let mut layout = create_five_line_layout();
+        let paragraphs = create_paragraphs_with_lines(&[3, 3]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(lines_of(&pages[0], 1), Vec::<usize>::new());
+        assert_eq!(lines_of(&pages[1], 1), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_widow_pushes_lines_to_next_page() {
+        // Three lines of room for a 4-line paragraph: 2 + 2 instead of 3 + 1
+        let mut layout = create_five_line_layout();
+        let paragraphs = create_paragraphs_with_lines(&[2, 4]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(lines_of(&pages[0], 1), vec![0, 1]);
+        assert_eq!(lines_of(&pages[1], 1), vec![2, 3]);
+        assert_eq!(pages[0].continued_on, Some(1));
+        assert_eq!(pages[1].continued_from, Some(0));
+
+        layout.set_widow_orphan(false);
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(lines_of(&pages[0], 1), vec![0, 1, 2]);
+        assert_eq!(lines_of(&pages[1], 1), vec![3]);
+    }
+
+    #[test]
+    fn test_long_paragraph_spans_pages_without_losing_lines() {
+        let mut layout = create_five_line_layout();
+        let paragraphs = create_paragraphs_with_lines(&[12]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        let placed: Vec<usize> = pages.iter().flat_map(|p| lines_of(p, 0)).collect();
+        assert_eq!(placed, (0..12).collect::<Vec<_>>());
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|p| p.lines.len() >= 2));
+    }
+
+    #[test]
+    fn test_heading_kept_with_next_paragraph() {
+        // A 1-line heading fits at the bottom of page 0, but its body would not start there
+        let mut layout = create_five_line_layout();
+        let mut paragraphs = create_paragraphs_with_lines(&[3, 1, 4]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(lines_of(&pages[0], 1), vec![0]);
+
+        paragraphs[1].properties = ParagraphProperties::with_keep(true, false);
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(pages.len(), 2);
+        assert!(lines_of(&pages[0], 1).is_empty());
+        assert_eq!(pages[1].lines[0].paragraph_index, Some(1));
+        assert_eq!(pages[1].lines[0].y, 0.0);
+        assert_eq!(lines_of(&pages[1], 2), vec![0, 1, 2, 3]);
+        assert!(pages.iter().all(|p| p.keep_violations.is_empty()));
+    }
+
+    #[test]
+    fn test_heading_stays_when_next_paragraph_starts_on_page() {
+        // Three lines of room: the heading and the body's first two lines stay on page 0
+        let mut layout = create_five_line_layout();
+        let mut paragraphs = create_paragraphs_with_lines(&[2, 1, 4]);
+        paragraphs[1].properties = ParagraphProperties::with_keep(true, false);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(lines_of(&pages[0], 1), vec![0]);
+        assert_eq!(lines_of(&pages[0], 2), vec![0, 1]);
+        assert_eq!(lines_of(&pages[1], 2), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_keep_together_moves_paragraph_whole() {
+        let mut layout = create_five_line_layout();
+        let mut paragraphs = create_paragraphs_with_lines(&[2, 4]);
+        paragraphs[1].properties = ParagraphProperties::with_keep(false, true);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert!(lines_of(&pages[0], 1).is_empty());
+        assert_eq!(lines_of(&pages[1], 1), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_unsatisfiable_keeps_fall_back_and_are_flagged() {
+        let mut layout = create_five_line_layout();
+
+        // Taller than a page: split anyway
+        let mut paragraphs = create_paragraphs_with_lines(&[1, 8]);
+        paragraphs[1].properties = ParagraphProperties::with_keep(false, true);
+        let pages = layout.layout_pages(&paragraphs);
+        let placed: Vec<usize> = pages.iter().flat_map(|p| lines_of(p, 1)).collect();
+        assert_eq!(placed, (0..8).collect::<Vec<_>>());
+        assert_eq!(pages[0].keep_violations, vec![1]);
+
+        // A keep-with-next chain longer than a page
+        let mut paragraphs = create_paragraphs_with_lines(&[3, 3, 2]);
+        paragraphs[0].properties = ParagraphProperties::with_keep(true, false);
+        paragraphs[1].properties = ParagraphProperties::with_keep(true, false);
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(pages[0].keep_violations, vec![0]);
+        let total_lines: usize = pages.iter().map(|p| p.lines.len()).sum();
+        assert_eq!(total_lines, 8);
+    }
+
+    #[test]
+    fn test_header_footer_page_numbers() {
+        let mut layout = create_five_line_layout();
+        layout.page_config.height = 112.0;
+        layout.page_config.header_height = 20.0;
+        layout.page_config.footer_height = 20.0;
+        layout.page_config.header_template = Some("Page {page} of {pages}".to_string());
+        layout.page_config.footer_template = Some("{page} / {pages}".to_string());
+        let paragraphs = create_paragraphs_with_lines(&[5, 5, 5]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(pages.len(), 3);
+
+        let page = &pages[1];
+        let header = page.header_region.unwrap();
+        let footer = page.footer_region.unwrap();
+        assert_eq!(page.header_lines.len(), 1);
+        assert_eq!(page.header_lines[0].text.as_deref(), Some("Page 2 of 3"));
+        assert_eq!(page.footer_lines[0].text.as_deref(), Some("2 / 3"));
+        assert_eq!(page.header_lines[0].paragraph_index, None);
+        assert_eq!(page.footer_lines[0].paragraph_index, None);
+        assert!(header.y + page.header_lines[0].y + page.header_lines[0].height <= header.bottom());
+        assert!(footer.y + page.footer_lines[0].y + page.footer_lines[0].height <= footer.bottom());
+
+        // The body sits between the bands
+        assert_eq!(page.content_bounds, Rect::new(0.0, 20.0, 500.0, 72.0));
+        assert!(page.content_bounds.y >= header.bottom());
+        assert!(page.content_bounds.bottom() <= footer.y);
+        assert_eq!(page.lines.len(), 5);
+    }
+
+    #[test]
+    fn test_pages_iter_counts_pages_for_templates() {
+        let mut layout = create_five_line_layout();
+        layout.page_config.height = 92.0;
+        layout.page_config.footer_height = 20.0;
+        layout.page_config.footer_template = Some("{page} of {pages}".to_string());
+        let paragraphs = create_paragraphs_with_lines(&[4, 4, 4]);
+
+        let eager: Vec<RenderedPage> = layout.layout_pages(&paragraphs).into_iter().map(RenderedPage::from).collect();
+        let lazy: Vec<RenderedPage> = layout
+            .pages_iter(&paragraphs)
+            .map(|page| RenderedPage { page_width: 0.0, page_height: 0.0, ..page })
+            .collect();
+        assert_eq!(lazy, eager);
+
+        let footers: Vec<_> = lazy.iter().map(|p| p.footer_lines[0].text.clone().unwrap()).collect();
+        assert_eq!(footers, vec!["1 of 3", "2 of 3", "3 of 3"]);
+        assert!(lazy.iter().all(|p| p.header_lines.is_empty()));
+    }
+
+    #[test]
+    fn test_columns_fill_before_next_page() {
+        let mut layout = create_five_line_layout();
+        layout.page_config.columns = 2;
+        layout.page_config.column_gap = 20.0;
+        let paragraphs = create_paragraphs_with_lines(&[1; 12]);
+
+        let pages = layout.layout_pages(&paragraphs);
+        assert_eq!(pages.len(), 2);
+
+        // Paragraphs 0-4 run down column 0, 5-9 down column 1, the rest on page 1
+        let config = &layout.page_config;
+        for (column, range) in [(0, 0..5), (1, 5..10)] {
+            let region = config.column_region(column);
+            for (row, para_index) in range.enumerate() {
+                let line = &pages[0].lines[para_index];
+                assert_eq!(line.paragraph_index, Some(para_index));
+                assert_eq!(config.margin_left + line.x, region.x);
+                assert!((line.y - row as f32 * 14.4).abs() < 1e-3);
+                assert!(line.width <= region.width);
+            }
+        }
+        assert_eq!(pages[1].lines.iter().map(|l| l.paragraph_index).collect::<Vec<_>>(), vec![Some(10), Some(11)]);
+        assert!(pages[1].lines.iter().all(|l| l.x == 0.0));
+    }
+
+    #[test]
+    fn test_column_regions() {
+        let config = PageConfig {
+            width: 500.0,
+            margin_left: 20.0,
+            margin_right: 20.0,
+            header_height: 10.0,
+            columns: 2,
+            column_gap: 20.0,
+            ..Default::default()
+        };
+
+        assert_eq!(config.column_region(0), Rect::new(20.0, 82.0, 220.0, config.content_height()));
+        assert_eq!(config.column_region(1).x, 260.0);
+        assert_eq!(config.column_region(1).right(), 480.0);
+    }
+
+    #[test]
+    fn test_count_pages_matches_layout_pages() {
+        let mut two_columns = create_five_line_layout();
+        two_columns.set_columns(2);
+        let mut banded = create_five_line_layout();
+        banded.page_config.height = 100.0;
+        banded.page_config.footer_height = 28.0;
+        banded.page_config.footer_template = Some("{page} of {pages}".to_string());
+        let mut no_widows = create_five_line_layout();
+        no_widows.set_widow_orphan(false);
+
+        let sizes: [&[usize]; 5] = [&[], &[1], &[3, 3, 3], &[12, 1, 7], &[2, 4, 1, 1, 9, 3, 30]];
+        for mut layout in [PageLayout::new(), create_five_line_layout(), two_columns, banded, no_widows] {
+            for line_counts in sizes {
+                let mut paragraphs = create_paragraphs_with_lines(line_counts);
+                if let Some(para) = paragraphs.get_mut(1) {
+                    para.properties = ParagraphProperties::with_keep(true, false);
+                }
+                let document = DocumentLayout {
+                    paragraphs,
+                    total_width: 500.0,
+                    total_height: 0.0,
+                    line_height: 14.4,
+                    word_count: 0,
+                    char_count: 0,
+                };
+
+                let expected = layout.layout_pages(&document.paragraphs).len();
+                assert_eq!(layout.count_pages(&document), expected, "line counts {:?}", line_counts);
+            }
+        }
+    }
+
+    fn svg_test_page() -> (RenderedPage, Vec<ParagraphLayout>) {
+        let mut para = create_test_paragraphs().remove(0);
+        para.text = "Hello world\nfish & chips".to_string();
+        let first = para.lines[0].clone();
+        para.lines = vec![
+            LineLayoutInfo { start: 0, end: 12, ..first.clone() },
+            LineLayoutInfo { line_number: 1, start: 12, end: 24, ..first },
+        ];
+        let paragraphs = vec![para];
+
+        let mut page_layout = PageLayout::new();
+        let mut pages: Vec<RenderedPage> = page_layout.pages_iter(&paragraphs).collect();
+        assert_eq!(pages.len(), 1);
+        (pages.remove(0), paragraphs)
+    }
+
+    #[test]
+    fn test_rendered_page_to_svg() {
+        let (page, paragraphs) = svg_test_page();
+        let svg = page.to_svg(&paragraphs, &[], &TextShaper::new());
+
+        let view_box = format!(r#"viewBox="0 0 {} {}""#, page.page_width, page.page_height);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(&view_box), "missing {} in {}", view_box, svg);
+        assert_eq!(svg.matches("<text ").count(), 2);
+        assert!(svg.contains(">Hello world</text>"));
+        assert!(svg.contains(">fish &amp; chips</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_rendered_page_to_svg_run_formatting() {
+        let (page, paragraphs) = svg_test_page();
+        let bold = TextAttributes { bold: Some(true), ..Default::default() };
+        let italic = TextAttributes { italic: Some(true), ..Default::default() };
+        let spans = vec![vec![(0..5, bold), (19..24, italic)]];
+        let svg = page.to_svg(&paragraphs, &spans, &TextShaper::new());
+
+        assert!(svg.contains(r#"font-weight="bold">Hello</tspan> world</text>"#), "{}", svg);
+        assert!(svg.contains(r#">fish &amp; <tspan"#));
+        assert!(svg.contains(r#"font-style="italic">chips</tspan>"#));
+    }
+
+    #[test]
+    fn test_hit_test_line_starts_and_ends() {
+        let text = "First paragraph\nSecond one\nThird";
+        let mut line_layout = LineLayout::new();
+        let layout = line_layout.layout_document(text, 400.0);
+        let mut page_layout = PageLayout::new();
+        page_layout.layout_pages(&layout.paragraphs);
+        let paragraphs = &layout.paragraphs;
+
+        let page = &page_layout.pages[0];
+        let bounds = page.content_bounds;
+        let second = page.lines.iter().find(|line| line.paragraph_index == Some(1)).unwrap();
+        let mid_y = bounds.y + second.y + second.height / 2.0;
+
+        // Start of the second paragraph, and the same row clicked in the left margin
+        let start_x = bounds.x + second.x + 0.5;
+        assert_eq!(page_layout.hit_test(0, start_x, mid_y, paragraphs, &line_layout), Some(16));
+        assert_eq!(page_layout.hit_test(0, 0.0, mid_y, paragraphs, &line_layout), Some(16));
+        assert_eq!(&text[16..], "Second one\nThird");
+
+        // Past the end of the line clamps to its end
+        assert_eq!(page_layout.hit_test(0, bounds.right() + 50.0, mid_y, paragraphs, &line_layout), Some(26));
+    }
+
+    #[test]
+    fn test_hit_test_clamps_to_nearest_line() {
+        let text = "One\nTwo";
+        let mut line_layout = LineLayout::new();
+        let layout = line_layout.layout_document(text, 400.0);
+        let mut page_layout = PageLayout::new();
+        page_layout.layout_pages(&layout.paragraphs);
+        let paragraphs = &layout.paragraphs;
+
+        // Above the first line (top margin) and below the last (bottom of the page)
+        assert_eq!(page_layout.hit_test(0, 0.0, 0.0, paragraphs, &line_layout), Some(0));
+        let bottom = page_layout.page_config.height;
+        assert_eq!(page_layout.hit_test(0, 1000.0, bottom, paragraphs, &line_layout), Some(text.len()));
+
+        assert_eq!(page_layout.hit_test(1, 0.0, 0.0, paragraphs, &line_layout), None);
+    }
+
+    fn document_of(paragraphs: Vec<ParagraphLayout>) -> DocumentLayout {
+        DocumentLayout {
+            paragraphs,
+            total_width: 0.0,
+            total_height: 0.0,
+            line_height: 14.4,
+            word_count: 0,
+            char_count: 0,
+        }
+    }
+
+    /// Asserts that reflowing `before` into `after` from `start` matches a full layout
+    fn assert_reflow_matches(config: PageConfig, before: Vec<ParagraphLayout>, after: Vec<ParagraphLayout>, start: usize) -> usize {
+        let mut incremental = PageLayout::with_page_config(config.clone());
+        incremental.layout_pages(&before);
+        let first = incremental.reflow_from(&document_of(after.clone()), start);
+
+        let mut full = PageLayout::with_page_config(config);
+        full.layout_pages(&after);
+
+        let rendered = |pages: &[Page]| pages.iter().cloned().map(RenderedPage::from).collect::<Vec<_>>();
+        assert_eq!(rendered(&incremental.pages), rendered(&full.pages));
+        first
+    }
+
+    fn long_document() -> Vec<ParagraphLayout> {
+        let (_, paragraphs) = create_paginated_layout();
+        std::iter::repeat_n(paragraphs, 4).flatten().collect()
+    }
+
+    #[test]
+    fn test_reflow_from_matches_full_layout() {
+        let (page_layout, _) = create_paginated_layout();
+        let config = page_layout.page_config.clone();
+        let before = long_document();
+        let middle = before.len() / 2;
+
+        // A paragraph growing by many lines pushes everything after it on
+        let mut grown = before.clone();
+        grown[middle] = create_long_paragraph();
+        let first = assert_reflow_matches(config.clone(), before.clone(), grown.clone(), middle);
+        assert!(first > 0, "expected earlier pages to be reused");
+
+        // Shrinking it back pulls content up onto the first affected page
+        assert_reflow_matches(config.clone(), grown, before.clone(), middle);
+
+        // Removing a paragraph shifts later indices
+        let mut removed = before.clone();
+        removed.remove(middle);
+        assert_reflow_matches(config.clone(), before.clone(), removed, middle);
+
+        // An edit past the last paragraph only appends
+        let mut appended = before.clone();
+        appended.push(create_long_paragraph());
+        assert_reflow_matches(config, before.clone(), appended, before.len());
+    }
+
+    #[test]
+    fn test_reflow_from_keep_chain_and_page_count() {
+        let (page_layout, _) = create_paginated_layout();
+        let config = PageConfig {
+            footer_height: 20.0,
+            footer_template: Some("Page {page} of {pages}".to_string()),
+            ..page_layout.page_config.clone()
+        };
+        let mut before = long_document();
+        let middle = before.len() / 2;
+        before[middle - 1].properties.keep_with_next = true;
+
+        let mut after = before.clone();
+        after[middle] = create_long_paragraph();
+        assert_reflow_matches(config.clone(), before.clone(), after, middle);
+
+        // Fewer pages rewrite every footer's page count
+        let mut shorter = before.clone();
+        shorter.truncate(middle + 1);
+        assert_reflow_matches(config, before, shorter, middle);
+    }
+
+    #[test]
+    fn test_reflow_from_keeps_vertical_alignment() {
+        let (page_layout, _) = create_paginated_layout();
+        let config = PageConfig { vertical_align: VerticalAlign::Center, ..page_layout.page_config.clone() };
+        let before = long_document();
+        let middle = before.len() / 2;
+
+        let mut after = before.clone();
+        after[middle] = create_long_paragraph();
+        let first = assert_reflow_matches(config, before, after, middle);
+        assert!(first > 0, "expected earlier pages to be reused");
+    }
+
+    fn aligned_layout(align: VerticalAlign) -> PageLayout {
+        let mut layout = create_five_line_layout();
+        layout.page_config.vertical_align = align;
+        layout
+    }
+
+    #[test]
+    fn test_vertical_center_half_full_page() {
+        let paragraphs = create_paragraphs_with_lines(&[2, 1]);
+        let pages = aligned_layout(VerticalAlign::Center).layout_pages(&paragraphs);
+
+        let lines = &pages[0].lines;
+        let top_gap = lines[0].y;
+        let bottom_gap = 72.0 - (lines[2].y + lines[2].height);
+        assert!((top_gap - 14.4).abs() < 0.01, "top gap {}", top_gap);
+        assert!((top_gap - bottom_gap).abs() < 0.01, "{} vs {}", top_gap, bottom_gap);
+        // Line spacing is unchanged
+        assert!((lines[1].y - lines[0].y - 14.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_vertical_bottom_and_full_pages() {
+        let paragraphs = create_paragraphs_with_lines(&[5, 2]);
+        let pages = aligned_layout(VerticalAlign::Bottom).layout_pages(&paragraphs);
+
+        // A full page has no room to move
+        assert_eq!(pages[0].lines[0].y, 0.0);
+        let last = pages[1].lines.last().unwrap();
+        assert!((last.y + last.height - 72.0).abs() < 0.01);
+        assert!((pages[1].lines[0].y - 43.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_vertical_justify_spreads_space_between_paragraphs() {
+        let paragraphs = create_paragraphs_with_lines(&[1, 1, 1]);
+        let pages = aligned_layout(VerticalAlign::Justify).layout_pages(&paragraphs);
+
+        let ys: Vec<f32> = pages[0].lines.iter().map(|line| line.y).collect();
+        assert_eq!(ys[0], 0.0);
+        assert!((ys[1] - 28.8).abs() < 0.01, "{:?}", ys);
+        assert!((ys[2] + 14.4 - 72.0).abs() < 0.01, "{:?}", ys);
+
+        // A single paragraph stays at the top
+        let pages = aligned_layout(VerticalAlign::Justify).layout_pages(&create_paragraphs_with_lines(&[2]));
+        assert_eq!(pages[0].lines[0].y, 0.0);
+    }
+
+    /// Two columns of six 14.4pt lines each
+    fn two_column_six_line_layout(balance: bool) -> PageLayout {
+        let mut layout = create_five_line_layout();
+        layout.page_config.height = 87.0;
+        layout.set_columns(2);
+        layout.set_balance_last_page(balance);
+        layout
+    }
+
+    fn column_heights(page: &Page) -> Vec<usize> {
+        let mut columns: Vec<f32> = page.lines.iter().map(|line| line.x).collect();
+        columns.dedup();
+        columns.iter().map(|&x| page.lines.iter().filter(|line| line.x == x).count()).collect()
+    }
+
+    #[test]
+    fn test_balance_last_page_splits_leftover_lines_evenly() {
+        // A full first page, then seven one-line paragraphs
+        let paragraphs = create_paragraphs_with_lines(&[12, 1, 1, 1, 1, 1, 1, 1]);
+
+        let pages = two_column_six_line_layout(false).layout_pages(&paragraphs);
+        assert_eq!(column_heights(&pages[1]), [6, 1]);
+
+        let pages = two_column_six_line_layout(true).layout_pages(&paragraphs);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(column_heights(&pages[0]), [6, 6]);
+        assert_eq!(column_heights(&pages[1]), [4, 3]);
+
+        let second = &pages[1].lines;
+        let paragraph_order: Vec<usize> = second.iter().filter_map(|line| line.paragraph_index).collect();
+        assert_eq!(paragraph_order, [1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(second[4].y, 0.0);
+        assert!((second[3].y - 3.0 * 14.4).abs() < 0.01);
+        assert!(second[4].x > second[3].x);
+    }
+
+    #[test]
+    fn test_balance_last_page_splits_one_paragraph() {
+        let paragraphs = create_paragraphs_with_lines(&[7]);
+        let pages = two_column_six_line_layout(true).layout_pages(&paragraphs);
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(column_heights(&pages[0]), [4, 3]);
+        let source_lines: Vec<usize> = pages[0].lines.iter().map(|line| line.source_line_index).collect();
+        assert_eq!(source_lines, [0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_balance_last_page_respects_orphan_control() {
+        // A 3/2 split would leave the 3-line paragraph's first line as an orphan
+        let paragraphs = create_paragraphs_with_lines(&[2, 3]);
+        let pages = two_column_six_line_layout(true).layout_pages(&paragraphs);
+
+        assert_eq!(column_heights(&pages[0]), [2, 3]);
+        assert_eq!(lines_of(&pages[0], 1), [0, 1, 2]);
+        assert_eq!(pages[0].lines[2].y, 0.0);
+    }
 }
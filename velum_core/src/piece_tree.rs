@@ -1,7 +1,14 @@
 use serde::{Serialize, Deserialize};
-use crate::find::{SearchOptions, SearchResult, SearchResultSet, search, find_all_in_text};
+use crate::find::{SearchOptions, SearchResult, SearchResultSet, search};
 use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use memmap2::Mmap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
 use log::trace;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Represents which buffer a piece comes from
 /// -1 means original buffer (index 0), other values are buffer indices
@@ -112,7 +119,7 @@ impl Piece {
 }
 
 /// Represents a text selection with anchor and active positions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Selection {
     /// The anchor position (where selection started, stays fixed during shift+arrow)
     pub anchor: usize,
@@ -174,6 +181,75 @@ pub enum Change {
         offset: usize,
         text: String,
     },
+    Replace {
+        offset: usize,
+        removed: String,
+        inserted: String,
+    },
+}
+
+/// How consecutive typed characters are grouped into undo steps.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UndoGranularity {
+    /// Every insert is its own undo step
+    #[default]
+    Character,
+    /// Typing is grouped into words; trailing whitespace stays with its word
+    Word,
+    /// Typing is grouped into paragraphs, ending at each newline
+    Paragraph,
+}
+
+impl UndoGranularity {
+    /// Returns true if typing `next` after `previous` starts a new undo step
+    fn starts_new_step(self, previous: char, next: char) -> bool {
+        match self {
+            UndoGranularity::Character => true,
+            UndoGranularity::Word => previous == '\n' || (previous.is_whitespace() && !next.is_whitespace()),
+            UndoGranularity::Paragraph => previous == '\n',
+        }
+    }
+}
+
+/// One operation of an edit script produced by [`PieceTree::diff`].
+///
+/// Offsets are bytes into the document as it stands when the op is applied,
+/// so the ops must be applied in order.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Inserts `text` at byte `offset`
+    Insert { offset: usize, text: String },
+    /// Deletes `length` bytes at byte `offset`
+    Delete { offset: usize, length: usize },
+}
+
+/// An immutable view of a PieceTree's pieces at a point in time.
+///
+/// Buffers are append-only, so a snapshot only needs the piece list; it is
+/// shared through an `Arc` and cloning a snapshot never copies text.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pieces: Arc<[Piece]>,
+    buffer_count: usize,
+    total_char_count: usize,
+    total_length: usize,
+}
+
+impl Snapshot {
+    /// Character count of the document when the snapshot was taken
+    pub fn char_count(&self) -> usize {
+        self.total_char_count
+    }
+
+    /// Byte length of the document when the snapshot was taken
+    pub fn len(&self) -> usize {
+        self.total_length
+    }
+
+    /// Returns true if the document was empty when the snapshot was taken
+    pub fn is_empty(&self) -> bool {
+        self.total_length == 0
+    }
 }
 
 /// Main Piece Tree data structure
@@ -182,6 +258,8 @@ pub struct PieceTree {
     pub pieces: Vec<Piece>,
     /// Map of buffer IDs to their content
     pub buffers: Vec<String>,
+    /// Memory-mapped original buffer, used in place of `buffers[0]` when set
+    mapped: Option<Mmap>,
     /// Total character count
     pub total_char_count: usize,
     /// Total byte length
@@ -198,6 +276,21 @@ pub struct PieceTree {
     pub selection: Selection,
     /// Saved selection for undo/redo
     saved_selection: Option<Selection>,
+    /// How typed characters are coalesced into undo steps
+    undo_granularity: UndoGranularity,
+    /// End offset and last character of the typing run the top undo entry covers
+    typing_run: Option<(usize, char)>,
+    /// Bumped on every text mutation so caches can detect changes
+    revision: u64,
+    /// Byte offsets of line starts, rebuilt on the first line lookup after an edit
+    line_starts: Mutex<Option<LineStarts>>,
+}
+
+/// Line start offsets cached for one revision of the text
+#[derive(Debug)]
+struct LineStarts {
+    revision: u64,
+    starts: Vec<usize>,
 }
 
 impl PieceTree {
@@ -218,14 +311,19 @@ impl PieceTree {
         PieceTree {
             pieces: vec![piece],
             buffers,
+            mapped: None,
             total_char_count: char_length,
             total_length: length,
             next_buffer_index: 1,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             is_undoing_redoing: false,
+            undo_granularity: UndoGranularity::default(),
+            typing_run: None,
             selection: Selection::default(),
             saved_selection: None,
+            revision: 0,
+            line_starts: Mutex::new(None),
         }
     }
 
@@ -234,14 +332,19 @@ impl PieceTree {
         PieceTree {
             pieces: Vec::new(),
             buffers: vec![String::new()],
+            mapped: None,
             total_char_count: 0,
             total_length: 0,
             next_buffer_index: 1,  // First insert should use BufferId(1), referencing buffers[1]
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             is_undoing_redoing: false,
+            undo_granularity: UndoGranularity::default(),
+            typing_run: None,
             selection: Selection::default(),
             saved_selection: None,
+            revision: 0,
+            line_starts: Mutex::new(None),
         }
     }
 
@@ -259,14 +362,60 @@ impl PieceTree {
         PieceTree {
             pieces,
             buffers,
+            mapped: None,
             total_char_count,
             total_length,
             next_buffer_index,
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             is_undoing_redoing: false,
+            undo_granularity: UndoGranularity::default(),
+            typing_run: None,
             selection: Selection::default(),
             saved_selection: None,
+            revision: 0,
+            line_starts: Mutex::new(None),
+        }
+    }
+
+    /// Opens a file as a PieceTree without reading it into memory.
+    ///
+    /// The file is memory-mapped read-only and serves as the original buffer;
+    /// edits are appended to add buffers as usual. The content is validated as
+    /// UTF-8 once here, and is expected not to change while the tree is alive.
+    pub fn from_mmap(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only and the caller must not modify the
+        // file while the tree holds it.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let text = std::str::from_utf8(&mmap)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if text.is_empty() {
+            return Ok(PieceTree::empty());
+        }
+        let length = text.len();
+        let char_length = text.chars().count();
+
+        let mut tree = PieceTree::empty();
+        tree.pieces.push(Piece::new(0, length, BufferId::ORIGINAL, char_length));
+        tree.total_char_count = char_length;
+        tree.total_length = length;
+        tree.mapped = Some(mmap);
+        Ok(tree)
+    }
+
+    /// Returns the content of the buffer at `idx`, reading the original
+    /// buffer from the memory map when the tree was opened with `from_mmap`
+    pub fn buffer(&self, idx: usize) -> Option<&str> {
+        Self::buffer_in(&self.buffers, &self.mapped, idx)
+    }
+
+    /// Field-level form of `buffer`, usable while `pieces` is mutably borrowed
+    fn buffer_in<'a>(buffers: &'a [String], mapped: &'a Option<Mmap>, idx: usize) -> Option<&'a str> {
+        match (mapped, idx) {
+            // SAFETY: validated as UTF-8 in `from_mmap`
+            (Some(mmap), 0) => Some(unsafe { std::str::from_utf8_unchecked(mmap) }),
+            _ => buffers.get(idx).map(String::as_str),
         }
     }
 
@@ -353,6 +502,8 @@ impl PieceTree {
             return true;
         }
 
+        self.revision += 1;
+
         let char_count = text.chars().count();
         let byte_count = text.len();
 
@@ -361,7 +512,7 @@ impl PieceTree {
         let char_offset = std::cmp::min(char_offset, max_offset);
 
         // Record change for undo
-        if !self.is_undoing_redoing {
+        if !self.is_undoing_redoing && !self.extend_typing_run(char_offset, &text) {
             // Save current selection for undo
             self.saved_selection = Some(self.selection);
             self.undo_stack.push(Change::Insert {
@@ -372,6 +523,10 @@ impl PieceTree {
                 self.undo_stack.remove(0);
             }
             self.redo_stack.clear();
+            self.typing_run = match (char_count, text.chars().next()) {
+                (1, Some(c)) => Some((char_offset + 1, c)),
+                _ => None,
+            };
         }
 
         trace!("insert: char_offset={}, text='{}' ({} bytes, {} chars)",
@@ -427,33 +582,24 @@ impl PieceTree {
             trace!("insert at end");
         } else {
             // Split the piece and insert in the middle
-            // byte_offset_in_piece is the character offset within the piece where we split
+            // byte_offset_in_piece is a char boundary within the piece where we split
 
             // Capture original values before updating
             let original_piece_start = piece.start;
             let original_piece_length = piece.length;
 
-            // Calculate left and right pieces using char_indices for correct UTF-8 handling
             let piece_buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            let piece_buffer = &self.buffers[piece_buffer_idx];
+            let piece_buffer = Self::buffer_in(&self.buffers, &self.mapped, piece_buffer_idx).unwrap_or_default();
             let original_piece_text = &piece_buffer[original_piece_start..original_piece_start + original_piece_length];
 
-            // Get all characters in the piece
-            let chars: Vec<char> = original_piece_text.chars().collect();
-            let total_chars_in_piece = chars.len();
-
-            // Clamp byte_offset_in_piece to valid range
-            let split_char_idx = byte_offset_in_piece.min(total_chars_in_piece);
-
-            // left_piece: first split_char_idx characters
-            let left_chars: String = chars[..split_char_idx].iter().collect();
+            // left_piece: text before the split point
+            let (left_chars, right_chars) = original_piece_text.split_at(byte_offset_in_piece);
             let left_byte_count = left_chars.len();
             let left_char_count = left_chars.chars().count();
 
             // right_piece: remaining characters
-            let right_chars: String = chars[split_char_idx..].iter().collect();
             let right_piece_byte_length = right_chars.len();
-            let right_piece_char_length = right_chars.chars().count();
+            let right_piece_char_length = piece.piece_char_length.saturating_sub(left_char_count);
 
             trace!("left='{}' ({} bytes, {} chars)", left_chars, left_byte_count, left_char_count);
             trace!("right='{}' ({} bytes, {} chars)", right_chars, right_piece_byte_length, right_piece_char_length);
@@ -519,7 +665,7 @@ impl PieceTree {
 
                 // Find the byte offset for this character position
                 let piece_buffer_idx = Self::buffer_idx(&piece.buffer_id);
-                if let Some(buffer) = self.buffers.get(piece_buffer_idx) {
+                if let Some(buffer) = self.buffer(piece_buffer_idx) {
                     let piece_text = &buffer[piece.start..piece.start + piece.length];
 
                     // Find the byte offset of the char_offset_in_piece-th character
@@ -557,16 +703,20 @@ impl PieceTree {
     // ==================== Deletion ====================
 
     /// Deletes text from the specified byte position with the given byte length
-    /// Returns true if successful
+    /// Returns false if the range is empty, out of bounds or not on character boundaries
     pub fn delete(&mut self, offset: usize, length: usize) -> bool {
         if length == 0 || self.pieces.is_empty() {
             return false;
         }
 
         let end_offset = offset.saturating_add(length);
-        if end_offset > self.total_length {
+        if end_offset > self.total_length
+            || !self.is_char_boundary(offset)
+            || !self.is_char_boundary(end_offset)
+        {
             return false;
         }
+        self.revision += 1;
 
         // Record change for undo
         if !self.is_undoing_redoing {
@@ -610,8 +760,12 @@ impl PieceTree {
             let delete_start_in_piece = if offset > piece_start { offset - piece_start } else { 0 };
             let delete_end_in_piece = if end_offset < piece_end { end_offset - piece_start } else { piece.length };
 
+            // Char counts come from the buffer so multibyte text keeps accurate cached lengths
+            let left_chars = self.piece_char_count(piece, 0, delete_start_in_piece);
+            let removed_chars = self.piece_char_count(piece, delete_start_in_piece, delete_end_in_piece);
+
             deleted_bytes += delete_end_in_piece - delete_start_in_piece;
-            deleted_chars += delete_end_in_piece - delete_start_in_piece;
+            deleted_chars += removed_chars;
 
             if delete_start_in_piece > 0 {
                 // Keep left part
@@ -619,7 +773,7 @@ impl PieceTree {
                     piece.start,
                     delete_start_in_piece,
                     piece.buffer_id,
-                    delete_start_in_piece,
+                    left_chars,
                     piece.attributes.clone(),
                 );
                 new_pieces.push(left_piece);
@@ -633,7 +787,7 @@ impl PieceTree {
                     right_start,
                     right_length,
                     piece.buffer_id,
-                    right_length,
+                    piece.piece_char_length.saturating_sub(left_chars + removed_chars),
                     piece.attributes.clone(),
                 );
                 new_pieces.push(right_piece);
@@ -671,6 +825,110 @@ impl PieceTree {
         true
     }
 
+    /// Counts the chars in the byte range `start..end` of a piece
+    fn piece_char_count(&self, piece: &Piece, start: usize, end: usize) -> usize {
+        self.buffer(Self::buffer_idx(&piece.buffer_id))
+            .and_then(|buffer| buffer.get(piece.start + start..piece.start + end))
+            .map_or(end - start, |text| text.chars().count())
+    }
+
+    // ==================== Replacement ====================
+
+    /// Replaces `length` bytes at byte `offset` with `new_text` as a single edit
+    /// Records one undo step; an empty `new_text` deletes, `offset == total_length` appends
+    /// Returns false if the range is out of bounds or not on character boundaries
+    pub fn replace(&mut self, offset: usize, length: usize, new_text: String) -> bool {
+        let end_offset = offset.saturating_add(length);
+        if end_offset > self.total_length
+            || !self.is_char_boundary(offset)
+            || !self.is_char_boundary(end_offset)
+        {
+            return false;
+        }
+        if length == 0 && new_text.is_empty() {
+            return true;
+        }
+
+        // Record change for undo
+        if !self.is_undoing_redoing {
+            self.saved_selection = Some(self.selection);
+            self.undo_stack.push(Change::Replace {
+                offset,
+                removed: self.get_text_range(offset, length),
+                inserted: new_text.clone(),
+            });
+            if self.undo_stack.len() > MAX_UNDO_DEPTH {
+                self.undo_stack.remove(0);
+            }
+            self.redo_stack.clear();
+        }
+
+        // Splice without recording the delete and insert separately
+        let recording = !self.is_undoing_redoing;
+        self.is_undoing_redoing = true;
+        if length > 0 {
+            self.delete(offset, length);
+        }
+        let char_offset = self.get_text_range(0, offset).chars().count();
+        let inserted_chars = new_text.chars().count();
+        self.insert(char_offset, new_text);
+        self.is_undoing_redoing = !recording;
+
+        // Move selection after replacement text
+        if recording {
+            self.move_selection_to(char_offset + inserted_chars);
+        }
+
+        true
+    }
+
+    /// Runs `edits` as a single undo step, recorded as one replacement of the changed span
+    /// If `edits` returns an error the text is restored and nothing is recorded
+    pub fn group_edits<E>(&mut self, edits: impl FnOnce(&mut Self) -> Result<(), E>) -> Result<(), E> {
+        let recording = !self.is_undoing_redoing;
+        let before = self.get_text();
+        let selection = self.selection;
+
+        self.is_undoing_redoing = true;
+        let result = edits(self);
+        let after = self.get_text();
+        let (offset, removed_len, inserted_len) = changed_span(&before, &after);
+        let removed = before[offset..offset + removed_len].to_string();
+        let inserted = after[offset..offset + inserted_len].to_string();
+        if result.is_err() {
+            self.replace(offset, inserted_len, removed.clone());
+            self.selection = selection;
+        }
+        self.is_undoing_redoing = !recording;
+
+        if result.is_ok() && recording && before != after {
+            self.saved_selection = Some(selection);
+            self.undo_stack.push(Change::Replace { offset, removed, inserted });
+            if self.undo_stack.len() > MAX_UNDO_DEPTH {
+                self.undo_stack.remove(0);
+            }
+            self.redo_stack.clear();
+        }
+
+        result
+    }
+
+    /// Checks whether a byte offset falls on a character boundary
+    fn is_char_boundary(&self, offset: usize) -> bool {
+        let mut current_offset = 0usize;
+
+        for piece in &self.pieces {
+            if offset < current_offset + piece.length {
+                let buffer_idx = Self::buffer_idx(&piece.buffer_id);
+                return self.buffer(buffer_idx)
+                    .is_some_and(|buffer| buffer.is_char_boundary(piece.start + offset - current_offset));
+            }
+            current_offset += piece.length;
+        }
+
+        offset == current_offset
+    }
+
     // ==================== Text Retrieval ====================
 
     /// Gets the full text content
@@ -705,7 +963,7 @@ impl PieceTree {
             let end_in_piece = if end_offset < piece_end { end_offset - piece_start } else { piece.length };
 
             let buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            if let Some(buffer) = self.buffers.get(buffer_idx) {
+            if let Some(buffer) = self.buffer(buffer_idx) {
                 let start_byte = piece.start + start_in_piece;
                 let end_byte = piece.start + end_in_piece;
                 if start_byte < buffer.len() && end_byte <= buffer.len() {
@@ -719,10 +977,72 @@ impl PieceTree {
         result
     }
 
+    /// Iterates over the text of each piece in document order without allocating
+    pub fn chunks(&self) -> impl Iterator<Item = &str> + '_ {
+        self.chunks_range(0, self.total_length)
+    }
+
+    /// Iterates over the piece text covering the byte range `offset..offset + length`
+    pub fn chunks_range(&self, offset: usize, length: usize) -> impl Iterator<Item = &str> + '_ {
+        let end_offset = offset.saturating_add(length);
+
+        self.pieces
+            .iter()
+            .scan(0usize, |current_offset, piece| {
+                let piece_start = *current_offset;
+                *current_offset += piece.length;
+                Some((piece_start, piece))
+            })
+            .skip_while(move |(piece_start, piece)| piece_start + piece.length <= offset)
+            .take_while(move |(piece_start, _)| *piece_start < end_offset)
+            .filter_map(move |(piece_start, piece)| {
+                let start_in_piece = offset.saturating_sub(piece_start);
+                let end_in_piece = piece.length.min(end_offset - piece_start);
+
+                let buffer = self.buffer(Self::buffer_idx(&piece.buffer_id))?;
+                buffer.get(piece.start + start_in_piece..piece.start + end_in_piece)
+            })
+            .filter(|chunk| !chunk.is_empty())
+    }
+
     // ==================== Undo/Redo ====================
 
+    /// Sets how consecutive typed characters are grouped into undo steps
+    pub fn set_undo_granularity(&mut self, granularity: UndoGranularity) {
+        self.undo_granularity = granularity;
+        self.typing_run = None;
+    }
+
+    /// Returns how typed characters are grouped into undo steps
+    pub fn undo_granularity(&self) -> UndoGranularity {
+        self.undo_granularity
+    }
+
+    /// Folds a single typed character into the top undo entry when it
+    /// continues the current typing run. Returns true if it was folded.
+    fn extend_typing_run(&mut self, char_offset: usize, text: &str) -> bool {
+        let mut chars = text.chars();
+        let (Some(next), None) = (chars.next(), chars.next()) else {
+            return false;
+        };
+        let Some((end, previous)) = self.typing_run else {
+            return false;
+        };
+        if end != char_offset || self.undo_granularity.starts_new_step(previous, next) {
+            return false;
+        }
+        let Some(Change::Insert { length, .. }) = self.undo_stack.last_mut() else {
+            return false;
+        };
+        *length += text.len();
+        self.typing_run = Some((end + 1, next));
+        self.redo_stack.clear();
+        true
+    }
+
     /// Undoes the last change
     pub fn undo(&mut self) -> bool {
+        self.typing_run = None;
         if let Some(change) = self.undo_stack.pop() {
             self.is_undoing_redoing = true;
             let redo_change = match change {
@@ -739,6 +1059,14 @@ impl PieceTree {
                     self.insert(offset, text);
                     Change::Insert { offset, length }
                 }
+                Change::Replace { offset, removed, inserted } => {
+                    self.replace(offset, inserted.len(), removed.clone());
+                    Change::Replace {
+                        offset,
+                        removed: inserted,
+                        inserted: removed,
+                    }
+                }
             };
             self.redo_stack.push(redo_change);
             self.is_undoing_redoing = false;
@@ -753,6 +1081,7 @@ impl PieceTree {
 
     /// Redoes the last undone change
     pub fn redo(&mut self) -> bool {
+        self.typing_run = None;
         if let Some(change) = self.redo_stack.pop() {
             self.is_undoing_redoing = true;
             let undo_change = match change {
@@ -769,6 +1098,14 @@ impl PieceTree {
                     self.insert(offset, text);
                     Change::Insert { offset, length }
                 }
+                Change::Replace { offset, removed, inserted } => {
+                    self.replace(offset, inserted.len(), removed.clone());
+                    Change::Replace {
+                        offset,
+                        removed: inserted,
+                        inserted: removed,
+                    }
+                }
             };
             self.undo_stack.push(undo_change);
             self.is_undoing_redoing = false;
@@ -791,6 +1128,110 @@ impl PieceTree {
         !self.redo_stack.is_empty()
     }
 
+    /// Number of changes that can be undone
+    pub fn undo_count(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Number of changes that can be redone
+    pub fn redo_count(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    // ==================== Snapshots ====================
+
+    /// Captures the current document state without copying any text
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pieces: self.pieces.iter().cloned().collect(),
+            buffer_count: self.buffers.len(),
+            total_char_count: self.total_char_count,
+            total_length: self.total_length,
+        }
+    }
+
+    /// Reverts the document to a snapshot taken from this tree.
+    ///
+    /// The restore is recorded as a single undoable change. Returns false if
+    /// the snapshot references buffers this tree does not have.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> bool {
+        if snapshot.buffer_count > self.buffers.len() {
+            return false;
+        }
+        let _ = self.group_edits::<()>(|tree| {
+            tree.pieces = snapshot.pieces.to_vec();
+            tree.total_char_count = snapshot.total_char_count;
+            tree.total_length = snapshot.total_length;
+            tree.revision += 1;
+            Ok(())
+        });
+        self.move_selection_to(self.selection.active);
+        true
+    }
+
+    // ==================== Diff ====================
+
+    /// Computes a character-level edit script turning this document into `other`.
+    ///
+    /// Uses a Myers diff over the span between the common prefix and suffix, so
+    /// the script is minimal in the number of inserted and deleted characters.
+    pub fn diff(&self, other: &PieceTree) -> Vec<DiffOp> {
+        let before = self.get_text();
+        let after = other.get_text();
+        let (prefix, removed_len, inserted_len) = changed_span(&before, &after);
+        let removed: Vec<char> = before[prefix..prefix + removed_len].chars().collect();
+        let inserted: Vec<char> = after[prefix..prefix + inserted_len].chars().collect();
+
+        let mut ops: Vec<DiffOp> = Vec::new();
+        let mut pos = prefix;
+        let (mut i, mut j) = (0, 0);
+        for step in myers_diff(&removed, &inserted) {
+            match step {
+                DiffStep::Equal => {
+                    pos += removed[i].len_utf8();
+                    i += 1;
+                    j += 1;
+                }
+                DiffStep::Delete => {
+                    let len = removed[i].len_utf8();
+                    match ops.last_mut() {
+                        Some(DiffOp::Delete { offset, length }) if *offset == pos => *length += len,
+                        _ => ops.push(DiffOp::Delete { offset: pos, length: len }),
+                    }
+                    i += 1;
+                }
+                DiffStep::Insert => {
+                    let ch = inserted[j];
+                    match ops.last_mut() {
+                        Some(DiffOp::Insert { offset, text }) if *offset + text.len() == pos => text.push(ch),
+                        _ => ops.push(DiffOp::Insert { offset: pos, text: ch.to_string() }),
+                    }
+                    pos += ch.len_utf8();
+                    j += 1;
+                }
+            }
+        }
+        ops
+    }
+
+    /// Applies an edit script from [`PieceTree::diff`] as a single undo step.
+    /// Returns false and leaves the document unchanged if any op is out of range
+    pub fn apply_diff(&mut self, ops: &[DiffOp]) -> bool {
+        self.group_edits(|tree| {
+            for op in ops {
+                let applied = match op {
+                    DiffOp::Insert { offset, text } => tree.replace(*offset, 0, text.clone()),
+                    DiffOp::Delete { offset, length } => tree.replace(*offset, *length, String::new()),
+                };
+                if !applied {
+                    return Err(());
+                }
+            }
+            Ok(())
+        })
+        .is_ok()
+    }
+
     // ==================== Navigation ====================
 
     /// Moves to the specified character position and returns (line, column)
@@ -805,7 +1246,7 @@ impl PieceTree {
 
         for piece in &self.pieces {
             let buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            if let Some(buffer) = self.buffers.get(buffer_idx) {
+            if let Some(buffer) = self.buffer(buffer_idx) {
                 let piece_text = if piece.start + piece.length <= buffer.len() {
                     &buffer[piece.start..piece.start + piece.length]
                 } else {
@@ -845,45 +1286,123 @@ impl PieceTree {
         (line, column)
     }
 
-    /// Gets the content of a specific line (1-indexed)
-    pub fn get_line(&self, line_number: usize) -> Option<String> {
-        if line_number == 0 || self.pieces.is_empty() {
+    /// Converts a byte offset to a 1-indexed (line, column) position, counting columns in chars
+    /// Offsets past the end clamp to the end of the document; offsets inside a multibyte
+    /// character resolve to that character's position
+    pub fn offset_to_position(&self, offset: usize) -> (usize, usize) {
+        let mut line = 1usize;
+        let mut column = 1usize;
+        let mut byte_offset = 0usize;
+
+        for chunk in self.chunks() {
+            for c in chunk.chars() {
+                if byte_offset + c.len_utf8() > offset {
+                    return (line, column);
+                }
+
+                if c == '\n' {
+                    line += 1;
+                    column = 1;
+                } else {
+                    column += 1;
+                }
+                byte_offset += c.len_utf8();
+            }
+        }
+
+        (line, column)
+    }
+
+    /// Converts a 1-indexed (line, column) position to a byte offset
+    /// Column `n + 1` addresses the end of a line with `n` chars; any position outside
+    /// the document returns None
+    pub fn position_to_offset(&self, line: usize, column: usize) -> Option<usize> {
+        if line == 0 || column == 0 {
             return None;
         }
 
         let mut current_line = 1usize;
-        let mut current_line_content = String::new();
+        let mut current_column = 1usize;
+        let mut byte_offset = 0usize;
 
-        for piece in &self.pieces {
-            let buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            if let Some(buffer) = self.buffers.get(buffer_idx) {
-                let piece_text = if piece.start + piece.length <= buffer.len() {
-                    &buffer[piece.start..piece.start + piece.length]
-                } else {
-                    ""
-                };
+        for chunk in self.chunks() {
+            for c in chunk.chars() {
+                if current_line == line && current_column == column {
+                    return Some(byte_offset);
+                }
 
-                for c in piece_text.chars() {
-                    if c == '\n' {
-                        if current_line == line_number {
-                            return Some(current_line_content);
-                        }
-                        current_line += 1;
-                        current_line_content.clear();
-                    } else {
-                        if current_line == line_number {
-                            current_line_content.push(c);
-                        }
+                if c == '\n' {
+                    if current_line == line {
+                        // Column runs past the end of the requested line
+                        return None;
                     }
+                    current_line += 1;
+                    current_column = 1;
+                } else {
+                    current_column += 1;
                 }
+                byte_offset += c.len_utf8();
             }
         }
 
-        if current_line == line_number && !current_line_content.is_empty() {
-            return Some(current_line_content);
+        (current_line == line && current_column == column).then_some(byte_offset)
+    }
+
+    /// Runs `f` on the byte offsets where each line starts, scanning the text
+    /// only if it changed since the offsets were last computed
+    fn with_line_starts<R>(&self, f: impl FnOnce(&[usize]) -> R) -> R {
+        let mut cache = self.line_starts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if cache.as_ref().is_none_or(|cached| cached.revision != self.revision) {
+            let mut starts = vec![0];
+            let mut offset = 0usize;
+            for chunk in self.chunks() {
+                starts.extend(chunk.bytes().enumerate().filter(|&(_, b)| b == b'\n').map(|(i, _)| offset + i + 1));
+                offset += chunk.len();
+            }
+            *cache = Some(LineStarts { revision: self.revision, starts });
         }
+        f(&cache.as_ref().expect("line starts were just computed").starts)
+    }
 
-        None
+    /// Byte range of the text of line `index` (0-indexed), excluding its newline
+    fn line_range(&self, starts: &[usize], index: usize) -> Range<usize> {
+        let end = starts.get(index + 1).map_or(self.total_length, |next| next - 1);
+        starts[index]..end
+    }
+
+    /// Gets the content of a specific line (1-indexed)
+    ///
+    /// Line starts are cached between edits, so fetching many lines costs one
+    /// scan of the text rather than one per line. An empty last line is `None`.
+    pub fn get_line(&self, line_number: usize) -> Option<String> {
+        self.get_lines(line_number..line_number + 1).pop()
+    }
+
+    /// Gets the content of lines `lines.start..lines.end` (1-indexed), stopping
+    /// at the last line; an empty last line is left out, as in [`Self::get_line`]
+    pub fn get_lines(&self, lines: Range<usize>) -> Vec<String> {
+        if self.pieces.is_empty() {
+            return Vec::new();
+        }
+
+        self.with_line_starts(|starts| {
+            let first = lines.start.max(1);
+            let last = lines.end.min(starts.len() + 1);
+            if first >= last {
+                return Vec::new();
+            }
+
+            let span = self.line_range(starts, first - 1).start..self.line_range(starts, last - 2).end;
+            let text: String = self.chunks_range(span.start, span.len()).collect();
+            (first..last)
+                .map(|line| {
+                    let range = self.line_range(starts, line - 1);
+                    (line, text[range.start - span.start..range.end - span.start].to_string())
+                })
+                .filter(|(line, content)| *line < starts.len() || !content.is_empty())
+                .map(|(_, content)| content)
+                .collect()
+        })
     }
 
     /// Gets the line count
@@ -892,12 +1411,7 @@ impl PieceTree {
             return 0;
         }
 
-        let full_text = self.get_text();
-        if full_text.is_empty() {
-            return 1;
-        }
-
-        full_text.chars().filter(|&c| c == '\n').count() + 1
+        self.with_line_starts(<[usize]>::len)
     }
 
     /// Gets the character offset for the start of a specific line (1-indexed)
@@ -916,7 +1430,7 @@ impl PieceTree {
 
         for piece in &self.pieces {
             let buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            if let Some(buffer) = self.buffers.get(buffer_idx) {
+            if let Some(buffer) = self.buffer(buffer_idx) {
                 let piece_text = if piece.start + piece.length <= buffer.len() {
                     &buffer[piece.start..piece.start + piece.length]
                 } else {
@@ -940,6 +1454,31 @@ impl PieceTree {
         char_count
     }
 
+    /// Converts a character offset to a byte offset, or None if it is past the end of the document
+    pub fn char_to_byte_offset(&self, char_offset: usize) -> Option<usize> {
+        if char_offset == 0 {
+            return Some(0);
+        }
+        let (piece_idx, byte_in_piece) = self.find_piece_and_byte_offset_from_char(char_offset)?;
+        let preceding: usize = self.pieces[..piece_idx].iter().map(|piece| piece.length).sum();
+        Some(preceding + byte_in_piece)
+    }
+
+    /// Gets the document length in bytes by summing the cached piece lengths
+    pub fn byte_len(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.length).sum()
+    }
+
+    /// Gets the document length in chars by summing the cached piece char counts
+    pub fn char_len(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.piece_char_length).sum()
+    }
+
+    /// Gets the document revision, which strictly increases on every edit, undo and redo
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
     /// Gets total character count
     pub fn char_count(&self) -> usize {
         self.total_char_count
@@ -970,12 +1509,131 @@ impl PieceTree {
         &self.pieces
     }
 
+    // ==================== Attributes ====================
+
+    /// Returns the formatted spans overlapping a byte range, clipped to it.
+    ///
+    /// Adjacent pieces with equal attributes are merged into one span; text
+    /// without formatting is not reported.
+    pub fn attribute_spans_in(&self, range: Range<usize>) -> Vec<(Range<usize>, TextAttributes)> {
+        let mut spans: Vec<(Range<usize>, TextAttributes)> = Vec::new();
+        let mut current_offset = 0usize;
+
+        for piece in &self.pieces {
+            let piece_start = current_offset;
+            let piece_end = current_offset + piece.length;
+            current_offset = piece_end;
+
+            if piece_end <= range.start {
+                continue;
+            }
+            if piece_start >= range.end {
+                break;
+            }
+
+            let attributes = match piece.attributes {
+                Some(ref attrs) if *attrs != TextAttributes::default() => attrs,
+                _ => continue,
+            };
+
+            let start = piece_start.max(range.start);
+            let end = piece_end.min(range.end);
+
+            match spans.last_mut() {
+                Some((last_range, last_attrs)) if last_range.end == start && last_attrs == attributes => {
+                    last_range.end = end;
+                }
+                _ => spans.push((start..end, attributes.clone())),
+            }
+        }
+
+        spans
+    }
+
+    /// Applies `attrs` to the byte range `offset..offset + length`, replacing any
+    /// formatting already there. Pieces straddling the range edges are split, so the
+    /// span then moves and shrinks with later inserts and deletes.
+    /// Returns false if the range is empty, out of bounds, or not on char boundaries.
+    pub fn set_attributes(&mut self, offset: usize, length: usize, attrs: TextAttributes) -> bool {
+        let end_offset = offset.saturating_add(length);
+        if length == 0
+            || end_offset > self.total_length
+            || !self.is_char_boundary(offset)
+            || !self.is_char_boundary(end_offset)
+        {
+            return false;
+        }
+
+        self.split_piece_at(offset);
+        self.split_piece_at(end_offset);
+
+        let attributes = if attrs == TextAttributes::default() { None } else { Some(attrs) };
+        let mut current_offset = 0usize;
+        for piece in &mut self.pieces {
+            let piece_start = current_offset;
+            current_offset += piece.length;
+            if piece_start >= end_offset {
+                break;
+            }
+            if piece_start >= offset {
+                piece.attributes = attributes.clone();
+            }
+        }
+
+        self.revision += 1;
+        true
+    }
+
+    /// Returns the formatting of the character at a byte offset, or the default
+    /// attributes for unformatted text and offsets past the end
+    pub fn attributes_at(&self, offset: usize) -> TextAttributes {
+        let mut current_offset = 0usize;
+        for piece in &self.pieces {
+            let piece_end = current_offset + piece.length;
+            if offset < piece_end {
+                return piece.attributes.clone().unwrap_or_default();
+            }
+            current_offset = piece_end;
+        }
+        TextAttributes::default()
+    }
+
+    /// Splits the piece containing a byte offset so that a piece boundary falls on it
+    fn split_piece_at(&mut self, offset: usize) {
+        let mut current_offset = 0usize;
+        for idx in 0..self.pieces.len() {
+            let piece_start = current_offset;
+            let piece_end = piece_start + self.pieces[idx].length;
+            if offset <= piece_start {
+                return;
+            }
+            if offset < piece_end {
+                let split = offset - piece_start;
+                let piece = &self.pieces[idx];
+                let left_chars = self.piece_char_count(piece, 0, split);
+                let right_piece = Piece::new_with_attrs(
+                    piece.start + split,
+                    piece.length - split,
+                    piece.buffer_id,
+                    piece.piece_char_length.saturating_sub(left_chars),
+                    piece.attributes.clone(),
+                );
+
+                let piece = &mut self.pieces[idx];
+                piece.length = split;
+                piece.piece_char_length = left_chars;
+                self.pieces.insert(idx + 1, right_piece);
+                return;
+            }
+            current_offset = piece_end;
+        }
+    }
+
     // ==================== Find & Replace ====================
 
     /// Finds all matches in the document
     pub fn find_all(&self, options: &SearchOptions) -> SearchResultSet {
-        let text = self.get_text();
-        find_all_in_text(&text, options)
+        SearchResultSet::search_piece_tree(self, options).unwrap_or_default()
     }
 
     /// Finds the next match starting from the given position
@@ -1010,7 +1668,7 @@ impl PieceTree {
             self.delete(result.start, matched_len);
 
             // Insert the replacement
-            self.insert(result.start, options.replace.clone());
+            self.insert(result.start, result.expand_replacement(&options.replace));
 
             true
         } else {
@@ -1025,27 +1683,18 @@ impl PieceTree {
             return 0;
         }
 
-        let results = self.find_all(options);
-
-        if results.results.is_empty() {
-            return 0;
-        }
+        let edits = self.find_all(options).plan_replace_all(&options.replace);
 
         // Work backwards to preserve positions
-        let mut replacements = 0;
-        for result in results.results.iter().rev() {
-            let matched_len = result.matched_text.len();
-
+        for edit in edits.iter().rev() {
             // Delete the matched text
-            self.delete(result.start, matched_len);
+            self.delete(edit.offset, edit.length);
 
             // Insert the replacement
-            self.insert(result.start, options.replace.clone());
-
-            replacements += 1;
+            self.insert(edit.offset, edit.replacement.clone());
         }
 
-        replacements
+        edits.len()
     }
 
     /// Searches for text with options, returns JSON result for FFI
@@ -1058,8 +1707,10 @@ impl PieceTree {
             case_sensitive: false,
             whole_word: false,
             regex: false,
+            wildcard: false,
             wrap_around: true,
             search_backward: false,
+            ignore_diacritics: false,
         });
 
         let results = self.find_all(&options);
@@ -1096,7 +1747,7 @@ impl PieceTree {
                  self.pieces.len(), self.total_char_count, self.total_length);
         for (i, piece) in self.pieces.iter().enumerate() {
             let buffer_idx = Self::buffer_idx(&piece.buffer_id);
-            let text = self.buffers.get(buffer_idx)
+            let text = self.buffer(buffer_idx)
                 .and_then(|b| {
                     let start = piece.start;
                     let len = piece.length.min(b.len().saturating_sub(start));
@@ -1109,6 +1760,149 @@ impl PieceTree {
     }
 }
 
+/// Counts the words in `text`: segments between Unicode word boundaries that
+/// contain a letter or digit. Punctuation is not counted, and each CJK
+/// ideograph is a word of its own.
+pub fn count_words(text: &str) -> usize {
+    text.unicode_words().count()
+}
+
+/// Returns the byte span where `before` and `after` differ as
+/// `(offset, length in before, length in after)`, aligned to character boundaries
+fn changed_span(before: &str, after: &str) -> (usize, usize, usize) {
+    let prefix: usize = before
+        .chars()
+        .zip(after.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = before[prefix..]
+        .chars()
+        .rev()
+        .zip(after[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (prefix, before.len() - prefix - suffix, after.len() - prefix - suffix)
+}
+
+/// A single step of an edit script from [`myers_diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffStep {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Shortest edit script from `a` to `b`, one step per element (Myers, 1986)
+///
+/// Uses the linear-space refinement: each middle snake splits the problem in two,
+/// so memory stays O(N + M) however different the inputs are.
+fn myers_diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffStep> {
+    let mut frontiers = Frontiers::new(a.len() + b.len());
+    let mut steps = Vec::with_capacity(a.len() + b.len());
+    diff_range(a, b, &mut frontiers, &mut steps);
+    steps
+}
+
+/// Furthest-reaching x per diagonal for the forward and backward searches
+struct Frontiers {
+    forward: Vec<usize>,
+    backward: Vec<usize>,
+    offset: isize,
+}
+
+impl Frontiers {
+    fn new(total_len: usize) -> Self {
+        let offset = max_edit_depth(total_len) + 1;
+        let len = 2 * offset as usize + 1;
+        Frontiers { forward: vec![0; len], backward: vec![0; len], offset }
+    }
+
+    fn idx(&self, k: isize) -> usize {
+        (k + self.offset) as usize
+    }
+}
+
+/// Search depth at which the forward and backward paths must have met
+fn max_edit_depth(total_len: usize) -> isize {
+    (total_len as isize + 1) / 2 + 1
+}
+
+/// Appends the edit script of `a` to `b` to `steps`
+fn diff_range<T: PartialEq>(a: &[T], b: &[T], frontiers: &mut Frontiers, steps: &mut Vec<DiffStep>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    steps.extend(std::iter::repeat_n(DiffStep::Equal, prefix));
+    if a.is_empty() {
+        steps.extend(std::iter::repeat_n(DiffStep::Insert, b.len()));
+    } else if b.is_empty() {
+        steps.extend(std::iter::repeat_n(DiffStep::Delete, a.len()));
+    } else {
+        let (x, y) = middle_snake(a, b, frontiers);
+        diff_range(&a[..x], &b[..y], frontiers, steps);
+        diff_range(&a[x..], &b[y..], frontiers, steps);
+    }
+    steps.extend(std::iter::repeat_n(DiffStep::Equal, suffix));
+}
+
+/// Finds the start of the middle snake of an optimal path from `a` to `b`, which must
+/// both be non-empty; the halves before and after it each need fewer edits than the whole
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T], frontiers: &mut Frontiers) -> (usize, usize) {
+    let (n, m) = (a.len(), b.len());
+    let delta = n as isize - m as isize;
+    let odd = delta % 2 != 0;
+    let start = frontiers.idx(1);
+    frontiers.forward[start] = 0;
+    frontiers.backward[start] = 0;
+
+    for d in 0..max_edit_depth(n + m) {
+        for k in (-d..=d).rev().step_by(2) {
+            let v = &frontiers.forward;
+            let mut x = if k == -d || (k != d && v[frontiers.idx(k - 1)] < v[frontiers.idx(k + 1)]) {
+                v[frontiers.idx(k + 1)]
+            } else {
+                v[frontiers.idx(k - 1)] + 1
+            };
+            let (x0, y0) = (x, (x as isize - k) as usize);
+            let mut y = y0;
+            while x < n && y < m && a[x] == b[y] {
+                x += 1;
+                y += 1;
+            }
+            let i = frontiers.idx(k);
+            frontiers.forward[i] = x;
+            if odd && (k - delta).abs() < d && x + frontiers.backward[frontiers.idx(delta - k)] >= n {
+                return (x0, y0);
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let v = &frontiers.backward;
+            let mut x = if k == -d || (k != d && v[frontiers.idx(k - 1)] < v[frontiers.idx(k + 1)]) {
+                v[frontiers.idx(k + 1)]
+            } else {
+                v[frontiers.idx(k - 1)] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            while x < n && y < m && a[n - x - 1] == b[m - y - 1] {
+                x += 1;
+                y += 1;
+            }
+            let i = frontiers.idx(k);
+            frontiers.backward[i] = x;
+            if !odd && (k - delta).abs() <= d && x + frontiers.forward[frontiers.idx(delta - k)] >= n {
+                return (n - x, m - y);
+            }
+        }
+    }
+
+    unreachable!("forward and backward searches always meet by depth (n + m + 1) / 2")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1439,4 +2233,674 @@ mod tests {
         assert_eq!(pt.get_selection_anchor(), 11); // end of text
         assert_eq!(pt.get_selection_active(), 11);
     }
+
+    // ==================== Attribute Span Tests ====================
+
+    fn create_formatted_tree() -> PieceTree {
+        let bold = TextAttributes { bold: Some(true), ..Default::default() };
+        let bold_italic = TextAttributes { bold: Some(true), italic: Some(true), ..Default::default() };
+        let italic = TextAttributes { italic: Some(true), ..Default::default() };
+
+        let mut pt = PieceTree::empty();
+        pt.insert_with_attrs(0, "Hello".to_string(), Some(bold));
+        pt.insert_with_attrs(5, " big".to_string(), Some(bold_italic));
+        pt.insert_with_attrs(9, " wide".to_string(), Some(italic));
+        pt.insert(14, " world".to_string());
+        pt
+    }
+
+    #[test]
+    fn test_attribute_spans_clipped_to_range() {
+        let pt = create_formatted_tree();
+        assert_eq!(pt.get_text(), "Hello big wide world");
+
+        let spans = pt.attribute_spans_in(2..12);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].0, 2..5);
+        assert_eq!(spans[0].1.bold, Some(true));
+        assert_eq!(spans[0].1.italic, None);
+        assert_eq!(spans[1].0, 5..9);
+        assert_eq!(spans[1].1.bold, Some(true));
+        assert_eq!(spans[1].1.italic, Some(true));
+        assert_eq!(spans[2].0, 9..12);
+        assert_eq!(spans[2].1.italic, Some(true));
+
+        // Unformatted text yields no spans
+        assert!(pt.attribute_spans_in(15..20).is_empty());
+    }
+
+    #[test]
+    fn test_attribute_spans_merge_adjacent_equal() {
+        let bold = TextAttributes { bold: Some(true), ..Default::default() };
+        let mut pt = PieceTree::empty();
+        pt.insert_with_attrs(0, "abc".to_string(), Some(bold.clone()));
+        pt.insert_with_attrs(3, "def".to_string(), Some(bold.clone()));
+
+        assert_eq!(pt.attribute_spans_in(0..6), vec![(0..6, bold)]);
+    }
+
+    #[test]
+    fn test_attribute_spans_serialization() {
+        let pt = create_formatted_tree();
+        let spans = pt.attribute_spans_in(4..6);
+
+        let json = serde_json::to_string(&spans).unwrap();
+        assert!(json.contains("\"bold\":true"));
+        assert!(json.contains("\"italic\":true"));
+
+        let restored: Vec<(Range<usize>, TextAttributes)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, spans);
+    }
+
+    #[test]
+    fn test_replace_middle() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert!(tree.replace(6, 5, "Velum".to_string()));
+        assert_eq!(tree.get_text(), "Hello Velum");
+        assert_eq!(tree.total_length, 11);
+        assert_eq!(tree.get_selection_range(), (11, 11));
+
+        // One undo reverts the whole replacement
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "Hello World");
+        assert!(!tree.can_undo());
+
+        assert!(tree.redo());
+        assert_eq!(tree.get_text(), "Hello Velum");
+    }
+
+    #[test]
+    fn test_replace_all_regex_captures() {
+        let mut tree = PieceTree::new("2024-01-31 and 1999-12-25".to_string());
+        let options = SearchOptions {
+            query: r"(\d{4})-(\d{2})-(\d{2})".to_string(),
+            replace: "$3/$2/$1".to_string(),
+            regex: true,
+            ..Default::default()
+        };
+
+        assert_eq!(tree.replace_all(&options), 2);
+        assert_eq!(tree.get_text(), "31/01/2024 and 25/12/1999");
+    }
+
+    #[test]
+    fn test_replace_at_start() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert!(tree.replace(0, 5, "Goodbye".to_string()));
+        assert_eq!(tree.get_text(), "Goodbye World");
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "Hello World");
+    }
+
+    #[test]
+    fn test_replace_empty_text_deletes() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert!(tree.replace(5, 6, String::new()));
+        assert_eq!(tree.get_text(), "Hello");
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "Hello World");
+    }
+
+    #[test]
+    fn test_replace_at_end_appends() {
+        let mut tree = PieceTree::new("Hello".to_string());
+        assert!(tree.replace(5, 0, " World".to_string()));
+        assert_eq!(tree.get_text(), "Hello World");
+    }
+
+    #[test]
+    fn test_replace_invalid_range() {
+        let mut tree = PieceTree::new("héllo".to_string());
+        assert!(!tree.replace(4, 5, "x".to_string()));
+        assert!(!tree.replace(2, 1, "x".to_string()));
+        assert_eq!(tree.get_text(), "héllo");
+        assert!(!tree.can_undo());
+    }
+
+    #[test]
+    fn test_chunks_match_get_text() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert_eq!(tree.chunks().collect::<String>(), tree.get_text());
+
+        tree.insert(5, ", dear".to_string());
+        tree.insert(0, ">> ".to_string());
+        assert_eq!(tree.chunks().collect::<String>(), tree.get_text());
+        assert!(tree.chunks().count() > 1);
+
+        tree.delete(3, 7);
+        tree.replace(0, 2, "«»".to_string());
+        assert_eq!(tree.chunks().collect::<String>(), tree.get_text());
+
+        tree.undo();
+        assert_eq!(tree.chunks().collect::<String>(), tree.get_text());
+    }
+
+    #[test]
+    fn test_chunks_empty_tree() {
+        let tree = PieceTree::empty();
+        assert_eq!(tree.chunks().count(), 0);
+    }
+
+    #[test]
+    fn test_chunks_range_clips_pieces() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        tree.insert(5, ", dear".to_string());
+        let text = tree.get_text();
+
+        for (offset, length) in [(0, 5), (3, 6), (4, 10), (11, 6), (0, text.len()), (15, 100)] {
+            assert_eq!(
+                tree.chunks_range(offset, length).collect::<String>(),
+                tree.get_text_range(offset, length),
+                "range {}..{}",
+                offset,
+                offset + length
+            );
+        }
+        assert_eq!(tree.chunks_range(2, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_byte_and_char_len_ascii() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert_eq!(tree.byte_len(), 11);
+        assert_eq!(tree.char_len(), 11);
+
+        tree.insert(5, ", dear".to_string());
+        tree.delete(0, 2);
+        assert_eq!(tree.byte_len(), tree.get_text().len());
+        assert_eq!(tree.char_len(), tree.get_text().chars().count());
+    }
+
+    #[test]
+    fn test_byte_and_char_len_multibyte() {
+        let mut tree = PieceTree::new("héllo wörld".to_string());
+        assert_eq!(tree.byte_len(), 13);
+        assert_eq!(tree.char_len(), 11);
+
+        tree.insert(5, " 世界".to_string());
+        assert_eq!(tree.byte_len(), 20);
+        assert_eq!(tree.char_len(), 14);
+
+        // Delete "é" (2 bytes, 1 char) splitting the original piece
+        tree.delete(1, 2);
+        assert_eq!(tree.get_text(), "hllo 世界 wörld");
+        assert_eq!(tree.byte_len(), 18);
+        assert_eq!(tree.char_len(), 13);
+        assert_eq!(tree.char_count(), 13);
+
+        // Delete across pieces: " 世界" and the following space
+        tree.delete(4, 8);
+        assert_eq!(tree.get_text(), "hllowörld");
+        assert_eq!(tree.byte_len(), tree.get_text().len());
+        assert_eq!(tree.char_len(), tree.get_text().chars().count());
+        assert_eq!(tree.char_count(), tree.char_len());
+    }
+
+    #[test]
+    fn test_char_to_byte_offset_multibyte() {
+        let tree = PieceTree::new("aé中b".to_string());
+        assert_eq!(tree.char_to_byte_offset(0), Some(0));
+        assert_eq!(tree.char_to_byte_offset(2), Some(3));
+        assert_eq!(tree.char_to_byte_offset(3), Some(6));
+        assert_eq!(tree.char_to_byte_offset(4), Some(7));
+        assert_eq!(tree.char_to_byte_offset(5), None);
+        assert_eq!(PieceTree::empty().char_to_byte_offset(0), Some(0));
+    }
+
+    #[test]
+    fn test_byte_and_char_len_empty() {
+        let tree = PieceTree::empty();
+        assert_eq!(tree.byte_len(), 0);
+        assert_eq!(tree.char_len(), 0);
+    }
+
+    #[test]
+    fn test_offset_to_position() {
+        let mut tree = PieceTree::new("ab\nçd\n".to_string());
+        tree.insert(6, "xyz".to_string());
+        assert_eq!(tree.get_text(), "ab\nçd\nxyz");
+
+        assert_eq!(tree.offset_to_position(0), (1, 1));
+        assert_eq!(tree.offset_to_position(2), (1, 3)); // end of line 1, on the newline
+        assert_eq!(tree.offset_to_position(3), (2, 1)); // start of line 2
+        assert_eq!(tree.offset_to_position(5), (2, 2)); // after the 2-byte "ç"
+        assert_eq!(tree.offset_to_position(4), (2, 1)); // inside "ç"
+        assert_eq!(tree.offset_to_position(7), (3, 1));
+        assert_eq!(tree.offset_to_position(10), (3, 4)); // end of document
+        assert_eq!(tree.offset_to_position(100), (3, 4)); // clamped
+    }
+
+    #[test]
+    fn test_position_to_offset() {
+        let mut tree = PieceTree::new("ab\nçd\n".to_string());
+        tree.insert(6, "xyz".to_string());
+
+        assert_eq!(tree.position_to_offset(1, 1), Some(0));
+        assert_eq!(tree.position_to_offset(1, 3), Some(2)); // end of line 1
+        assert_eq!(tree.position_to_offset(1, 4), None); // past end of line 1
+        assert_eq!(tree.position_to_offset(2, 1), Some(3));
+        assert_eq!(tree.position_to_offset(2, 2), Some(5));
+        assert_eq!(tree.position_to_offset(3, 4), Some(10)); // end of document
+        assert_eq!(tree.position_to_offset(3, 5), None);
+        assert_eq!(tree.position_to_offset(4, 1), None); // past the final line
+        assert_eq!(tree.position_to_offset(0, 1), None);
+        assert_eq!(tree.position_to_offset(1, 0), None);
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        let tree = PieceTree::new("line one\nzwei\n\nдва".to_string());
+        let text = tree.get_text();
+        for (offset, _) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+            let (line, column) = tree.offset_to_position(offset);
+            assert_eq!(tree.position_to_offset(line, column), Some(offset));
+        }
+        assert_eq!(PieceTree::empty().offset_to_position(0), (1, 1));
+        assert_eq!(PieceTree::empty().position_to_offset(1, 1), Some(0));
+    }
+
+    #[test]
+    fn test_revision_strictly_increases() {
+        let mut tree = PieceTree::new("Hello".to_string());
+        let mut last = tree.revision();
+
+        let mut assert_advanced = |tree: &PieceTree| {
+            assert!(tree.revision() > last, "{} <= {}", tree.revision(), last);
+            last = tree.revision();
+        };
+
+        tree.insert(5, " World".to_string());
+        assert_advanced(&tree);
+        tree.delete(0, 1);
+        assert_advanced(&tree);
+        tree.replace(0, 4, "Jell".to_string());
+        assert_advanced(&tree);
+        tree.undo();
+        assert_advanced(&tree);
+        tree.undo();
+        assert_advanced(&tree);
+        tree.redo();
+        assert_advanced(&tree);
+    }
+
+    #[test]
+    fn test_revision_unchanged_by_noop() {
+        let mut tree = PieceTree::new("Hello".to_string());
+        let revision = tree.revision();
+        tree.insert(0, String::new());
+        tree.delete(0, 0);
+        assert!(!tree.undo());
+        let _ = tree.get_text();
+        assert_eq!(tree.revision(), revision);
+    }
+
+    fn bold() -> TextAttributes {
+        TextAttributes { bold: Some(true), ..Default::default() }
+    }
+
+    #[test]
+    fn test_set_attributes_and_query() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        assert!(tree.set_attributes(6, 5, bold()));
+
+        assert_eq!(tree.attributes_at(5), TextAttributes::default());
+        assert_eq!(tree.attributes_at(6), bold());
+        assert_eq!(tree.attributes_at(10), bold());
+        assert_eq!(tree.attributes_at(11), TextAttributes::default());
+        assert_eq!(tree.get_text(), "Hello World");
+        assert_eq!(tree.char_len(), 11);
+    }
+
+    #[test]
+    fn test_set_attributes_span_moves_on_insert_before() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        tree.set_attributes(6, 5, bold());
+
+        tree.insert(0, ">> ".to_string());
+        assert_eq!(tree.attributes_at(8), TextAttributes::default());
+        assert_eq!(tree.attributes_at(9), bold());
+        assert_eq!(tree.attribute_spans_in(0..tree.len()), vec![(9..14, bold())]);
+    }
+
+    #[test]
+    fn test_set_attributes_span_truncated_by_delete_across_boundary() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        tree.set_attributes(6, 5, bold());
+
+        // Delete "o Wo", crossing into the bold span
+        tree.delete(4, 4);
+        assert_eq!(tree.get_text(), "Hellrld");
+        assert_eq!(tree.attribute_spans_in(0..tree.len()), vec![(4..7, bold())]);
+    }
+
+    #[test]
+    fn test_set_attributes_overlapping_splits_spans() {
+        let mut tree = PieceTree::new("abcdefghij".to_string());
+        let italic = TextAttributes { italic: Some(true), ..Default::default() };
+        tree.set_attributes(0, 6, bold());
+        tree.set_attributes(4, 4, italic.clone());
+
+        assert_eq!(
+            tree.attribute_spans_in(0..10),
+            vec![(0..4, bold()), (4..8, italic)]
+        );
+
+        // Clearing with default attributes removes formatting
+        tree.set_attributes(0, 10, TextAttributes::default());
+        assert!(tree.attribute_spans_in(0..10).is_empty());
+    }
+
+    #[test]
+    fn test_set_attributes_invalid_range() {
+        let mut tree = PieceTree::new("héllo".to_string());
+        assert!(!tree.set_attributes(0, 0, bold()));
+        assert!(!tree.set_attributes(4, 10, bold()));
+        assert!(!tree.set_attributes(2, 1, bold())); // inside "é"
+        assert_eq!(tree.piece_count(), 1);
+    }
+
+    #[test]
+    fn test_from_mmap_reads_small_range_of_large_file() {
+        let path = std::env::temp_dir().join(format!("velum_mmap_{}.txt", std::process::id()));
+        let line = "The quick brown fox jumps over the lazy dog.\n";
+        let content = line.repeat(100_000); // ~4.5 MB
+        std::fs::write(&path, &content).unwrap();
+
+        let mut tree = PieceTree::from_mmap(&path).unwrap();
+        assert!(tree.buffers[0].is_empty());
+        assert_eq!(tree.total_length, content.len());
+
+        // Reading a range borrows just those bytes from the mapping; nothing is copied
+        let offset = line.len() * 50_000 + 4;
+        assert_eq!(tree.get_text_range(offset, 5), "quick");
+        let chunks: Vec<&str> = tree.chunks_range(offset, 5).collect();
+        assert_eq!(chunks, ["quick"]);
+        let mapped = tree.buffer(0).unwrap().as_bytes().as_ptr_range();
+        assert!(mapped.contains(&chunks[0].as_ptr()));
+        assert!(tree.buffers.iter().all(String::is_empty));
+
+        // Edits go to an add buffer; the mapped original is untouched
+        tree.insert(offset, "very ".to_string());
+        assert_eq!(tree.get_text_range(offset, 11), "very quick ");
+        assert_eq!(tree.buffers.last().map(String::as_str), Some("very "));
+        assert_eq!(tree.get_text_range(0, 9), "The quick");
+
+        drop(tree);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_from_mmap_rejects_invalid_utf8() {
+        let path = std::env::temp_dir().join(format!("velum_mmap_bad_{}.txt", std::process::id()));
+        std::fs::write(&path, [0x66, 0x6f, 0xff, 0x6f]).unwrap();
+
+        let err = PieceTree::from_mmap(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut tree = PieceTree::new("Hello World".to_string());
+        tree.insert(5, ",".to_string());
+        let snapshot = tree.snapshot();
+        assert_eq!(snapshot.char_count(), 12);
+
+        tree.insert(12, "!".to_string());
+        tree.delete(0, 7);
+        assert_eq!(tree.get_text(), "World!");
+
+        let revision = tree.revision();
+        assert!(tree.restore(&snapshot));
+        assert_eq!(tree.get_text(), "Hello, World");
+        assert_eq!(tree.total_char_count, 12);
+        assert!(tree.revision() > revision);
+
+        // The restore itself is a single undo step
+        tree.undo();
+        assert_eq!(tree.get_text(), "World!");
+        tree.redo();
+        assert_eq!(tree.get_text(), "Hello, World");
+    }
+
+    #[test]
+    fn test_snapshot_shares_pieces() {
+        let mut tree = PieceTree::new("abc".to_string());
+        tree.insert(3, "def".to_string());
+        let snapshot = tree.snapshot();
+        let copy = snapshot.clone();
+        assert!(Arc::ptr_eq(&snapshot.pieces, &copy.pieces));
+
+        // Edits after the snapshot leave it untouched
+        tree.insert(0, "xyz".to_string());
+        assert_eq!(snapshot.pieces.len(), 2);
+        assert!(tree.restore(&copy));
+        assert_eq!(tree.get_text(), "abcdef");
+    }
+
+    #[test]
+    fn test_restore_rejects_foreign_snapshot() {
+        let mut other = PieceTree::new("abc".to_string());
+        other.insert(3, "def".to_string());
+        other.insert(6, "ghi".to_string());
+        let snapshot = other.snapshot();
+
+        let mut tree = PieceTree::new("abc".to_string());
+        assert!(!tree.restore(&snapshot));
+        assert_eq!(tree.get_text(), "abc");
+    }
+
+    fn assert_diff_round_trip(before: &str, after: &str) -> Vec<DiffOp> {
+        let mut tree = PieceTree::new(before.to_string());
+        let other = PieceTree::new(after.to_string());
+        let ops = tree.diff(&other);
+        assert!(tree.apply_diff(&ops));
+        assert_eq!(tree.get_text(), after);
+        ops
+    }
+
+    #[test]
+    fn test_diff_identical_is_empty() {
+        assert!(assert_diff_round_trip("same text", "same text").is_empty());
+        assert!(assert_diff_round_trip("", "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_single_insert_and_delete() {
+        assert_eq!(
+            assert_diff_round_trip("Hello World", "Hello, World"),
+            vec![DiffOp::Insert { offset: 5, text: ",".to_string() }]
+        );
+        assert_eq!(
+            assert_diff_round_trip("Hello, World", "Hello World"),
+            vec![DiffOp::Delete { offset: 5, length: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_diff_round_trips() {
+        assert_diff_round_trip("", "new document");
+        assert_diff_round_trip("old document", "");
+        assert_diff_round_trip("ABCABBA", "CBABAC");
+        assert_diff_round_trip("the quick brown fox", "a quick red fox jumps");
+        assert_diff_round_trip("line one\nline two\n", "line zero\nline one\nline 2\n");
+        assert_diff_round_trip("naïve café", "naive cafés ☕");
+    }
+
+    #[test]
+    fn test_diff_is_minimal() {
+        // Classic example from the Myers paper: edit distance 5
+        let ops = assert_diff_round_trip("ABCABBA", "CBABAC");
+        let edits: usize = ops
+            .iter()
+            .map(|op| match op {
+                DiffOp::Insert { text, .. } => text.chars().count(),
+                DiffOp::Delete { length, .. } => *length,
+            })
+            .sum();
+        assert_eq!(edits, 5);
+    }
+
+    #[test]
+    fn test_myers_diff_matches_edit_distance() {
+        fn edits(a: &str, b: &str) -> usize {
+            let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+            let steps = myers_diff(&a, &b);
+            let deleted = steps.iter().filter(|s| **s == DiffStep::Delete).count();
+            let inserted = steps.iter().filter(|s| **s == DiffStep::Insert).count();
+            let equal = steps.len() - deleted - inserted;
+            assert_eq!(equal + deleted, a.len());
+            assert_eq!(equal + inserted, b.len());
+            deleted + inserted
+        }
+
+        assert_eq!(edits("ABCABBA", "CBABAC"), 5);
+        assert_eq!(edits("abc", "xyz"), 6);
+        assert_eq!(edits("kitten", "sitting"), 5);
+        assert_eq!(edits("abcd", "acbd"), 2);
+        assert_eq!(edits("a", "b"), 2);
+    }
+
+    #[test]
+    fn test_diff_large_dissimilar_documents() {
+        // Completely different texts are the worst case for the edit depth
+        let before: String = (0..5_000).map(|i| if i % 7 == 0 { 'a' } else { 'b' }).collect();
+        let after: String = (0..5_000).map(|i| if i % 5 == 0 { 'c' } else { 'd' }).collect();
+        let ops = assert_diff_round_trip(&before, &after);
+        let removed: usize = ops
+            .iter()
+            .map(|op| match op {
+                DiffOp::Delete { length, .. } => *length,
+                DiffOp::Insert { .. } => 0,
+            })
+            .sum();
+        assert_eq!(removed, before.len());
+    }
+
+    #[test]
+    fn test_apply_diff_is_single_undo_step() {
+        let mut tree = PieceTree::new("one two three".to_string());
+        let other = PieceTree::new("one 2 three four".to_string());
+        let ops = tree.diff(&other);
+        assert!(tree.apply_diff(&ops));
+        tree.undo();
+        assert_eq!(tree.get_text(), "one two three");
+
+        // Out-of-range scripts are rejected without changing the document
+        assert!(!tree.apply_diff(&[DiffOp::Delete { offset: 10, length: 50 }]));
+        assert_eq!(tree.get_text(), "one two three");
+    }
+
+    fn type_text(tree: &mut PieceTree, text: &str) {
+        for c in text.chars() {
+            let offset = tree.total_char_count;
+            tree.insert(offset, c.to_string());
+        }
+    }
+
+    #[test]
+    fn test_count_words() {
+        assert_eq!(count_words("Hello, world! It's 3.5 times - really."), 6);
+        assert_eq!(count_words("你好，世界\n日本語"), 7);
+        assert_eq!(count_words(" \t - \n"), 0);
+    }
+
+    #[test]
+    fn test_word_granularity_undoes_one_word_at_a_time() {
+        let mut tree = PieceTree::empty();
+        tree.set_undo_granularity(UndoGranularity::Word);
+        type_text(&mut tree, "hello world");
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "hello ");
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "");
+        assert!(!tree.can_undo());
+
+        assert!(tree.redo());
+        assert_eq!(tree.get_text(), "hello ");
+        assert!(tree.redo());
+        assert_eq!(tree.get_text(), "hello world");
+    }
+
+    #[test]
+    fn test_character_granularity_undoes_each_insert() {
+        let mut tree = PieceTree::empty();
+        type_text(&mut tree, "abc");
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "ab");
+    }
+
+    #[test]
+    fn test_paragraph_granularity_breaks_at_newlines() {
+        let mut tree = PieceTree::empty();
+        tree.set_undo_granularity(UndoGranularity::Paragraph);
+        type_text(&mut tree, "one two\nthree");
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "one two\n");
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "");
+    }
+
+    #[test]
+    fn test_typing_run_stops_at_non_contiguous_insert() {
+        let mut tree = PieceTree::empty();
+        tree.set_undo_granularity(UndoGranularity::Word);
+        type_text(&mut tree, "abc");
+        tree.insert(0, "x".to_string());
+
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "abc");
+        assert!(tree.undo());
+        assert_eq!(tree.get_text(), "");
+    }
+
+    fn assert_lines_match_split(tree: &PieceTree) {
+        let text = tree.get_text();
+        let mut expected: Vec<&str> = text.split('\n').collect();
+        if expected.last() == Some(&"") {
+            expected.pop();
+        }
+
+        assert_eq!(tree.get_lines(1..expected.len() + 1), expected);
+        for (i, line) in expected.iter().enumerate() {
+            assert_eq!(tree.get_line(i + 1).as_deref(), Some(*line));
+        }
+        assert_eq!(tree.get_line(expected.len() + 1), None);
+    }
+
+    #[test]
+    fn test_lines_follow_edits_that_add_and_remove_newlines() {
+        let mut tree = PieceTree::new("first\nsecond\nthird".to_string());
+        assert_lines_match_split(&tree);
+
+        tree.insert(3, "\nfü\n\n".to_string());
+        assert_lines_match_split(&tree);
+        assert_eq!(tree.get_line_count(), 6);
+
+        tree.insert(tree.get_text().chars().count(), "\nlast\n".to_string());
+        assert_lines_match_split(&tree);
+
+        tree.delete(2, 5);
+        assert_lines_match_split(&tree);
+
+        assert!(tree.undo());
+        assert_lines_match_split(&tree);
+        assert_eq!(tree.get_line(2).as_deref(), Some("fü"));
+    }
+
+    #[test]
+    fn test_get_lines_clamps_range() {
+        let tree = PieceTree::new("a\nb\nc".to_string());
+
+        assert_eq!(tree.get_lines(0..3), vec!["a", "b"]);
+        assert_eq!(tree.get_lines(2..10), vec!["b", "c"]);
+        assert!(tree.get_lines(4..6).is_empty());
+        assert!(PieceTree::empty().get_lines(1..2).is_empty());
+    }
 }
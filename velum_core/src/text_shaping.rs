@@ -1,12 +1,16 @@
-use harfbuzz_rs::{Face, Font, Owned, UnicodeBuffer, shape};
-use std::path::Path;
+use harfbuzz_rs::{Blob, Direction, Face, Font, Language, Owned, Tag, UnicodeBuffer, shape};
+use harfbuzz_rs::Feature as HbFeature;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Represents a shaped glyph with positioning information
 #[derive(Debug, Clone, Copy)]
 pub struct GlyphInfo {
     /// The glyph ID in the font
     pub codepoint: u32,
-    /// The cluster index (character index) this glyph belongs to
+    /// The cluster this glyph belongs to, as a byte offset into the shaped text
     pub cluster: u32,
     /// X advance width in logical pixels
     pub x_advance: f32,
@@ -16,130 +20,461 @@ pub struct GlyphInfo {
     pub x_offset: f32,
     /// Y offset in logical pixels
     pub y_offset: f32,
+    /// Size of this glyph relative to the shaper's font size
+    /// (below 1.0 for synthesized small caps)
+    pub scale: f32,
 }
 
-/// A text shaper that uses HarfBuzz
-#[derive(Debug)]
-pub struct TextShaper<'a> {
-    /// The HarfBuzz font (None if no font loaded)
-    /// Owned type manages the font data lifetime
-    font: Option<Owned<Font<'a>>>,
-    /// Units per EM for the current font
-    upem: i32,
-    /// Current font size in points
-    font_size_pt: f32,
-    /// Scaling factor from font units to logical pixels
-    /// pixel = unit * scale_factor
-    scale_factor: f32,
+/// Direction in which text is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+    TopToBottom,
+    BottomToTop,
+}
+
+impl TextDirection {
+    fn to_hb(self) -> Direction {
+        match self {
+            TextDirection::LeftToRight => Direction::Ltr,
+            TextDirection::RightToLeft => Direction::Rtl,
+            TextDirection::TopToBottom => Direction::Ttb,
+            TextDirection::BottomToTop => Direction::Btt,
+        }
+    }
+
+    fn from_hb(direction: Direction) -> Option<Self> {
+        match direction {
+            Direction::Ltr => Some(TextDirection::LeftToRight),
+            Direction::Rtl => Some(TextDirection::RightToLeft),
+            Direction::Ttb => Some(TextDirection::TopToBottom),
+            Direction::Btt => Some(TextDirection::BottomToTop),
+            Direction::Invalid => None,
+        }
+    }
+}
+
+/// An OpenType feature setting, such as `liga` off or `smcp` on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feature {
+    /// Four-byte OpenType feature tag
+    pub tag: [u8; 4],
+    /// 0 disables the feature, 1 enables it; some features take other values
+    pub value: u32,
+}
+
+impl Feature {
+    pub fn new(tag: &[u8; 4], value: u32) -> Self {
+        Feature { tag: *tag, value }
+    }
+
+    pub fn enabled(tag: &[u8; 4]) -> Self {
+        Self::new(tag, 1)
+    }
+
+    pub fn disabled(tag: &[u8; 4]) -> Self {
+        Self::new(tag, 0)
+    }
+
+    fn to_hb(self) -> HbFeature {
+        HbFeature::new(&self.tag, self.value, ..)
+    }
 }
 
-impl<'a> TextShaper<'a> {
-    /// Creates a new text shaper with a default system font
+/// Segment properties applied to the HarfBuzz buffer before shaping.
+/// Unset (or unparsable) properties are guessed from the text.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ShapeOptions {
+    /// ISO 15924 script tag, e.g. "Arab" or "Latn"
+    pub script: Option<String>,
+    /// BCP 47 language tag, e.g. "ar" or "en-US"
+    pub language: Option<String>,
+    /// Layout direction
+    pub direction: Option<TextDirection>,
+    /// Features applied on top of the shaper's own feature settings
+    pub features: Vec<Feature>,
+}
+
+impl ShapeOptions {
     pub fn new() -> Self {
-        Self::try_new().unwrap_or_else(|| {
-            // Fallback: use minimal shaper without actual font
-            TextShaper::fallback()
-        })
+        Self::default()
     }
 
-    /// Creates a new text shaper, returning None if no font can be loaded
-    pub fn try_new() -> Option<Self> {
-        // Try to load a font from common locations
-        #[cfg(target_os = "macos")]
-        {
-            if let Some(path) = Self::find_macOS_font() {
-                return Self::load_from_path(&path);
-            }
+    pub fn with_script(mut self, script: impl Into<String>) -> Self {
+        self.script = Some(script.into());
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_direction(mut self, direction: TextDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn with_feature(mut self, feature: Feature) -> Self {
+        self.features.push(feature);
+        self
+    }
+
+    /// Builds a HarfBuzz buffer for `text` with these properties set
+    fn buffer(&self, text: &str) -> UnicodeBuffer {
+        let mut buffer = UnicodeBuffer::new().add_str(text);
+        if let Some(script) = self.script.as_deref().and_then(|s| s.parse::<Tag>().ok()) {
+            buffer = buffer.set_script(script);
         }
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(path) = Self::find_windows_font() {
-                return Self::load_from_path(&path);
-            }
+        if let Some(language) = self.language.as_deref().and_then(|l| l.parse::<Language>().ok()) {
+            buffer = buffer.set_language(language);
         }
-        #[cfg(target_os = "linux")]
-        {
-            if let Some(path) = Self::find_linux_font() {
-                return Self::load_from_path(&path);
+        if let Some(direction) = self.direction {
+            buffer = buffer.set_direction(direction.to_hb());
+        }
+        buffer.guess_segment_properties()
+    }
+}
+
+/// Vertical font metrics in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    /// Distance from the baseline to the top of the tallest glyphs (positive)
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of descenders; negative,
+    /// following the font's hhea/OS/2 sign convention
+    pub descent: f32,
+    /// Extra spacing the font recommends between lines
+    pub line_gap: f32,
+}
+
+impl FontMetrics {
+    /// Baseline-to-baseline distance recommended by the font
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/// Maximum number of shaped strings kept by a shaper's cache
+pub const SHAPE_CACHE_CAPACITY: usize = 1024;
+
+/// Strings longer than this (in bytes) are shaped without caching
+const SHAPE_CACHE_MAX_TEXT_LEN: usize = 256;
+
+/// Hit/miss counters of a shaper's cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ShapeCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of cached strings
+    pub entries: usize,
+}
+
+/// Shaped text keyed by string, font size and shaping options
+type ShapeKey = (String, u32, ShapeOptions);
+
+/// Least-recently-used cache of shaping results
+#[derive(Debug, Default)]
+struct ShapeCache {
+    entries: HashMap<ShapeKey, (u64, f32, Vec<GlyphInfo>)>,
+    /// Entries ordered by last use, oldest first
+    recency: BTreeMap<u64, ShapeKey>,
+    clock: u64,
+    stats: ShapeCacheStats,
+}
+
+impl ShapeCache {
+    fn get(&mut self, key: &ShapeKey) -> Option<(f32, Vec<GlyphInfo>)> {
+        self.clock += 1;
+        match self.entries.get_mut(key) {
+            Some((used, width, glyphs)) => {
+                let previous = std::mem::replace(used, self.clock);
+                let key = self.recency.remove(&previous).expect("cache entry has a recency slot");
+                self.recency.insert(self.clock, key);
+                self.stats.hits += 1;
+                Some((*width, glyphs.clone()))
+            }
+            None => {
+                self.stats.misses += 1;
+                None
             }
         }
+    }
 
-        // Try any available font in system paths
-        if let Some(path) = Self::find_any_system_font() {
-            return Self::load_from_path(&path);
+    fn insert(&mut self, key: ShapeKey, width: f32, glyphs: Vec<GlyphInfo>) {
+        if self.entries.len() >= SHAPE_CACHE_CAPACITY {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
         }
+        self.clock += 1;
+        self.recency.insert(self.clock, key.clone());
+        self.entries.insert(key, (self.clock, width, glyphs));
+    }
 
-        None
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
     }
+}
 
-    #[cfg(target_os = "macos")]
-    fn find_macOS_font() -> Option<&'static str> {
-        // Try common macOS font paths
-        let paths = [
-            "/System/Library/Fonts/Supplemental/Arial.ttf",
-            "/Library/Fonts/Arial.ttf",
-            "/System/Library/Fonts/Helvetica.dfont",
-        ];
-        for path in &paths {
-            if Path::new(path).exists() {
-                return Some(path);
+/// Glyph ID HarfBuzz reports for characters missing from a font
+const NOTDEF_GLYPH: u32 = 0;
+
+/// Relative size of small-caps letters synthesized from lowercase text
+pub const SMALL_CAPS_SCALE: f32 = 0.7;
+
+/// Capitalization applied to text before shaping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum CapsStyle {
+    /// Text is shaped as written
+    #[default]
+    Normal,
+    /// All letters are uppercased (`<w:caps/>`)
+    AllCaps,
+    /// All letters are uppercased; originally-lowercase letters are drawn
+    /// at [`SMALL_CAPS_SCALE`] (`<w:smallCaps/>`)
+    SmallCaps,
+}
+
+/// Errors raised while loading a font for shaping
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShapingError {
+    /// None of the searched locations holds a font file
+    NoFontFound { searched: Vec<PathBuf> },
+    /// A font file was found but could not be read
+    FontUnreadable { path: PathBuf, message: String },
+}
+
+impl std::fmt::Display for ShapingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShapingError::NoFontFound { searched } => {
+                write!(f, "No font found in {} searched location(s)", searched.len())
+            }
+            ShapingError::FontUnreadable { path, message } => {
+                write!(f, "Failed to read font {}: {}", path.display(), message)
             }
         }
-        None
     }
+}
+
+impl std::error::Error for ShapingError {}
+
+/// Font file extensions picked up when searching a directory
+const FONT_EXTENSIONS: [&str; 3] = ["ttf", "otf", "ttc"];
+
+/// Directories added with [`TextShaper::register_font_dir`]
+static REGISTERED_FONT_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
 
-    #[cfg(target_os = "windows")]
-    fn find_windows_font() -> Option<&'static str> {
-        let path = "C:\\Windows\\Fonts\\arial.ttf";
-        if Path::new(path).exists() {
-            return Some(path);
+/// Font files under `dir`, recursively and in name order; symlinked directories are not followed
+fn font_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            files.extend(font_files_in(&path));
+        } else if path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| FONT_EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(ext)))
+        {
+            files.push(path);
         }
-        None
     }
+    files
+}
 
-    #[cfg(target_os = "linux")]
-    fn find_linux_font() -> Option<&'static str> {
-        let paths = [
-            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
-            "/usr/share/fonts/liberation/LiberationSans-Regular.ttf",
-        ];
-        for path in &paths {
-            if Path::new(path).exists() {
-                return Some(path);
+/// Resolve a leading `~` against `home`; `None` when it is needed but unknown
+fn expand_home(path: &str, home: Option<&Path>) -> Option<PathBuf> {
+    match path.strip_prefix('~') {
+        Some(rest) => home.map(|home| home.join(rest.trim_start_matches(['/', '\\']))),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+/// The `<dir>` entries of a fontconfig `fonts.conf`
+///
+/// Relative directories resolve against `base`, the configuration's own
+/// directory, or against the XDG data directory with `prefix="xdg"`.
+/// `<include>`d configuration files are not followed.
+fn parse_fontconfig_dirs(xml: &str, base: &Path, home: Option<&Path>) -> Vec<PathBuf> {
+    let dir_pattern = regex::Regex::new(r#"<dir(\s[^>]*)?>\s*([^<]*?)\s*</dir>"#).unwrap();
+    let xdg_data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(".local/share")));
+
+    dir_pattern.captures_iter(xml)
+        .filter_map(|caps| {
+            let attrs = caps.get(1).map_or("", |m| m.as_str());
+            let dir = caps.get(2)?.as_str().replace("&amp;", "&");
+            if dir.is_empty() {
+                return None;
             }
+
+            let path = expand_home(&dir, home)?;
+            if path.is_absolute() {
+                Some(path)
+            } else if attrs.contains(r#"prefix="xdg""#) {
+                xdg_data_home.as_ref().map(|data| data.join(path))
+            } else {
+                Some(base.join(path))
+            }
+        })
+        .collect()
+}
+
+/// A text shaper that uses HarfBuzz
+#[derive(Debug)]
+pub struct TextShaper {
+    /// The HarfBuzz font (None if no font loaded)
+    /// The font owns its data, which is freed when the shaper is dropped
+    font: Option<Owned<Font<'static>>>,
+    /// Units per EM for the current font
+    upem: i32,
+    /// Current font size in points
+    font_size_pt: f32,
+    /// Scaling factor from font units to logical pixels
+    /// pixel = unit * scale_factor
+    scale_factor: f32,
+    /// Fonts tried in order for characters the primary font lacks
+    fallbacks: Vec<Owned<Font<'static>>>,
+    /// Feature settings applied to every shaping call
+    features: Vec<Feature>,
+    /// Recently shaped strings; cleared whenever fonts, size or features change
+    cache: Mutex<ShapeCache>,
+}
+
+impl TextShaper {
+    /// Creates a new text shaper with a default system font, falling back to
+    /// estimated character widths when none can be loaded
+    pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|_| TextShaper::fallback())
+    }
+
+    /// Creates a new text shaper from the first loadable system font
+    ///
+    /// Directories added with [`Self::register_font_dir`] are searched first,
+    /// then the platform's preferred fonts, the directories listed in the
+    /// fontconfig configuration and the platform's font directories.
+    ///
+    /// Fails instead of falling back when no font exists, e.g. in headless CI
+    /// environments; use `.ok()` where an `Option` is more convenient.
+    pub fn try_new() -> Result<Self, ShapingError> {
+        Self::from_font_paths(&Self::system_font_paths())
+    }
+
+    /// Creates a text shaper from the first usable font among `paths`
+    ///
+    /// Files are loaded directly; directories are searched recursively, in
+    /// name order, for `.ttf`, `.otf` and `.ttc` files.
+    pub fn with_search_paths(paths: &[PathBuf], size_pt: f32) -> Result<Self, ShapingError> {
+        let mut shaper = Self::from_font_paths(paths)?;
+        shaper.set_font_size(size_pt);
+        Ok(shaper)
+    }
+
+    /// Adds a directory searched before the system locations by [`Self::new`]
+    /// and [`Self::try_new`], e.g. for fonts installed alongside the application
+    pub fn register_font_dir(dir: impl Into<PathBuf>) {
+        let dir = dir.into();
+        let mut dirs = REGISTERED_FONT_DIRS.lock().unwrap_or_else(|e| e.into_inner());
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
         }
-        None
     }
 
-    fn find_any_system_font() -> Option<&'static str> {
-        // Fallback: try common paths on any platform
+    /// Font locations searched by [`Self::try_new`], in order of preference
+    fn system_font_paths() -> Vec<PathBuf> {
         #[cfg(target_os = "macos")]
-        return Self::find_macOS_font();
-
+        let (preferred, dirs) = (
+            [
+                "/System/Library/Fonts/Supplemental/Arial.ttf",
+                "/Library/Fonts/Arial.ttf",
+                "/System/Library/Fonts/Helvetica.dfont",
+            ],
+            ["/System/Library/Fonts", "/Library/Fonts", "~/Library/Fonts"],
+        );
         #[cfg(target_os = "windows")]
-        return Self::find_windows_font();
-
+        let (preferred, dirs) = (["C:\\Windows\\Fonts\\arial.ttf"], ["C:\\Windows\\Fonts"]);
         #[cfg(target_os = "linux")]
-        return Self::find_linux_font();
-
+        let (preferred, dirs) = (
+            [
+                "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+                "/usr/share/fonts/liberation/LiberationSans-Regular.ttf",
+            ],
+            ["~/.local/share/fonts", "~/.fonts", "/usr/local/share/fonts", "/usr/share/fonts"],
+        );
         #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-        None
+        let (preferred, dirs): ([&str; 0], [&str; 0]) = ([], []);
+
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let mut paths = REGISTERED_FONT_DIRS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        paths.extend(preferred.iter().map(PathBuf::from));
+        paths.extend(Self::fontconfig_dirs(home.as_deref()));
+        paths.extend(dirs.iter().filter_map(|dir| expand_home(dir, home.as_deref())));
+        paths
     }
 
-    /// Load font from a specific path
-    fn load_from_path(path: &str) -> Option<Self> {
-        match std::fs::read(path) {
-            Ok(bytes) => {
-                // Leak the bytes to get static lifetime - acceptable for font data
-                // that is kept for the lifetime of the application
-                let font_data: &'static [u8] = Box::leak(bytes.into_boxed_slice());
-                Some(Self::new_from_font_data(font_data, 12.0))
-            }
-            Err(e) => {
-                eprintln!("Failed to load font from {}: {}", path, e);
-                None
+    /// Font directories from the fontconfig configuration named by
+    /// `FONTCONFIG_FILE`, `FONTCONFIG_PATH` or the system default
+    fn fontconfig_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+        let config = match (std::env::var_os("FONTCONFIG_FILE"), std::env::var_os("FONTCONFIG_PATH")) {
+            (Some(file), _) => PathBuf::from(file),
+            (None, Some(dir)) => PathBuf::from(dir).join("fonts.conf"),
+            (None, None) if cfg!(unix) => PathBuf::from("/etc/fonts/fonts.conf"),
+            (None, None) => return Vec::new(),
+        };
+
+        match std::fs::read_to_string(&config) {
+            Ok(xml) => parse_fontconfig_dirs(&xml, config.parent().unwrap_or(Path::new("")), home),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Loads the first usable font among `paths`, searching directories for font files
+    fn from_font_paths(paths: &[PathBuf]) -> Result<Self, ShapingError> {
+        let mut last_error = None;
+
+        for path in paths {
+            let candidates = if path.is_dir() {
+                font_files_in(path)
+            } else if path.is_file() {
+                vec![path.clone()]
+            } else {
+                continue;
+            };
+
+            for candidate in candidates {
+                match Self::load_from_path(&candidate) {
+                    Ok(shaper) => return Ok(shaper),
+                    Err(error) => last_error = Some(error),
+                }
             }
         }
+
+        Err(last_error.unwrap_or_else(|| ShapingError::NoFontFound { searched: paths.to_vec() }))
+    }
+
+    /// Load font from a specific path, rejecting files without any glyphs
+    fn load_from_path(path: &Path) -> Result<Self, ShapingError> {
+        let unreadable = |message: String| ShapingError::FontUnreadable {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let bytes = std::fs::read(path).map_err(|error| unreadable(error.to_string()))?;
+        let shaper = Self::from_owned_bytes(bytes, 0, 12.0);
+        match &shaper.font {
+            Some(font) if font.face().glyph_count() > 0 => Ok(shaper),
+            _ => Err(unreadable("not a usable font file".to_string())),
+        }
     }
 
     /// Creates a fallback shaper that uses estimated character widths
@@ -149,37 +484,138 @@ impl<'a> TextShaper<'a> {
             upem: 1000,
             font_size_pt: 12.0,
             scale_factor: 1.0,
+            fallbacks: Vec::new(),
+            features: Vec::new(),
+            cache: Mutex::default(),
         }
     }
 
-    /// Creates a TextShaper from font data bytes
-    fn new_from_font_data(bytes: &'static [u8], font_size_pt: f32) -> Self {
+    /// Creates a TextShaper that takes ownership of font data, so the font
+    /// is released when the shaper is dropped
+    pub fn from_owned_bytes(data: Vec<u8>, face_index: u32, font_size_pt: f32) -> Self {
         // Only create font if we have valid bytes
-        if bytes.is_empty() {
+        if data.is_empty() {
             return TextShaper::fallback();
         }
 
-        let face = Face::from_bytes(bytes, 0);
+        let font = Self::load_font(data, face_index);
+        let upem = font.scale().0;
+
+        TextShaper {
+            font: Some(font),
+            upem,
+            font_size_pt,
+            scale_factor: Self::scale_for(font_size_pt, upem),
+            fallbacks: Vec::new(),
+            features: Vec::new(),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Creates a HarfBuzz font that owns `data`, scaled to font units
+    fn load_font(data: Vec<u8>, face_index: u32) -> Owned<Font<'static>> {
+        let blob = Blob::with_bytes_owned(Arc::new(data), |data| data.as_slice());
+        let face = Face::new(blob, face_index);
         let mut font = Font::new(face);
         let upem = font.scale().0.max(1);  // Avoid division by zero
         font.set_scale(upem, upem);
+        font
+    }
+
+    /// Appends a font to the fallback chain, used for characters that
+    /// the primary font and earlier fallbacks lack
+    pub fn add_fallback(&mut self, data: Vec<u8>, face_index: u32) {
+        if !data.is_empty() {
+            self.fallbacks.push(Self::load_font(data, face_index));
+            self.clear_shape_cache();
+        }
+    }
 
+    /// Appends the font file at `path` to the fallback chain
+    pub fn add_fallback_from_path(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let data = std::fs::read(path)?;
+        self.add_fallback(data, 0);
+        Ok(())
+    }
+
+    /// Number of fonts in the fallback chain
+    pub fn fallback_count(&self) -> usize {
+        self.fallbacks.len()
+    }
+
+    /// Computes the font-unit to logical-pixel scale for a size in points
+    fn scale_for(font_size_pt: f32, upem: i32) -> f32 {
         let pixels_per_em = font_size_pt * (96.0 / 72.0);
-        let scale_factor = pixels_per_em / (upem as f32);
+        pixels_per_em / (upem as f32)
+    }
 
-        TextShaper {
-            font: Some(font),
-            upem,
-            font_size_pt,
-            scale_factor,
+    /// Current font size in points
+    pub fn font_size(&self) -> f32 {
+        self.font_size_pt
+    }
+
+    /// Changes the font size, keeping the loaded face
+    pub fn set_font_size(&mut self, pt: f32) {
+        self.font_size_pt = pt;
+        if self.font.is_some() {
+            self.scale_factor = Self::scale_for(pt, self.upem);
+        }
+        self.clear_shape_cache();
+    }
+
+    /// Drops all cached shaping results
+    pub fn clear_shape_cache(&mut self) {
+        self.cache.get_mut().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Hit/miss counters and size of the shaping cache
+    pub fn shape_cache_stats(&self) -> ShapeCacheStats {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        ShapeCacheStats {
+            entries: cache.entries.len(),
+            ..cache.stats
         }
     }
 
     /// Create from specific bytes (for testing or specific loading)
     pub fn new_from_bytes(bytes: &[u8], font_size_pt: f32) -> Self {
-        // Copy bytes and leak them to get static lifetime
-        let font_data: &'static [u8] = Box::leak(bytes.to_vec().into_boxed_slice());
-        Self::new_from_font_data(font_data, font_size_pt)
+        Self::from_owned_bytes(bytes.to_vec(), 0, font_size_pt)
+    }
+
+    /// Returns the primary font's vertical metrics, read from its hhea/OS/2
+    /// tables. Without a font, metrics are estimated from the font size.
+    pub fn metrics(&self) -> FontMetrics {
+        let pixels_per_em = self.font_size_pt * (96.0 / 72.0);
+        match self.font.as_ref().and_then(|font| font.get_font_h_extents()) {
+            Some(extents) => FontMetrics {
+                ascent: extents.ascender as f32 * self.scale_factor,
+                descent: extents.descender as f32 * self.scale_factor,
+                line_gap: extents.line_gap as f32 * self.scale_factor,
+            },
+            None => FontMetrics {
+                ascent: pixels_per_em * 0.8,
+                descent: -pixels_per_em * 0.2,
+                line_gap: 0.0,
+            },
+        }
+    }
+
+    /// Sets a feature for every subsequent shaping call, replacing any
+    /// earlier setting of the same tag
+    pub fn set_feature(&mut self, feature: Feature) {
+        self.features.retain(|f| f.tag != feature.tag);
+        self.features.push(feature);
+        self.clear_shape_cache();
+    }
+
+    /// Enables or disables pair kerning (`kern`)
+    pub fn set_kerning(&mut self, enabled: bool) {
+        self.set_feature(Feature::new(b"kern", enabled as u32));
+    }
+
+    /// Enables or disables standard ligatures (`liga`)
+    pub fn set_ligatures(&mut self, enabled: bool) {
+        self.set_feature(Feature::new(b"liga", enabled as u32));
     }
 
     /// Check if a font is loaded
@@ -189,6 +625,22 @@ impl<'a> TextShaper<'a> {
 
     /// Shapes text and returns the total width and glyph infos in logical pixels
     pub fn shape(&self, text: &str) -> (f32, Vec<GlyphInfo>) {
+        self.shape_with_options(text, &ShapeOptions::default())
+    }
+
+    /// Shapes text with additional OpenType feature settings, which take
+    /// precedence over the shaper's own
+    pub fn shape_with_features(&self, text: &str, features: &[Feature]) -> (f32, Vec<GlyphInfo>) {
+        let options = ShapeOptions {
+            features: features.to_vec(),
+            ..ShapeOptions::default()
+        };
+        self.shape_with_options(text, &options)
+    }
+
+    /// Shapes text with explicit script, language and direction. Glyphs are
+    /// returned in visual order, so right-to-left text has descending clusters.
+    pub fn shape_with_options(&self, text: &str, options: &ShapeOptions) -> (f32, Vec<GlyphInfo>) {
         // For empty text or fallback fonts, use estimated widths
         if text.is_empty() {
             return (0.0, Vec::new());
@@ -199,24 +651,65 @@ impl<'a> TextShaper<'a> {
             return self.estimate_widths(text);
         }
 
-        let font = self.font.as_ref().unwrap();
+        if text.len() > SHAPE_CACHE_MAX_TEXT_LEN {
+            return self.shape_uncached(text, options);
+        }
+
+        let key = (text.to_string(), self.font_size_pt.to_bits(), options.clone());
+        if let Some(hit) = self.cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return hit;
+        }
+
+        let (width, glyphs) = self.shape_uncached(text, options);
+        self.cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, width, glyphs.clone());
+        (width, glyphs)
+    }
+
+    fn shape_uncached(&self, text: &str, options: &ShapeOptions) -> (f32, Vec<GlyphInfo>) {
+        let glyphs = self.shape_with_font(0, text, options);
+        let total_width_px = glyphs.iter().map(|g| g.x_advance).sum();
+
+        (total_width_px, glyphs)
+    }
+
+    /// Shapes text with the font at `index` in the chain (0 is the primary
+    /// font), handing `.notdef` runs on to the next fallback. Fallback runs
+    /// keep the properties of the text they were cut from.
+    fn shape_with_font(&self, index: usize, text: &str, options: &ShapeOptions) -> Vec<GlyphInfo> {
+        let (font, scale_factor) = match index {
+            0 => match self.font.as_ref() {
+                Some(font) => (font, self.scale_factor),
+                None => return self.estimate_widths(text).1,
+            },
+            _ => {
+                let font = &self.fallbacks[index - 1];
+                (font, Self::scale_for(self.font_size_pt, font.scale().0))
+            }
+        };
 
-        let buffer = UnicodeBuffer::new().add_str(text);
-        let output = shape(font, buffer, &[]);
+        let buffer = options.buffer(text);
+        let direction = buffer.get_direction();
+        // Later settings of a tag override earlier ones
+        let features: Vec<HbFeature> = self.features
+            .iter()
+            .chain(&options.features)
+            .map(|f| f.to_hb())
+            .collect();
+        let output = shape(font, buffer, &features);
 
         let positions = output.get_glyph_positions();
         let infos = output.get_glyph_infos();
 
-        let mut total_width_px = 0.0;
         let mut glyphs = Vec::with_capacity(positions.len());
 
         for (position, info) in positions.iter().zip(infos.iter()) {
-            let x_advance_px = position.x_advance as f32 * self.scale_factor;
-            let y_advance_px = position.y_advance as f32 * self.scale_factor;
-            let x_offset_px = position.x_offset as f32 * self.scale_factor;
-            let y_offset_px = position.y_offset as f32 * self.scale_factor;
-
-            total_width_px += x_advance_px;
+            let x_advance_px = position.x_advance as f32 * scale_factor;
+            let y_advance_px = position.y_advance as f32 * scale_factor;
+            let x_offset_px = position.x_offset as f32 * scale_factor;
+            let y_offset_px = position.y_offset as f32 * scale_factor;
 
             glyphs.push(GlyphInfo {
                 codepoint: info.codepoint,
@@ -225,10 +718,72 @@ impl<'a> TextShaper<'a> {
                 y_advance: y_advance_px,
                 x_offset: x_offset_px,
                 y_offset: y_offset_px,
+                scale: 1.0,
             });
         }
 
-        (total_width_px, glyphs)
+        if index < self.fallbacks.len() && glyphs.iter().any(|g| g.codepoint == NOTDEF_GLYPH) {
+            let resolved = ShapeOptions {
+                direction: TextDirection::from_hb(direction),
+                ..options.clone()
+            };
+            glyphs = self.reshape_missing(index + 1, text, &resolved, glyphs);
+        }
+
+        glyphs
+    }
+
+    /// Replaces each run of `.notdef` glyphs with the text it covers shaped
+    /// by the font at `index`. Whole clusters are reshaped so that a base
+    /// character is not drawn twice when only its combining mark is missing.
+    fn reshape_missing(
+        &self,
+        index: usize,
+        text: &str,
+        options: &ShapeOptions,
+        glyphs: Vec<GlyphInfo>,
+    ) -> Vec<GlyphInfo> {
+        let mut result: Vec<GlyphInfo> = Vec::with_capacity(glyphs.len());
+        let mut i = 0;
+
+        while i < glyphs.len() {
+            if glyphs[i].codepoint != NOTDEF_GLYPH {
+                result.push(glyphs[i]);
+                i += 1;
+                continue;
+            }
+
+            let run_end = glyphs[i..]
+                .iter()
+                .position(|g| g.codepoint != NOTDEF_GLYPH)
+                .map_or(glyphs.len(), |n| i + n);
+            let run = &glyphs[i..run_end];
+            let start = run.iter().map(|g| g.cluster).min().unwrap_or(0) as usize;
+            let last = run.iter().map(|g| g.cluster).max().unwrap_or(0);
+            // The run ends where the next cluster begins, in logical order
+            let end = glyphs
+                .iter()
+                .map(|g| g.cluster)
+                .filter(|&c| c > last)
+                .min()
+                .map_or(text.len(), |c| c as usize);
+            let range = start as u32..end as u32;
+
+            result.retain(|g| !range.contains(&g.cluster));
+            i = run_end;
+            while i < glyphs.len() && range.contains(&glyphs[i].cluster) {
+                i += 1;
+            }
+
+            for glyph in self.shape_with_font(index, &text[start..end], options) {
+                result.push(GlyphInfo {
+                    cluster: glyph.cluster + start as u32,
+                    ..glyph
+                });
+            }
+        }
+
+        result
     }
 
     /// Estimate character widths without a real font
@@ -238,7 +793,7 @@ impl<'a> TextShaper<'a> {
         let mut glyphs = Vec::new();
         let mut total_width = 0.0f32;
 
-        for (i, ch) in text.chars().enumerate() {
+        for (i, ch) in text.char_indices() {
             // CJK characters are wider
             let width = if ch.is_ascii() {
                 char_width
@@ -253,6 +808,7 @@ impl<'a> TextShaper<'a> {
                 y_advance: self.font_size_pt,
                 x_offset: 0.0,
                 y_offset: 0.0,
+                scale: 1.0,
             });
 
             total_width += width;
@@ -266,12 +822,177 @@ impl<'a> TextShaper<'a> {
         let (width, _) = self.shape(text);
         width
     }
+
+    /// Shapes text after applying a capitalization style.
+    ///
+    /// Glyph clusters still refer to byte offsets in the original `text`.
+    /// For small caps, glyphs of originally-lowercase letters are scaled by
+    /// [`SMALL_CAPS_SCALE`], and the returned width reflects that.
+    pub fn shape_with_caps(&self, text: &str, caps: CapsStyle) -> (f32, Vec<GlyphInfo>) {
+        if caps == CapsStyle::Normal {
+            return self.shape(text);
+        }
+
+        let mut total_width = 0.0f32;
+        let mut glyphs = Vec::new();
+
+        for (segment_start, segment, reduced) in caps_segments(text, caps) {
+            // Uppercase the segment, remembering where each original char starts
+            let mut upper = String::with_capacity(segment.len());
+            let mut origins: Vec<(usize, usize)> = Vec::new();
+            for (offset, ch) in segment.char_indices() {
+                origins.push((upper.len(), segment_start + offset));
+                upper.extend(ch.to_uppercase());
+            }
+
+            let scale = if reduced { SMALL_CAPS_SCALE } else { 1.0 };
+            let (width, segment_glyphs) = self.shape(&upper);
+            total_width += width * scale;
+
+            for glyph in segment_glyphs {
+                let cluster = glyph.cluster as usize;
+                let origin = origins
+                    .iter()
+                    .rev()
+                    .find(|(upper_offset, _)| *upper_offset <= cluster)
+                    .map_or(segment_start, |&(_, original)| original);
+
+                glyphs.push(GlyphInfo {
+                    cluster: origin as u32,
+                    x_advance: glyph.x_advance * scale,
+                    y_advance: glyph.y_advance * scale,
+                    x_offset: glyph.x_offset * scale,
+                    y_offset: glyph.y_offset * scale,
+                    scale: glyph.scale * scale,
+                    ..glyph
+                });
+            }
+        }
+
+        (total_width, glyphs)
+    }
+
+    /// Measure text width in logical pixels after applying a capitalization style
+    pub fn measure_width_with_caps(&self, text: &str, caps: CapsStyle) -> f32 {
+        let (width, _) = self.shape_with_caps(text, caps);
+        width
+    }
+}
+
+/// Splits text into `(byte_offset, segment, reduced)` runs, where `reduced`
+/// marks lowercase letters that small caps draws at a smaller size
+fn caps_segments(text: &str, caps: CapsStyle) -> Vec<(usize, &str, bool)> {
+    let mut segments = Vec::new();
+    let mut start = 0usize;
+    let mut current: Option<bool> = None;
+
+    for (offset, ch) in text.char_indices() {
+        let reduced = caps == CapsStyle::SmallCaps && ch.is_lowercase();
+        if current.is_some_and(|r| r != reduced) {
+            segments.push((start, &text[start..offset], current.unwrap_or(false)));
+            start = offset;
+        }
+        current = Some(reduced);
+    }
+
+    if let Some(reduced) = current {
+        segments.push((start, &text[start..], reduced));
+    }
+
+    segments
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_try_new_without_search_paths_is_an_error() {
+        let error = TextShaper::from_font_paths(&[]).unwrap_err();
+        assert_eq!(error, ShapingError::NoFontFound { searched: Vec::new() });
+    }
+
+    #[test]
+    fn test_try_new_skips_missing_paths_and_fontless_dirs() {
+        let paths = [
+            PathBuf::from("/nonexistent/velum/Missing.ttf"),
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/ooxml"),
+        ];
+
+        let error = TextShaper::from_font_paths(&paths).unwrap_err();
+        assert_eq!(error, ShapingError::NoFontFound { searched: paths.to_vec() });
+        assert_eq!(error.to_string(), "No font found in 2 searched location(s)");
+    }
+
+    fn fixture_fonts() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/fonts")
+    }
+
+    /// The fixture font at 12pt: printable ASCII and a few Arabic letters, each
+    /// 500/1000 em wide, an "ffi"/"fi" ligature and an "AV" kerning pair
+    fn fixture_shaper() -> TextShaper {
+        TextShaper::with_search_paths(&[fixture_fonts().join("VelumTest.ttf")], 12.0).unwrap()
+    }
+
+    #[test]
+    fn test_with_search_paths_finds_font_in_directory() {
+        let missing = PathBuf::from("/nonexistent/velum/fonts");
+        let shaper = TextShaper::with_search_paths(&[missing, fixture_fonts()], 10.0).unwrap();
+
+        // The fixture font gives every printable ASCII glyph a 500/1000 em advance
+        let em = 10.0 * 96.0 / 72.0;
+        assert!(shaper.has_font());
+        assert_eq!(shaper.font_size(), 10.0);
+        assert!((shaper.measure_width("AB") - em).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_with_search_paths_accepts_font_files_and_rejects_non_fonts() {
+        let file = fixture_fonts().join("VelumTest.ttf");
+        let shaper = TextShaper::with_search_paths(&[file], 12.0).unwrap();
+        assert!((shaper.measure_width("A") - 8.0).abs() < 0.01);
+
+        let not_a_font = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let error = TextShaper::with_search_paths(&[not_a_font.clone()], 12.0).unwrap_err();
+        assert!(matches!(error, ShapingError::FontUnreadable { path, .. } if path == not_a_font));
+    }
+
+    #[test]
+    fn test_register_font_dir_is_searched_first() {
+        // A missing directory keeps other tests' system font lookups unchanged
+        let dir = PathBuf::from("/nonexistent/velum/registered-fonts");
+        TextShaper::register_font_dir(&dir);
+        TextShaper::register_font_dir(&dir);
+
+        let paths = TextShaper::system_font_paths();
+        assert_eq!(paths.iter().filter(|path| **path == dir).count(), 1);
+        // Registered directories precede the preferred font files
+        assert!(paths.iter().take_while(|path| path.extension().is_none()).any(|path| *path == dir));
+    }
+
+    #[test]
+    fn test_parse_fontconfig_dirs() {
+        let xml = r#"<?xml version="1.0"?>
+<fontconfig>
+  <dir>/usr/share/fonts</dir>
+  <dir>~/.fonts</dir>
+  <dir prefix="relative">extra</dir>
+  <include ignore_missing="yes">conf.d</include>
+  <dir>  /opt/fonts  </dir>
+</fontconfig>"#;
+
+        let dirs = parse_fontconfig_dirs(xml, Path::new("/etc/fonts"), Some(Path::new("/home/velum")));
+        assert_eq!(dirs, [
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/home/velum/.fonts"),
+            PathBuf::from("/etc/fonts/extra"),
+            PathBuf::from("/opt/fonts"),
+        ]);
+
+        let without_home = parse_fontconfig_dirs("<dir>~/.fonts</dir>", Path::new("/etc/fonts"), None);
+        assert!(without_home.is_empty());
+    }
+
     #[test]
     fn test_text_shaper_new() {
         let shaper = TextShaper::new();
@@ -443,4 +1164,255 @@ mod tests {
         let width2 = shaper.measure_width("test");
         assert_eq!(width1, width2, "Width measurements should be consistent");
     }
+
+    #[test]
+    fn test_all_caps_uses_uppercase_glyphs() {
+        let shaper = TextShaper::new();
+        let (width, glyphs) = shaper.shape_with_caps("abc", CapsStyle::AllCaps);
+        let (upper_width, upper_glyphs) = shaper.shape("ABC");
+
+        assert_eq!(width, upper_width);
+        let codepoints: Vec<u32> = glyphs.iter().map(|g| g.codepoint).collect();
+        let upper_codepoints: Vec<u32> = upper_glyphs.iter().map(|g| g.codepoint).collect();
+        assert_eq!(codepoints, upper_codepoints);
+    }
+
+    #[test]
+    fn test_small_caps_reduces_lowercase_letters() {
+        let shaper = TextShaper::new();
+        let (width, glyphs) = shaper.shape_with_caps("Hello", CapsStyle::SmallCaps);
+        let (_, upper_glyphs) = shaper.shape("HELLO");
+
+        assert_eq!(glyphs.len(), 5);
+        for (glyph, upper) in glyphs.iter().zip(upper_glyphs.iter()) {
+            assert_eq!(glyph.codepoint, upper.codepoint, "Small caps should use uppercase glyphs");
+        }
+        assert_eq!(glyphs[0].scale, 1.0);
+        assert!(glyphs[1..].iter().all(|g| g.scale == SMALL_CAPS_SCALE));
+
+        let expected = shaper.measure_width("H") + shaper.measure_width("ELLO") * SMALL_CAPS_SCALE;
+        assert!((width - expected).abs() < 0.01);
+        assert!(width < shaper.measure_width("HELLO"));
+    }
+
+    #[test]
+    fn test_owned_bytes_shapers_can_be_dropped() {
+        let bytes = std::fs::read(fixture_fonts().join("VelumTest.ttf")).unwrap();
+        let reference = TextShaper::from_owned_bytes(bytes.clone(), 0, 12.0).measure_width("hello");
+        assert!((reference - 40.0).abs() < 0.01, "width {}", reference);
+
+        for _ in 0..50 {
+            let shaper = TextShaper::from_owned_bytes(bytes.clone(), 0, 12.0);
+            assert!(shaper.has_font());
+            assert_eq!(shaper.measure_width("hello"), reference);
+        }
+    }
+
+    #[test]
+    fn test_owned_bytes_empty_falls_back() {
+        let shaper = TextShaper::from_owned_bytes(Vec::new(), 0, 12.0);
+        assert!(!shaper.has_font());
+    }
+
+    #[test]
+    fn test_set_font_size_scales_linearly() {
+        let mut shaper = TextShaper::new();
+        shaper.set_font_size(12.0);
+        let small = shaper.measure_width("Hello, world");
+
+        shaper.set_font_size(24.0);
+        assert_eq!(shaper.font_size(), 24.0);
+        let large = shaper.measure_width("Hello, world");
+
+        assert!(small > 0.0);
+        assert!((large / small - 2.0).abs() < 1e-3, "expected ~2x, got {}", large / small);
+    }
+
+    #[test]
+    fn test_fallback_font_fills_missing_glyphs() {
+        let mut shaper = fixture_shaper();
+        // The fixture font has no Hebrew or emoji; its fallback companion does
+        let text = "Hi \u{5D0}\u{1F60A}!";
+        let (_, before) = shaper.shape(text);
+        assert!(before.iter().any(|g| g.codepoint == NOTDEF_GLYPH));
+
+        shaper.add_fallback_from_path(fixture_fonts().join("VelumTestFallback.ttf")).unwrap();
+        assert_eq!(shaper.fallback_count(), 1);
+        let (width, glyphs) = shaper.shape(text);
+
+        assert!(glyphs.iter().all(|g| g.codepoint != NOTDEF_GLYPH));
+        let clusters: Vec<u32> = glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(clusters, vec![0, 1, 2, 3, 5, 9]);
+        let sum: f32 = glyphs.iter().map(|g| g.x_advance).sum();
+        assert!((width - sum).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_cjk_fallback_after_latin_font() {
+        let mut shaper = fixture_shaper();
+        let data = std::fs::read(fixture_fonts().join("VelumTestFallback.ttf")).unwrap();
+        shaper.add_fallback(data, 0);
+
+        // Latin letters are half an em wide in the primary font, ideographs a full em in the fallback
+        let em = 12.0 * 96.0 / 72.0;
+        let (width, glyphs) = shaper.shape("Hello \u{4E16}\u{754C}");
+        assert!(glyphs.iter().all(|g| g.codepoint != NOTDEF_GLYPH));
+        assert!((width - 5.0 * em).abs() < 0.01, "width {}", width);
+    }
+
+    #[test]
+    fn test_fallback_ignores_empty_data() {
+        let mut shaper = TextShaper::new();
+        shaper.add_fallback(Vec::new(), 0);
+        assert_eq!(shaper.fallback_count(), 0);
+    }
+
+    #[test]
+    fn test_font_metrics() {
+        let shaper = TextShaper::new();
+        let metrics = shaper.metrics();
+        assert!(metrics.ascent > 0.0);
+        assert!(metrics.descent < 0.0);
+        assert!(metrics.line_gap >= 0.0);
+
+        // A 12pt font is 16 logical pixels per em; line heights sit around 1-1.5 em
+        let line_height = metrics.line_height();
+        assert!((16.0..=24.0).contains(&line_height), "line height {}", line_height);
+    }
+
+    #[test]
+    fn test_font_metrics_scale_with_size() {
+        let mut shaper = TextShaper::new();
+        let small = shaper.metrics();
+        shaper.set_font_size(24.0);
+        let large = shaper.metrics();
+        assert!((large.ascent / small.ascent - 2.0).abs() < 1e-3);
+        assert!((large.line_height() / small.line_height() - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_font_metrics_fallback_estimate() {
+        let metrics = TextShaper::fallback().metrics();
+        assert_eq!(metrics.line_height(), 16.0);
+    }
+
+    #[test]
+    fn test_shape_arabic_with_explicit_direction() {
+        let shaper = fixture_shaper();
+        let word = "\u{633}\u{644}\u{627}\u{645}";
+        let rtl = ShapeOptions::new()
+            .with_script("Arab")
+            .with_language("ar")
+            .with_direction(TextDirection::RightToLeft);
+        let (_, rtl_glyphs) = shaper.shape_with_options(word, &rtl);
+        let rtl_clusters: Vec<u32> = rtl_glyphs.iter().map(|g| g.cluster).collect();
+        assert_eq!(rtl_clusters, vec![6, 4, 2, 0]);
+
+        let ltr = ShapeOptions::new().with_direction(TextDirection::LeftToRight);
+        let (_, ltr_glyphs) = shaper.shape_with_options(word, &ltr);
+        let ltr_clusters: Vec<u32> = ltr_glyphs.iter().map(|g| g.cluster).collect();
+        assert_ne!(rtl_clusters, ltr_clusters);
+
+        // Unset properties are auto-detected from the text
+        let (_, auto_glyphs) = shaper.shape(word);
+        let auto_codepoints: Vec<u32> = auto_glyphs.iter().map(|g| g.codepoint).collect();
+        let rtl_codepoints: Vec<u32> = rtl_glyphs.iter().map(|g| g.codepoint).collect();
+        assert_eq!(auto_codepoints, rtl_codepoints);
+    }
+
+    #[test]
+    fn test_shape_options_ignore_invalid_tags() {
+        let shaper = fixture_shaper();
+        let options = ShapeOptions::new().with_script("not a script");
+        assert_eq!(shaper.shape_with_options("abc", &options).0, shaper.shape("abc").0);
+    }
+
+    #[test]
+    fn test_kerning_toggle() {
+        // The fixture font pulls "V" a tenth of an em closer to "A"
+        let mut shaper = fixture_shaper();
+        let em = 12.0 * 96.0 / 72.0;
+        let kerned = shaper.measure_width("AV");
+
+        shaper.set_kerning(false);
+        let unkerned = shaper.measure_width("AV");
+        assert!((unkerned - em).abs() < 0.01, "unkerned {}", unkerned);
+        assert!((kerned - 0.9 * em).abs() < 0.01, "kerned {}", kerned);
+
+        shaper.set_kerning(true);
+        assert_eq!(shaper.measure_width("AV"), kerned);
+    }
+
+    #[test]
+    fn test_shape_with_features_overrides_shaper_settings() {
+        let mut shaper = fixture_shaper();
+        shaper.set_kerning(false);
+        let (unkerned, _) = shaper.shape("AV");
+        let (kerned, _) = shaper.shape_with_features("AV", &[Feature::enabled(b"kern")]);
+        assert!(kerned < unkerned);
+    }
+
+    #[test]
+    fn test_ligatures_toggle() {
+        let mut shaper = fixture_shaper();
+        let (_, with_liga) = shaper.shape("ffi");
+        shaper.set_ligatures(false);
+        let (_, without_liga) = shaper.shape("ffi");
+        assert_eq!(with_liga.len(), 1);
+        assert_eq!(without_liga.len(), 3);
+    }
+
+    #[test]
+    fn test_shape_cache_hits_on_repeated_text() {
+        let shaper = fixture_shaper();
+        let first = shaper.measure_width("hello");
+        let second = shaper.measure_width("hello");
+        assert_eq!(first, second);
+
+        let stats = shaper.shape_cache_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.entries, 1);
+
+        shaper.measure_width(&"long ".repeat(100));
+        assert_eq!(shaper.shape_cache_stats().entries, 1);
+    }
+
+    #[test]
+    fn test_shape_cache_invalidated_by_size_change() {
+        let mut shaper = fixture_shaper();
+        let small = shaper.measure_width("cache");
+        shaper.set_font_size(24.0);
+        assert_eq!(shaper.shape_cache_stats().entries, 0);
+        let large = shaper.measure_width("cache");
+        assert!((large - 2.0 * small).abs() < 0.01, "small {} vs large {}", small, large);
+
+        shaper.clear_shape_cache();
+        assert_eq!(shaper.shape_cache_stats().entries, 0);
+    }
+
+    #[test]
+    fn test_shape_cache_evicts_least_recently_used() {
+        let mut cache = ShapeCache::default();
+        let key = |i: usize| (i.to_string(), 0, ShapeOptions::default());
+        for i in 0..SHAPE_CACHE_CAPACITY {
+            cache.insert(key(i), i as f32, Vec::new());
+        }
+        // Touch the oldest entry so the next insert evicts the second oldest
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(SHAPE_CACHE_CAPACITY), 0.0, Vec::new());
+
+        assert_eq!(cache.entries.len(), SHAPE_CACHE_CAPACITY);
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(1)).is_none());
+    }
+
+    #[test]
+    fn test_caps_clusters_refer_to_original_text() {
+        let shaper = TextShaper::fallback();
+        let (_, glyphs) = shaper.shape_with_caps("aßb", CapsStyle::AllCaps);
+        let clusters: Vec<u32> = glyphs.iter().map(|g| g.cluster).collect();
+        // "ß" uppercases to "SS", both of which map back to its byte offset
+        assert_eq!(clusters, vec![0, 1, 1, 3]);
+    }
 }
@@ -3,9 +3,11 @@
 //! This module provides a command-based undo/redo system with support for:
 //! - Text insertion/deletion commands
 //! - Command merging for continuous input
+//! - Persisting the history to bytes for session restore
 
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
 use crate::piece_tree::{PieceTree, Selection};
 
 /// Default maximum history size
@@ -14,6 +16,9 @@ pub const DEFAULT_MAX_HISTORY_SIZE: usize = 100;
 /// Default time window for merging commands (500ms)
 pub const DEFAULT_MERGE_WINDOW_MS: u64 = 500;
 
+/// Default memory budget for the payloads held by the history (64 MiB)
+pub const DEFAULT_MAX_HISTORY_BYTES: usize = 64 * 1024 * 1024;
+
 /// Error types for command execution
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommandError {
@@ -54,12 +59,17 @@ pub trait Command: Send + Sync + std::fmt::Debug {
     /// Returns a human-readable name for this command
     fn name(&self) -> &str;
 
+    /// Returns the number of payload bytes this command holds
+    fn byte_cost(&self) -> usize {
+        0
+    }
+
     /// Returns self as Any for downcasting
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
 /// Execution state captured during command execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandExecution {
     /// The type of operation performed
     pub operation_type: OperationType,
@@ -71,13 +81,24 @@ pub struct CommandExecution {
     pub inserted_text: Option<String>,
     /// The text that was deleted (for delete operations)
     pub deleted_text: Option<String>,
-    /// Selection state before the command
+    /// Selection state before the command, restored when it is undone
     pub prev_selection: Selection,
-    /// Selection state after the command
+    /// Selection state after the command, restored when it is redone
     pub next_selection: Selection,
+    /// Executions of a composite command's children, in execution order
+    pub children: Vec<CommandExecution>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl CommandExecution {
+    /// Returns the number of text bytes captured by this execution
+    pub fn byte_cost(&self) -> usize {
+        self.inserted_text.as_ref().map_or(0, String::len)
+            + self.deleted_text.as_ref().map_or(0, String::len)
+            + self.children.iter().map(CommandExecution::byte_cost).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OperationType {
     Insert,
     Delete,
@@ -85,33 +106,70 @@ pub enum OperationType {
     Custom,
 }
 
+impl OperationType {
+    /// Human-readable description suitable for "Undo ..." / "Redo ..." labels
+    pub fn description(&self) -> &'static str {
+        match self {
+            OperationType::Insert => "Typing",
+            OperationType::Delete => "Delete",
+            OperationType::Composite => "Multiple Changes",
+            OperationType::Custom => "Edit",
+        }
+    }
+}
+
 /// Metadata for a command in the history
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandMetadata {
     /// Timestamp when the command was executed
+    #[serde(with = "instant_millis")]
     pub timestamp: Instant,
     /// User-friendly name
     pub display_name: String,
     /// Whether this command was created by merging multiple commands
     pub is_merged: bool,
+    /// Human-readable description of the operation, e.g. "Typing"
+    pub description: String,
+    /// Whether later commands may be merged into this one
+    pub mergeable: bool,
 }
 
 impl CommandMetadata {
     pub fn new(display_name: impl Into<String>) -> Self {
+        let display_name = display_name.into();
         CommandMetadata {
             timestamp: Instant::now(),
-            display_name: display_name.into(),
+            description: display_name.clone(),
+            display_name,
             is_merged: false,
+            mergeable: true,
         }
     }
 
     pub fn merged() -> Self {
         CommandMetadata {
-            timestamp: Instant::now(),
-            display_name: "Merged operations".to_string(),
             is_merged: true,
+            ..CommandMetadata::new("Merged operations")
         }
     }
+
+    /// Creates metadata whose description is derived from the execution's operation type
+    pub fn for_execution(display_name: impl Into<String>, execution: &CommandExecution) -> Self {
+        CommandMetadata {
+            description: execution.operation_type.description().to_string(),
+            ..CommandMetadata::new(display_name)
+        }
+    }
+}
+
+/// The outcome of an undo or redo, for the caller to update its view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryStep {
+    /// The type of operation that was undone or redone
+    pub operation_type: OperationType,
+    /// Selection the document was returned to: the one before the command
+    /// on undo, the one after it on redo
+    pub selection: Selection,
 }
 
 /// Wrapper that combines a command with its execution state and metadata
@@ -122,6 +180,32 @@ pub struct CommandRecord {
     pub metadata: CommandMetadata,
 }
 
+impl CommandRecord {
+    /// Returns the memory cost of the command and its execution payloads
+    pub fn byte_cost(&self) -> usize {
+        self.command.byte_cost() + self.execution.byte_cost()
+    }
+}
+
+/// Serializes an `Instant` as milliseconds since the Unix epoch
+mod instant_millis {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        let wall = SystemTime::now() - instant.elapsed();
+        let millis = wall.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        serializer.serialize_u64(millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let millis = u64::deserialize(deserializer)?;
+        let wall = UNIX_EPOCH + Duration::from_millis(millis);
+        let age = SystemTime::now().duration_since(wall).unwrap_or_default();
+        Ok(Instant::now().checked_sub(age).unwrap_or_else(Instant::now))
+    }
+}
+
 // ==================== Insert Command ====================
 
 /// Command for inserting text
@@ -158,6 +242,8 @@ impl Command for InsertCommand {
         doc.insert(self.offset, self.text.clone())
             .then(|| ())
             .ok_or_else(|| CommandError::ExecutionFailed("Insert failed".to_string()))?;
+        // Typing leaves the caret after the inserted text
+        doc.move_selection_to(self.offset + self.text.chars().count());
 
         Ok(CommandExecution {
             operation_type: OperationType::Insert,
@@ -167,6 +253,7 @@ impl Command for InsertCommand {
             deleted_text: None,
             prev_selection,
             next_selection: doc.selection,
+            children: Vec::new(),
         })
     }
 
@@ -191,7 +278,7 @@ impl Command for InsertCommand {
     fn merge(&self, other: &dyn Command) -> Option<Arc<dyn Command>> {
         if let Some(other_insert) = other.as_any().downcast_ref::<InsertCommand>() {
             // Merge if other is immediately after this (continuous typing)
-            if other_insert.offset == self.offset + self.text.len() {
+            if other_insert.offset == self.offset + self.text.chars().count() {
                 let mut new_text = self.text.clone();
                 new_text.push_str(&other_insert.text);
                 return Some(Arc::new(InsertCommand {
@@ -199,9 +286,9 @@ impl Command for InsertCommand {
                     text: new_text,
                 }));
             } else if other_insert.offset == self.offset && !other_insert.text.is_empty() {
-                // Same position insert (e.g., IME commit)
-                let mut new_text = self.text.clone();
-                new_text.push_str(&other_insert.text);
+                // Same position insert (e.g., IME commit) lands before this text
+                let mut new_text = other_insert.text.clone();
+                new_text.push_str(&self.text);
                 return Some(Arc::new(InsertCommand {
                     offset: self.offset,
                     text: new_text,
@@ -219,6 +306,10 @@ impl Command for InsertCommand {
         "Insert"
     }
 
+    fn byte_cost(&self) -> usize {
+        self.text.len()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -266,24 +357,30 @@ impl Command for DeleteCommand {
             deleted_text: Some(deleted_text),
             prev_selection,
             next_selection: doc.selection,
+            children: Vec::new(),
         })
     }
 
     fn undo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
-        if let Some(ref text) = execution.deleted_text {
-            doc.insert(execution.offset, text.clone())
-                .then(|| ())
-                .ok_or_else(|| CommandError::ExecutionFailed("Undo delete failed".to_string()))?;
-        }
+        let text = execution.deleted_text
+            .as_ref()
+            .ok_or_else(|| CommandError::InvalidState("Delete execution has no deleted text".to_string()))?;
+        doc.insert(execution.offset, text.clone())
+            .then(|| ())
+            .ok_or_else(|| CommandError::ExecutionFailed("Undo delete failed".to_string()))?;
         doc.selection = execution.prev_selection;
         Ok(())
     }
 
     fn redo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
+        // Only delete again if the range still holds the text removed originally
+        if execution.deleted_text.as_deref() != Some(doc.get_text_range(execution.offset, execution.length).as_str()) {
+            return Err(CommandError::InvalidState("Document no longer matches deleted text".to_string()));
+        }
         doc.delete(execution.offset, execution.length)
             .then(|| ())
             .ok_or_else(|| CommandError::ExecutionFailed("Redo delete failed".to_string()))?;
-        doc.selection = execution.prev_selection;
+        doc.selection = execution.next_selection;
         Ok(())
     }
 
@@ -330,30 +427,55 @@ impl CompositeCommand {
     }
 }
 
-impl Command for CompositeCommand {
-    fn execute(&self, _doc: &mut PieceTree) -> Result<CommandExecution, CommandError> {
-        Ok(CommandExecution {
+impl CompositeCommand {
+    /// Builds the execution record of a composite whose children already ran
+    pub fn execution_from(prev_selection: Selection, children: Vec<CommandExecution>) -> CommandExecution {
+        CommandExecution {
             operation_type: OperationType::Composite,
             offset: 0,
             length: 0,
             inserted_text: None,
             deleted_text: None,
-            prev_selection: Selection::default(),
-            next_selection: Selection::default(),
-        })
+            prev_selection,
+            next_selection: children.last().map_or(prev_selection, |e| e.next_selection),
+            children,
+        }
     }
+}
+
+impl Command for CompositeCommand {
+    fn execute(&self, doc: &mut PieceTree) -> Result<CommandExecution, CommandError> {
+        let prev_selection = doc.selection;
+        let mut children = Vec::with_capacity(self.commands.len());
+        for cmd in &self.commands {
+            match cmd.execute(doc) {
+                Ok(execution) => children.push(execution),
+                Err(err) => {
+                    // Roll back the children that already ran
+                    for (cmd, execution) in self.commands.iter().zip(&children).rev() {
+                        cmd.undo(doc, execution)?;
+                    }
+                    return Err(err);
+                }
+            }
+        }
 
-    fn undo(&self, _doc: &mut PieceTree, _execution: &CommandExecution) -> Result<(), CommandError> {
-        for _cmd in self.commands.iter().rev() {
-            // Would need to track execution for each command
+        Ok(Self::execution_from(prev_selection, children))
+    }
+
+    fn undo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
+        for (cmd, child) in self.commands.iter().zip(&execution.children).rev() {
+            cmd.undo(doc, child)?;
         }
+        doc.selection = execution.prev_selection;
         Ok(())
     }
 
-    fn redo(&self, _doc: &mut PieceTree, _execution: &CommandExecution) -> Result<(), CommandError> {
-        for _cmd in &self.commands {
-            // Would need to track execution for each command
+    fn redo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
+        for (cmd, child) in self.commands.iter().zip(&execution.children) {
+            cmd.redo(doc, child)?;
         }
+        doc.selection = execution.next_selection;
         Ok(())
     }
 
@@ -369,19 +491,128 @@ impl Command for CompositeCommand {
         &self.name
     }
 
+    fn byte_cost(&self) -> usize {
+        self.commands.iter().map(|c| c.byte_cost()).sum()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
 
+// ==================== Persistence ====================
+
+/// Serializable form of the built-in commands
+#[derive(Debug, Serialize, Deserialize)]
+enum StoredCommand {
+    Insert { offset: usize, text: String },
+    Delete { offset: usize, length: usize },
+    Composite { name: String, commands: Vec<StoredCommand> },
+}
+
+impl StoredCommand {
+    /// Returns `None` for commands defined outside this module
+    fn from_command(command: &dyn Command) -> Option<Self> {
+        let any = command.as_any();
+        if let Some(insert) = any.downcast_ref::<InsertCommand>() {
+            Some(StoredCommand::Insert { offset: insert.offset, text: insert.text.clone() })
+        } else if let Some(delete) = any.downcast_ref::<DeleteCommand>() {
+            Some(StoredCommand::Delete { offset: delete.offset, length: delete.length })
+        } else if let Some(composite) = any.downcast_ref::<CompositeCommand>() {
+            let commands = composite.commands
+                .iter()
+                .map(|c| Self::from_command(&**c))
+                .collect::<Option<Vec<_>>>()?;
+            Some(StoredCommand::Composite { name: composite.name.clone(), commands })
+        } else {
+            None
+        }
+    }
+
+    fn into_command(self) -> Arc<dyn Command> {
+        match self {
+            StoredCommand::Insert { offset, text } => Arc::new(InsertCommand::new(offset, text)),
+            StoredCommand::Delete { offset, length } => Arc::new(DeleteCommand::new(offset, length)),
+            StoredCommand::Composite { name, commands } => {
+                let mut composite = CompositeCommand::new(name);
+                for command in commands {
+                    composite.add_command(command.into_command());
+                }
+                Arc::new(composite)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRecord {
+    command: StoredCommand,
+    execution: CommandExecution,
+    metadata: CommandMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredHistory {
+    max_history_size: usize,
+    #[serde(default = "default_max_history_bytes")]
+    max_history_bytes: usize,
+    merge_window_ms: Option<u64>,
+    undo_stack: Vec<StoredRecord>,
+    redo_stack: Vec<StoredRecord>,
+}
+
+fn default_max_history_bytes() -> usize {
+    DEFAULT_MAX_HISTORY_BYTES
+}
+
+/// Converts a stack to its stored form. Records beyond a command that cannot
+/// be stored are unreachable after a reload, so only the records above the
+/// last such command are kept.
+fn store_stack(stack: &[CommandRecord]) -> Vec<StoredRecord> {
+    let mut stored = Vec::with_capacity(stack.len());
+    for record in stack {
+        match StoredCommand::from_command(&*record.command) {
+            Some(command) => stored.push(StoredRecord {
+                command,
+                execution: record.execution.clone(),
+                metadata: record.metadata.clone(),
+            }),
+            None => stored.clear(),
+        }
+    }
+    stored
+}
+
+fn restore_stack(stack: Vec<StoredRecord>) -> Vec<CommandRecord> {
+    stack
+        .into_iter()
+        .map(|record| CommandRecord {
+            command: record.command.into_command(),
+            execution: record.execution,
+            metadata: record.metadata,
+        })
+        .collect()
+}
+
 // ==================== Undo/Redo Manager ====================
 
+/// Commands collected by an open transaction
+struct Transaction {
+    command: CompositeCommand,
+    prev_selection: Selection,
+    children: Vec<CommandExecution>,
+    /// Number of `begin_transaction` calls not yet committed
+    depth: usize,
+}
+
 pub struct UndoRedoManager {
     undo_stack: Vec<CommandRecord>,
     redo_stack: Vec<CommandRecord>,
     max_history_size: usize,
-    merge_window_ms: u64,
+    max_history_bytes: usize,
+    merge_window: Option<Duration>,
     last_command_time: Option<Instant>,
+    transaction: Option<Transaction>,
 }
 
 impl Default for UndoRedoManager {
@@ -406,8 +637,10 @@ impl UndoRedoManager {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             max_history_size: DEFAULT_MAX_HISTORY_SIZE,
-            merge_window_ms: DEFAULT_MERGE_WINDOW_MS,
+            max_history_bytes: DEFAULT_MAX_HISTORY_BYTES,
+            merge_window: Some(Duration::from_millis(DEFAULT_MERGE_WINDOW_MS)),
             last_command_time: None,
+            transaction: None,
         }
     }
 
@@ -416,8 +649,10 @@ impl UndoRedoManager {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             max_history_size,
-            merge_window_ms,
+            max_history_bytes: DEFAULT_MAX_HISTORY_BYTES,
+            merge_window: Some(Duration::from_millis(merge_window_ms)),
             last_command_time: None,
+            transaction: None,
         }
     }
 
@@ -428,67 +663,213 @@ impl UndoRedoManager {
         }
     }
 
-    pub fn set_merge_window(&mut self, duration: Duration) {
-        self.merge_window_ms = duration.as_millis() as u64;
+    /// Sets the payload budget of the history, evicting the oldest undo
+    /// records until it is met
+    pub fn set_max_history_bytes(&mut self, bytes: usize) {
+        self.max_history_bytes = bytes;
+        self.enforce_byte_budget();
+    }
+
+    /// Returns the summed payload size of the undo and redo stacks
+    pub fn history_bytes(&self) -> usize {
+        self.undo_stack.iter().chain(&self.redo_stack).map(CommandRecord::byte_cost).sum()
+    }
+
+    /// Evicts the oldest undo records while the history exceeds its byte
+    /// budget. The redo stack is never touched.
+    fn enforce_byte_budget(&mut self) {
+        let mut total = self.history_bytes();
+        let mut evict = 0;
+        while total > self.max_history_bytes && evict < self.undo_stack.len() {
+            total -= self.undo_stack[evict].byte_cost();
+            evict += 1;
+        }
+        self.undo_stack.drain(..evict);
+    }
+
+    /// Sets how long after a command a following one may merge into it;
+    /// `None` disables merging entirely
+    pub fn set_merge_window(&mut self, window: Option<Duration>) {
+        self.merge_window = window;
+    }
+
+    pub fn merge_window(&self) -> Option<Duration> {
+        self.merge_window
+    }
+
+    /// Starts grouping executed commands into one undo step. Transactions
+    /// nest by flattening: only the outermost commit records the step.
+    pub fn begin_transaction(&mut self, doc: &PieceTree) {
+        match self.transaction.as_mut() {
+            Some(transaction) => transaction.depth += 1,
+            None => {
+                self.transaction = Some(Transaction {
+                    command: CompositeCommand::new("Transaction"),
+                    prev_selection: doc.selection,
+                    children: Vec::new(),
+                    depth: 1,
+                });
+            }
+        }
+    }
+
+    /// Ends the innermost transaction; the outermost one pushes every command
+    /// executed since `begin_transaction` as a single undo step
+    pub fn commit_transaction(&mut self) -> Result<(), CommandError> {
+        let transaction = self.transaction
+            .as_mut()
+            .ok_or_else(|| CommandError::InvalidState("No transaction in progress".to_string()))?;
+
+        transaction.depth -= 1;
+        if transaction.depth > 0 {
+            return Ok(());
+        }
+
+        let transaction = self.transaction.take().expect("transaction checked above");
+        if !transaction.children.is_empty() {
+            let execution = CompositeCommand::execution_from(transaction.prev_selection, transaction.children);
+            self.push_command(Arc::new(transaction.command), execution);
+            self.enforce_byte_budget();
+        }
+        self.last_command_time = None;
+        Ok(())
+    }
+
+    /// Checks if a transaction is collecting commands
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
     }
 
     pub fn execute(&mut self, doc: &mut PieceTree, command: Arc<dyn Command>) -> Result<(), CommandError> {
+        self.execute_with(doc, command, true)
+    }
+
+    /// Executes a command that neither merges into the previous command nor
+    /// accepts later ones, e.g. a paste followed by typing
+    pub fn execute_unmergeable(&mut self, doc: &mut PieceTree, command: Arc<dyn Command>) -> Result<(), CommandError> {
+        self.execute_with(doc, command, false)
+    }
+
+    fn execute_with(&mut self, doc: &mut PieceTree, command: Arc<dyn Command>, mergeable: bool) -> Result<(), CommandError> {
         let execution = command.execute(doc)?;
 
-        let should_merge = command.is_mergeable()
-            && self.last_command_time.map_or(false, |t| {
-                t.elapsed() < Duration::from_millis(self.merge_window_ms)
+        if let Some(transaction) = self.transaction.as_mut() {
+            self.redo_stack.clear();
+            transaction.command.add_command(command);
+            transaction.children.push(execution);
+            return Ok(());
+        }
+
+        let should_merge = mergeable
+            && command.is_mergeable()
+            && self.merge_window.zip(self.last_command_time).is_some_and(|(window, t)| {
+                t.elapsed() < window
             })
-            && !self.undo_stack.is_empty();
+            && self.undo_stack.last().is_some_and(|r| r.metadata.mergeable);
 
         self.redo_stack.clear();
 
-        if should_merge {
+        let merged = if should_merge {
             let last_record = self.undo_stack.last_mut().unwrap();
-            if let Some(merged) = last_record.command.merge(&*command) {
-                let new_execution = merged.execute(doc)?;
+            last_record.command.merge(&*command).map(|merged| {
+                // The document already holds both edits; only the record is combined
+                let new_execution = Self::merged_execution(&last_record.execution, &execution);
+                last_record.metadata = CommandMetadata {
+                    is_merged: true,
+                    ..CommandMetadata::for_execution(merged.name(), &new_execution)
+                };
                 last_record.command = merged;
                 last_record.execution = new_execution;
-                last_record.metadata = CommandMetadata::merged();
-            } else {
-                self.push_command(command, execution);
-            }
+            })
         } else {
+            None
+        };
+
+        if merged.is_none() {
             self.push_command(command, execution);
+            if let Some(record) = self.undo_stack.last_mut() {
+                record.metadata.mergeable = mergeable;
+            }
         }
+        self.enforce_byte_budget();
 
         self.last_command_time = Some(Instant::now());
 
         Ok(())
     }
 
+    /// Combines the executions of an insert and the insert merged into it.
+    /// The second text lands at its own offset inside the first, e.g. before
+    /// it when an IME recomposes at the same position.
+    fn merged_execution(first: &CommandExecution, second: &CommandExecution) -> CommandExecution {
+        let splice = |a: &Option<String>, b: &Option<String>| match (a, b) {
+            (None, None) => None,
+            _ => {
+                let (a, b) = (a.as_deref().unwrap_or(""), b.as_deref().unwrap_or(""));
+                let at = second.offset.saturating_sub(first.offset);
+                let split = a.char_indices().nth(at).map_or(a.len(), |(i, _)| i);
+                Some(format!("{}{}{}", &a[..split], b, &a[split..]))
+            }
+        };
+
+        CommandExecution {
+            operation_type: first.operation_type.clone(),
+            offset: first.offset,
+            length: first.length + second.length,
+            inserted_text: splice(&first.inserted_text, &second.inserted_text),
+            deleted_text: splice(&first.deleted_text, &second.deleted_text),
+            prev_selection: first.prev_selection,
+            next_selection: second.next_selection,
+            children: Vec::new(),
+        }
+    }
+
     fn push_command(&mut self, command: Arc<dyn Command>, execution: CommandExecution) {
         if self.undo_stack.len() >= self.max_history_size {
             self.undo_stack.remove(0);
         }
 
+        let metadata = CommandMetadata::for_execution(command.name(), &execution);
         self.undo_stack.push(CommandRecord {
             command,
             execution,
-            metadata: CommandMetadata::new(""),
+            metadata,
         });
     }
 
-    pub fn undo(&mut self, doc: &mut PieceTree) -> Result<(), CommandError> {
+    /// Undoes the last command and restores the selection from before it
+    pub fn undo(&mut self, doc: &mut PieceTree) -> Result<HistoryStep, CommandError> {
+        if self.in_transaction() {
+            return Err(CommandError::InvalidState("Transaction in progress".to_string()));
+        }
         if let Some(record) = self.undo_stack.pop() {
             record.command.undo(doc, &record.execution)?;
+            let step = HistoryStep {
+                operation_type: record.execution.operation_type.clone(),
+                selection: record.execution.prev_selection,
+            };
+            doc.selection = step.selection;
             self.redo_stack.push(record);
-            Ok(())
+            Ok(step)
         } else {
             Err(CommandError::InvalidState("Nothing to undo".to_string()))
         }
     }
 
-    pub fn redo(&mut self, doc: &mut PieceTree) -> Result<(), CommandError> {
+    /// Redoes the last undone command and restores the selection from after it
+    pub fn redo(&mut self, doc: &mut PieceTree) -> Result<HistoryStep, CommandError> {
+        if self.in_transaction() {
+            return Err(CommandError::InvalidState("Transaction in progress".to_string()));
+        }
         if let Some(record) = self.redo_stack.pop() {
             record.command.redo(doc, &record.execution)?;
+            let step = HistoryStep {
+                operation_type: record.execution.operation_type.clone(),
+                selection: record.execution.next_selection,
+            };
+            doc.selection = step.selection;
             self.undo_stack.push(record);
-            Ok(())
+            Ok(step)
         } else {
             Err(CommandError::InvalidState("Nothing to redo".to_string()))
         }
@@ -510,6 +891,16 @@ impl UndoRedoManager {
         self.redo_stack.len()
     }
 
+    /// Metadata of the command the next `undo` would revert
+    pub fn peek_undo(&self) -> Option<&CommandMetadata> {
+        self.undo_stack.last().map(|r| &r.metadata)
+    }
+
+    /// Metadata of the command the next `redo` would reapply
+    pub fn peek_redo(&self) -> Option<&CommandMetadata> {
+        self.redo_stack.last().map(|r| &r.metadata)
+    }
+
     pub fn next_undo_name(&self) -> Option<&str> {
         self.undo_stack.last().map(|r| r.command.name())
     }
@@ -522,6 +913,36 @@ impl UndoRedoManager {
         self.undo_stack.clear();
         self.redo_stack.clear();
         self.last_command_time = None;
+        self.transaction = None;
+    }
+
+    /// Serializes the undo and redo history. Commands defined outside this
+    /// module cannot be stored; history past them is dropped.
+    pub fn serialize(&self) -> Vec<u8> {
+        let history = StoredHistory {
+            max_history_size: self.max_history_size,
+            max_history_bytes: self.max_history_bytes,
+            merge_window_ms: self.merge_window.map(|w| w.as_millis() as u64),
+            undo_stack: store_stack(&self.undo_stack),
+            redo_stack: store_stack(&self.redo_stack),
+        };
+        serde_json::to_vec(&history).unwrap_or_default()
+    }
+
+    /// Restores a manager from bytes produced by `serialize`
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, CommandError> {
+        let history: StoredHistory = serde_json::from_slice(bytes)
+            .map_err(|e| CommandError::InvalidState(format!("Invalid history data: {}", e)))?;
+
+        Ok(UndoRedoManager {
+            undo_stack: restore_stack(history.undo_stack),
+            redo_stack: restore_stack(history.redo_stack),
+            max_history_size: history.max_history_size,
+            max_history_bytes: history.max_history_bytes,
+            merge_window: history.merge_window_ms.map(Duration::from_millis),
+            last_command_time: None,
+            transaction: None,
+        })
     }
 
     #[cfg(test)]
@@ -704,8 +1125,8 @@ mod tests {
 
     #[test]
     fn test_insert_command_merge_same_position() {
-        let cmd1 = InsertCommand::new(0, "H".to_string());
-        let cmd2 = InsertCommand::new(0, "ello".to_string());
+        let cmd1 = InsertCommand::new(0, "o".to_string());
+        let cmd2 = InsertCommand::new(0, "Hell".to_string());
 
         let merged = cmd1.merge(&cmd2);
         assert!(merged.is_some());
@@ -762,6 +1183,7 @@ mod tests {
             deleted_text: None,
             prev_selection: Selection::new(0, 0),
             next_selection: Selection::new(5, 5),
+            children: Vec::new(),
         };
 
         assert_eq!(execution.operation_type, OperationType::Insert);
@@ -780,7 +1202,7 @@ mod tests {
     #[test]
     fn test_merge_window_default() {
         let manager = UndoRedoManager::new();
-        assert_eq!(manager.merge_window_ms, DEFAULT_MERGE_WINDOW_MS);
+        assert_eq!(manager.merge_window, Some(Duration::from_millis(DEFAULT_MERGE_WINDOW_MS)));
     }
 
     #[test]
@@ -822,4 +1244,468 @@ mod tests {
         // Should be able to undo delete
         assert!(pt.can_undo());
     }
+
+    #[test]
+    fn test_transaction_undoes_and_redoes_in_one_step() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+
+        manager.begin_transaction(&pt);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "a"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1, "b"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(2, "c"))).unwrap();
+        assert_eq!(manager.undo_count(), 0);
+        manager.commit_transaction().unwrap();
+
+        assert_eq!(pt.get_text(), "abc");
+        assert_eq!(manager.undo_count(), 1);
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "");
+        assert!(!manager.can_undo());
+
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "abc");
+        assert!(!manager.can_redo());
+        assert_eq!(manager.undo_count(), 1);
+    }
+
+    #[test]
+    fn test_nested_transactions_flatten() {
+        let mut pt = PieceTree::new("Hello".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+
+        manager.begin_transaction(&pt);
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(0, 1))).unwrap();
+        manager.begin_transaction(&pt);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "J"))).unwrap();
+        manager.commit_transaction().unwrap();
+        assert!(manager.in_transaction());
+        assert!(manager.undo(&mut pt).is_err());
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(5, "!"))).unwrap();
+        manager.commit_transaction().unwrap();
+
+        assert_eq!(pt.get_text(), "Jello!");
+        assert_eq!(manager.undo_count(), 1);
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "Hello");
+        assert!(manager.commit_transaction().is_err());
+    }
+
+    #[test]
+    fn test_empty_transaction_records_nothing() {
+        let pt = PieceTree::new("x".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.begin_transaction(&pt);
+        manager.commit_transaction().unwrap();
+        assert!(!manager.can_undo());
+    }
+
+    #[test]
+    fn test_composite_command_execute_undo() {
+        let mut pt = PieceTree::new("World".to_string());
+        let mut composite = CompositeCommand::new("Greeting");
+        composite.add_command(Arc::new(InsertCommand::new(0, "Hello ")));
+        composite.add_command(Arc::new(InsertCommand::new(11, "!")));
+
+        let execution = composite.execute(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "Hello World!");
+        assert_eq!(execution.children.len(), 2);
+
+        composite.undo(&mut pt, &execution).unwrap();
+        assert_eq!(pt.get_text(), "World");
+        composite.redo(&mut pt, &execution).unwrap();
+        assert_eq!(pt.get_text(), "Hello World!");
+    }
+
+    #[test]
+    fn test_peek_labels_after_typing_and_deleting() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        assert!(manager.peek_undo().is_none());
+        assert!(manager.peek_redo().is_none());
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "Hello"))).unwrap();
+        assert_eq!(manager.peek_undo().unwrap().description, "Typing");
+        assert_eq!(manager.peek_undo().unwrap().display_name, "Insert");
+
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(4, 1))).unwrap();
+        assert_eq!(manager.peek_undo().unwrap().description, "Delete");
+        assert!(manager.peek_redo().is_none());
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(manager.peek_undo().unwrap().description, "Typing");
+        assert_eq!(manager.peek_redo().unwrap().description, "Delete");
+        assert!(manager.can_undo());
+        assert!(manager.can_redo());
+
+        manager.undo(&mut pt).unwrap();
+        assert!(manager.peek_undo().is_none());
+        assert!(!manager.can_undo());
+        assert_eq!(manager.peek_redo().unwrap().description, "Typing");
+    }
+
+    #[test]
+    fn test_peek_transaction_description() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        manager.begin_transaction(&pt);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "ab"))).unwrap();
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(0, 1))).unwrap();
+        manager.commit_transaction().unwrap();
+
+        assert_eq!(manager.peek_undo().unwrap().description, "Multiple Changes");
+    }
+
+    #[test]
+    fn test_typing_merges_within_window() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_secs(60)));
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "Hel"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(3, "lo"))).unwrap();
+
+        assert_eq!(pt.get_text(), "Hello");
+        assert_eq!(manager.undo_count(), 1);
+        assert!(manager.peek_undo().unwrap().is_merged);
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "");
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "Hello");
+    }
+
+    /// Insert that counts how often it and the commands merged from it execute
+    #[derive(Debug)]
+    struct CountingInsert {
+        insert: InsertCommand,
+        executions: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Command for CountingInsert {
+        fn execute(&self, doc: &mut PieceTree) -> Result<CommandExecution, CommandError> {
+            self.executions.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.insert.execute(doc)
+        }
+
+        fn undo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
+            self.insert.undo(doc, execution)
+        }
+
+        fn redo(&self, doc: &mut PieceTree, execution: &CommandExecution) -> Result<(), CommandError> {
+            self.insert.redo(doc, execution)
+        }
+
+        fn merge(&self, other: &dyn Command) -> Option<Arc<dyn Command>> {
+            let other = other.as_any().downcast_ref::<CountingInsert>()?;
+            let merged = self.insert.merge(&other.insert)?;
+            let insert = merged.as_any().downcast_ref::<InsertCommand>()?.clone();
+            Some(Arc::new(CountingInsert { insert, executions: self.executions.clone() }))
+        }
+
+        fn is_mergeable(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            self.insert.name()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_merge_applies_each_edit_once() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_secs(60)));
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counting = |offset, text: &str| {
+            Arc::new(CountingInsert { insert: InsertCommand::new(offset, text), executions: executions.clone() })
+        };
+
+        manager.execute(&mut pt, counting(0, "Hel")).unwrap();
+        manager.execute(&mut pt, counting(3, "lo")).unwrap();
+
+        // The merged command only replaces the record; it is not run again
+        assert_eq!(manager.undo_count(), 1);
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(pt.get_text(), "Hello");
+    }
+
+    #[test]
+    fn test_merged_insert_at_same_offset_keeps_document_order() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_secs(60)));
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "ab"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "c"))).unwrap();
+        assert_eq!(pt.get_text(), "cab");
+        assert_eq!(manager.undo_count(), 1);
+        assert_eq!(manager.undo_stack[0].execution.inserted_text.as_deref(), Some("cab"));
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "");
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "cab");
+    }
+
+    #[test]
+    fn test_typing_after_multibyte_text_merges() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_secs(60)));
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "é"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1, "t"))).unwrap();
+        assert_eq!(pt.get_text(), "ét");
+        assert_eq!(manager.undo_count(), 1);
+        assert_eq!(manager.undo_stack[0].execution.inserted_text.as_deref(), Some("ét"));
+    }
+
+    #[test]
+    fn test_inserts_outside_window_stay_separate() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_millis(1)));
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "a"))).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1, "b"))).unwrap();
+
+        assert_eq!(manager.undo_count(), 2);
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "a");
+    }
+
+    #[test]
+    fn test_disabled_merge_window_never_merges() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(None);
+        assert!(manager.merge_window().is_none());
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "a"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1, "b"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(2, "c"))).unwrap();
+
+        assert_eq!(pt.get_text(), "abc");
+        assert_eq!(manager.undo_count(), 3);
+    }
+
+    #[test]
+    fn test_paste_never_merges_with_typing() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::new();
+        manager.set_merge_window(Some(Duration::from_secs(60)));
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "ab"))).unwrap();
+        manager.execute_unmergeable(&mut pt, Arc::new(InsertCommand::new(2, "PASTE"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(7, "cd"))).unwrap();
+        assert!(!manager.undo_stack[1].metadata.mergeable);
+
+        assert_eq!(pt.get_text(), "abPASTEcd");
+        assert_eq!(manager.undo_count(), 3);
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "abPASTE");
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "ab");
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let mut pt = PieceTree::new("hello".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(5, " world"))).unwrap();
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(0, 1))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "J"))).unwrap();
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "ello world");
+
+        let bytes = manager.serialize();
+        let mut restored = UndoRedoManager::deserialize(&bytes).unwrap();
+        assert_eq!(restored.undo_count(), 2);
+        assert_eq!(restored.redo_count(), 1);
+        assert_eq!(restored.peek_undo().unwrap().description, "Delete");
+
+        restored.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "Jello world");
+        restored.undo(&mut pt).unwrap();
+        restored.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "hello world");
+        restored.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "hello");
+        assert!(!restored.can_undo());
+    }
+
+    #[test]
+    fn test_serialize_round_trip_transaction() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        manager.begin_transaction(&pt);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "ab"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(2, "cd"))).unwrap();
+        manager.commit_transaction().unwrap();
+
+        let mut restored = UndoRedoManager::deserialize(&manager.serialize()).unwrap();
+        restored.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "");
+        restored.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "abcd");
+    }
+
+    #[test]
+    fn test_deserialize_invalid_bytes() {
+        let result = UndoRedoManager::deserialize(b"not history");
+        assert!(matches!(result, Err(CommandError::InvalidState(_))));
+    }
+
+    #[test]
+    fn test_byte_budget_evicts_oldest_records() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        // Each 1000-byte insert costs 2000 bytes: the command text plus the execution copy
+        manager.set_max_history_bytes(5000);
+
+        let chunk = "x".repeat(1000);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, chunk.as_str()))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1000, chunk.as_str()))).unwrap();
+        assert_eq!(manager.undo_count(), 2);
+        assert_eq!(manager.history_bytes(), 4000);
+
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(2000, chunk.as_str()))).unwrap();
+        assert_eq!(manager.undo_count(), 2);
+        assert!(manager.history_bytes() <= 5000);
+
+        manager.undo(&mut pt).unwrap();
+        manager.undo(&mut pt).unwrap();
+        assert!(!manager.can_undo());
+        assert_eq!(pt.get_text().len(), 1000);
+    }
+
+    #[test]
+    fn test_byte_budget_never_evicts_redo() {
+        let mut pt = PieceTree::new("".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+        let chunk = "y".repeat(1000);
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "a"))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(1, chunk.as_str()))).unwrap();
+        manager.undo(&mut pt).unwrap();
+
+        manager.set_max_history_bytes(100);
+        assert_eq!(manager.redo_count(), 1);
+        assert_eq!(manager.undo_count(), 0);
+
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text().len(), 1001);
+    }
+
+    #[test]
+    fn test_execution_byte_cost() {
+        let mut pt = PieceTree::new("hello world".to_string());
+        let execution = DeleteCommand::new(5, 6).execute(&mut pt).unwrap();
+        assert_eq!(execution.byte_cost(), 6);
+    }
+
+    #[test]
+    fn test_delete_world_undo_redo() {
+        let mut pt = PieceTree::new("hello world".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(6, 5))).unwrap();
+        assert_eq!(pt.get_text(), "hello ");
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "hello world");
+
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "hello ");
+    }
+
+    #[test]
+    fn test_delete_same_range_twice_with_intervening_edit() {
+        let mut pt = PieceTree::new("hello world".to_string());
+        let mut manager = UndoRedoManager::with_settings(100, 0);
+
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(0, 5))).unwrap();
+        manager.execute(&mut pt, Arc::new(InsertCommand::new(0, "howdy"))).unwrap();
+        manager.execute(&mut pt, Arc::new(DeleteCommand::new(0, 5))).unwrap();
+        assert_eq!(pt.get_text(), " world");
+
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "howdy world");
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), " world");
+        manager.undo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), "hello world");
+
+        manager.redo(&mut pt).unwrap();
+        manager.redo(&mut pt).unwrap();
+        manager.redo(&mut pt).unwrap();
+        assert_eq!(pt.get_text(), " world");
+    }
+
+    #[test]
+    fn test_delete_redo_rejects_changed_document() {
+        let mut pt = PieceTree::new("hello world".to_string());
+        let cmd = DeleteCommand::new(6, 5);
+        let execution = cmd.execute(&mut pt).unwrap();
+        assert_eq!(execution.deleted_text.as_deref(), Some("world"));
+
+        cmd.undo(&mut pt, &execution).unwrap();
+        pt.delete(6, 1);
+        pt.insert(6, "W".to_string());
+        assert!(cmd.redo(&mut pt, &execution).is_err());
+        assert_eq!(pt.get_text(), "hello World");
+    }
+
+    #[test]
+    fn test_undo_insert_restores_caret_before_insert() {
+        let mut doc = PieceTree::new("Hello".to_string());
+        doc.move_selection_to(5);
+        let mut manager = UndoRedoManager::new();
+
+        manager.execute(&mut doc, Arc::new(InsertCommand::new(5, " World"))).unwrap();
+        assert_eq!(doc.selection, Selection::new(11, 11));
+
+        // The user moves the caret away before undoing
+        doc.move_selection_to(0);
+        let step = manager.undo(&mut doc).unwrap();
+        assert_eq!(step.operation_type, OperationType::Insert);
+        assert_eq!(step.selection, Selection::new(5, 5));
+        assert_eq!(doc.selection, Selection::new(5, 5));
+        assert_eq!(doc.get_text(), "Hello");
+
+        let step = manager.redo(&mut doc).unwrap();
+        assert_eq!(step.selection, Selection::new(11, 11));
+        assert_eq!(doc.selection, Selection::new(11, 11));
+    }
+
+    #[test]
+    fn test_insert_moves_caret_by_characters() {
+        let mut doc = PieceTree::new("cafe".to_string());
+        let mut manager = UndoRedoManager::new();
+
+        manager.execute(&mut doc, Arc::new(InsertCommand::new(3, "é"))).unwrap();
+        assert_eq!(doc.get_text(), "cafée");
+        assert_eq!(doc.selection, Selection::new(4, 4));
+    }
+
+    #[test]
+    fn test_undo_merged_typing_restores_first_caret() {
+        let mut doc = PieceTree::new(String::new());
+        let mut manager = UndoRedoManager::new();
+
+        manager.execute(&mut doc, Arc::new(InsertCommand::new(0, "a"))).unwrap();
+        manager.execute(&mut doc, Arc::new(InsertCommand::new(1, "b"))).unwrap();
+        assert_eq!(manager.undo_count(), 1);
+
+        let step = manager.undo(&mut doc).unwrap();
+        assert_eq!(step.selection, Selection::new(0, 0));
+        assert_eq!(manager.redo(&mut doc).unwrap().selection, Selection::new(2, 2));
+    }
 }
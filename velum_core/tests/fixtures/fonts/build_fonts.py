@@ -0,0 +1,156 @@
+#!/usr/bin/env python3
+"""Builds the minimal TrueType fixtures used by velum_core's shaping tests.
+
+Usage: python3 build_fonts.py tests/fixtures/fonts
+"""
+import struct
+import sys
+
+
+def u16(v): return struct.pack('>H', v)
+def i16(v): return struct.pack('>h', v)
+def u32(v): return struct.pack('>I', v)
+
+
+def checksum(data):
+    data += b'\0' * (-len(data) % 4)
+    return sum(struct.unpack('>%dI' % (len(data) // 4), data)) & 0xFFFFFFFF
+
+
+def cmap_table(mapping):
+    # One format 12 subtable, referenced by the Unicode full and Windows UCS-4 records
+    groups = []
+    for cp, gid in sorted(mapping.items()):
+        if groups and groups[-1][1] + 1 == cp and groups[-1][2] + (cp - groups[-1][0]) == gid:
+            groups[-1][1] = cp
+        else:
+            groups.append([cp, cp, gid])
+    sub = u16(12) + u16(0) + u32(16 + 12 * len(groups)) + u32(0) + u32(len(groups))
+    for start, end, gid in groups:
+        sub += u32(start) + u32(end) + u32(gid)
+    header = u16(0) + u16(2)
+    offset = 4 + 8 * 2
+    header += u16(0) + u16(4) + u32(offset)
+    header += u16(3) + u16(10) + u32(offset)
+    return header + sub
+
+
+def name_table(family):
+    records = [(1, family), (2, 'Regular'), (4, family), (6, family.replace(' ', ''))]
+    strings = b''
+    entries = b''
+    for name_id, text in records:
+        data = text.encode('utf-16-be')
+        entries += u16(3) + u16(1) + u16(0x409) + u16(name_id) + u16(len(data)) + u16(len(strings))
+        strings += data
+    return u16(0) + u16(len(records)) + u16(6 + 12 * len(records)) + entries + strings
+
+
+def layout_table(feature_tag, lookup):
+    """GSUB/GPOS with a DFLT script, one feature and one lookup"""
+    lang_sys = u16(0) + u16(0xFFFF) + u16(1) + u16(0)
+    script = u16(4) + u16(0) + lang_sys
+    script_list = u16(1) + b'DFLT' + u16(8) + script
+    feature = u16(0) + u16(1) + u16(0)
+    feature_list = u16(1) + feature_tag + u16(8) + feature
+    lookup_list = u16(1) + u16(4) + lookup
+    script_off = 10
+    feature_off = script_off + len(script_list)
+    lookup_off = feature_off + len(feature_list)
+    return u16(1) + u16(0) + u16(script_off) + u16(feature_off) + u16(lookup_off) \
+        + script_list + feature_list + lookup_list
+
+
+def coverage(glyphs):
+    return u16(1) + u16(len(glyphs)) + b''.join(u16(g) for g in glyphs)
+
+
+def ligature_lookup(first, ligatures):
+    """Lookup type 4: ligatures starting with `first`, as (components, glyph) pairs"""
+    ligs = [u16(glyph) + u16(len(components)) + b''.join(u16(c) for c in components[1:])
+            for components, glyph in ligatures]
+    lig_set = u16(len(ligs))
+    offset = 2 + 2 * len(ligs)
+    body = b''
+    for lig in ligs:
+        lig_set += u16(offset + len(body))
+        body += lig
+    lig_set += body
+    cov = coverage([first])
+    subtable = u16(1) + u16(8) + u16(1) + u16(8 + len(cov)) + cov + lig_set
+    return u16(4) + u16(0) + u16(1) + u16(8) + subtable
+
+
+def pair_lookup(first, second, x_advance):
+    """Lookup type 2: one pair adjusting the first glyph's advance"""
+    pair_set = u16(1) + u16(second) + i16(x_advance)
+    cov = coverage([first])
+    subtable = u16(1) + u16(12) + u16(0x0004) + u16(0) + u16(1) + u16(12 + len(cov)) + cov + pair_set
+    return u16(2) + u16(0) + u16(1) + u16(8) + subtable
+
+
+def build(family, glyphs, mapping, gsub=None, gpos=None):
+    """`glyphs` is a list of advances; `mapping` maps code points to glyph IDs"""
+    count = len(glyphs)
+    tables = {
+        b'cmap': cmap_table(mapping),
+        b'glyf': b'',
+        b'head': u32(0x00010000) + u32(0x00010000) + u32(0) + u32(0x5F0F3CF5) + u16(3) + u16(1000)
+                 + b'\0' * 16 + i16(0) + i16(-200) + i16(max(glyphs)) + i16(800)
+                 + u16(0) + u16(8) + i16(2) + i16(0) + i16(0),
+        b'hhea': u32(0x00010000) + i16(800) + i16(-200) + i16(0) + u16(max(glyphs)) + i16(0) + i16(0)
+                 + i16(max(glyphs)) + i16(1) + i16(0) + i16(0) + b'\0' * 8 + i16(0) + u16(count),
+        b'hmtx': b''.join(u16(advance) + i16(0) for advance in glyphs),
+        b'loca': b'\0\0' * (count + 1),
+        b'maxp': u32(0x00005000) + u16(count),
+        b'name': name_table(family),
+        b'post': u32(0x00030000) + b'\0' * 28,
+    }
+    if gsub:
+        tables[b'GSUB'] = gsub
+    if gpos:
+        tables[b'GPOS'] = gpos
+
+    tags = sorted(tables)
+    num = len(tags)
+    entry_selector = num.bit_length() - 1
+    search_range = (1 << entry_selector) * 16
+    out = u32(0x00010000) + u16(num) + u16(search_range) + u16(entry_selector) + u16(num * 16 - search_range)
+    offset = 12 + 16 * num
+    directory = b''
+    data = b''
+    for tag in tags:
+        table = tables[tag]
+        directory += tag + u32(checksum(table)) + u32(offset + len(data)) + u32(len(table))
+        data += table + b'\0' * (-len(table) % 4)
+    font = bytearray(out + directory + data)
+    head = offset + sum(len(tables[t]) + (-len(tables[t]) % 4) for t in tags[:tags.index(b'head')])
+    struct.pack_into('>I', font, head + 8, (0xB1B0AFBA - checksum(bytes(font))) & 0xFFFFFFFF)
+    return bytes(font)
+
+
+def velum_test():
+    # .notdef, printable ASCII, two ligatures and four Arabic letters, all 500/1000 em
+    mapping = {cp: cp - 0x1F for cp in range(0x20, 0x7F)}
+    f_f_i, f_i = 96, 97
+    for gid, cp in enumerate([0x627, 0x633, 0x644, 0x645], start=98):
+        mapping[cp] = gid
+    gid = lambda ch: mapping[ord(ch)]
+    gsub = layout_table(b'liga', ligature_lookup(gid('f'), [
+        ([gid('f'), gid('f'), gid('i')], f_f_i),
+        ([gid('f'), gid('i')], f_i),
+    ]))
+    gpos = layout_table(b'kern', pair_lookup(gid('A'), gid('V'), -100))
+    return build('Velum Test', [500] * 102, mapping, gsub, gpos)
+
+
+def velum_test_fallback():
+    # .notdef plus Hebrew alef, an emoji and two CJK ideographs, 1000/1000 em
+    mapping = {0x5D0: 1, 0x1F60A: 2, 0x4E16: 3, 0x754C: 4}
+    return build('Velum Test Fallback', [500, 1000, 1000, 1000, 1000], mapping)
+
+
+if __name__ == '__main__':
+    out = sys.argv[1]
+    open(out + '/VelumTest.ttf', 'wb').write(velum_test())
+    open(out + '/VelumTestFallback.ttf', 'wb').write(velum_test_fallback())
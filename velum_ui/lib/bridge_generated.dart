@@ -15,7 +15,10 @@ abstract class VelumCore {
 
   FlutterRustBridgeTaskConstMeta get kHelloVelumConstMeta;
 
-  Future<String> getSampleDocument({dynamic hint});
+  Future<String> getSampleDocument({
+    required DocumentHandle handle,
+    dynamic hint,
+  });
 
   FlutterRustBridgeTaskConstMeta get kGetSampleDocumentConstMeta;
 
@@ -23,11 +26,23 @@ abstract class VelumCore {
 
   FlutterRustBridgeTaskConstMeta get kMultiplyConstMeta;
 
-  Future<String> createEmptyDocument({dynamic hint});
+  Future<DocumentHandle> createDocument({dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCreateDocumentConstMeta;
+
+  Future<void> closeDocument({required DocumentHandle handle, dynamic hint});
+
+  FlutterRustBridgeTaskConstMeta get kCloseDocumentConstMeta;
+
+  Future<String> createEmptyDocument({
+    required DocumentHandle handle,
+    dynamic hint,
+  });
 
   FlutterRustBridgeTaskConstMeta get kCreateEmptyDocumentConstMeta;
 
   Future<String> insertText({
+    required DocumentHandle handle,
     required int offset,
     required String newText,
     dynamic hint,
@@ -35,7 +50,16 @@ abstract class VelumCore {
 
   FlutterRustBridgeTaskConstMeta get kInsertTextConstMeta;
 
+  Future<String> applyEditsJson({
+    required DocumentHandle handle,
+    required String editsJson,
+    dynamic hint,
+  });
+
+  FlutterRustBridgeTaskConstMeta get kApplyEditsJsonConstMeta;
+
   Future<String> deleteText({
+    required DocumentHandle handle,
     required int offset,
     required int length,
     dynamic hint,
@@ -44,6 +68,7 @@ abstract class VelumCore {
   FlutterRustBridgeTaskConstMeta get kDeleteTextConstMeta;
 
   Future<String> getTextRange({
+    required DocumentHandle handle,
     required int offset,
     required int length,
     dynamic hint,
@@ -51,33 +76,50 @@ abstract class VelumCore {
 
   FlutterRustBridgeTaskConstMeta get kGetTextRangeConstMeta;
 
-  Future<int> getLineCount({dynamic hint});
+  Future<int> getLineCount({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kGetLineCountConstMeta;
 
-  Future<String?> getLineContent({required int lineNumber, dynamic hint});
+  Future<String?> getLineContent({
+    required DocumentHandle handle,
+    required int lineNumber,
+    dynamic hint,
+  });
 
   FlutterRustBridgeTaskConstMeta get kGetLineContentConstMeta;
 
-  Future<String> getFullText({dynamic hint});
+  Future<String> getFullText({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kGetFullTextConstMeta;
 
-  Future<String> undo({dynamic hint});
+  Future<String> undo({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kUndoConstMeta;
 
-  Future<String> redo({dynamic hint});
+  Future<String> redo({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kRedoConstMeta;
 
-  Future<bool> canUndo({dynamic hint});
+  Future<bool> canUndo({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kCanUndoConstMeta;
 
-  Future<bool> canRedo({dynamic hint});
+  Future<bool> canRedo({required DocumentHandle handle, dynamic hint});
 
   FlutterRustBridgeTaskConstMeta get kCanRedoConstMeta;
+
+  Future<(int, int)> historyDepth({
+    required DocumentHandle handle,
+    dynamic hint,
+  });
+
+  FlutterRustBridgeTaskConstMeta get kHistoryDepthConstMeta;
+}
+
+class DocumentHandle {
+  final int field0;
+
+  const DocumentHandle({required this.field0});
 }
 
 class VelumCoreImpl implements VelumCore {
@@ -109,15 +151,20 @@ class VelumCoreImpl implements VelumCore {
         argNames: [],
       );
 
-  Future<String> getSampleDocument({dynamic hint}) {
+  Future<String> getSampleDocument({
+    required DocumentHandle handle,
+    dynamic hint,
+  }) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_get_sample_document(port_),
+        callFfi: (port_) =>
+            _platform.inner.wire_get_sample_document(port_, arg0),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kGetSampleDocumentConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
@@ -126,7 +173,7 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kGetSampleDocumentConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "get_sample_document",
-        argNames: [],
+        argNames: ["handle"],
       );
 
   Future<int> multiply({required int a, required int b, dynamic hint}) {
@@ -151,41 +198,90 @@ class VelumCoreImpl implements VelumCore {
         argNames: ["a", "b"],
       );
 
-  Future<String> createEmptyDocument({dynamic hint}) {
+  Future<DocumentHandle> createDocument({dynamic hint}) {
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_create_empty_document(port_),
-        parseSuccessData: _wire2api_String,
+        callFfi: (port_) => _platform.inner.wire_create_document(port_),
+        parseSuccessData: _wire2api_document_handle,
         parseErrorData: null,
 
-        constMeta: kCreateEmptyDocumentConstMeta,
+        constMeta: kCreateDocumentConstMeta,
         argValues: [],
         hint: hint,
       ),
     );
   }
 
+  FlutterRustBridgeTaskConstMeta get kCreateDocumentConstMeta =>
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "create_document",
+        argNames: [],
+      );
+
+  Future<void> closeDocument({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    return _platform.executeNormal(
+      FlutterRustBridgeTask(
+        callFfi: (port_) => _platform.inner.wire_close_document(port_, arg0),
+        parseSuccessData: _wire2api_unit,
+        parseErrorData: _wire2api_String,
+
+        constMeta: kCloseDocumentConstMeta,
+        argValues: [handle],
+        hint: hint,
+      ),
+    );
+  }
+
+  FlutterRustBridgeTaskConstMeta get kCloseDocumentConstMeta =>
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "close_document",
+        argNames: ["handle"],
+      );
+
+  Future<String> createEmptyDocument({
+    required DocumentHandle handle,
+    dynamic hint,
+  }) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    return _platform.executeNormal(
+      FlutterRustBridgeTask(
+        callFfi: (port_) =>
+            _platform.inner.wire_create_empty_document(port_, arg0),
+        parseSuccessData: _wire2api_String,
+        parseErrorData: _wire2api_String,
+
+        constMeta: kCreateEmptyDocumentConstMeta,
+        argValues: [handle],
+        hint: hint,
+      ),
+    );
+  }
+
   FlutterRustBridgeTaskConstMeta get kCreateEmptyDocumentConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "create_empty_document",
-        argNames: [],
+        argNames: ["handle"],
       );
 
   Future<String> insertText({
+    required DocumentHandle handle,
     required int offset,
     required String newText,
     dynamic hint,
   }) {
-    var arg0 = api2wire_usize(offset);
-    var arg1 = _platform.api2wire_String(newText);
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    var arg1 = api2wire_usize(offset);
+    var arg2 = _platform.api2wire_String(newText);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_insert_text(port_, arg0, arg1),
+        callFfi: (port_) =>
+            _platform.inner.wire_insert_text(port_, arg0, arg1, arg2),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kInsertTextConstMeta,
-        argValues: [offset, newText],
+        argValues: [handle, offset, newText],
         hint: hint,
       ),
     );
@@ -194,24 +290,54 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kInsertTextConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "insert_text",
-        argNames: ["offset", "newText"],
+        argNames: ["handle", "offset", "newText"],
+      );
+
+  Future<String> applyEditsJson({
+    required DocumentHandle handle,
+    required String editsJson,
+    dynamic hint,
+  }) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    var arg1 = _platform.api2wire_String(editsJson);
+    return _platform.executeNormal(
+      FlutterRustBridgeTask(
+        callFfi: (port_) =>
+            _platform.inner.wire_apply_edits_json(port_, arg0, arg1),
+        parseSuccessData: _wire2api_String,
+        parseErrorData: _wire2api_String,
+
+        constMeta: kApplyEditsJsonConstMeta,
+        argValues: [handle, editsJson],
+        hint: hint,
+      ),
+    );
+  }
+
+  FlutterRustBridgeTaskConstMeta get kApplyEditsJsonConstMeta =>
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "apply_edits_json",
+        argNames: ["handle", "editsJson"],
       );
 
   Future<String> deleteText({
+    required DocumentHandle handle,
     required int offset,
     required int length,
     dynamic hint,
   }) {
-    var arg0 = api2wire_usize(offset);
-    var arg1 = api2wire_usize(length);
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    var arg1 = api2wire_usize(offset);
+    var arg2 = api2wire_usize(length);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_delete_text(port_, arg0, arg1),
+        callFfi: (port_) =>
+            _platform.inner.wire_delete_text(port_, arg0, arg1, arg2),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kDeleteTextConstMeta,
-        argValues: [offset, length],
+        argValues: [handle, offset, length],
         hint: hint,
       ),
     );
@@ -220,25 +346,27 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kDeleteTextConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "delete_text",
-        argNames: ["offset", "length"],
+        argNames: ["handle", "offset", "length"],
       );
 
   Future<String> getTextRange({
+    required DocumentHandle handle,
     required int offset,
     required int length,
     dynamic hint,
   }) {
-    var arg0 = api2wire_usize(offset);
-    var arg1 = api2wire_usize(length);
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    var arg1 = api2wire_usize(offset);
+    var arg2 = api2wire_usize(length);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
         callFfi: (port_) =>
-            _platform.inner.wire_get_text_range(port_, arg0, arg1),
+            _platform.inner.wire_get_text_range(port_, arg0, arg1, arg2),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kGetTextRangeConstMeta,
-        argValues: [offset, length],
+        argValues: [handle, offset, length],
         hint: hint,
       ),
     );
@@ -247,18 +375,19 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kGetTextRangeConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "get_text_range",
-        argNames: ["offset", "length"],
+        argNames: ["handle", "offset", "length"],
       );
 
-  Future<int> getLineCount({dynamic hint}) {
+  Future<int> getLineCount({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_get_line_count(port_),
+        callFfi: (port_) => _platform.inner.wire_get_line_count(port_, arg0),
         parseSuccessData: _wire2api_usize,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kGetLineCountConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
@@ -267,19 +396,25 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kGetLineCountConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "get_line_count",
-        argNames: [],
+        argNames: ["handle"],
       );
 
-  Future<String?> getLineContent({required int lineNumber, dynamic hint}) {
-    var arg0 = api2wire_usize(lineNumber);
+  Future<String?> getLineContent({
+    required DocumentHandle handle,
+    required int lineNumber,
+    dynamic hint,
+  }) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    var arg1 = api2wire_usize(lineNumber);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_get_line_content(port_, arg0),
+        callFfi: (port_) =>
+            _platform.inner.wire_get_line_content(port_, arg0, arg1),
         parseSuccessData: _wire2api_opt_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kGetLineContentConstMeta,
-        argValues: [lineNumber],
+        argValues: [handle, lineNumber],
         hint: hint,
       ),
     );
@@ -288,18 +423,19 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kGetLineContentConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "get_line_content",
-        argNames: ["lineNumber"],
+        argNames: ["handle", "lineNumber"],
       );
 
-  Future<String> getFullText({dynamic hint}) {
+  Future<String> getFullText({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_get_full_text(port_),
+        callFfi: (port_) => _platform.inner.wire_get_full_text(port_, arg0),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kGetFullTextConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
@@ -308,86 +444,145 @@ class VelumCoreImpl implements VelumCore {
   FlutterRustBridgeTaskConstMeta get kGetFullTextConstMeta =>
       const FlutterRustBridgeTaskConstMeta(
         debugName: "get_full_text",
-        argNames: [],
+        argNames: ["handle"],
       );
 
-  Future<String> undo({dynamic hint}) {
+  Future<String> undo({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_undo(port_),
+        callFfi: (port_) => _platform.inner.wire_undo(port_, arg0),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kUndoConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
   }
 
   FlutterRustBridgeTaskConstMeta get kUndoConstMeta =>
-      const FlutterRustBridgeTaskConstMeta(debugName: "undo", argNames: []);
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "undo",
+        argNames: ["handle"],
+      );
 
-  Future<String> redo({dynamic hint}) {
+  Future<String> redo({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_redo(port_),
+        callFfi: (port_) => _platform.inner.wire_redo(port_, arg0),
         parseSuccessData: _wire2api_String,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kRedoConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
   }
 
   FlutterRustBridgeTaskConstMeta get kRedoConstMeta =>
-      const FlutterRustBridgeTaskConstMeta(debugName: "redo", argNames: []);
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "redo",
+        argNames: ["handle"],
+      );
 
-  Future<bool> canUndo({dynamic hint}) {
+  Future<bool> canUndo({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_can_undo(port_),
+        callFfi: (port_) => _platform.inner.wire_can_undo(port_, arg0),
         parseSuccessData: _wire2api_bool,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kCanUndoConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
   }
 
   FlutterRustBridgeTaskConstMeta get kCanUndoConstMeta =>
-      const FlutterRustBridgeTaskConstMeta(debugName: "can_undo", argNames: []);
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "can_undo",
+        argNames: ["handle"],
+      );
 
-  Future<bool> canRedo({dynamic hint}) {
+  Future<bool> canRedo({required DocumentHandle handle, dynamic hint}) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
     return _platform.executeNormal(
       FlutterRustBridgeTask(
-        callFfi: (port_) => _platform.inner.wire_can_redo(port_),
+        callFfi: (port_) => _platform.inner.wire_can_redo(port_, arg0),
         parseSuccessData: _wire2api_bool,
-        parseErrorData: null,
+        parseErrorData: _wire2api_String,
 
         constMeta: kCanRedoConstMeta,
-        argValues: [],
+        argValues: [handle],
         hint: hint,
       ),
     );
   }
 
   FlutterRustBridgeTaskConstMeta get kCanRedoConstMeta =>
-      const FlutterRustBridgeTaskConstMeta(debugName: "can_redo", argNames: []);
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "can_redo",
+        argNames: ["handle"],
+      );
+
+  Future<(int, int)> historyDepth({
+    required DocumentHandle handle,
+    dynamic hint,
+  }) {
+    var arg0 = _platform.api2wire_box_autoadd_document_handle(handle);
+    return _platform.executeNormal(
+      FlutterRustBridgeTask(
+        callFfi: (port_) => _platform.inner.wire_history_depth(port_, arg0),
+        parseSuccessData: _wire2api___record__usize_usize,
+        parseErrorData: _wire2api_String,
+
+        constMeta: kHistoryDepthConstMeta,
+        argValues: [handle],
+        hint: hint,
+      ),
+    );
+  }
+
+  FlutterRustBridgeTaskConstMeta get kHistoryDepthConstMeta =>
+      const FlutterRustBridgeTaskConstMeta(
+        debugName: "history_depth",
+        argNames: ["handle"],
+      );
 
   void dispose() {
     _platform.dispose();
   }
   // Section: wire2api
 
+  (int, int) _wire2api___record__usize_usize(dynamic raw) {
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2) {
+      throw Exception('Expected 2 elements, got ${arr.length}');
+    }
+    return (_wire2api_usize(arr[0]), _wire2api_usize(arr[1]));
+  }
+
   String _wire2api_String(dynamic raw) {
     return raw as String;
   }
 
+  bool _wire2api_bool(dynamic raw) {
+    return raw as bool;
+  }
+
+  DocumentHandle _wire2api_document_handle(dynamic raw) {
+    final arr = raw as List<dynamic>;
+    if (arr.length != 1)
+      throw Exception('unexpected arr length: expect 1 but see ${arr.length}');
+    return DocumentHandle(field0: _wire2api_u64(arr[0]));
+  }
+
   int _wire2api_i32(dynamic raw) {
     return raw as int;
   }
@@ -396,6 +591,10 @@ class VelumCoreImpl implements VelumCore {
     return raw == null ? null : _wire2api_String(raw);
   }
 
+  int _wire2api_u64(dynamic raw) {
+    return castInt(raw);
+  }
+
   int _wire2api_u8(dynamic raw) {
     return raw as int;
   }
@@ -404,12 +603,12 @@ class VelumCoreImpl implements VelumCore {
     return raw as Uint8List;
   }
 
-  int _wire2api_usize(dynamic raw) {
-    return castInt(raw);
+  void _wire2api_unit(dynamic raw) {
+    return;
   }
 
-  bool _wire2api_bool(dynamic raw) {
-    return raw as bool;
+  int _wire2api_usize(dynamic raw) {
+    return castInt(raw);
   }
 }
 
@@ -420,6 +619,11 @@ int api2wire_i32(int raw) {
   return raw;
 }
 
+@protected
+int api2wire_u64(int raw) {
+  return raw;
+}
+
 @protected
 int api2wire_u8(int raw) {
   return raw;
@@ -441,6 +645,15 @@ class VelumCorePlatform extends FlutterRustBridgeBase<VelumCoreWire> {
     return api2wire_uint_8_list(utf8.encoder.convert(raw));
   }
 
+  @protected
+  ffi.Pointer<wire_DocumentHandle> api2wire_box_autoadd_document_handle(
+    DocumentHandle raw,
+  ) {
+    final ptr = inner.new_box_autoadd_document_handle_0();
+    _api_fill_to_wire_document_handle(raw, ptr.ref);
+    return ptr;
+  }
+
   @protected
   ffi.Pointer<wire_uint_8_list> api2wire_uint_8_list(Uint8List raw) {
     final ans = inner.new_uint_8_list_0(raw.length);
@@ -451,6 +664,20 @@ class VelumCorePlatform extends FlutterRustBridgeBase<VelumCoreWire> {
   // Section: finalizer
 
   // Section: api_fill_to_wire
+
+  void _api_fill_to_wire_box_autoadd_document_handle(
+    DocumentHandle apiObj,
+    ffi.Pointer<wire_DocumentHandle> wireObj,
+  ) {
+    _api_fill_to_wire_document_handle(apiObj, wireObj.ref);
+  }
+
+  void _api_fill_to_wire_document_handle(
+    DocumentHandle apiObj,
+    wire_DocumentHandle wireObj,
+  ) {
+    wireObj.field0 = api2wire_u64(apiObj.field0);
+  }
 }
 
 // ignore_for_file: camel_case_types, non_constant_identifier_names, avoid_positional_boolean_parameters, annotate_overrides, constant_identifier_names
@@ -544,16 +771,21 @@ class VelumCoreWire implements FlutterRustBridgeWireBase {
   late final _wire_hello_velum = _wire_hello_velumPtr
       .asFunction<void Function(int)>();
 
-  void wire_get_sample_document(int port_) {
-    return _wire_get_sample_document(port_);
+  void wire_get_sample_document(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+  ) {
+    return _wire_get_sample_document(port_, handle);
   }
 
   late final _wire_get_sample_documentPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>(
-        'wire_get_sample_document',
-      );
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_get_sample_document');
   late final _wire_get_sample_document = _wire_get_sample_documentPtr
-      .asFunction<void Function(int)>();
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
 
   void wire_multiply(int port_, int a, int b) {
     return _wire_multiply(port_, a, b);
@@ -566,23 +798,53 @@ class VelumCoreWire implements FlutterRustBridgeWireBase {
   late final _wire_multiply = _wire_multiplyPtr
       .asFunction<void Function(int, int, int)>();
 
-  void wire_create_empty_document(int port_) {
-    return _wire_create_empty_document(port_);
+  void wire_create_document(int port_) {
+    return _wire_create_document(port_);
   }
 
-  late final _wire_create_empty_documentPtr =
+  late final _wire_create_documentPtr =
       _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>(
-        'wire_create_empty_document',
+        'wire_create_document',
       );
-  late final _wire_create_empty_document = _wire_create_empty_documentPtr
+  late final _wire_create_document = _wire_create_documentPtr
       .asFunction<void Function(int)>();
 
+  void wire_close_document(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_close_document(port_, handle);
+  }
+
+  late final _wire_close_documentPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_close_document');
+  late final _wire_close_document = _wire_close_documentPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
+  void wire_create_empty_document(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+  ) {
+    return _wire_create_empty_document(port_, handle);
+  }
+
+  late final _wire_create_empty_documentPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_create_empty_document');
+  late final _wire_create_empty_document = _wire_create_empty_documentPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
   void wire_insert_text(
     int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
     int offset,
     ffi.Pointer<wire_uint_8_list> new_text,
   ) {
-    return _wire_insert_text(port_, offset, new_text);
+    return _wire_insert_text(port_, handle, offset, new_text);
   }
 
   late final _wire_insert_textPtr =
@@ -590,88 +852,222 @@ class VelumCoreWire implements FlutterRustBridgeWireBase {
         ffi.NativeFunction<
           ffi.Void Function(
             ffi.Int64,
+            ffi.Pointer<wire_DocumentHandle>,
             ffi.UintPtr,
             ffi.Pointer<wire_uint_8_list>,
           )
         >
       >('wire_insert_text');
   late final _wire_insert_text = _wire_insert_textPtr
-      .asFunction<void Function(int, int, ffi.Pointer<wire_uint_8_list>)>();
+      .asFunction<
+        void Function(
+          int,
+          ffi.Pointer<wire_DocumentHandle>,
+          int,
+          ffi.Pointer<wire_uint_8_list>,
+        )
+      >();
+
+  void wire_apply_edits_json(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+    ffi.Pointer<wire_uint_8_list> edits_json,
+  ) {
+    return _wire_apply_edits_json(port_, handle, edits_json);
+  }
 
-  void wire_delete_text(int port_, int offset, int length) {
-    return _wire_delete_text(port_, offset, length);
+  late final _wire_apply_edits_jsonPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(
+            ffi.Int64,
+            ffi.Pointer<wire_DocumentHandle>,
+            ffi.Pointer<wire_uint_8_list>,
+          )
+        >
+      >('wire_apply_edits_json');
+  late final _wire_apply_edits_json = _wire_apply_edits_jsonPtr
+      .asFunction<
+        void Function(
+          int,
+          ffi.Pointer<wire_DocumentHandle>,
+          ffi.Pointer<wire_uint_8_list>,
+        )
+      >();
+
+  void wire_delete_text(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+    int offset,
+    int length,
+  ) {
+    return _wire_delete_text(port_, handle, offset, length);
   }
 
   late final _wire_delete_textPtr =
       _lookup<
         ffi.NativeFunction<
-          ffi.Void Function(ffi.Int64, ffi.UintPtr, ffi.UintPtr)
+          ffi.Void Function(
+            ffi.Int64,
+            ffi.Pointer<wire_DocumentHandle>,
+            ffi.UintPtr,
+            ffi.UintPtr,
+          )
         >
       >('wire_delete_text');
   late final _wire_delete_text = _wire_delete_textPtr
-      .asFunction<void Function(int, int, int)>();
+      .asFunction<
+        void Function(int, ffi.Pointer<wire_DocumentHandle>, int, int)
+      >();
 
-  void wire_get_text_range(int port_, int offset, int length) {
-    return _wire_get_text_range(port_, offset, length);
+  void wire_get_text_range(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+    int offset,
+    int length,
+  ) {
+    return _wire_get_text_range(port_, handle, offset, length);
   }
 
   late final _wire_get_text_rangePtr =
       _lookup<
         ffi.NativeFunction<
-          ffi.Void Function(ffi.Int64, ffi.UintPtr, ffi.UintPtr)
+          ffi.Void Function(
+            ffi.Int64,
+            ffi.Pointer<wire_DocumentHandle>,
+            ffi.UintPtr,
+            ffi.UintPtr,
+          )
         >
       >('wire_get_text_range');
   late final _wire_get_text_range = _wire_get_text_rangePtr
-      .asFunction<void Function(int, int, int)>();
+      .asFunction<
+        void Function(int, ffi.Pointer<wire_DocumentHandle>, int, int)
+      >();
 
-  void wire_get_line_count(int port_) {
-    return _wire_get_line_count(port_);
+  void wire_get_line_count(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_get_line_count(port_, handle);
   }
 
   late final _wire_get_line_countPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>(
-        'wire_get_line_count',
-      );
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_get_line_count');
   late final _wire_get_line_count = _wire_get_line_countPtr
-      .asFunction<void Function(int)>();
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
 
-  void wire_get_line_content(int port_, int line_number) {
-    return _wire_get_line_content(port_, line_number);
+  void wire_get_line_content(
+    int port_,
+    ffi.Pointer<wire_DocumentHandle> handle,
+    int line_number,
+  ) {
+    return _wire_get_line_content(port_, handle, line_number);
   }
 
   late final _wire_get_line_contentPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64, ffi.UintPtr)>>(
-        'wire_get_line_content',
-      );
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(
+            ffi.Int64,
+            ffi.Pointer<wire_DocumentHandle>,
+            ffi.UintPtr,
+          )
+        >
+      >('wire_get_line_content');
   late final _wire_get_line_content = _wire_get_line_contentPtr
-      .asFunction<void Function(int, int)>();
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>, int)>();
 
-  void wire_get_full_text(int port_) {
-    return _wire_get_full_text(port_);
+  void wire_get_full_text(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_get_full_text(port_, handle);
   }
 
   late final _wire_get_full_textPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>(
-        'wire_get_full_text',
-      );
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_get_full_text');
   late final _wire_get_full_text = _wire_get_full_textPtr
-      .asFunction<void Function(int)>();
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
 
-  void wire_undo(int port_) {
-    return _wire_undo(port_);
+  void wire_undo(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_undo(port_, handle);
   }
 
   late final _wire_undoPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_undo');
-  late final _wire_undo = _wire_undoPtr.asFunction<void Function(int)>();
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_undo');
+  late final _wire_undo = _wire_undoPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
 
-  void wire_redo(int port_) {
-    return _wire_redo(port_);
+  void wire_redo(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_redo(port_, handle);
   }
 
   late final _wire_redoPtr =
-      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Int64)>>('wire_redo');
-  late final _wire_redo = _wire_redoPtr.asFunction<void Function(int)>();
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_redo');
+  late final _wire_redo = _wire_redoPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
+  void wire_can_undo(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_can_undo(port_, handle);
+  }
+
+  late final _wire_can_undoPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_can_undo');
+  late final _wire_can_undo = _wire_can_undoPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
+  void wire_can_redo(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_can_redo(port_, handle);
+  }
+
+  late final _wire_can_redoPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_can_redo');
+  late final _wire_can_redo = _wire_can_redoPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
+  void wire_history_depth(int port_, ffi.Pointer<wire_DocumentHandle> handle) {
+    return _wire_history_depth(port_, handle);
+  }
+
+  late final _wire_history_depthPtr =
+      _lookup<
+        ffi.NativeFunction<
+          ffi.Void Function(ffi.Int64, ffi.Pointer<wire_DocumentHandle>)
+        >
+      >('wire_history_depth');
+  late final _wire_history_depth = _wire_history_depthPtr
+      .asFunction<void Function(int, ffi.Pointer<wire_DocumentHandle>)>();
+
+  ffi.Pointer<wire_DocumentHandle> new_box_autoadd_document_handle_0() {
+    return _new_box_autoadd_document_handle_0();
+  }
+
+  late final _new_box_autoadd_document_handle_0Ptr =
+      _lookup<ffi.NativeFunction<ffi.Pointer<wire_DocumentHandle> Function()>>(
+        'new_box_autoadd_document_handle_0',
+      );
+  late final _new_box_autoadd_document_handle_0 =
+      _new_box_autoadd_document_handle_0Ptr
+          .asFunction<ffi.Pointer<wire_DocumentHandle> Function()>();
 
   ffi.Pointer<wire_uint_8_list> new_uint_8_list_0(int len) {
     return _new_uint_8_list_0(len);
@@ -700,6 +1096,11 @@ final class _Dart_Handle extends ffi.Opaque {}
 
 final class BufferId extends ffi.Opaque {}
 
+final class wire_DocumentHandle extends ffi.Struct {
+  @ffi.Uint64()
+  external int field0;
+}
+
 final class wire_uint_8_list extends ffi.Struct {
   external ffi.Pointer<ffi.Uint8> ptr;
 